@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct AllanVariance {
+        inputs: { "input": Vec<f64> },
+        outputs: { "deviation": AllanDeviation },
+        statics: {
+            "sample_rate": f64 = 1.0,
+            "taus": Vec<f64> = Vec::<f64>::new(),
+            "max_samples": usize = 1_000_000,
+            "report_interval": usize = 10_000,
+        },
+        state: {
+            "record": Vec<f64> = Vec::<f64>::new(),
+            "pending_samples": usize = 0,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let taus = self.get_statics::<Vec<f64>>("taus")?.get_value();
+            let max_samples = self.get_statics::<usize>("max_samples")?.get_value();
+            let report_interval = self.get_statics::<usize>("report_interval")?.get_value();
+            if sample_rate.is_nan() || sample_rate <= 0.0 || max_samples < 3 || report_interval < 1
+                || taus.iter().any(|tau| tau.is_nan() || *tau <= 0.0) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("record", Vec::<f64>::new())?;
+            self.set_state_value("pending_samples", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let taus = self.get_statics::<Vec<f64>>("taus")?.get_value();
+            let max_samples = self.get_statics::<usize>("max_samples")?.get_value();
+            let report_interval = self.get_statics::<usize>("report_interval")?.get_value();
+            let mut record = self.get_state_value::<Vec<f64>>("record")?;
+            let mut pending_samples = self.get_state_value::<usize>("pending_samples")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut report = None;
+            {
+                let _lock = self.lock.lock().unwrap();
+                pending_samples += input_signal.len();
+                record.extend(input_signal);
+                if record.len() > max_samples {
+                    record.drain(..record.len() - max_samples);
+                }
+                if pending_samples >= report_interval {
+                    pending_samples = 0;
+                    let factors = averaging_factors(&taus, sample_rate, record.len());
+                    report = Some(overlapping_allan_deviation(&record, sample_rate, &factors));
+                }
+            }
+            self.set_state_value("record", record)?;
+            self.set_state_value("pending_samples", pending_samples)?;
+            if let Some(deviation) = report {
+                self.send_output::<AllanDeviation>("deviation", deviation)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AllanDeviation {
+    pub taus: Vec<f64>,
+    pub deviation: Vec<f64>,
+}
+
+// Overlapping Allan deviation of rate samples for averaging factors m (tau = m/sample_rate),
+// computed on the integrated signal x(k) = sum y / sample_rate. Factors needing more than the
+// record are skipped.
+pub fn overlapping_allan_deviation(rates: &[f64], sample_rate: f64, factors: &[usize]) -> AllanDeviation {
+    let tau0 = 1.0 / sample_rate;
+    let mut integrated = Vec::with_capacity(rates.len() + 1);
+    integrated.push(0.0);
+    for rate in rates {
+        integrated.push(integrated[integrated.len() - 1] + rate * tau0);
+    }
+    let n = integrated.len();
+    let mut result = AllanDeviation::default();
+    for &m in factors {
+        if m == 0 || n <= 2 * m {
+            continue;
+        }
+        let terms = n - 2 * m;
+        let sum: f64 = (0..terms)
+            .map(|k| integrated[k + 2 * m] - 2.0 * integrated[k + m] + integrated[k])
+            .map(|d| d * d)
+            .sum();
+        let tau = m as f64 * tau0;
+        result.taus.push(tau);
+        result.deviation.push((sum / (2.0 * tau * tau * terms as f64)).sqrt());
+    }
+    result
+}
+
+// Averaging factors for the requested taus, octave spacing up to a third of the record if none.
+fn averaging_factors(taus: &[f64], sample_rate: f64, samples: usize) -> Vec<usize> {
+    if taus.is_empty() {
+        let mut factors = Vec::new();
+        let mut m = 1;
+        while 3 * m <= samples {
+            factors.push(m);
+            m *= 2;
+        }
+        return factors;
+    }
+    let mut factors: Vec<usize> = taus.iter().map(|tau| (tau * sample_rate).round().max(1.0) as usize).collect();
+    factors.dedup();
+    factors
+}
+
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use rustfft::{FftPlanner, Fft, num_complex::Complex};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct BearingFault {
+        inputs: { "input": Vec<f64> },
+        outputs: { "fault_amplitudes": Vec<f64>, "envelope_spectrum": Vec<f64> },
+        statics: {
+            "sample_rate": f64 = 1.0,
+            "frame_size": usize = 4096,
+            "band_low": f64 = 0.0,
+            "band_high": f64 = 0.0,
+            "fault_frequencies": Vec<f64> = Vec::<f64>::new(),
+            "harmonics": usize = 1,
+            "tolerance": f64 = 1.0,
+        },
+        state: { "buffer": Vec<f64> = Vec::<f64>::new() },
+        fields: {
+            forward: Option<Arc<dyn Fft<f64>>> = None,
+            inverse: Option<Arc<dyn Fft<f64>>> = None,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let band_low = self.get_statics::<f64>("band_low")?.get_value();
+            let band_high = self.get_statics::<f64>("band_high")?.get_value();
+            let harmonics = self.get_statics::<usize>("harmonics")?.get_value();
+            if sample_rate.is_nan() || sample_rate <= 0.0 || frame_size < 2 || harmonics < 1
+                || band_low < 0.0 || band_high <= band_low || band_high > sample_rate / 2.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mut planner = FftPlanner::new();
+            self.forward = Some(planner.plan_fft_forward(frame_size));
+            self.inverse = Some(planner.plan_fft_inverse(frame_size));
+            self.set_state_value("buffer", Vec::<f64>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let band_low = self.get_statics::<f64>("band_low")?.get_value();
+            let band_high = self.get_statics::<f64>("band_high")?.get_value();
+            let fault_frequencies = self.get_statics::<Vec<f64>>("fault_frequencies")?.get_value();
+            let harmonics = self.get_statics::<usize>("harmonics")?.get_value();
+            let tolerance = self.get_statics::<f64>("tolerance")?.get_value();
+            let mut buffer = self.get_state_value::<Vec<f64>>("buffer")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut results = Vec::<(Vec<f64>, Vec<f64>)>::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                buffer.extend(input_signal);
+                let resolution = sample_rate / frame_size as f64;
+                while buffer.len() >= frame_size {
+                    let mut frame: Vec<Complex<f64>> = buffer.drain(..frame_size).map(|x| Complex::new(x, 0.0)).collect();
+                    self.forward.as_ref().unwrap().process(&mut frame);
+                    // band-pass and analytic signal in one step: keep the positive band, doubled
+                    for (k, value) in frame.iter_mut().enumerate() {
+                        let frequency = k as f64 * resolution;
+                        let in_band = 2 * k < frame_size && frequency >= band_low && frequency <= band_high;
+                        *value = if in_band { *value * 2.0 } else { Complex::new(0.0, 0.0) };
+                    }
+                    self.inverse.as_ref().unwrap().process(&mut frame);
+                    let envelope: Vec<f64> = frame.iter().map(|c| c.norm() / frame_size as f64).collect();
+                    let mean = envelope.iter().sum::<f64>() / frame_size as f64;
+                    let mut spectrum: Vec<Complex<f64>> = envelope.iter().map(|e| Complex::new(e - mean, 0.0)).collect();
+                    self.forward.as_ref().unwrap().process(&mut spectrum);
+                    let envelope_spectrum: Vec<f64> = spectrum[..frame_size / 2 + 1].iter()
+                        .map(|c| 2.0 * c.norm() / frame_size as f64)
+                        .collect();
+                    let amplitudes = fault_amplitudes(&envelope_spectrum, resolution, &fault_frequencies, harmonics, tolerance);
+                    results.push((amplitudes, envelope_spectrum));
+                }
+            }
+            self.set_state_value("buffer", buffer)?;
+            for (amplitudes, envelope_spectrum) in results {
+                self.send_output::<Vec<f64>>("fault_amplitudes", amplitudes)?;
+                self.send_output::<Vec<f64>>("envelope_spectrum", envelope_spectrum)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Largest envelope spectrum line within `tolerance` Hz of each fault frequency, summed over
+// the first `harmonics` multiples.
+pub fn fault_amplitudes(spectrum: &[f64], resolution: f64, fault_frequencies: &[f64], harmonics: usize, tolerance: f64) -> Vec<f64> {
+    fault_frequencies.iter().map(|&frequency| {
+        (1..=harmonics).map(|harmonic| {
+            let center = frequency * harmonic as f64;
+            let lower = ((center - tolerance) / resolution).floor().max(0.0) as usize;
+            let upper = (((center + tolerance) / resolution).ceil() as usize).min(spectrum.len().saturating_sub(1));
+            (lower..=upper).filter_map(|k| spectrum.get(k)).fold(0.0, |peak: f64, &a| peak.max(a))
+        }).sum()
+    }).collect()
+}
+
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use utils::math::matrix::Matrix;
+
+stream_block! {
+    pub struct CovarianceEstimator {
+        inputs: { "input": Matrix<f64> },
+        outputs: { "covariance": Matrix<f64> },
+        statics: {
+            "mode": String = "sliding".to_string(),
+            "window": usize = 1024,
+            "forgetting": f64 = 0.99,
+            "remove_mean": bool = true,
+            "report_interval": usize = 1024,
+        },
+        state: {
+            "accumulator": RunningCovariance = RunningCovariance::default(),
+            "pending_samples": usize = 0,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            let window = self.get_statics::<usize>("window")?.get_value();
+            let forgetting = self.get_statics::<f64>("forgetting")?.get_value();
+            let report_interval = self.get_statics::<usize>("report_interval")?.get_value();
+            let valid = match mode.as_str() {
+                "sliding" => window > 0,
+                "exponential" => forgetting > 0.0 && forgetting <= 1.0,
+                _ => false,
+            };
+            if !valid || report_interval == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("accumulator", RunningCovariance::default())?;
+            self.set_state_value("pending_samples", 0)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            let window = self.get_statics::<usize>("window")?.get_value();
+            let forgetting = self.get_statics::<f64>("forgetting")?.get_value();
+            let remove_mean = self.get_statics::<bool>("remove_mean")?.get_value();
+            let report_interval = self.get_statics::<usize>("report_interval")?.get_value();
+            let mut accumulator = self.get_state_value::<RunningCovariance>("accumulator")?;
+            let mut pending_samples = self.get_state_value::<usize>("pending_samples")?;
+            // one row per channel, one column per sample
+            let input = self.recv_input::<Matrix<f64>>("input")?;
+            if accumulator.channels == 0 {
+                accumulator = RunningCovariance::new(input.rows);
+            }
+            if input.rows != accumulator.channels {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let mut reports = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                let channels = input.to_vec();
+                for k in 0..input.cols {
+                    let sample: Vec<f64> = channels.iter().map(|channel| channel[k]).collect();
+                    if mode == "sliding" {
+                        accumulator.push_sliding(&sample, window);
+                    } else {
+                        accumulator.push_exponential(&sample, forgetting);
+                    }
+                    pending_samples += 1;
+                    if pending_samples >= report_interval {
+                        reports.push(accumulator.covariance(remove_mean));
+                        pending_samples = 0;
+                    }
+                }
+            }
+            self.set_state_value("accumulator", accumulator)?;
+            self.set_state_value("pending_samples", pending_samples)?;
+            for covariance in reports {
+                self.send_output::<Matrix<f64>>("covariance", Matrix::from_vec(covariance))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Weighted sums of x and x*x^T over the multi-channel samples seen so far. In sliding mode every
+// sample has weight 1 and the oldest one is subtracted once the window is full, in exponential
+// mode the sums decay by the forgetting factor at every sample.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunningCovariance {
+    channels: usize,
+    weight: f64,
+    sum: Vec<f64>,
+    cross: Vec<f64>,
+    history: VecDeque<Vec<f64>>,
+}
+impl RunningCovariance {
+    pub fn new(channels: usize) -> Self {
+        RunningCovariance {
+            channels,
+            weight: 0.0,
+            sum: vec![0.0; channels],
+            cross: vec![0.0; channels * channels],
+            history: VecDeque::new(),
+        }
+    }
+    fn accumulate(&mut self, sample: &[f64], sign: f64) {
+        self.weight += sign;
+        for i in 0..self.channels {
+            self.sum[i] += sign * sample[i];
+            for j in 0..self.channels {
+                self.cross[i * self.channels + j] += sign * sample[i] * sample[j];
+            }
+        }
+    }
+    pub fn push_sliding(&mut self, sample: &[f64], window: usize) {
+        self.accumulate(sample, 1.0);
+        self.history.push_back(sample.to_vec());
+        if self.history.len() > window {
+            let oldest = self.history.pop_front().unwrap();
+            self.accumulate(&oldest, -1.0);
+        }
+    }
+    pub fn push_exponential(&mut self, sample: &[f64], forgetting: f64) {
+        self.weight *= forgetting;
+        self.sum.iter_mut().for_each(|v| *v *= forgetting);
+        self.cross.iter_mut().for_each(|v| *v *= forgetting);
+        self.accumulate(sample, 1.0);
+    }
+    // Biased (1/weight) estimate, the mean is removed only when requested.
+    pub fn covariance(&self, remove_mean: bool) -> Vec<Vec<f64>> {
+        let n = self.channels;
+        if self.weight <= 0.0 {
+            return vec![vec![0.0; n]; n];
+        }
+        let mean: Vec<f64> = self.sum.iter().map(|s| s / self.weight).collect();
+        (0..n).map(|i| (0..n).map(|j| {
+            let moment = self.cross[i * n + j] / self.weight;
+            if remove_mean { moment - mean[i] * mean[j] } else { moment }
+        }).collect()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_sliding_matches_batch() {
+        let samples = [[1.0, 2.0], [2.0, 1.0], [4.0, 0.0], [3.0, 5.0], [0.0, 1.0]];
+        let mut running = RunningCovariance::new(2);
+        for sample in samples.iter() {
+            running.push_sliding(sample, 3);
+        }
+        // window holds the last three samples: means 7/3 and 2
+        let covariance = running.covariance(true);
+        let expected_xx = (16.0 + 9.0 + 0.0) / 3.0 - 49.0 / 9.0;
+        let expected_xy = (0.0 + 15.0 + 0.0) / 3.0 - 14.0 / 3.0;
+        assert!((covariance[0][0] - expected_xx).abs() < 1.0e-12);
+        assert!((covariance[0][1] - expected_xy).abs() < 1.0e-12);
+        assert!((covariance[1][0] - expected_xy).abs() < 1.0e-12);
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+pub const MAX_IMFS: usize = 8;
+const IMF_NAMES: [&str; MAX_IMFS] = ["imf_0", "imf_1", "imf_2", "imf_3", "imf_4", "imf_5", "imf_6", "imf_7"];
+
+stream_block! {
+    pub struct Emd {
+        inputs: { "input": Vec<f64> },
+        outputs: {
+            "imf_0": Vec<f64>,
+            "imf_1": Vec<f64>,
+            "imf_2": Vec<f64>,
+            "imf_3": Vec<f64>,
+            "imf_4": Vec<f64>,
+            "imf_5": Vec<f64>,
+            "imf_6": Vec<f64>,
+            "imf_7": Vec<f64>,
+            "residual": Vec<f64>,
+        },
+        statics: {
+            "window": usize = 512,
+            "max_imfs": usize = 4,
+            "max_sifts": usize = 10,
+            "sift_tolerance": f64 = 0.2,
+        },
+        state: { "pending": Vec<f64> = Vec::<f64>::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let window = self.get_statics::<usize>("window")?.get_value();
+            let max_imfs = self.get_statics::<usize>("max_imfs")?.get_value();
+            let max_sifts = self.get_statics::<usize>("max_sifts")?.get_value();
+            let sift_tolerance = self.get_statics::<f64>("sift_tolerance")?.get_value();
+            if window < 4 || max_imfs == 0 || max_imfs > MAX_IMFS || max_sifts == 0
+                || sift_tolerance.is_nan() || sift_tolerance <= 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("pending", Vec::<f64>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let window = self.get_statics::<usize>("window")?.get_value();
+            let max_imfs = self.get_statics::<usize>("max_imfs")?.get_value();
+            let max_sifts = self.get_statics::<usize>("max_sifts")?.get_value();
+            let sift_tolerance = self.get_statics::<f64>("sift_tolerance")?.get_value();
+            let mut pending = self.get_state_value::<Vec<f64>>("pending")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut decompositions = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                pending.extend_from_slice(&input_signal);
+                while pending.len() >= window {
+                    let frame: Vec<f64> = pending.drain(..window).collect();
+                    decompositions.push(emd(&frame, max_imfs, max_sifts, sift_tolerance));
+                }
+            }
+            self.set_state_value("pending", pending)?;
+            for (mut imfs, residual) in decompositions {
+                // IMFs the window did not have are sent as zeros, so every port gets one frame per
+                // window
+                imfs.resize(max_imfs, vec![0.0; window]);
+                for (imf_name, imf) in IMF_NAMES.iter().zip(imfs) {
+                    self.send_output::<Vec<f64>>(imf_name, imf)?;
+                }
+                self.send_output::<Vec<f64>>("residual", residual)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Indices of the strict local maxima, or minima, of x. The first sample of a plateau counts.
+fn extrema(x: &[f64], maxima: bool) -> Vec<usize> {
+    let sign = if maxima { 1.0 } else { -1.0 };
+    let mut ret = Vec::new();
+    let mut i = 1;
+    while i + 1 < x.len() {
+        let mut j = i;
+        while j + 1 < x.len() && x[j + 1] == x[i] {
+            j += 1;
+        }
+        if j + 1 < x.len() && sign * (x[i] - x[i - 1]) > 0.0 && sign * (x[i] - x[j + 1]) > 0.0 {
+            ret.push(i);
+        }
+        i = j + 1;
+    }
+    ret
+}
+
+// Natural cubic spline through (knots, values), evaluated at 0, 1, .., n - 1. Knots are strictly
+// increasing, two of them give the straight line.
+fn natural_spline(knots: &[f64], values: &[f64], n: usize) -> Vec<f64> {
+    let m = knots.len();
+    let h: Vec<f64> = knots.windows(2).map(|w| w[1] - w[0]).collect();
+    // second derivatives at the knots, zero at both ends, from the tridiagonal system
+    let mut second = vec![0.0; m];
+    if m > 2 {
+        let mut diagonal = vec![0.0; m];
+        let mut rhs = vec![0.0; m];
+        for i in 1..m - 1 {
+            diagonal[i] = 2.0 * (h[i - 1] + h[i]);
+            rhs[i] = 6.0 * ((values[i + 1] - values[i]) / h[i] - (values[i] - values[i - 1]) / h[i - 1]);
+            if i > 1 {
+                let factor = h[i - 1] / diagonal[i - 1];
+                diagonal[i] -= factor * h[i - 1];
+                rhs[i] -= factor * rhs[i - 1];
+            }
+        }
+        for i in (1..m - 1).rev() {
+            second[i] = (rhs[i] - h[i] * second[i + 1]) / diagonal[i];
+        }
+    }
+    let mut segment = 0;
+    (0..n).map(|t| {
+        let t = t as f64;
+        while segment + 2 < m && t > knots[segment + 1] {
+            segment += 1;
+        }
+        let (a, b) = (knots[segment + 1] - t, t - knots[segment]);
+        let hs = h[segment];
+        (second[segment] * a * a * a + second[segment + 1] * b * b * b) / (6.0 * hs)
+            + (values[segment] / hs - second[segment] * hs / 6.0) * a
+            + (values[segment + 1] / hs - second[segment + 1] * hs / 6.0) * b
+    }).collect()
+}
+
+// Envelope through the extrema, held at the outermost extremum value up to both ends of the
+// window to limit the spline swing there.
+fn envelope(x: &[f64], points: &[usize]) -> Vec<f64> {
+    let n = x.len();
+    let mut knots = Vec::with_capacity(points.len() + 2);
+    let mut values = Vec::with_capacity(points.len() + 2);
+    if points[0] > 0 {
+        knots.push(0.0);
+        values.push(x[points[0]]);
+    }
+    for &p in points {
+        knots.push(p as f64);
+        values.push(x[p]);
+    }
+    if points[points.len() - 1] < n - 1 {
+        knots.push((n - 1) as f64);
+        values.push(x[points[points.len() - 1]]);
+    }
+    natural_spline(&knots, &values, n)
+}
+
+// Empirical mode decomposition (Huang et al., 1998). Each IMF is sifted from the current residual
+// by subtracting the mean of the spline envelopes through its maxima and minima, until the Cauchy
+// criterion sum (h_prev - h)^2 / sum h_prev^2 drops below `tolerance` or after `max_sifts`
+// iterations. Stops early once the residual has too few extrema to be sifted, so the IMFs plus
+// the residual always add up to x.
+pub fn emd(x: &[f64], max_imfs: usize, max_sifts: usize, tolerance: f64) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let mut residual = x.to_vec();
+    let mut imfs = Vec::new();
+    while imfs.len() < max_imfs {
+        let mut h = residual.clone();
+        let mut sifted = false;
+        for _ in 0..max_sifts {
+            let (maxima, minima) = (extrema(&h, true), extrema(&h, false));
+            if maxima.len() < 2 || minima.len() < 2 {
+                break;
+            }
+            let (upper, lower) = (envelope(&h, &maxima), envelope(&h, &minima));
+            let next: Vec<f64> = h.iter().zip(upper.iter().zip(&lower)).map(|(v, (u, l))| v - 0.5 * (u + l)).collect();
+            let change: f64 = h.iter().zip(&next).map(|(a, b)| (a - b) * (a - b)).sum();
+            let energy: f64 = h.iter().map(|v| v * v).sum();
+            h = next;
+            sifted = true;
+            if change <= tolerance * energy {
+                break;
+            }
+        }
+        if !sifted {
+            break;
+        }
+        residual.iter_mut().zip(&h).for_each(|(r, v)| *r -= v);
+        imfs.push(h);
+    }
+    (imfs, residual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    #[test]
+    fn test_emd() {
+        let knots = [0.0, 2.0, 5.0, 9.0];
+        let straight = |t: f64| 1.0 + 0.5 * t;
+        let line = natural_spline(&knots, &knots.map(straight), 10);
+        assert!(line.iter().enumerate().all(|(t, v)| (v - straight(t as f64)).abs() < 1.0e-12));
+        assert_eq!(extrema(&[0.0, 1.0, 1.0, 0.0, -1.0, 2.0], true), vec![1]);
+        assert_eq!(extrema(&[0.0, 1.0, 1.0, 0.0, -1.0, 2.0], false), vec![4]);
+        // a fast tone over a slow one, the first IMF recovers the fast tone away from the edges
+        let fast: Vec<f64> = (0..512).map(|n| (2.0 * PI * 0.05 * n as f64).sin()).collect();
+        let slow: Vec<f64> = (0..512).map(|n| 2.0 * (2.0 * PI * 0.004 * n as f64).sin()).collect();
+        let x: Vec<f64> = fast.iter().zip(&slow).map(|(a, b)| a + b).collect();
+        let (imfs, residual) = emd(&x, 3, 20, 0.05);
+        assert!(!imfs.is_empty());
+        let error = (64..448).map(|n| (imfs[0][n] - fast[n]).abs()).fold(0.0, f64::max);
+        assert!(error < 0.1);
+        let rebuilt: Vec<f64> = (0..512).map(|n| imfs.iter().map(|imf| imf[n]).sum::<f64>() + residual[n]).collect();
+        assert!(rebuilt.iter().zip(&x).all(|(a, b)| (a - b).abs() < 1.0e-9));
+        // a monotonic window has no IMF
+        let ramp: Vec<f64> = (0..64).map(|n| n as f64).collect();
+        assert_eq!(emd(&ramp, 3, 10, 0.2), (Vec::<Vec<f64>>::new(), ramp));
+    }
+}
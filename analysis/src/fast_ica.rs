@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use utils::math::matrix::Matrix;
+use crate::eigen::symmetric_eigen;
+
+stream_block! {
+    pub struct FastIca {
+        inputs: { "input": Matrix<f64> },
+        outputs: { "output": Matrix<f64>, "unmixing": Matrix<f64> },
+        statics: {
+            "components": usize = 0,
+            "training_samples": usize = 4096,
+            "nonlinearity": String = "logcosh".to_string(),
+            "max_iterations": usize = 200,
+            "tolerance": f64 = 1.0e-6,
+        },
+        state: {
+            "model": IcaModel = IcaModel::default(),
+            "training": Vec<Vec<f64>> = Vec::<Vec<f64>>::new(),
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let training_samples = self.get_statics::<usize>("training_samples")?.get_value();
+            let nonlinearity = self.get_statics::<String>("nonlinearity")?.get_value();
+            let max_iterations = self.get_statics::<usize>("max_iterations")?.get_value();
+            let tolerance = self.get_statics::<f64>("tolerance")?.get_value();
+            if training_samples < 2 || max_iterations == 0 || tolerance.is_nan() || tolerance <= 0.0
+                || !["logcosh", "exp", "cube"].contains(&nonlinearity.as_str()) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("model", IcaModel::default())?;
+            self.set_state_value("training", Vec::<Vec<f64>>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let components = self.get_statics::<usize>("components")?.get_value();
+            let training_samples = self.get_statics::<usize>("training_samples")?.get_value();
+            let nonlinearity = self.get_statics::<String>("nonlinearity")?.get_value();
+            let max_iterations = self.get_statics::<usize>("max_iterations")?.get_value();
+            let tolerance = self.get_statics::<f64>("tolerance")?.get_value();
+            let model = self.get_state_value::<IcaModel>("model")?;
+            let mut training = self.get_state_value::<Vec<Vec<f64>>>("training")?;
+            // one row per channel, one column per sample
+            let input = self.recv_input::<Matrix<f64>>("input")?.to_vec();
+            let expected_channels = if !model.mean.is_empty() { model.mean.len() } else if !training.is_empty() { training.len() } else { input.len() };
+            if input.is_empty() || input.len() != expected_channels {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            if !model.unmixing.is_empty() {
+                let separated;
+                {
+                    let _lock = self.lock.lock().unwrap();
+                    separated = model.apply(&input);
+                }
+                self.send_output::<Matrix<f64>>("output", Matrix::from_vec(separated))?;
+                return Ok(());
+            }
+            // learning phase, the separated training block is emitted once the model is ready
+            if training.is_empty() {
+                training = vec![Vec::new(); input.len()];
+            }
+            for (buffer, channel) in training.iter_mut().zip(input.iter()) {
+                buffer.extend_from_slice(channel);
+            }
+            if training[0].len() < training_samples {
+                self.set_state_value("training", training)?;
+                return Ok(());
+            }
+            let components = if components == 0 { training.len() } else { components };
+            let learned;
+            {
+                let _lock = self.lock.lock().unwrap();
+                learned = fast_ica(&training, components, &nonlinearity, max_iterations, tolerance);
+            }
+            let Some(model) = learned else {
+                eprintln!("FastIca {}: training data is rank deficient for {} components", self.name, components);
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            };
+            if !model.converged {
+                eprintln!("FastIca {}: no convergence after {} iterations", self.name, model.iterations);
+            }
+            let separated = model.apply(&training);
+            self.set_state_value("model", model.clone())?;
+            self.set_state_value("training", Vec::<Vec<f64>>::new())?;
+            self.send_output::<Matrix<f64>>("unmixing", Matrix::from_vec(model.unmixing))?;
+            self.send_output::<Matrix<f64>>("output", Matrix::from_vec(separated))?;
+            Ok(())
+        }
+    }
+}
+
+// Learned separation y = unmixing * (x - mean), one row of `unmixing` per component.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IcaModel {
+    pub mean: Vec<f64>,
+    pub unmixing: Vec<Vec<f64>>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+impl IcaModel {
+    pub fn apply(&self, channels: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let samples = channels.first().map_or(0, |c| c.len());
+        self.unmixing.iter().map(|row| (0..samples).map(|k| {
+            row.iter().zip(channels.iter()).zip(self.mean.iter()).map(|((w, channel), mean)| w * (channel[k] - mean)).sum()
+        }).collect()).collect()
+    }
+}
+
+fn multiply(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    a.iter().map(|row| (0..b[0].len()).map(|j| row.iter().zip(b.iter()).map(|(x, b_row)| x * b_row[j]).sum()).collect()).collect()
+}
+
+// W <- (W W^T)^-1/2 W, keeps the component estimates orthogonal in the whitened space.
+fn decorrelate(w: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let gram: Vec<Vec<f64>> = w.iter().map(|a| w.iter().map(|b| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()).collect()).collect();
+    let (values, vectors) = symmetric_eigen(&gram);
+    let n = w.len();
+    let inverse_root: Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| {
+        values.iter().zip(vectors.iter()).map(|(value, vector)| vector[i] * vector[j] / value.max(f64::MIN_POSITIVE).sqrt()).sum()
+    }).collect()).collect();
+    multiply(&inverse_root, w)
+}
+
+// g and g' of the contrast function, negentropy approximations from Hyvarinen's FastICA.
+fn contrast(nonlinearity: &str, u: f64) -> (f64, f64) {
+    match nonlinearity {
+        "exp" => {
+            let e = (-0.5 * u * u).exp();
+            (u * e, (1.0 - u * u) * e)
+        }
+        "cube" => (u * u * u, 3.0 * u * u),
+        _ => {
+            let t = u.tanh();
+            (t, 1.0 - t * t)
+        }
+    }
+}
+
+// Symmetric FastICA on channels x samples data: centering, PCA whitening down to `components`
+// dimensions and fixed point iterations from the identity. None if the data is rank deficient.
+pub fn fast_ica(channels: &[Vec<f64>], components: usize, nonlinearity: &str, max_iterations: usize, tolerance: f64) -> Option<IcaModel> {
+    let n = channels.len();
+    let samples = channels.first().map_or(0, |c| c.len());
+    if components == 0 || components > n || samples < 2 {
+        return None;
+    }
+    let mean: Vec<f64> = channels.iter().map(|c| c.iter().sum::<f64>() / samples as f64).collect();
+    let centered: Vec<Vec<f64>> = channels.iter().zip(mean.iter()).map(|(c, m)| c.iter().map(|x| x - m).collect()).collect();
+    let covariance: Vec<Vec<f64>> = centered.iter().map(|a| centered.iter().map(|b| {
+        a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>() / samples as f64
+    }).collect()).collect();
+    let (values, vectors) = symmetric_eigen(&covariance);
+    if values[components - 1] <= 1.0e-12 * values[0].abs().max(f64::MIN_POSITIVE) {
+        return None;
+    }
+    let whitening: Vec<Vec<f64>> = (0..components).map(|i| vectors[i].iter().map(|v| v / values[i].sqrt()).collect()).collect();
+    let z = multiply(&whitening, &centered);
+    let mut w: Vec<Vec<f64>> = (0..components).map(|i| (0..components).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < max_iterations && !converged {
+        iterations += 1;
+        let mut updated = Vec::with_capacity(components);
+        for row in w.iter() {
+            let mut next = vec![0.0; components];
+            let mut derivative = 0.0;
+            for k in 0..samples {
+                let u: f64 = row.iter().zip(z.iter()).map(|(wi, zi)| wi * zi[k]).sum();
+                let (g, dg) = contrast(nonlinearity, u);
+                for (value, zi) in next.iter_mut().zip(z.iter()) {
+                    *value += zi[k] * g;
+                }
+                derivative += dg;
+            }
+            updated.push(next.iter().zip(row.iter()).map(|(value, wi)| (value - derivative * wi) / samples as f64).collect());
+        }
+        let updated = decorrelate(&updated);
+        let change = updated.iter().zip(w.iter())
+            .map(|(a, b)| (1.0 - a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>().abs()).abs())
+            .fold(0.0, f64::max);
+        w = updated;
+        converged = change < tolerance;
+    }
+    Some(IcaModel { mean, unmixing: multiply(&w, &whitening), iterations, converged })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_separates_two_sources() {
+        let samples = 4000;
+        let sine: Vec<f64> = (0..samples).map(|k| (0.013 * k as f64).sin()).collect();
+        let square: Vec<f64> = (0..samples).map(|k| if (k / 97) % 2 == 0 { 1.0 } else { -1.0 }).collect();
+        let mixed = vec![
+            sine.iter().zip(square.iter()).map(|(s, q)| 0.8 * s + 0.6 * q + 0.5).collect::<Vec<f64>>(),
+            sine.iter().zip(square.iter()).map(|(s, q)| 0.3 * s - 0.9 * q).collect::<Vec<f64>>(),
+        ];
+        let model = fast_ica(&mixed, 2, "logcosh", 200, 1.0e-8).unwrap();
+        assert!(model.converged);
+        let separated = model.apply(&mixed);
+        let correlation = |a: &[f64], b: &[f64]| {
+            let dot: f64 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+            let na: f64 = a.iter().map(|x| x * x).sum::<f64>().sqrt();
+            let nb: f64 = b.iter().map(|x| x * x).sum::<f64>().sqrt();
+            (dot / (na * nb)).abs()
+        };
+        let best = |source: &[f64]| separated.iter().map(|c| correlation(c, source)).fold(0.0, f64::max);
+        assert!(best(&sine) > 0.99);
+        assert!(best(&square) > 0.99);
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use rustfft::{FftPlanner, Fft, num_complex::Complex};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct FrfEstimator {
+        inputs: { "excitation": Vec<f64>, "response": Vec<f64> },
+        outputs: { "h1": Vec<Complex<f64>>, "h2": Vec<Complex<f64>>, "coherence": Vec<f64> },
+        statics: {
+            "segment_size": usize = 1024,
+            "overlap": usize = 512,
+            "averages": usize = 16,
+        },
+        state: {
+            "pending_excitation": Vec<f64> = Vec::<f64>::new(),
+            "pending_response": Vec<f64> = Vec::<f64>::new(),
+            "auto_excitation": Vec<f64> = Vec::<f64>::new(),
+            "auto_response": Vec<f64> = Vec::<f64>::new(),
+            "cross_real": Vec<f64> = Vec::<f64>::new(),
+            "cross_imag": Vec<f64> = Vec::<f64>::new(),
+            "segments": usize = 0,
+        },
+        fields: { fft_core: Option<Arc<dyn Fft<f64>>> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let segment_size = self.get_statics::<usize>("segment_size")?.get_value();
+            let overlap = self.get_statics::<usize>("overlap")?.get_value();
+            let averages = self.get_statics::<usize>("averages")?.get_value();
+            if segment_size < 2 || overlap >= segment_size || averages < 1 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mut planner = FftPlanner::new();
+            self.fft_core = Some(planner.plan_fft_forward(segment_size));
+            let bins = segment_size / 2 + 1;
+            self.set_state_value("pending_excitation", Vec::<f64>::new())?;
+            self.set_state_value("pending_response", Vec::<f64>::new())?;
+            self.set_state_value("auto_excitation", vec![0.0; bins])?;
+            self.set_state_value("auto_response", vec![0.0; bins])?;
+            self.set_state_value("cross_real", vec![0.0; bins])?;
+            self.set_state_value("cross_imag", vec![0.0; bins])?;
+            self.set_state_value("segments", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let segment_size = self.get_statics::<usize>("segment_size")?.get_value();
+            let overlap = self.get_statics::<usize>("overlap")?.get_value();
+            let averages = self.get_statics::<usize>("averages")?.get_value();
+            let mut pending_excitation = self.get_state_value::<Vec<f64>>("pending_excitation")?;
+            let mut pending_response = self.get_state_value::<Vec<f64>>("pending_response")?;
+            let mut auto_excitation = self.get_state_value::<Vec<f64>>("auto_excitation")?;
+            let mut auto_response = self.get_state_value::<Vec<f64>>("auto_response")?;
+            let mut cross_real = self.get_state_value::<Vec<f64>>("cross_real")?;
+            let mut cross_imag = self.get_state_value::<Vec<f64>>("cross_imag")?;
+            let mut segments = self.get_state_value::<usize>("segments")?;
+            let excitation = self.recv_input::<Vec<f64>>("excitation")?;
+            let response = self.recv_input::<Vec<f64>>("response")?;
+            if excitation.len() != response.len() {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let mut estimates = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                pending_excitation.extend(excitation);
+                pending_response.extend(response);
+                let window: Vec<f64> = (0..segment_size)
+                    .map(|n| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * n as f64 / segment_size as f64).cos())
+                    .collect();
+                let bins = segment_size / 2 + 1;
+                while pending_excitation.len() >= segment_size {
+                    let spectrum = |signal: &[f64]| {
+                        let mut frame: Vec<Complex<f64>> = signal[..segment_size].iter().zip(window.iter())
+                            .map(|(x, w)| Complex::new(x * w, 0.0))
+                            .collect();
+                        self.fft_core.as_ref().unwrap().process(&mut frame);
+                        frame
+                    };
+                    let x = spectrum(&pending_excitation);
+                    let y = spectrum(&pending_response);
+                    for (k, (xk, yk)) in x.iter().zip(y.iter()).take(bins).enumerate() {
+                        let gxy = xk.conj() * yk;
+                        auto_excitation[k] += xk.norm_sqr();
+                        auto_response[k] += yk.norm_sqr();
+                        cross_real[k] += gxy.re;
+                        cross_imag[k] += gxy.im;
+                    }
+                    pending_excitation.drain(..segment_size - overlap);
+                    pending_response.drain(..segment_size - overlap);
+                    segments += 1;
+                    if segments == averages {
+                        let cross: Vec<Complex<f64>> = cross_real.iter().zip(cross_imag.iter()).map(|(re, im)| Complex::new(*re, *im)).collect();
+                        estimates.push(frf_estimates(&auto_excitation, &auto_response, &cross));
+                        auto_excitation = vec![0.0; bins];
+                        auto_response = vec![0.0; bins];
+                        cross_real = vec![0.0; bins];
+                        cross_imag = vec![0.0; bins];
+                        segments = 0;
+                    }
+                }
+            }
+            self.set_state_value("pending_excitation", pending_excitation)?;
+            self.set_state_value("pending_response", pending_response)?;
+            self.set_state_value("auto_excitation", auto_excitation)?;
+            self.set_state_value("auto_response", auto_response)?;
+            self.set_state_value("cross_real", cross_real)?;
+            self.set_state_value("cross_imag", cross_imag)?;
+            self.set_state_value("segments", segments)?;
+            for (h1, h2, coherence) in estimates {
+                self.send_output::<Vec<Complex<f64>>>("h1", h1)?;
+                self.send_output::<Vec<Complex<f64>>>("h2", h2)?;
+                self.send_output::<Vec<f64>>("coherence", coherence)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// H1 = Gxy/Gxx (noise on the response), H2 = Gyy/Gyx (noise on the excitation) and the
+// ordinary coherence |Gxy|^2/(Gxx Gyy) from averaged auto and cross spectra.
+pub fn frf_estimates(auto_excitation: &[f64], auto_response: &[f64], cross: &[Complex<f64>]) -> (Vec<Complex<f64>>, Vec<Complex<f64>>, Vec<f64>) {
+    let mut h1 = Vec::with_capacity(cross.len());
+    let mut h2 = Vec::with_capacity(cross.len());
+    let mut coherence = Vec::with_capacity(cross.len());
+    for ((&gxx, &gyy), &gxy) in auto_excitation.iter().zip(auto_response.iter()).zip(cross.iter()) {
+        h1.push(if gxx > 0.0 { gxy / gxx } else { Complex::new(0.0, 0.0) });
+        h2.push(if gxy.norm_sqr() > 0.0 { Complex::new(gyy, 0.0) / gxy.conj() } else { Complex::new(0.0, 0.0) });
+        coherence.push(if gxx * gyy > 0.0 { gxy.norm_sqr() / (gxx * gyy) } else { 0.0 });
+    }
+    (h1, h2, coherence)
+}
+
@@ -0,0 +1,148 @@
+pub mod order_analysis;
+pub mod bearing_fault;
+pub mod rainflow;
+pub mod allan_variance;
+pub mod swept_sine;
+pub mod frf_estimator;
+pub mod peak_tracker;
+pub mod signal_quality;
+pub mod tdoa_estimator;
+pub mod covariance_estimator;
+pub mod eigen;
+pub mod fast_ica;
+mod smoothing;
+pub mod trend_extractor;
+pub mod stl;
+pub mod music;
+pub mod emd;
+use std::ffi::c_char;
+use data_model::modules::{Version,ModuleStructFFI};
+use processor_engine::stream_processor::StreamProcessor;
+use processor_engine::ffi::{TraitObjectRepr, export_stream_processor, get_error_return};
+#[unsafe(no_mangle)]
+pub static MODULE: ModuleStructFFI  = ModuleStructFFI {
+    name: b"Signal analysis\0".as_ptr() as *const c_char,
+    description: b"The library provides blocks for machinery, structural and sensor analysis.\0".as_ptr() as *const c_char,
+    authors: b"Sofia Silvestri\0".as_ptr() as *const c_char,
+    release_date: b"2026/10/16\0".as_ptr() as *const c_char,
+    version: Version{ major: 0,minor: 1,build: 0},
+    dependencies: std::ptr::null(),
+    dependency_number: 0,
+    provides: [b"OrderAnalysis\0".as_ptr() as *const c_char,
+        b"BearingFault\0".as_ptr() as *const c_char,
+        b"Rainflow\0".as_ptr() as *const c_char,
+        b"AllanVariance\0".as_ptr() as *const c_char,
+        b"SweptSine\0".as_ptr() as *const c_char,
+        b"FrfEstimator\0".as_ptr() as *const c_char,
+        b"PeakTracker\0".as_ptr() as *const c_char,
+        b"SignalQuality\0".as_ptr() as *const c_char,
+        b"TdoaEstimator\0".as_ptr() as *const c_char,
+        b"CovarianceEstimator\0".as_ptr() as *const c_char,
+        b"FastIca\0".as_ptr() as *const c_char,
+        b"TrendExtractor\0".as_ptr() as *const c_char,
+        b"Stl\0".as_ptr() as *const c_char,
+        b"Music\0".as_ptr() as *const c_char,
+        b"Emd\0".as_ptr() as *const c_char].as_ptr(),
+    provides_lengths: 15,
+};
+/// Creates the block `proc_block` named `block_name`, both passed by the host as UTF-8 bytes.
+///
+/// # Safety
+/// A non null `proc_block` and `block_name` must point to `proc_block_len` and `block_name_len`
+/// readable bytes that stay valid while the block exists.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_processor_modules(proc_block: *const u8, 
+    proc_block_len: usize, 
+    block_name: *const u8, 
+    block_name_len: usize) -> TraitObjectRepr {
+    let proc_block_str = block_template::ffi_name!(proc_block, proc_block_len);
+    let block_name_str = block_template::ffi_name!(block_name, block_name_len);
+    let proc: Box<dyn StreamProcessor>;
+    match proc_block_str {
+        "OrderAnalysis" => {
+            proc = Box::new(order_analysis::OrderAnalysis::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "BearingFault" => {
+            proc = Box::new(bearing_fault::BearingFault::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Rainflow" => {
+            proc = Box::new(rainflow::Rainflow::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "AllanVariance" => {
+            proc = Box::new(allan_variance::AllanVariance::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "SweptSine" => {
+            proc = Box::new(swept_sine::SweptSine::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "FrfEstimator" => {
+            proc = Box::new(frf_estimator::FrfEstimator::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "PeakTracker" => {
+            proc = Box::new(peak_tracker::PeakTracker::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "SignalQuality" => {
+            proc = Box::new(signal_quality::SignalQuality::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "TdoaEstimator" => {
+            proc = Box::new(tdoa_estimator::TdoaEstimator::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "CovarianceEstimator" => {
+            proc = Box::new(covariance_estimator::CovarianceEstimator::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "FastIca" => {
+            proc = Box::new(fast_ica::FastIca::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "TrendExtractor" => {
+            proc = Box::new(trend_extractor::TrendExtractor::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Stl" => {
+            proc = Box::new(stl::Stl::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Music" => {
+            proc = Box::new(music::Music::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Emd" => {
+            proc = Box::new(emd::Emd::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        _ => {
+            eprintln!("Processor block {} not found", proc_block_str);
+            get_error_return(1)
+        }
+    }
+}
+
+// Schema of every block get_processor_modules creates, keyed by block type, see stream_block!.
+pub fn block_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "OrderAnalysis": order_analysis::OrderAnalysis::schema(),
+        "BearingFault": bearing_fault::BearingFault::schema(),
+        "Rainflow": rainflow::Rainflow::schema(),
+        "AllanVariance": allan_variance::AllanVariance::schema(),
+        "SweptSine": swept_sine::SweptSine::schema(),
+        "FrfEstimator": frf_estimator::FrfEstimator::schema(),
+        "PeakTracker": peak_tracker::PeakTracker::schema(),
+        "SignalQuality": signal_quality::SignalQuality::schema(),
+        "TdoaEstimator": tdoa_estimator::TdoaEstimator::schema(),
+        "CovarianceEstimator": covariance_estimator::CovarianceEstimator::schema(),
+        "FastIca": fast_ica::FastIca::schema(),
+        "TrendExtractor": trend_extractor::TrendExtractor::schema(),
+        "Stl": stl::Stl::schema(),
+        "Music": music::Music::schema(),
+        "Emd": emd::Emd::schema(),
+    })
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use std::f64::consts::PI;
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::eigen::symmetric_eigen;
+
+stream_block! {
+    pub struct Music {
+        inputs: { "input": Vec<f64> },
+        outputs: { "pseudospectrum": Vec<f64>, "frequencies": Vec<f64>, "estimates": Vec<f64> },
+        statics: {
+            "sinusoids": usize = 1,
+            "dimension": usize = 16,
+            "frame_size": usize = 256,
+            "grid_size": usize = 1024,
+            "sample_rate": f64 = 1.0,
+        },
+        state: { "pending": Vec<f64> = Vec::<f64>::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sinusoids = self.get_statics::<usize>("sinusoids")?.get_value();
+            let dimension = self.get_statics::<usize>("dimension")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let grid_size = self.get_statics::<usize>("grid_size")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            if sinusoids == 0 || dimension <= 2 * sinusoids || frame_size < dimension || grid_size < 3
+                || sample_rate.is_nan() || sample_rate <= 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("pending", Vec::<f64>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let sinusoids = self.get_statics::<usize>("sinusoids")?.get_value();
+            let dimension = self.get_statics::<usize>("dimension")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let grid_size = self.get_statics::<usize>("grid_size")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let mut pending = self.get_state_value::<Vec<f64>>("pending")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut results = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                pending.extend_from_slice(&input_signal);
+                while pending.len() >= frame_size {
+                    let frame: Vec<f64> = pending.drain(..frame_size).collect();
+                    let pseudospectrum = music(&frame, sinusoids, dimension, grid_size);
+                    let estimates = peak_frequencies(&pseudospectrum, sinusoids).iter().map(|f| f * sample_rate).collect::<Vec<f64>>();
+                    results.push((pseudospectrum, estimates));
+                }
+            }
+            self.set_state_value("pending", pending)?;
+            for (pseudospectrum, estimates) in results {
+                let frequencies = (0..grid_size).map(|g| 0.5 * sample_rate * g as f64 / (grid_size - 1) as f64).collect();
+                self.send_output::<Vec<f64>>("frequencies", frequencies)?;
+                self.send_output::<Vec<f64>>("pseudospectrum", pseudospectrum)?;
+                self.send_output::<Vec<f64>>("estimates", estimates)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Forward-backward averaged correlation matrix of the overlapping length `dimension` snapshots
+// of the frame. The averaging keeps it symmetric and decorrelates coherent sinusoids.
+pub fn correlation_matrix(frame: &[f64], dimension: usize) -> Vec<Vec<f64>> {
+    let snapshots = frame.len() + 1 - dimension;
+    let mut r = vec![vec![0.0; dimension]; dimension];
+    for snapshot in frame.windows(dimension) {
+        for i in 0..dimension {
+            for j in 0..dimension {
+                r[i][j] += snapshot[i] * snapshot[j] + snapshot[dimension - 1 - i] * snapshot[dimension - 1 - j];
+            }
+        }
+    }
+    r.iter_mut().flatten().for_each(|v| *v /= 2.0 * snapshots as f64);
+    r
+}
+
+// MUSIC pseudospectrum 1 / sum |e(f)^H v|^2 over the noise eigenvectors v, on `grid` frequencies
+// evenly spaced from 0 to half the sample rate, in cycles per sample. Each real sinusoid spans two
+// dimensions of the signal subspace.
+pub fn music(frame: &[f64], sinusoids: usize, dimension: usize, grid: usize) -> Vec<f64> {
+    let (_, vectors) = symmetric_eigen(&correlation_matrix(frame, dimension));
+    let noise = &vectors[2 * sinusoids..];
+    (0..grid).map(|g| {
+        let f = 0.5 * g as f64 / (grid - 1) as f64;
+        let projection: f64 = noise.iter().map(|v| {
+            let (re, im) = v.iter().enumerate().fold((0.0, 0.0), |(re, im), (k, vk)| {
+                let phase = 2.0 * PI * f * k as f64;
+                (re + vk * phase.cos(), im + vk * phase.sin())
+            });
+            re * re + im * im
+        }).sum();
+        1.0 / projection.max(f64::MIN_POSITIVE)
+    }).collect()
+}
+
+// Frequencies, in cycles per sample, of the `count` highest local maxima of the pseudospectrum,
+// refined by a parabola through the peak and its neighbours, in ascending order.
+pub fn peak_frequencies(pseudospectrum: &[f64], count: usize) -> Vec<f64> {
+    let n = pseudospectrum.len();
+    let mut peaks: Vec<usize> = (0..n).filter(|&i| {
+        (i == 0 || pseudospectrum[i] > pseudospectrum[i - 1]) && (i + 1 == n || pseudospectrum[i] >= pseudospectrum[i + 1])
+    }).collect();
+    peaks.sort_by(|a, b| pseudospectrum[*b].total_cmp(&pseudospectrum[*a]));
+    peaks.truncate(count);
+    let step = 0.5 / (n - 1) as f64;
+    let mut ret: Vec<f64> = peaks.iter().map(|&i| {
+        if i == 0 || i + 1 == n {
+            return i as f64 * step;
+        }
+        let (a, b, c) = (pseudospectrum[i - 1].ln(), pseudospectrum[i].ln(), pseudospectrum[i + 1].ln());
+        let curvature = a - 2.0 * b + c;
+        let offset = if curvature < 0.0 { 0.5 * (a - c) / curvature } else { 0.0 };
+        (i as f64 + offset) * step
+    }).collect();
+    ret.sort_by(f64::total_cmp);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_music() {
+        // two tones 0.01 cycles per sample apart, below the 1 / 64 resolution of a 64 sample FFT
+        let frame: Vec<f64> = (0..64).map(|n| {
+            let n = n as f64;
+            (2.0 * PI * 0.2 * n).cos() + 0.8 * (2.0 * PI * 0.21 * n + 1.0).cos()
+        }).collect();
+        let pseudospectrum = music(&frame, 2, 24, 2001);
+        let estimates = peak_frequencies(&pseudospectrum, 2);
+        assert_eq!(estimates.len(), 2);
+        assert!((estimates[0] - 0.2).abs() < 1.0e-3 && (estimates[1] - 0.21).abs() < 1.0e-3);
+        let r = correlation_matrix(&frame, 4);
+        assert!((0..4).all(|i| (0..4).all(|j| r[i][j] == r[j][i])));
+        assert_eq!(peak_frequencies(&[1.0, 2.0, 1.0, 3.0], 2), vec![0.5 / 3.0, 0.5]);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use rustfft::{FftPlanner, Fft, num_complex::Complex};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct OrderAnalysis {
+        inputs: { "input": Vec<f64>, "rpm": Vec<f64> },
+        outputs: { "order_spectrum": OrderSpectrum },
+        statics: {
+            "sample_rate": f64 = 1.0,
+            "samples_per_revolution": usize = 64,
+            "revolutions": usize = 16,
+        },
+        state: {
+            "angle": f64 = 0.0,
+            "next_angle": f64 = 0.0,
+            "previous_sample": f64 = 0.0,
+            "angle_samples": Vec<f64> = Vec::<f64>::new(),
+            "frame_rpm": Vec<f64> = Vec::<f64>::new(),
+        },
+        fields: { fft_core: Option<Arc<dyn Fft<f64>>> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let samples_per_revolution = self.get_statics::<usize>("samples_per_revolution")?.get_value();
+            let revolutions = self.get_statics::<usize>("revolutions")?.get_value();
+            if sample_rate.is_nan() || sample_rate <= 0.0 || samples_per_revolution < 2 || revolutions < 1 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mut planner = FftPlanner::new();
+            self.fft_core = Some(planner.plan_fft_forward(samples_per_revolution * revolutions));
+            self.set_state_value("angle", 0.0)?;
+            self.set_state_value("next_angle", 0.0)?;
+            self.set_state_value("previous_sample", 0.0)?;
+            self.set_state_value("angle_samples", Vec::<f64>::new())?;
+            self.set_state_value("frame_rpm", Vec::<f64>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let samples_per_revolution = self.get_statics::<usize>("samples_per_revolution")?.get_value();
+            let revolutions = self.get_statics::<usize>("revolutions")?.get_value();
+            let mut angle = self.get_state_value::<f64>("angle")?;
+            let mut next_angle = self.get_state_value::<f64>("next_angle")?;
+            let mut previous_sample = self.get_state_value::<f64>("previous_sample")?;
+            let mut angle_samples = self.get_state_value::<Vec<f64>>("angle_samples")?;
+            let mut frame_rpm = self.get_state_value::<Vec<f64>>("frame_rpm")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let rpm = self.recv_input::<Vec<f64>>("rpm")?;
+            if rpm.len() != input_signal.len() {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let frame_size = samples_per_revolution * revolutions;
+            let mut spectra = Vec::<OrderSpectrum>::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                let Some(fft) = self.fft_core.as_ref() else {
+                    return Err(StreamingError::InvalidStatics)
+                };
+                angle_samples.extend(angle_resample(&input_signal, &rpm, sample_rate, 1.0 / samples_per_revolution as f64,
+                    &mut angle, &mut next_angle, &mut previous_sample));
+                frame_rpm.extend(rpm.iter());
+                while angle_samples.len() >= frame_size {
+                    // Hann window, amplitudes scaled so a pure order of amplitude A reads A
+                    let window: Vec<f64> = (0..frame_size)
+                        .map(|n| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * n as f64 / frame_size as f64).cos())
+                        .collect();
+                    let gain: f64 = window.iter().sum();
+                    let mut frame: Vec<Complex<f64>> = angle_samples.drain(..frame_size).zip(window.iter())
+                        .map(|(x, w)| Complex::new(x * w, 0.0))
+                        .collect();
+                    fft.process(&mut frame);
+                    let bins = frame_size / 2 + 1;
+                    spectra.push(OrderSpectrum {
+                        orders: (0..bins).map(|k| k as f64 / revolutions as f64).collect(),
+                        amplitude: frame[..bins].iter().enumerate()
+                            .map(|(k, c)| c.norm() / gain * if k == 0 { 1.0 } else { 2.0 })
+                            .collect(),
+                        mean_rpm: frame_rpm.iter().sum::<f64>() / frame_rpm.len().max(1) as f64,
+                    });
+                    frame_rpm.clear();
+                }
+            }
+            self.set_state_value("angle", angle)?;
+            self.set_state_value("next_angle", next_angle)?;
+            self.set_state_value("previous_sample", previous_sample)?;
+            self.set_state_value("angle_samples", angle_samples)?;
+            self.set_state_value("frame_rpm", frame_rpm)?;
+            for spectrum in spectra {
+                self.send_output::<OrderSpectrum>("order_spectrum", spectrum)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrderSpectrum {
+    pub orders: Vec<f64>,
+    pub amplitude: Vec<f64>,
+    pub mean_rpm: f64,
+}
+
+// Resamples a time signal to a uniform shaft angle grid. `angle` (revolutions) and `previous_sample`
+// describe the last processed sample, `next_angle` is the next grid point; all carry across frames.
+pub fn angle_resample(signal: &[f64], rpm: &[f64], sample_rate: f64, step: f64,
+    angle: &mut f64, next_angle: &mut f64, previous_sample: &mut f64) -> Vec<f64> {
+    let mut resampled = Vec::new();
+    for (&x, &speed) in signal.iter().zip(rpm.iter()) {
+        let advance = speed.abs() / 60.0 / sample_rate;
+        let current_angle = *angle + advance;
+        while *next_angle <= current_angle && advance > 0.0 {
+            let fraction = (*next_angle - *angle) / advance;
+            resampled.push(*previous_sample + (x - *previous_sample) * fraction);
+            *next_angle += step;
+        }
+        *angle = current_angle;
+        *previous_sample = x;
+    }
+    resampled
+}
+
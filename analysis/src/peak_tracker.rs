@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use rustfft::{FftPlanner, Fft, num_complex::Complex};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct PeakTracker {
+        inputs: { "input": Vec<f64> },
+        outputs: { "tracks": Vec<PeakTrack>, "noise_floor": f64 },
+        statics: {
+            "sample_rate": f64 = 1.0,
+            "frame_size": usize = 2048,
+            "hop_size": usize = 1024,
+            "max_peaks": usize = 5,
+            "threshold_db": f64 = 10.0,
+            "max_deviation": f64 = 5.0,
+            "max_missed": usize = 3,
+        },
+        state: {
+            "pending": Vec<f64> = Vec::<f64>::new(),
+            "active_tracks": Vec<PeakTrack> = Vec::<PeakTrack>::new(),
+            "next_id": usize = 0,
+        },
+        fields: { fft_core: Option<Arc<dyn Fft<f64>>> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let hop_size = self.get_statics::<usize>("hop_size")?.get_value();
+            let max_peaks = self.get_statics::<usize>("max_peaks")?.get_value();
+            if sample_rate.is_nan() || sample_rate <= 0.0 || frame_size < 4 || hop_size < 1 || max_peaks < 1 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mut planner = FftPlanner::new();
+            self.fft_core = Some(planner.plan_fft_forward(frame_size));
+            self.set_state_value("pending", Vec::<f64>::new())?;
+            self.set_state_value("active_tracks", Vec::<PeakTrack>::new())?;
+            self.set_state_value("next_id", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let hop_size = self.get_statics::<usize>("hop_size")?.get_value();
+            let max_peaks = self.get_statics::<usize>("max_peaks")?.get_value();
+            let threshold_db = self.get_statics::<f64>("threshold_db")?.get_value();
+            let max_deviation = self.get_statics::<f64>("max_deviation")?.get_value();
+            let max_missed = self.get_statics::<usize>("max_missed")?.get_value();
+            let mut pending = self.get_state_value::<Vec<f64>>("pending")?;
+            let mut tracks = self.get_state_value::<Vec<PeakTrack>>("active_tracks")?;
+            let mut next_id = self.get_state_value::<usize>("next_id")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut reports = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                pending.extend(input_signal);
+                let window: Vec<f64> = (0..frame_size)
+                    .map(|n| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * n as f64 / frame_size as f64).cos())
+                    .collect();
+                let gain: f64 = window.iter().sum();
+                while pending.len() >= frame_size {
+                    let mut frame: Vec<Complex<f64>> = pending[..frame_size].iter().zip(window.iter())
+                        .map(|(x, w)| Complex::new(x * w, 0.0))
+                        .collect();
+                    self.fft_core.as_ref().unwrap().process(&mut frame);
+                    pending.drain(..hop_size.min(pending.len()));
+                    let magnitude: Vec<f64> = frame[..frame_size / 2 + 1].iter().map(|c| 2.0 * c.norm() / gain).collect();
+                    // the median bin is a robust noise floor as long as tones occupy few bins
+                    let mut sorted = magnitude.clone();
+                    sorted.sort_by(|a, b| a.total_cmp(b));
+                    let noise_floor = sorted[sorted.len() / 2];
+                    let threshold = noise_floor * 10.0_f64.powf(threshold_db / 20.0);
+                    let peaks = find_peaks(&magnitude, sample_rate / frame_size as f64, threshold, max_peaks);
+                    associate(&mut tracks, &peaks, max_deviation, max_missed, &mut next_id);
+                    reports.push((tracks.iter().filter(|track| track.missed == 0).cloned().collect::<Vec<PeakTrack>>(), noise_floor));
+                }
+            }
+            self.set_state_value("pending", pending)?;
+            self.set_state_value("active_tracks", tracks)?;
+            self.set_state_value("next_id", next_id)?;
+            for (tracks, noise_floor) in reports {
+                self.send_output::<Vec<PeakTrack>>("tracks", tracks)?;
+                self.send_output::<f64>("noise_floor", noise_floor)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeakTrack {
+    pub id: usize,
+    pub frequency: f64,
+    pub amplitude: f64,
+    pub age: usize,
+    pub missed: usize,
+}
+
+// Local maxima above `threshold` sorted by decreasing amplitude, frequency refined by
+// parabolic interpolation of the log magnitude. Returns (frequency, amplitude) pairs.
+pub fn find_peaks(magnitude: &[f64], resolution: f64, threshold: f64, max_peaks: usize) -> Vec<(f64, f64)> {
+    let mut peaks = Vec::new();
+    for k in 1..magnitude.len().saturating_sub(1) {
+        let (left, center, right) = (magnitude[k - 1], magnitude[k], magnitude[k + 1]);
+        if center <= threshold || center < left || center <= right {
+            continue;
+        }
+        let (a, b, c) = (left.max(1.0e-300).ln(), center.ln(), right.max(1.0e-300).ln());
+        let curvature = a - 2.0 * b + c;
+        let offset = if curvature < 0.0 { 0.5 * (a - c) / curvature } else { 0.0 };
+        peaks.push(((k as f64 + offset) * resolution, (b - 0.25 * (a - c) * offset).exp()));
+    }
+    peaks.sort_by(|x, y| y.1.total_cmp(&x.1));
+    peaks.truncate(max_peaks);
+    peaks
+}
+
+// Greedy association, strongest peaks first, each to the closest free track within max_deviation.
+pub fn associate(tracks: &mut Vec<PeakTrack>, peaks: &[(f64, f64)], max_deviation: f64, max_missed: usize, next_id: &mut usize) {
+    let mut matched = vec![false; tracks.len()];
+    for &(frequency, amplitude) in peaks {
+        let closest = tracks.iter().enumerate()
+            .filter(|(index, track)| !matched[*index] && (track.frequency - frequency).abs() <= max_deviation)
+            .min_by(|(_, x), (_, y)| (x.frequency - frequency).abs().total_cmp(&(y.frequency - frequency).abs()))
+            .map(|(index, _)| index);
+        match closest {
+            Some(index) => {
+                matched[index] = true;
+                let track = &mut tracks[index];
+                track.frequency = frequency;
+                track.amplitude = amplitude;
+                track.age += 1;
+                track.missed = 0;
+            }
+            None => {
+                tracks.push(PeakTrack { id: *next_id, frequency, amplitude, age: 1, missed: 0 });
+                matched.push(true);
+                *next_id += 1;
+            }
+        }
+    }
+    for (track, matched) in tracks.iter_mut().zip(matched.iter()) {
+        if !matched {
+            track.missed += 1;
+        }
+    }
+    tracks.retain(|track| track.missed <= max_missed);
+}
+
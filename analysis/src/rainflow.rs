@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct Rainflow {
+        inputs: { "input": Vec<f64> },
+        outputs: { "histogram": RainflowHistogram },
+        statics: {
+            "bins": usize = 32,
+            "max_range": f64 = 1.0,
+            "hysteresis": f64 = 0.0,
+            "report_interval": usize = 1024,
+        },
+        state: {
+            "counter": RainflowCounter = RainflowCounter::default(),
+            "counts": Vec<f64> = Vec::<f64>::new(),
+            "pending_samples": usize = 0,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let bins = self.get_statics::<usize>("bins")?.get_value();
+            let max_range = self.get_statics::<f64>("max_range")?.get_value();
+            let hysteresis = self.get_statics::<f64>("hysteresis")?.get_value();
+            let report_interval = self.get_statics::<usize>("report_interval")?.get_value();
+            if bins < 1 || report_interval < 1 || max_range.is_nan() || max_range <= 0.0 || hysteresis.is_nan() || hysteresis < 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("counter", RainflowCounter::new(hysteresis))?;
+            self.set_state_value("counts", vec![0.0; bins])?;
+            self.set_state_value("pending_samples", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let max_range = self.get_statics::<f64>("max_range")?.get_value();
+            let report_interval = self.get_statics::<usize>("report_interval")?.get_value();
+            let mut counter = self.get_state_value::<RainflowCounter>("counter")?;
+            let mut counts = self.get_state_value::<Vec<f64>>("counts")?;
+            let mut pending_samples = self.get_state_value::<usize>("pending_samples")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut report = None;
+            {
+                let _lock = self.lock.lock().unwrap();
+                let mut cycles = Vec::new();
+                for &x in input_signal.iter() {
+                    counter.push(x, &mut cycles);
+                }
+                accumulate(&mut counts, &cycles, max_range);
+                pending_samples += input_signal.len();
+                if pending_samples >= report_interval {
+                    pending_samples = 0;
+                    let mut residue = vec![0.0; counts.len()];
+                    accumulate(&mut residue, &counter.residue(), max_range);
+                    let bins = counts.len();
+                    report = Some(RainflowHistogram {
+                        bin_edges: (0..=bins).map(|k| max_range * k as f64 / bins as f64).collect(),
+                        counts: counts.clone(),
+                        residue,
+                    });
+                }
+            }
+            self.set_state_value("counter", counter)?;
+            self.set_state_value("counts", counts)?;
+            self.set_state_value("pending_samples", pending_samples)?;
+            if let Some(histogram) = report {
+                self.send_output::<RainflowHistogram>("histogram", histogram)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Cumulative cycle counts per range bin, residue holds the half cycles still open at report time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RainflowHistogram {
+    pub bin_edges: Vec<f64>,
+    pub counts: Vec<f64>,
+    pub residue: Vec<f64>,
+}
+
+fn accumulate(counts: &mut [f64], cycles: &[Cycle], max_range: f64) {
+    let bins = counts.len();
+    for cycle in cycles {
+        let bin = ((cycle.range / max_range * bins as f64) as usize).min(bins - 1);
+        counts[bin] += cycle.count;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cycle {
+    pub range: f64,
+    pub mean: f64,
+    pub count: f64,
+}
+
+// Streaming ASTM E1049 rainflow counter. Reversals smaller than `hysteresis` are ignored, the
+// unmatched reversals stay on the stack and are reported as half cycles by `residue`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RainflowCounter {
+    hysteresis: f64,
+    stack: Vec<f64>,
+    candidate: Option<f64>,
+    direction: i8,
+}
+impl RainflowCounter {
+    pub fn new(hysteresis: f64) -> Self {
+        RainflowCounter { hysteresis, stack: Vec::new(), candidate: None, direction: 0 }
+    }
+    pub fn push(&mut self, value: f64, cycles: &mut Vec<Cycle>) {
+        let Some(candidate) = self.candidate else {
+            self.candidate = Some(value);
+            return;
+        };
+        let change = value - candidate;
+        if self.direction == 0 {
+            if change.abs() > self.hysteresis {
+                self.reversal(candidate, cycles);
+                self.direction = if change > 0.0 { 1 } else { -1 };
+                self.candidate = Some(value);
+            }
+        } else if change * self.direction as f64 >= 0.0 {
+            self.candidate = Some(value);
+        } else if change.abs() > self.hysteresis {
+            self.reversal(candidate, cycles);
+            self.direction = -self.direction;
+            self.candidate = Some(value);
+        }
+    }
+    fn reversal(&mut self, point: f64, cycles: &mut Vec<Cycle>) {
+        self.stack.push(point);
+        while self.stack.len() >= 3 {
+            let n = self.stack.len();
+            let x = (self.stack[n - 1] - self.stack[n - 2]).abs();
+            let y = (self.stack[n - 2] - self.stack[n - 3]).abs();
+            if x < y {
+                break;
+            }
+            let mean = (self.stack[n - 2] + self.stack[n - 3]) / 2.0;
+            if n == 3 {
+                cycles.push(Cycle { range: y, mean, count: 0.5 });
+                self.stack.remove(0);
+            } else {
+                cycles.push(Cycle { range: y, mean, count: 1.0 });
+                self.stack.drain(n - 3..n - 1);
+            }
+        }
+    }
+    // Half cycles between the reversals not closed yet, including the pending extreme.
+    pub fn residue(&self) -> Vec<Cycle> {
+        let mut points = self.stack.clone();
+        if self.direction != 0 {
+            points.extend(self.candidate);
+        }
+        points.windows(2)
+            .map(|pair| Cycle { range: (pair[1] - pair[0]).abs(), mean: (pair[0] + pair[1]) / 2.0, count: 0.5 })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_astm_example() {
+        let mut counter = RainflowCounter::new(0.0);
+        let mut cycles = Vec::new();
+        for value in [-2.0, 1.0, -3.0, 5.0, -1.0, 3.0, -4.0, 4.0, -2.0] {
+            counter.push(value, &mut cycles);
+        }
+        cycles.extend(counter.residue());
+        let total = |range: f64| cycles.iter().filter(|c| c.range == range).map(|c| c.count).sum::<f64>();
+        assert_eq!(total(3.0), 0.5);
+        assert_eq!(total(4.0), 1.5);
+        assert_eq!(total(6.0), 0.5);
+        assert_eq!(total(8.0), 1.0);
+        assert_eq!(total(9.0), 0.5);
+        assert_eq!(cycles.iter().map(|c| c.count).sum::<f64>(), 4.0);
+    }
+}
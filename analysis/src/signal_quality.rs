@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use event_bus::EventKind;
+
+stream_block! {
+    pub struct SignalQuality {
+        inputs: { "input": Vec<f64> },
+        outputs: { "score": f64, "report": QualityReport },
+        statics: {
+            "clip_level": f64 = 1.0,
+            "clip_tolerance": f64 = 1.0e-6,
+            "clip_run": usize = 3,
+            "stuck_run": usize = 64,
+            "zero_run": usize = 64,
+            "publish_events": bool = true,
+        },
+        state: { "monitor": QualityMonitor = QualityMonitor::default() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let clip_level = self.get_statics::<f64>("clip_level")?.get_value();
+            let clip_run = self.get_statics::<usize>("clip_run")?.get_value();
+            let stuck_run = self.get_statics::<usize>("stuck_run")?.get_value();
+            let zero_run = self.get_statics::<usize>("zero_run")?.get_value();
+            if clip_level.is_nan() || clip_level <= 0.0 || clip_run < 1 || stuck_run < 2 || zero_run < 1 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("monitor", QualityMonitor::default())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let clip_level = self.get_statics::<f64>("clip_level")?.get_value();
+            let clip_tolerance = self.get_statics::<f64>("clip_tolerance")?.get_value();
+            let clip_run = self.get_statics::<usize>("clip_run")?.get_value();
+            let stuck_run = self.get_statics::<usize>("stuck_run")?.get_value();
+            let zero_run = self.get_statics::<usize>("zero_run")?.get_value();
+            let publish_events = self.get_statics::<bool>("publish_events")?.get_value();
+            let mut monitor = self.get_state_value::<QualityMonitor>("monitor")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let report;
+            {
+                let _lock = self.lock.lock().unwrap();
+                report = monitor.check(&input_signal, clip_level, clip_tolerance, (clip_run, stuck_run, zero_run));
+            }
+            self.set_state_value("monitor", monitor)?;
+            if publish_events && report.score < 1.0 {
+                event_bus::publish(self.name, EventKind::Quality, serde_json::to_value(&report).unwrap_or_default());
+            }
+            self.send_output::<f64>("score", report.score)?;
+            self.send_output::<QualityReport>("report", report)?;
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QualityReport {
+    pub samples: usize,
+    pub clipped: usize,
+    pub stuck: usize,
+    pub zero: usize,
+    pub non_finite: usize,
+    pub score: f64,
+}
+
+// Run lengths carried across frames. A sample is flagged once its run reaches the configured
+// length, the samples already in the run are flagged at that moment.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QualityMonitor {
+    clip_run: usize,
+    stuck_run: usize,
+    zero_run: usize,
+    last_value: Option<f64>,
+}
+impl QualityMonitor {
+    pub fn check(&mut self, signal: &[f64], clip_level: f64, clip_tolerance: f64, runs: (usize, usize, usize)) -> QualityReport {
+        let (clip_limit, stuck_limit, zero_limit) = runs;
+        let flag = |run: usize, limit: usize| if run == limit { limit } else if run > limit { 1 } else { 0 };
+        let mut report = QualityReport { samples: signal.len(), ..QualityReport::default() };
+        let mut flagged = 0;
+        for &x in signal {
+            if !x.is_finite() {
+                report.non_finite += 1;
+                flagged += 1;
+                self.clip_run = 0;
+                self.stuck_run = 0;
+                self.zero_run = 0;
+                self.last_value = None;
+                continue;
+            }
+            self.clip_run = if x.abs() >= clip_level - clip_tolerance { self.clip_run + 1 } else { 0 };
+            self.zero_run = if x == 0.0 { self.zero_run + 1 } else { 0 };
+            self.stuck_run = if self.last_value == Some(x) && x != 0.0 { self.stuck_run + 1 } else { 1 };
+            self.last_value = Some(x);
+            let clipped = flag(self.clip_run, clip_limit);
+            let zero = flag(self.zero_run, zero_limit);
+            let stuck = if clipped > 0 { 0 } else { flag(self.stuck_run, stuck_limit) };
+            report.clipped += clipped;
+            report.zero += zero;
+            report.stuck += stuck;
+            flagged += clipped + zero + stuck;
+        }
+        report.score = if signal.is_empty() { 1.0 } else { 1.0 - (flagged.min(signal.len()) as f64 / signal.len() as f64) };
+        report
+    }
+}
+
@@ -0,0 +1,162 @@
+// Solves the normal equations of a weighted polynomial fit, sums[k] = sum w t^k and
+// rhs[k] = sum w t^k y, and returns the constant coefficient, the fit at t = 0. None when the
+// system is singular.
+fn polynomial_at_origin(sums: &[f64], rhs: &[f64], degree: usize) -> Option<f64> {
+    let size = degree + 1;
+    let mut system: Vec<Vec<f64>> = (0..size).map(|i| {
+        let mut row: Vec<f64> = (0..size).map(|j| sums[i + j]).collect();
+        row.push(rhs[i]);
+        row
+    }).collect();
+    let scale = sums[0].abs().max(f64::MIN_POSITIVE);
+    for c in 0..size {
+        let pivot = (c..size).max_by(|a, b| system[*a][c].abs().total_cmp(&system[*b][c].abs()))?;
+        if system[pivot][c].abs() <= 1.0e-12 * scale {
+            return None;
+        }
+        system.swap(c, pivot);
+        let (upper, lower) = system.split_at_mut(c + 1);
+        for row in lower {
+            let factor = row[c] / upper[c][c];
+            row[c..].iter_mut().zip(&upper[c][c..]).for_each(|(v, p)| *v -= factor * p);
+        }
+    }
+    let mut coefficients = vec![0.0; size];
+    for r in (0..size).rev() {
+        let known: f64 = (r + 1..size).map(|k| system[r][k] * coefficients[k]).sum();
+        coefficients[r] = (system[r][size] - known) / system[r][r];
+    }
+    Some(coefficients[0])
+}
+
+// Tricube weighted local polynomial regression of y, sampled at 0, 1, .., n - 1, evaluated at
+// `at` from its q nearest samples. When q exceeds the samples the bandwidth grows by q / n, as in
+// STL. Non empty `robustness` multiplies the weights. A degenerate fit falls back to a lower
+// degree, down to the weighted mean.
+pub fn loess(y: &[f64], robustness: &[f64], at: f64, q: usize, degree: usize) -> f64 {
+    let n = y.len();
+    if n == 0 {
+        return 0.0;
+    }
+    let neighbours = q.clamp(1, n);
+    let start = (at - (neighbours - 1) as f64 / 2.0).round().clamp(0.0, (n - neighbours) as f64) as usize;
+    let end = start + neighbours;
+    let mut bandwidth = (at - start as f64).abs().max((at - (end - 1) as f64).abs());
+    if q > n {
+        bandwidth *= q as f64 / n as f64;
+    }
+    let mut sums = vec![0.0; 2 * degree + 1];
+    let mut rhs = vec![0.0; degree + 1];
+    for i in start..end {
+        let t = i as f64 - at;
+        let u = if bandwidth > 0.0 { (t.abs() / bandwidth).min(1.0) } else { 0.0 };
+        let mut w = (1.0 - u * u * u).powi(3);
+        if !robustness.is_empty() {
+            w *= robustness[i];
+        }
+        let mut power = w;
+        for (k, sum) in sums.iter_mut().enumerate() {
+            *sum += power;
+            if k <= degree {
+                rhs[k] += power * y[i];
+            }
+            power *= t;
+        }
+    }
+    if sums[0] <= 0.0 {
+        return y[start..end].iter().sum::<f64>() / neighbours as f64;
+    }
+    (0..=degree).rev().find_map(|d| polynomial_at_origin(&sums, &rhs, d)).unwrap_or(rhs[0] / sums[0])
+}
+
+// Whittaker smoother, the discrete penalized spline minimizing
+// sum (y - z)^2 + lambda * sum (second difference of z)^2. (I + lambda D'D) z = y is solved by a
+// banded Cholesky factorization in O(n).
+pub fn whittaker(y: &[f64], lambda: f64) -> Vec<f64> {
+    let n = y.len();
+    if n < 3 || lambda <= 0.0 {
+        return y.to_vec();
+    }
+    // bands of I + lambda D'D, D the (n - 2) x n second difference matrix
+    let mut diagonal = vec![6.0; n];
+    diagonal[0] = 1.0;
+    diagonal[n - 1] = 1.0;
+    if n > 3 {
+        diagonal[1] = 5.0;
+        diagonal[n - 2] = 5.0;
+    } else {
+        diagonal[1] = 4.0;
+    }
+    let mut first = vec![-4.0; n];
+    first[1] = -2.0;
+    first[n - 1] = -2.0;
+    let diagonal: Vec<f64> = diagonal.iter().map(|d| 1.0 + lambda * d).collect();
+    let first: Vec<f64> = first.iter().map(|d| lambda * d).collect();
+    let second = lambda;
+    // L[i][i], L[i][i - 1] and L[i][i - 2] of the factor
+    let (mut l0, mut l1, mut l2) = (vec![0.0; n], vec![0.0; n], vec![0.0; n]);
+    for i in 0..n {
+        if i >= 2 {
+            l2[i] = second / l0[i - 2];
+        }
+        if i >= 1 {
+            let above = if i >= 2 { l2[i] * l1[i - 1] } else { 0.0 };
+            l1[i] = (first[i] - above) / l0[i - 1];
+        }
+        l0[i] = (diagonal[i] - l1[i] * l1[i] - l2[i] * l2[i]).sqrt();
+    }
+    let mut z = vec![0.0; n];
+    for i in 0..n {
+        let mut value = y[i];
+        if i >= 1 {
+            value -= l1[i] * z[i - 1];
+        }
+        if i >= 2 {
+            value -= l2[i] * z[i - 2];
+        }
+        z[i] = value / l0[i];
+    }
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let mut value = z[i];
+        if i + 1 < n {
+            value -= l1[i + 1] * x[i + 1];
+        }
+        if i + 2 < n {
+            value -= l2[i + 2] * x[i + 2];
+        }
+        x[i] = value / l0[i];
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_smoothers() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1.0e-9;
+        let line: Vec<f64> = (0..20).map(|i| 2.0 - 0.5 * i as f64).collect();
+        let parabola: Vec<f64> = (0..20).map(|i| (i as f64 - 7.0).powi(2)).collect();
+        // local fits reproduce polynomials of their degree, inside and beyond the samples
+        for at in [0.0, 4.5, 19.0, 20.0, -1.0] {
+            assert!(close(loess(&line, &[], at, 7, 1), 2.0 - 0.5 * at));
+            assert!(close(loess(&parabola, &[], at, 9, 2), (at - 7.0).powi(2)));
+            assert!(close(loess(&line, &[], at, 40, 1), 2.0 - 0.5 * at));
+        }
+        // a zero robustness weight removes an outlier
+        let mut spiked = line.clone();
+        spiked[10] = 100.0;
+        let mut robustness = vec![1.0; 20];
+        robustness[10] = 0.0;
+        assert!(close(loess(&spiked, &robustness, 10.0, 7, 1), -3.0));
+        // the spline penalty leaves straight lines alone and flattens curvature as lambda grows
+        assert!(whittaker(&line, 1.0e3).iter().zip(&line).all(|(a, b)| (a - b).abs() < 1.0e-6));
+        let smooth = whittaker(&parabola, 1.0e8);
+        let curvature = |z: &[f64]| z.windows(3).map(|w| (w[0] - 2.0 * w[1] + w[2]).abs()).fold(0.0, f64::max);
+        assert!(curvature(&smooth) < 1.0e-3 && curvature(&parabola) > 1.0);
+        assert_eq!(whittaker(&parabola, 0.0), parabola);
+        let three = whittaker(&[0.0, 3.0, 0.0], 1.0);
+        assert!(close(three.iter().sum::<f64>(), 3.0) && three[1] < 3.0);
+    }
+}
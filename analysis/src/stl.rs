@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+use crate::smoothing::loess;
+
+// Seasonal-trend decomposition by LOESS of windows of periods * period samples, emitted as the
+// seasonal, trend and remainder parts once each window is complete. seasonal_span is the LOESS
+// span, in periods, smoothing each cycle subseries. With robust the outer loop reweights the fits
+// so that outliers end up in the remainder instead of bending the trend.
+stream_block! {
+    pub struct Stl {
+        inputs: { "input": Vec<f64> },
+        outputs: { "seasonal": Vec<f64>, "trend": Vec<f64>, "remainder": Vec<f64> },
+        statics: {
+            "period": usize = 0,
+            "periods": usize = 4,
+            "seasonal_span": usize = 7,
+            "robust": bool = false,
+        },
+        state: { "window": Vec<f64> = Vec::<f64>::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let period = self.get_statics::<usize>("period")?.get_value();
+            let periods = self.get_statics::<usize>("periods")?.get_value();
+            let seasonal_span = self.get_statics::<usize>("seasonal_span")?.get_value();
+            if period < 2 || periods < 2 || seasonal_span < 3 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("window", Vec::<f64>::with_capacity(period * periods))?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let period = self.get_statics::<usize>("period")?.get_value();
+            let periods = self.get_statics::<usize>("periods")?.get_value();
+            let seasonal_span = self.get_statics::<usize>("seasonal_span")?.get_value();
+            let robust = self.get_statics::<bool>("robust")?.get_value();
+            let mut window = self.get_state_value::<Vec<f64>>("window")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut decompositions = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                // the iteration counts recommended by the STL paper
+                let (inner, outer) = if robust { (1, 15) } else { (2, 0) };
+                for x in input_signal {
+                    window.push(x);
+                    if window.len() == period * periods {
+                        decompositions.push(stl(&window, period, seasonal_span, inner, outer));
+                        window.clear();
+                    }
+                }
+            }
+            self.set_state_value("window", window)?;
+            for decomposition in decompositions {
+                self.send_output::<Vec<f64>>("seasonal", decomposition.seasonal)?;
+                self.send_output::<Vec<f64>>("trend", decomposition.trend)?;
+                self.send_output::<Vec<f64>>("remainder", decomposition.remainder)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Decomposition {
+    pub seasonal: Vec<f64>,
+    pub trend: Vec<f64>,
+    pub remainder: Vec<f64>,
+}
+
+fn moving_average(x: &[f64], length: usize) -> Vec<f64> {
+    x.windows(length).map(|w| w.iter().sum::<f64>() / length as f64).collect()
+}
+
+fn next_odd(x: f64) -> usize {
+    let n = x.ceil() as usize;
+    if n.is_multiple_of(2) { n + 1 } else { n }
+}
+
+// STL (Cleveland et al., 1990) with local linear fits: the inner loop smooths the cycle
+// subseries of the detrended series, removes their low-pass part to get the seasonal component
+// and smooths the deseasonalized series into the trend. Each outer iteration recomputes bisquare
+// robustness weights from the remainder.
+pub fn stl(y: &[f64], period: usize, seasonal_span: usize, inner: usize, outer: usize) -> Decomposition {
+    let n = y.len();
+    let seasonal_span = next_odd(seasonal_span.max(7) as f64);
+    let low_pass_span = next_odd(period as f64);
+    let trend_span = next_odd(1.5 * period as f64 / (1.0 - 1.5 / seasonal_span as f64));
+    let mut robustness = Vec::new();
+    let mut seasonal = vec![0.0; n];
+    let mut trend = vec![0.0; n];
+    for iteration in 0..=outer {
+        for _ in 0..inner {
+            let detrended: Vec<f64> = y.iter().zip(&trend).map(|(y, t)| y - t).collect();
+            // each subseries is extended by one cycle at both ends, hence the period offset below
+            let mut cycles = vec![0.0; n + 2 * period];
+            for phase in 0..period {
+                let subseries: Vec<f64> = detrended.iter().skip(phase).step_by(period).copied().collect();
+                let weights: Vec<f64> = robustness.iter().skip(phase).step_by(period).copied().collect();
+                for k in 0..subseries.len() + 2 {
+                    cycles[phase + k * period] = loess(&subseries, &weights, k as f64 - 1.0, seasonal_span, 1);
+                }
+            }
+            let low_pass = moving_average(&moving_average(&moving_average(&cycles, period), period), 3);
+            let low_pass: Vec<f64> = (0..n).map(|i| loess(&low_pass, &[], i as f64, low_pass_span, 1)).collect();
+            seasonal = (0..n).map(|i| cycles[period + i] - low_pass[i]).collect();
+            let deseasonalized: Vec<f64> = y.iter().zip(&seasonal).map(|(y, s)| y - s).collect();
+            trend = (0..n).map(|i| loess(&deseasonalized, &robustness, i as f64, trend_span, 1)).collect();
+        }
+        if iteration < outer {
+            let residuals: Vec<f64> = (0..n).map(|i| (y[i] - seasonal[i] - trend[i]).abs()).collect();
+            let mut sorted = residuals.clone();
+            sorted.sort_by(f64::total_cmp);
+            let h = 6.0 * (sorted[(n - 1) / 2] + sorted[n / 2]) / 2.0;
+            robustness = residuals.iter().map(|r| {
+                let u = if h > 0.0 { r / h } else { 0.0 };
+                if u < 1.0 { (1.0 - u * u).powi(2) } else { 0.0 }
+            }).collect();
+        }
+    }
+    let remainder = (0..n).map(|i| y[i] - seasonal[i] - trend[i]).collect();
+    Decomposition { seasonal, trend, remainder }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_stl() {
+        let period = 12;
+        let pattern: Vec<f64> = (0..period).map(|k| (2.0 * std::f64::consts::PI * k as f64 / period as f64).sin()).collect();
+        let mut y: Vec<f64> = (0..96).map(|i| 0.05 * i as f64 + pattern[i % period]).collect();
+        let result = stl(&y, period, 7, 2, 0);
+        for i in 0..y.len() {
+            assert!((result.seasonal[i] - pattern[i % period]).abs() < 0.05, "{} {}", i, result.seasonal[i]);
+            assert!((result.trend[i] - 0.05 * i as f64).abs() < 0.05, "{} {}", i, result.trend[i]);
+            assert!((result.seasonal[i] + result.trend[i] + result.remainder[i] - y[i]).abs() < 1.0e-12);
+        }
+        // the robust fit leaves an outlier in the remainder
+        y[50] += 20.0;
+        let result = stl(&y, period, 7, 1, 15);
+        assert!(result.remainder[50] > 19.0);
+        assert!((result.trend[50] - 2.5).abs() < 0.1 && (result.seasonal[50] - pattern[50 % period]).abs() < 0.1);
+    }
+}
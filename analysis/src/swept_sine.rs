@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use rustfft::{FftPlanner, num_complex::Complex};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct SweptSine {
+        inputs: { "response": Vec<f64> },
+        outputs: {
+            "excitation": Vec<f64>,
+            "impulse_response": Vec<f64>,
+            "frequency_response": MeasuredResponse,
+        },
+        statics: {
+            "sample_rate": f64 = 48000.0,
+            "start_frequency": f64 = 20.0,
+            "stop_frequency": f64 = 20000.0,
+            "duration": f64 = 5.0,
+            "tail": f64 = 1.0,
+            "amplitude": f64 = 0.5,
+            "frame_size": usize = 1024,
+            "ir_length": usize = 4096,
+            "repeat": bool = false,
+        },
+        state: {
+            "sweep": Vec<f64> = Vec::<f64>::new(),
+            "position": usize = 0,
+            "recorded": Vec<f64> = Vec::<f64>::new(),
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let start_frequency = self.get_statics::<f64>("start_frequency")?.get_value();
+            let stop_frequency = self.get_statics::<f64>("stop_frequency")?.get_value();
+            let duration = self.get_statics::<f64>("duration")?.get_value();
+            let tail = self.get_statics::<f64>("tail")?.get_value();
+            let amplitude = self.get_statics::<f64>("amplitude")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let ir_length = self.get_statics::<usize>("ir_length")?.get_value();
+            if !(sample_rate > 0.0 && start_frequency > 0.0 && stop_frequency > start_frequency && duration > 0.0 && tail >= 0.0)
+                || stop_frequency > sample_rate / 2.0 || frame_size < 1 || ir_length < 2 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mut sweep = exponential_sweep(start_frequency, stop_frequency, duration, sample_rate, amplitude);
+            sweep.resize(sweep.len() + (tail * sample_rate).round() as usize, 0.0);
+            self.set_state_value("sweep", sweep)?;
+            self.set_state_value("position", 0usize)?;
+            self.set_state_value("recorded", Vec::<f64>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let ir_length = self.get_statics::<usize>("ir_length")?.get_value();
+            let repeat = self.get_statics::<bool>("repeat")?.get_value();
+            let sweep = self.get_state_value::<Vec<f64>>("sweep")?;
+            let mut position = self.get_state_value::<usize>("position")?;
+            let mut recorded = self.get_state_value::<Vec<f64>>("recorded")?;
+            // the excitation frame goes out first, the device under test answers on the response input;
+            // once the sweep and its tail are recorded the block keeps sending silence unless repeating
+            let end = (position + frame_size).min(sweep.len());
+            let mut excitation = sweep[position.min(end)..end].to_vec();
+            excitation.resize(frame_size, 0.0);
+            self.send_output::<Vec<f64>>("excitation", excitation)?;
+            let response = self.recv_input::<Vec<f64>>("response")?;
+            let mut result = None;
+            if position < sweep.len() {
+                let _lock = self.lock.lock().unwrap();
+                recorded.extend(response);
+                position = end;
+                if position >= sweep.len() {
+                    recorded.truncate(sweep.len());
+                    let impulse_response = deconvolve(&sweep, &recorded, ir_length);
+                    let response = measured_response(&impulse_response, sample_rate);
+                    result = Some((impulse_response, response));
+                    recorded.clear();
+                    if repeat {
+                        position = 0;
+                    }
+                }
+            }
+            self.set_state_value("position", position)?;
+            self.set_state_value("recorded", recorded)?;
+            if let Some((impulse_response, response)) = result {
+                self.send_output::<Vec<f64>>("impulse_response", impulse_response)?;
+                self.send_output::<MeasuredResponse>("frequency_response", response)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeasuredResponse {
+    pub frequencies: Vec<f64>,
+    pub magnitude: Vec<f64>,
+    pub phase: Vec<f64>,
+}
+
+// Exponential (Farina) sweep from start to stop frequency.
+pub fn exponential_sweep(start_frequency: f64, stop_frequency: f64, duration: f64, sample_rate: f64, amplitude: f64) -> Vec<f64> {
+    let rate = (stop_frequency / start_frequency).ln();
+    let samples = (duration * sample_rate).round() as usize;
+    (0..samples).map(|n| {
+        let t = n as f64 / sample_rate;
+        amplitude * (2.0 * std::f64::consts::PI * start_frequency * duration / rate * ((t * rate / duration).exp() - 1.0)).sin()
+    }).collect()
+}
+
+// Impulse response by regularized spectral division of the recording by the excitation. The
+// regularization only matters outside the swept band, where the excitation has no energy;
+// harmonic distortion products wrap to the end of the linear convolution and are cut by ir_length.
+pub fn deconvolve(excitation: &[f64], recorded: &[f64], ir_length: usize) -> Vec<f64> {
+    let size = (excitation.len() + recorded.len()).next_power_of_two();
+    let mut planner = FftPlanner::new();
+    let forward = planner.plan_fft_forward(size);
+    let inverse = planner.plan_fft_inverse(size);
+    let spectrum = |signal: &[f64]| {
+        let mut buffer: Vec<Complex<f64>> = signal.iter().map(|x| Complex::new(*x, 0.0)).collect();
+        buffer.resize(size, Complex::new(0.0, 0.0));
+        forward.process(&mut buffer);
+        buffer
+    };
+    let excitation_spectrum = spectrum(excitation);
+    let recorded_spectrum = spectrum(recorded);
+    let peak = excitation_spectrum.iter().map(|c| c.norm_sqr()).fold(0.0, f64::max);
+    let regularization = 1.0e-6 * peak;
+    let mut transfer: Vec<Complex<f64>> = recorded_spectrum.iter().zip(excitation_spectrum.iter())
+        .map(|(r, s)| r * s.conj() / (s.norm_sqr() + regularization))
+        .collect();
+    inverse.process(&mut transfer);
+    transfer.iter().take(ir_length).map(|c| c.re / size as f64).collect()
+}
+
+pub fn measured_response(impulse_response: &[f64], sample_rate: f64) -> MeasuredResponse {
+    let size = impulse_response.len();
+    let mut buffer: Vec<Complex<f64>> = impulse_response.iter().map(|x| Complex::new(*x, 0.0)).collect();
+    FftPlanner::new().plan_fft_forward(size).process(&mut buffer);
+    let bins = size / 2 + 1;
+    MeasuredResponse {
+        frequencies: (0..bins).map(|k| k as f64 * sample_rate / size as f64).collect(),
+        magnitude: buffer[..bins].iter().map(|c| c.norm()).collect(),
+        phase: buffer[..bins].iter().map(|c| c.arg()).collect(),
+    }
+}
+
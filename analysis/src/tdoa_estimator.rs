@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use rustfft::{FftPlanner, Fft, num_complex::Complex};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct TdoaEstimator {
+        inputs: { "reference": Vec<f64>, "signal": Vec<f64> },
+        outputs: { "delay": f64, "peak": f64 },
+        statics: {
+            "sample_rate": f64 = 1.0,
+            "frame_size": usize = 1024,
+            "max_delay": f64 = 0.0,
+        },
+        state: {
+            "pending_reference": Vec<f64> = Vec::<f64>::new(),
+            "pending_signal": Vec<f64> = Vec::<f64>::new(),
+        },
+        fields: {
+            forward: Option<Arc<dyn Fft<f64>>> = None,
+            inverse: Option<Arc<dyn Fft<f64>>> = None,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let max_delay = self.get_statics::<f64>("max_delay")?.get_value();
+            if sample_rate.is_nan() || sample_rate <= 0.0 || frame_size < 2 || max_delay.is_nan() || max_delay < 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            // zero padding to twice the frame avoids circular wrap of the correlation
+            let mut planner = FftPlanner::new();
+            self.forward = Some(planner.plan_fft_forward(2 * frame_size));
+            self.inverse = Some(planner.plan_fft_inverse(2 * frame_size));
+            self.set_state_value("pending_reference", Vec::<f64>::new())?;
+            self.set_state_value("pending_signal", Vec::<f64>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let max_delay = self.get_statics::<f64>("max_delay")?.get_value();
+            let mut pending_reference = self.get_state_value::<Vec<f64>>("pending_reference")?;
+            let mut pending_signal = self.get_state_value::<Vec<f64>>("pending_signal")?;
+            let reference = self.recv_input::<Vec<f64>>("reference")?;
+            let signal = self.recv_input::<Vec<f64>>("signal")?;
+            if reference.len() != signal.len() {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let max_lag = if max_delay > 0.0 { ((max_delay * sample_rate).ceil() as usize).min(frame_size - 1) } else { frame_size - 1 };
+            let mut estimates = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                pending_reference.extend(reference);
+                pending_signal.extend(signal);
+                while pending_reference.len() >= frame_size {
+                    let spectrum = |samples: Vec<f64>| {
+                        let mut buffer: Vec<Complex<f64>> = samples.into_iter().map(|x| Complex::new(x, 0.0)).collect();
+                        buffer.resize(2 * frame_size, Complex::new(0.0, 0.0));
+                        self.forward.as_ref().unwrap().process(&mut buffer);
+                        buffer
+                    };
+                    let x_reference = spectrum(pending_reference.drain(..frame_size).collect());
+                    let x_signal = spectrum(pending_signal.drain(..frame_size).collect());
+                    let mut cross: Vec<Complex<f64>> = x_signal.iter().zip(x_reference.iter())
+                        .map(|(s, r)| {
+                            let product = s * r.conj();
+                            let magnitude = product.norm();
+                            if magnitude > 0.0 { product / magnitude } else { Complex::new(0.0, 0.0) }
+                        })
+                        .collect();
+                    self.inverse.as_ref().unwrap().process(&mut cross);
+                    let correlation: Vec<f64> = cross.iter().map(|c| c.re / (2 * frame_size) as f64).collect();
+                    let (lag, peak) = correlation_peak(&correlation, max_lag);
+                    estimates.push((lag / sample_rate, peak));
+                }
+            }
+            self.set_state_value("pending_reference", pending_reference)?;
+            self.set_state_value("pending_signal", pending_signal)?;
+            for (delay, peak) in estimates {
+                self.send_output::<f64>("delay", delay)?;
+                self.send_output::<f64>("peak", peak)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Lag (samples, positive when `signal` lags `reference`) and height of the GCC-PHAT peak within
+// +-max_lag, refined by parabolic interpolation. `correlation` is the circular inverse transform.
+pub fn correlation_peak(correlation: &[f64], max_lag: usize) -> (f64, f64) {
+    let size = correlation.len() as isize;
+    let at = |lag: isize| correlation[lag.rem_euclid(size) as usize];
+    let max_lag = max_lag as isize;
+    let mut best = 0;
+    for lag in -max_lag..=max_lag {
+        if at(lag) > at(best) {
+            best = lag;
+        }
+    }
+    let (left, center, right) = (at(best - 1), at(best), at(best + 1));
+    let curvature = left - 2.0 * center + right;
+    let offset = if curvature < 0.0 { 0.5 * (left - right) / curvature } else { 0.0 };
+    (best as f64 + offset, center - 0.25 * (left - right) * offset)
+}
+
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+use crate::smoothing::{loess, whittaker};
+
+// Splits the stream into a slow trend and the residual, by a local linear or quadratic LOESS fit
+// ("loess", degree) or a penalized spline ("spline", lambda) over the last window samples. The
+// trend is read at the newest sample, or at the window centre with centered, which delays both
+// outputs by (window - 1) / 2 samples but follows the trend without the lag of an end point fit.
+// Until the window fills up the centre is the first sample.
+stream_block! {
+    pub struct TrendExtractor {
+        inputs: { "input": Vec<f64> },
+        outputs: { "trend": Vec<f64>, "residual": Vec<f64> },
+        statics: {
+            "method": String = "loess".to_string(),
+            "window": usize = 101,
+            "degree": usize = 1,
+            "lambda": f64 = 1.0e4,
+            "centered": bool = false,
+        },
+        state: { "history": Vec<f64> = Vec::<f64>::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let method = self.get_statics::<String>("method")?.get_value();
+            let window = self.get_statics::<usize>("window")?.get_value();
+            let degree = self.get_statics::<usize>("degree")?.get_value();
+            let lambda = self.get_statics::<f64>("lambda")?.get_value();
+            if !matches!(method.as_str(), "loess" | "spline") || window < 3 || degree > 2 || !lambda.is_finite() || lambda < 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("history", Vec::<f64>::with_capacity(window))?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let method = self.get_statics::<String>("method")?.get_value();
+            let window = self.get_statics::<usize>("window")?.get_value();
+            let degree = self.get_statics::<usize>("degree")?.get_value();
+            let lambda = self.get_statics::<f64>("lambda")?.get_value();
+            let centered = self.get_statics::<bool>("centered")?.get_value();
+            let mut history = self.get_state_value::<Vec<f64>>("history")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut trend = Vec::with_capacity(input_signal.len());
+            let mut residual = Vec::with_capacity(input_signal.len());
+            {
+                let _lock = self.lock.lock().unwrap();
+                for x in input_signal {
+                    if history.len() == window {
+                        history.remove(0);
+                    }
+                    history.push(x);
+                    let position = if centered { (history.len() - 1).saturating_sub((window - 1) / 2) } else { history.len() - 1 };
+                    let value = trend_at(&history, position, &method, degree, lambda);
+                    trend.push(value);
+                    residual.push(history[position] - value);
+                }
+            }
+            self.set_state_value("history", history)?;
+            self.send_output::<Vec<f64>>("trend", trend)?;
+            self.send_output::<Vec<f64>>("residual", residual)?;
+            Ok(())
+        }
+    }
+}
+
+// Trend at `position` of the window.
+fn trend_at(window: &[f64], position: usize, method: &str, degree: usize, lambda: f64) -> f64 {
+    if method == "spline" {
+        whittaker(window, lambda)[position]
+    } else {
+        loess(window, &[], position as f64, window.len(), degree)
+    }
+}
+
@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+#[cfg(feature = "cpal")]
+use std::sync::{Arc, Mutex, mpsc};
+#[cfg(feature = "cpal")]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(feature = "cpal")]
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+#[cfg(feature = "cpal")]
+use cpal::{BufferSize, StreamConfig};
+
+// Fills a device buffer from the queued samples, the part the queue cannot cover is silence.
+// Returns false on such an underrun.
+pub fn fill_output(output: &mut [f32], queue: &mut VecDeque<f32>) -> bool {
+    let available = queue.len().min(output.len());
+    for (sample, value) in output.iter_mut().zip(queue.drain(..available)) {
+        *sample = value;
+    }
+    output[available..].fill(0.0);
+    available == output.len()
+}
+
+// Stream configuration, a zero buffer_size leaves the buffer size to the host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioConfig {
+    pub device: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub buffer_size: u32,
+}
+
+#[cfg(feature = "cpal")]
+impl AudioConfig {
+    fn stream_config(&self) -> StreamConfig {
+        StreamConfig {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            buffer_size: if self.buffer_size == 0 { BufferSize::Default } else { BufferSize::Fixed(self.buffer_size) },
+        }
+    }
+}
+
+// An empty name selects the default device of the host, otherwise the first device whose name
+// contains it, ignoring case.
+#[cfg(feature = "cpal")]
+fn find_device(name: &str, input: bool) -> Result<cpal::Device, String> {
+    let host = cpal::default_host();
+    if name.is_empty() {
+        let device = if input { host.default_input_device() } else { host.default_output_device() };
+        return device.ok_or_else(|| format!("no default {} device", if input { "input" } else { "output" }));
+    }
+    let wanted = name.to_lowercase();
+    let devices: Vec<cpal::Device> = if input {
+        host.input_devices().map_err(|e| e.to_string())?.collect()
+    } else {
+        host.output_devices().map_err(|e| e.to_string())?.collect()
+    };
+    devices.into_iter().find(|d| d.to_string().to_lowercase().contains(&wanted)).ok_or_else(|| format!("no audio device matching {}", name))
+}
+
+// Neither the cpal device nor the stream is Send on every host, both are opened, played and
+// dropped by a thread of their own that lives until `running` is cleared, by the owner or by a
+// stream error.
+#[cfg(feature = "cpal")]
+fn spawn_stream<F>(build: F, running: Arc<AtomicBool>) -> Result<(), String>
+where
+    F: FnOnce(Arc<AtomicBool>) -> Result<cpal::Stream, String> + Send + 'static,
+{
+    let (ready_sender, ready_receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _stream = match build(running.clone()).and_then(|s| s.play().map(|_| s).map_err(|e| e.to_string())) {
+            Ok(stream) => {
+                let _ = ready_sender.send(Ok(()));
+                stream
+            }
+            Err(error) => {
+                let _ = ready_sender.send(Err(error));
+                return;
+            }
+        };
+        while running.load(Ordering::Relaxed) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    });
+    ready_receiver.recv().map_err(|e| e.to_string())?
+}
+
+// Interleaved f32 buffers of the device are handed over as they come, a buffer that finds the
+// queue full is dropped and counted as an overrun.
+#[cfg(feature = "cpal")]
+pub struct AudioCapture {
+    receiver: Mutex<mpsc::Receiver<Vec<f32>>>,
+    running: Arc<AtomicBool>,
+    overruns: Arc<AtomicUsize>,
+}
+#[cfg(feature = "cpal")]
+impl AudioCapture {
+    pub fn open(config: &AudioConfig, queue_buffers: usize) -> Result<Self, String> {
+        let config = config.clone();
+        let (sender, receiver) = mpsc::sync_channel(queue_buffers);
+        let running = Arc::new(AtomicBool::new(true));
+        let overruns = Arc::new(AtomicUsize::new(0));
+        let counter = overruns.clone();
+        spawn_stream(move |running| {
+            find_device(&config.device, true)?.build_input_stream::<f32, _, _>(
+                config.stream_config(),
+                move |data, _| {
+                    if let Err(mpsc::TrySendError::Full(_)) = sender.try_send(data.to_vec()) {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    }
+                },
+                move |error| {
+                    eprintln!("audio input: {}", error);
+                    running.store(false, Ordering::Relaxed);
+                },
+                None,
+            ).map_err(|e| e.to_string())
+        }, running.clone())?;
+        Ok(AudioCapture { receiver: Mutex::new(receiver), running, overruns })
+    }
+    // Err once the stream has ended, Ok(None) when nothing arrived within `timeout`.
+    pub fn receive(&self, timeout: Duration) -> Result<Option<Vec<f64>>, String> {
+        match self.receiver.lock().unwrap().recv_timeout(timeout) {
+            Ok(buffer) => Ok(Some(buffer.into_iter().map(|v| v as f64).collect())),
+            Err(mpsc::RecvTimeoutError::Timeout) => Ok(None),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err("the audio input stream has ended".to_string()),
+        }
+    }
+    pub fn overruns(&self) -> usize {
+        self.overruns.load(Ordering::Relaxed)
+    }
+}
+#[cfg(feature = "cpal")]
+impl Drop for AudioCapture {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+// Frames are queued on a bounded channel, so a full queue blocks the sender and the pipeline
+// runs at the pace of the device clock. The device buffers are filled from the queue, with
+// silence and an underrun count when it runs dry after playback has started.
+#[cfg(feature = "cpal")]
+pub struct AudioPlayback {
+    sender: mpsc::SyncSender<Vec<f32>>,
+    running: Arc<AtomicBool>,
+    underruns: Arc<AtomicUsize>,
+}
+#[cfg(feature = "cpal")]
+impl AudioPlayback {
+    pub fn open(config: &AudioConfig, queue_frames: usize) -> Result<Self, String> {
+        let config = config.clone();
+        let (sender, receiver) = mpsc::sync_channel::<Vec<f32>>(queue_frames);
+        let running = Arc::new(AtomicBool::new(true));
+        let underruns = Arc::new(AtomicUsize::new(0));
+        let counter = underruns.clone();
+        spawn_stream(move |running| {
+            let mut queue = VecDeque::new();
+            let mut started = false;
+            find_device(&config.device, false)?.build_output_stream::<f32, _, _>(
+                config.stream_config(),
+                move |data, _| {
+                    while queue.len() < data.len() && let Ok(frame) = receiver.try_recv() {
+                        queue.extend(frame);
+                        started = true;
+                    }
+                    if !fill_output(data, &mut queue) && started {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    }
+                },
+                move |error| {
+                    eprintln!("audio output: {}", error);
+                    running.store(false, Ordering::Relaxed);
+                },
+                None,
+            ).map_err(|e| e.to_string())
+        }, running.clone())?;
+        Ok(AudioPlayback { sender, running, underruns })
+    }
+    pub fn send(&self, frame: &[f64]) -> Result<(), String> {
+        self.sender.send(frame.iter().map(|v| *v as f32).collect()).map_err(|_| "the audio output stream has ended".to_string())
+    }
+    pub fn underruns(&self) -> usize {
+        self.underruns.load(Ordering::Relaxed)
+    }
+}
+#[cfg(feature = "cpal")]
+impl Drop for AudioPlayback {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+// Without the cpal feature the blocks still load, init reports how to enable it.
+#[cfg(not(feature = "cpal"))]
+pub struct AudioCapture;
+#[cfg(not(feature = "cpal"))]
+impl AudioCapture {
+    pub fn open(_config: &AudioConfig, _queue_buffers: usize) -> Result<Self, String> {
+        Err("the audio_io crate was built without the cpal feature".to_string())
+    }
+    pub fn receive(&self, _timeout: Duration) -> Result<Option<Vec<f64>>, String> {
+        Err("the audio_io crate was built without the cpal feature".to_string())
+    }
+    pub fn overruns(&self) -> usize {
+        0
+    }
+}
+#[cfg(not(feature = "cpal"))]
+pub struct AudioPlayback;
+#[cfg(not(feature = "cpal"))]
+impl AudioPlayback {
+    pub fn open(_config: &AudioConfig, _queue_frames: usize) -> Result<Self, String> {
+        Err("the audio_io crate was built without the cpal feature".to_string())
+    }
+    pub fn send(&self, _frame: &[f64]) -> Result<(), String> {
+        Err("the audio_io crate was built without the cpal feature".to_string())
+    }
+    pub fn underruns(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_fill_output() {
+        let mut queue: VecDeque<f32> = (1..=6).map(|v| v as f32).collect();
+        let mut output = [9.0f32; 4];
+        assert!(fill_output(&mut output, &mut queue));
+        assert_eq!(output, [1.0, 2.0, 3.0, 4.0]);
+        assert!(!fill_output(&mut output, &mut queue));
+        assert_eq!(output, [5.0, 6.0, 0.0, 0.0]);
+        assert!(queue.is_empty());
+    }
+}
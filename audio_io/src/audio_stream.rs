@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::{FrameBuffer, stream_block};
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::time::Duration;
+use crate::audio_device::{AudioCapture, AudioConfig, AudioPlayback};
+
+// Captures the sound card input and emits frames of frame_size sample frames, the channels
+// interleaved. An empty device selects the default input, a zero buffer_size lets the host
+// choose. Device buffers arriving while queue_buffers are still pending are dropped and counted
+// in overruns.
+stream_block! {
+    pub struct AudioIn {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "device": String = String::new(),
+            "sample_rate": usize = 48000,
+            "channels": usize = 1,
+            "buffer_size": usize = 0,
+            "frame_size": usize = 1024,
+            "queue_buffers": usize = 16,
+        },
+        state: { "overruns": usize = 0 },
+        fields: { capture: Option<AudioCapture> = None, pending: FrameBuffer<f64> = FrameBuffer::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let device = self.get_statics::<String>("device")?.get_value();
+            let sample_rate = self.get_statics::<usize>("sample_rate")?.get_value();
+            let channels = self.get_statics::<usize>("channels")?.get_value();
+            let buffer_size = self.get_statics::<usize>("buffer_size")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let queue_buffers = self.get_statics::<usize>("queue_buffers")?.get_value();
+            if !valid_config(sample_rate, channels, buffer_size) || frame_size == 0 || queue_buffers == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let config = AudioConfig { device, sample_rate: sample_rate as u32, channels: channels as u16, buffer_size: buffer_size as u32 };
+            match AudioCapture::open(&config, queue_buffers) {
+                Ok(capture) => self.capture = Some(capture),
+                Err(error) => {
+                    eprintln!("AudioIn {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.pending = FrameBuffer::new();
+            self.set_state_value("overruns", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let channels = self.get_statics::<usize>("channels")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            // the wait is bounded so that the run loop still sees a stop request on a silent device
+            let capture = self.capture.as_ref().ok_or(StreamingError::InvalidStateTransition)?;
+            let (received, overruns) = (capture.receive(Duration::from_millis(100)), capture.overruns());
+            let frames = match received {
+                Ok(buffer) => {
+                    let _lock = self.lock.lock().unwrap();
+                    if let Some(buffer) = buffer {
+                        self.pending.push(buffer);
+                    }
+                    std::iter::from_fn(|| self.pending.take(frame_size * channels)).collect::<Vec<_>>()
+                }
+                Err(error) => {
+                    eprintln!("AudioIn {}: {}", self.name, error);
+                    self.stop()?;
+                    return Err(StreamingError::InvalidInput);
+                }
+            };
+            self.set_state_value("overruns", overruns)?;
+            for frame in frames {
+                self.send_output::<Vec<f64>>("output", frame)?;
+            }
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.capture = None;
+            Ok(())
+        }
+    }
+}
+
+// Plays interleaved input frames on the sound card. Up to queue_frames frames wait for the
+// device, beyond that the block waits, so the pipeline follows the device clock. Device buffers
+// the queue could not fill are padded with silence and counted in underruns.
+stream_block! {
+    pub struct AudioOut {
+        inputs: { "input": Vec<f64> },
+        statics: {
+            "device": String = String::new(),
+            "sample_rate": usize = 48000,
+            "channels": usize = 1,
+            "buffer_size": usize = 0,
+            "queue_frames": usize = 4,
+        },
+        state: { "underruns": usize = 0 },
+        fields: { playback: Option<AudioPlayback> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let device = self.get_statics::<String>("device")?.get_value();
+            let sample_rate = self.get_statics::<usize>("sample_rate")?.get_value();
+            let channels = self.get_statics::<usize>("channels")?.get_value();
+            let buffer_size = self.get_statics::<usize>("buffer_size")?.get_value();
+            let queue_frames = self.get_statics::<usize>("queue_frames")?.get_value();
+            if !valid_config(sample_rate, channels, buffer_size) || queue_frames == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let config = AudioConfig { device, sample_rate: sample_rate as u32, channels: channels as u16, buffer_size: buffer_size as u32 };
+            match AudioPlayback::open(&config, queue_frames) {
+                Ok(playback) => self.playback = Some(playback),
+                Err(error) => {
+                    eprintln!("AudioOut {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state_value("underruns", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let channels = self.get_statics::<usize>("channels")?.get_value();
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            if !input_signal.len().is_multiple_of(channels) {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            // not under the block lock, the send waits for the device while the queue is full
+            let playback = self.playback.as_ref().ok_or(StreamingError::InvalidStateTransition)?;
+            let (result, underruns) = (playback.send(&input_signal), playback.underruns());
+            if let Err(error) = result {
+                eprintln!("AudioOut {}: {}", self.name, error);
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            self.set_state_value("underruns", underruns)?;
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.playback = None;
+            Ok(())
+        }
+    }
+}
+
+fn valid_config(sample_rate: usize, channels: usize, buffer_size: usize) -> bool {
+    sample_rate > 0 && sample_rate <= u32::MAX as usize && channels > 0 && channels <= u16::MAX as usize
+        && buffer_size <= u32::MAX as usize
+}
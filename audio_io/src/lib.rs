@@ -0,0 +1,55 @@
+pub mod audio_device;
+pub mod audio_stream;
+use std::ffi::c_char;
+use data_model::modules::{Version,ModuleStructFFI};
+use processor_engine::stream_processor::StreamProcessor;
+use processor_engine::ffi::{TraitObjectRepr, export_stream_processor, get_error_return};
+#[unsafe(no_mangle)]
+pub static MODULE: ModuleStructFFI  = ModuleStructFFI {
+    name: b"Audio input and output\0".as_ptr() as *const c_char,
+    description: b"The library provides blocks capturing and playing audio through the sound card.\0".as_ptr() as *const c_char,
+    authors: b"Sofia Silvestri\0".as_ptr() as *const c_char,
+    release_date: b"2026/10/16\0".as_ptr() as *const c_char,
+    version: Version{ major: 0,minor: 1,build: 0},
+    dependencies: std::ptr::null(),
+    dependency_number: 0,
+    provides: [b"AudioIn\0".as_ptr() as *const c_char,
+        b"AudioOut\0".as_ptr() as *const c_char].as_ptr(),
+    provides_lengths: 2,
+};
+/// Creates the block `proc_block` named `block_name`, both passed by the host as UTF-8 bytes.
+///
+/// # Safety
+/// A non null `proc_block` and `block_name` must point to `proc_block_len` and `block_name_len`
+/// readable bytes that stay valid while the block exists.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_processor_modules(proc_block: *const u8, 
+    proc_block_len: usize, 
+    block_name: *const u8, 
+    block_name_len: usize) -> TraitObjectRepr {
+    let proc_block_str = block_template::ffi_name!(proc_block, proc_block_len);
+    let block_name_str = block_template::ffi_name!(block_name, block_name_len);
+    let proc: Box<dyn StreamProcessor>;
+    match proc_block_str {
+        "AudioIn" => {
+            proc = Box::new(audio_stream::AudioIn::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "AudioOut" => {
+            proc = Box::new(audio_stream::AudioOut::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        _ => {
+            eprintln!("Processor block {} not found", proc_block_str);
+            get_error_return(1)
+        }
+    }
+}
+
+// Schema of every block get_processor_modules creates, keyed by block type, see stream_block!.
+pub fn block_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "AudioIn": audio_stream::AudioIn::schema(),
+        "AudioOut": audio_stream::AudioOut::schema(),
+    })
+}
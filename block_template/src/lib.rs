@@ -0,0 +1,280 @@
+// Declarative form of a processing block. The block lists its ports, statics, parameters, state
+// entries and extra fields with their defaults, the macro writes the StreamBlockMacro struct and
+// the constructor every block otherwise repeats:
+//
+//   stream_block! {
+//       pub struct Gain {
+//           inputs: { "input": Vec<f64> },
+//           outputs: { "output": Vec<f64> },
+//           statics: { "gain": f64 = 1.0 },
+//           parameters: { "gain": f64 = 1.0 },
+//           state: { "count": usize = 0 },
+//           fields: { history: Vec<f64> = Vec::new() },
+//       }
+//       impl StreamProcessor {
+//           fn init(&mut self) -> Result<(), StreamingError> { ... }
+//           fn process(&mut self) -> Result<(), StreamingError> { ... }
+//           fn teardown(&mut self) -> Result<(), StreamingError> { ... }
+//       }
+//   }
+//
+// Every section is optional but they keep this order. With the impl section the StreamProcessor
+// implementation gets the canonical run loop and stop, the block only writes init and process.
+// The optional teardown releases what the block holds beyond its state, a device, a socket or a
+// file to finish. The generated stop calls it on every stop, also the ones process requests on an
+// error, so it has to tolerate running twice. The block is stopped even when teardown fails, the
+// error is returned after.
+// Blocks that work on fixed size frames read them with recv_exact::<T>(port, n), which collects
+// the incoming Vec<T> frames of the port and keeps the samples beyond n for the next call. The
+// generated stop drops those remainders.
+// The block also gets schema(), its ports, statics, parameters and state entries as JSON in the
+// layout of the .project block files, with the types and defaults as written in the declaration.
+// It needs no instance, so an editor or a loader can check a pipeline against it.
+// The generated code names the same items as a handwritten block, so the block file keeps the
+// usual imports (HashMap, Arc, Mutex, StreamBlockMacro, the memory manager and connector traits).
+use std::collections::VecDeque;
+use serde_json::{Map, Value, json};
+
+pub use serde_json;
+
+// Samples received but not yet consumed by recv_exact.
+pub struct FrameBuffer<T> {
+    pending: VecDeque<T>,
+}
+impl<T> FrameBuffer<T> {
+    pub fn new() -> Self {
+        Self { pending: VecDeque::new() }
+    }
+    pub fn push(&mut self, samples: Vec<T>) {
+        self.pending.extend(samples);
+    }
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+    // The oldest n samples, None until that many are pending.
+    pub fn take(&mut self, n: usize) -> Option<Vec<T>> {
+        if self.pending.len() < n {
+            return None;
+        }
+        Some(self.pending.drain(..n).collect())
+    }
+}
+impl<T> Default for FrameBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Port name to type, as listed in the "inputs" and "outputs" of a block schema.
+pub fn schema_ports(ports: &[(&str, &str)]) -> Value {
+    Value::Object(ports.iter().map(|(name, data_type)| (name.to_string(), json!(data_type))).collect::<Map<String, Value>>())
+}
+
+// Statics, parameters or state entries of a block schema, given as (name, type, default). State
+// entries have no limits, the others get null as no block declares any.
+pub fn schema_entries(category: &str, entries: &[(&str, &str, &str)]) -> Value {
+    Value::Object(entries.iter().map(|(name, data_type, default)| {
+        let mut entry = json!({"category": category, "name": name, "data_type": data_type, "default": default});
+        if category != "state" {
+            entry["limits"] = Value::Null;
+        }
+        (name.to_string(), entry)
+    }).collect::<Map<String, Value>>())
+}
+
+// Longest block type or block name accepted from the host, anything longer is a corrupted length.
+pub const MAX_FFI_NAME: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FfiNameError {
+    InvalidName,
+    InvalidEncoding,
+}
+
+/// Reads a name passed by the host to get_processor_modules as a pointer and a length.
+///
+/// # Safety
+/// A non null `ptr` must point to `len` readable bytes that outlive the returned string.
+pub unsafe fn ffi_name<'a>(ptr: *const u8, len: usize) -> Result<&'a str, FfiNameError> {
+    if ptr.is_null() || len == 0 || len > MAX_FFI_NAME {
+        return Err(FfiNameError::InvalidName);
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+    std::str::from_utf8(bytes).map_err(|_| FfiNameError::InvalidEncoding)
+}
+
+// The name argument of get_processor_modules, or the early return of get_error_return(2) for a
+// null, empty or oversized name and get_error_return(3) for invalid UTF-8. Code 1 stays the
+// unknown block. get_error_return has to be in scope where the macro is used, and the calling
+// function has to be unsafe with a safety contract covering the pointer, as ffi_name's.
+#[macro_export]
+macro_rules! ffi_name {
+    ($ptr:expr, $len:expr) => {
+        match unsafe { $crate::ffi_name($ptr, $len) } {
+            Ok(name) => name,
+            Err(error) => {
+                eprintln!("Invalid processor block argument: {:?}", error);
+                return match error {
+                    $crate::FfiNameError::InvalidName => get_error_return(2),
+                    $crate::FfiNameError::InvalidEncoding => get_error_return(3),
+                };
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! stream_block {
+    // The impl section is split into the StreamProcessor methods and the teardown, which becomes
+    // an inherent method called by the generated stop.
+    (@processor $block:ident [$($items:tt)*] []
+        fn teardown(&mut $this:ident) -> Result<(), StreamingError> $body:block $($rest:tt)*) => {
+        impl $block {
+            fn teardown(&mut $this) -> Result<(), StreamingError> $body
+        }
+        $crate::stream_block!(@processor $block [$($items)*] [teardown] $($rest)*);
+    };
+    (@processor $block:ident [$($items:tt)*] [$($teardown:ident)?] $next:tt $($rest:tt)*) => {
+        $crate::stream_block!(@processor $block [$($items)* $next] [$($teardown)?] $($rest)*);
+    };
+    (@processor $block:ident [$($items:tt)*] [$($teardown:ident)?]) => {
+        impl StreamProcessor for $block {
+            $($items)*
+            fn run(&mut self) -> Result<(), StreamingError> {
+                if self.check_state(StreamingState::Stopped) {
+                    return Err(StreamingError::InvalidStateTransition);
+                }
+                if !self.is_initialized() {
+                    return Err(StreamingError::InvalidStatics)
+                }
+                self.set_state(StreamingState::Running);
+                while !self.check_state(StreamingState::Stopped) {
+                    self.process()?;
+                }
+                Ok(())
+            }
+            fn stop(&mut self) -> Result<(), StreamingError> {
+                let result: Result<(), StreamingError> = Ok(());
+                $(let result = self.$teardown();)?
+                self.frame_buffers.clear();
+                self.set_state(StreamingState::Stopped);
+                result
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $block:ident {
+            $(inputs: { $($input:literal : $input_type:ty),* $(,)? } $(,)?)?
+            $(outputs: { $($output:literal : $output_type:ty),* $(,)? } $(,)?)?
+            $(statics: { $($statics:literal : $statics_type:ty = $statics_default:expr),* $(,)? } $(,)?)?
+            $(parameters: { $($parameter:literal : $parameter_type:ty = $parameter_default:expr),* $(,)? } $(,)?)?
+            $(state: { $($state:literal : $state_type:ty = $state_default:expr),* $(,)? } $(,)?)?
+            $(fields: { $($field:ident : $field_type:ty = $field_default:expr),* $(,)? } $(,)?)?
+        }
+        $(impl StreamProcessor { $($processor:tt)* })?
+    ) => {
+        $(#[$meta])*
+        #[derive(StreamBlockMacro)]
+        $vis struct $block {
+            name:       &'static str,
+            inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+            outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+            parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+            statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+            state:      HashMap<&'static str, Box<dyn DataTrait>>,
+            lock:       Arc<Mutex<()>>,
+            proc_state: Arc<Mutex<StreamingState>>,
+            frame_buffers: HashMap<&'static str, Box<dyn std::any::Any + Send>>,
+            $($($field: $field_type,)*)?
+        }
+        impl $block {
+            pub fn new(name: &'static str) -> Self {
+                let mut ret = Self {
+                    name,
+                    inputs: HashMap::new(),
+                    outputs: HashMap::new(),
+                    parameters: HashMap::new(),
+                    statics: HashMap::new(),
+                    state: HashMap::new(),
+                    lock: Arc::new(Mutex::new(())),
+                    proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+                    frame_buffers: HashMap::new(),
+                    $($($field: $field_default,)*)?
+                };
+                $($(let _ = ret.new_input::<$input_type>($input);)*)?
+                $($(let _ = ret.new_output::<$output_type>($output);)*)?
+                $($(let _ = ret.new_statics::<$statics_type>($statics, $statics_default, None);)*)?
+                $($(let _ = ret.new_parameter::<$parameter_type>($parameter, $parameter_default, None);)*)?
+                $($(let _ = ret.new_state::<$state_type>($state, $state_default);)*)?
+                ret
+            }
+            pub fn schema() -> $crate::serde_json::Value {
+                $crate::serde_json::json!({
+                    "inputs": $crate::schema_ports(&[$($(($input, stringify!($input_type)),)*)?]),
+                    "outputs": $crate::schema_ports(&[$($(($output, stringify!($output_type)),)*)?]),
+                    "statics": $crate::schema_entries("static", &[$($(($statics, stringify!($statics_type), stringify!($statics_default)),)*)?]),
+                    "parameters": $crate::schema_entries("parameter", &[$($(($parameter, stringify!($parameter_type), stringify!($parameter_default)),)*)?]),
+                    "states": $crate::schema_entries("state", &[$($(($state, stringify!($state_type), stringify!($state_default)),)*)?]),
+                })
+            }
+            pub fn recv_exact<T: Clone + std::fmt::Debug + Send + Sync + 'static>(&mut self, port: &'static str, n: usize) -> Result<Vec<T>, StreamingError> {
+                loop {
+                    let buffer = self.frame_buffers.entry(port).or_insert_with(|| Box::new($crate::FrameBuffer::<T>::new()));
+                    let buffer = buffer.downcast_mut::<$crate::FrameBuffer<T>>().ok_or(StreamingError::InvalidInput)?;
+                    if let Some(frame) = buffer.take(n) {
+                        return Ok(frame);
+                    }
+                    let input = self.recv_input::<Vec<T>>(port)?;
+                    if let Some(buffer) = self.frame_buffers.get_mut(port).and_then(|b| b.downcast_mut::<$crate::FrameBuffer<T>>()) {
+                        buffer.push(input);
+                    }
+                }
+            }
+        }
+        $(
+            $crate::stream_block!(@processor $block [] [] $($processor)*);
+        )?
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_frame_buffer() {
+        let mut buffer = FrameBuffer::new();
+        buffer.push(vec![1, 2, 3]);
+        assert_eq!(buffer.take(4), None);
+        buffer.push(vec![4, 5]);
+        assert_eq!(buffer.take(4), Some(vec![1, 2, 3, 4]));
+        assert_eq!(buffer.len(), 1);
+        buffer.push((6..14).collect());
+        assert_eq!(buffer.take(4), Some(vec![5, 6, 7, 8]));
+        assert_eq!(buffer.take(4), Some(vec![9, 10, 11, 12]));
+        assert_eq!(buffer.take(0), Some(vec![]));
+        assert_eq!(buffer.take(2), None);
+    }
+    #[test]
+    fn test_schema() {
+        assert_eq!(schema_ports(&[("input", "Vec<f64>")]), json!({"input": "Vec<f64>"}));
+        assert_eq!(schema_ports(&[]), json!({}));
+        assert_eq!(schema_entries("static", &[("order", "usize", "4")]),
+            json!({"order": {"category": "static", "name": "order", "data_type": "usize", "default": "4", "limits": null}}));
+        assert_eq!(schema_entries("state", &[("count", "usize", "0")]),
+            json!({"count": {"category": "state", "name": "count", "data_type": "usize", "default": "0"}}));
+    }
+    #[test]
+    fn test_ffi_name() {
+        let name = b"Fft";
+        assert_eq!(unsafe { ffi_name(name.as_ptr(), name.len()) }, Ok("Fft"));
+        assert_eq!(unsafe { ffi_name(std::ptr::null(), 3) }, Err(FfiNameError::InvalidName));
+        assert_eq!(unsafe { ffi_name(name.as_ptr(), 0) }, Err(FfiNameError::InvalidName));
+        assert_eq!(unsafe { ffi_name(name.as_ptr(), MAX_FFI_NAME + 1) }, Err(FfiNameError::InvalidName));
+        let invalid = [b'F', 0xff, b't'];
+        assert_eq!(unsafe { ffi_name(invalid.as_ptr(), invalid.len()) }, Err(FfiNameError::InvalidEncoding));
+    }
+}
@@ -0,0 +1,96 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+// Line protocol escaping: measurements escape commas and spaces, tag keys, tag values and field
+// keys also escape the equal sign.
+fn escape(text: &str, equal_sign: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == ',' || c == ' ' || (equal_sign && c == '=') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// Parses "key=value,key=value" tag lists, an empty string has no tags.
+pub fn parse_tags(text: &str) -> Option<Vec<(String, String)>> {
+    if text.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    text.split(',').map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        let (key, value) = (key.trim(), value.trim());
+        if key.is_empty() || value.is_empty() {
+            return None;
+        }
+        Some((key.to_string(), value.to_string()))
+    }).collect()
+}
+
+// One point in line protocol. Non finite values cannot be written and are left out, None if no
+// field remains.
+pub fn format_line(measurement: &str, tags: &[(String, String)], fields: &[(String, f64)], timestamp_ns: u128) -> Option<String> {
+    let fields: Vec<String> = fields.iter()
+        .filter(|(_, value)| value.is_finite())
+        .map(|(key, value)| format!("{}={:?}", escape(key, true), value))
+        .collect();
+    if fields.is_empty() {
+        return None;
+    }
+    let mut line = escape(measurement, false);
+    for (key, value) in tags {
+        line.push_str(&format!(",{}={}", escape(key, true), escape(value, true)));
+    }
+    Some(format!("{} {} {}", line, fields.join(","), timestamp_ns))
+}
+
+// Sends a batch to the InfluxDB v2 write endpoint over plain HTTP. `url` is http://host:port
+// with an optional path prefix.
+pub fn write_lines(url: &str, org: &str, bucket: &str, token: &str, lines: &[String], timeout: Duration) -> Result<(), String> {
+    let rest = url.strip_prefix("http://").ok_or(format!("{}: only http:// urls are supported", url))?;
+    let (host, prefix) = rest.split_once('/').map(|(h, p)| (h, format!("/{}", p.trim_end_matches('/')))).unwrap_or((rest, String::new()));
+    let body = lines.join("\n");
+    let mut request = format!("POST {}/api/v2/write?org={}&bucket={}&precision=ns HTTP/1.1\r\nHost: {}\r\n",
+        prefix, percent_encode(org), percent_encode(bucket), host);
+    if !token.is_empty() {
+        request.push_str(&format!("Authorization: Token {}\r\n", token));
+    }
+    request.push_str(&format!("Content-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body));
+    let mut stream = TcpStream::connect(host).map_err(|e| format!("{}: {}", host, e))?;
+    stream.set_read_timeout(Some(timeout)).and_then(|_| stream.set_write_timeout(Some(timeout))).map_err(|e| e.to_string())?;
+    stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).map_err(|e| e.to_string())?;
+    let status = response.split_whitespace().nth(1).unwrap_or("");
+    if status.starts_with('2') {
+        Ok(())
+    } else {
+        Err(format!("write rejected: {}", response.lines().last().unwrap_or(status)))
+    }
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+        _ => format!("%{:02X}", b),
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_line_protocol() {
+        let tags = parse_tags("site=north hall, sensor=a=1").unwrap();
+        assert_eq!(tags[1], ("sensor".to_string(), "a=1".to_string()));
+        assert!(parse_tags("site").is_none());
+        let fields = vec![("rms".to_string(), 0.5), ("band power".to_string(), 2.0), ("score".to_string(), f64::NAN)];
+        let line = format_line("vibration,raw", &tags, &fields, 1_700_000_000_000_000_000).unwrap();
+        assert_eq!(line, "vibration\\,raw,site=north\\ hall,sensor=a\\=1 rms=0.5,band\\ power=2.0 1700000000000000000");
+        assert!(format_line("m", &[], &fields[2..], 0).is_none());
+        assert_eq!(percent_encode("my org"), "my%20org");
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::influx::{format_line, parse_tags, write_lines};
+
+stream_block! {
+    pub struct InfluxSink {
+        inputs: { "input": Vec<f64> },
+        statics: {
+            "url": String = "http://127.0.0.1:8086".to_string(),
+            "org": String = String::new(),
+            "bucket": String = String::new(),
+            "token": String = String::new(),
+            "measurement": String = "signal".to_string(),
+            "tags": String = String::new(),
+            "fields": Vec<String> = Vec::<String>::new(),
+            "flush_interval_ms": usize = 1000,
+            "batch_size": usize = 5000,
+            "timeout_ms": usize = 5000,
+        },
+        state: { "pending": Vec<String> = Vec::<String>::new() },
+        fields: { last_flush: Option<Instant> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let url = self.get_statics::<String>("url")?.get_value();
+            let bucket = self.get_statics::<String>("bucket")?.get_value();
+            let measurement = self.get_statics::<String>("measurement")?.get_value();
+            let tags = self.get_statics::<String>("tags")?.get_value();
+            let batch_size = self.get_statics::<usize>("batch_size")?.get_value();
+            if !url.starts_with("http://") || bucket.is_empty() || measurement.is_empty() || parse_tags(&tags).is_none() || batch_size == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("pending", Vec::<String>::new())?;
+            self.last_flush = Some(Instant::now());
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let measurement = self.get_statics::<String>("measurement")?.get_value();
+            let tags = parse_tags(&self.get_statics::<String>("tags")?.get_value()).ok_or(StreamingError::InvalidStatics)?;
+            let names = self.get_statics::<Vec<String>>("fields")?.get_value();
+            let flush_interval = Duration::from_millis(self.get_statics::<usize>("flush_interval_ms")?.get_value() as u64);
+            let batch_size = self.get_statics::<usize>("batch_size")?.get_value();
+            let mut pending = self.get_state_value::<Vec<String>>("pending")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+            {
+                let _lock = self.lock.lock().unwrap();
+                // each frame is one point, unnamed elements become value_<index>
+                let fields: Vec<(String, f64)> = input_signal.iter().enumerate()
+                    .map(|(k, value)| (names.get(k).cloned().unwrap_or(format!("value_{}", k)), *value))
+                    .collect();
+                if let Some(line) = format_line(&measurement, &tags, &fields, timestamp) {
+                    pending.push(line);
+                }
+            }
+            let due = self.last_flush.is_none_or(|last| last.elapsed() >= flush_interval);
+            let full = pending.len() >= batch_size;
+            self.set_state_value("pending", pending)?;
+            if due || full {
+                self.flush()?;
+            }
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.flush()
+        }
+    }
+}
+impl InfluxSink {
+    // Writes the pending lines, on failure they are kept for the next attempt up to a few
+    // batches, the oldest are dropped beyond that.
+    fn flush(&mut self) -> Result<(), StreamingError> {
+        let url = self.get_statics::<String>("url")?.get_value();
+        let org = self.get_statics::<String>("org")?.get_value();
+        let bucket = self.get_statics::<String>("bucket")?.get_value();
+        let token = self.get_statics::<String>("token")?.get_value();
+        let batch_size = self.get_statics::<usize>("batch_size")?.get_value();
+        let timeout = Duration::from_millis(self.get_statics::<usize>("timeout_ms")?.get_value() as u64);
+        let mut pending = self.get_state_value::<Vec<String>>("pending")?;
+        self.last_flush = Some(Instant::now());
+        if pending.is_empty() {
+            return Ok(());
+        }
+        match write_lines(&url, &org, &bucket, &token, &pending, timeout) {
+            Ok(()) => pending.clear(),
+            Err(error) => {
+                eprintln!("InfluxSink {}: {}", self.name, error);
+                let excess = pending.len().saturating_sub(4 * batch_size);
+                pending.drain(..excess);
+            }
+        }
+        self.set_state_value("pending", pending)?;
+        Ok(())
+    }
+}
+
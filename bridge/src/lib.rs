@@ -0,0 +1,107 @@
+pub mod ros2_link;
+pub mod ros2_bridge;
+pub mod modbus;
+pub mod modbus_source;
+pub mod influx;
+pub mod influx_sink;
+pub mod nats;
+pub mod nats_stream;
+pub mod network;
+pub mod network_stream;
+pub mod mqtt;
+pub mod mqtt_sink;
+use std::ffi::c_char;
+use data_model::modules::{Version,ModuleStructFFI};
+use processor_engine::stream_processor::StreamProcessor;
+use processor_engine::ffi::{TraitObjectRepr, export_stream_processor, get_error_return};
+#[unsafe(no_mangle)]
+pub static MODULE: ModuleStructFFI  = ModuleStructFFI {
+    name: b"Middleware bridges\0".as_ptr() as *const c_char,
+    description: b"The library provides blocks bridging streams to external middleware.\0".as_ptr() as *const c_char,
+    authors: b"Sofia Silvestri\0".as_ptr() as *const c_char,
+    release_date: b"2026/10/16\0".as_ptr() as *const c_char,
+    version: Version{ major: 0,minor: 1,build: 0},
+    dependencies: std::ptr::null(),
+    dependency_number: 0,
+    provides: [b"Ros2Source\0".as_ptr() as *const c_char,
+        b"Ros2Sink\0".as_ptr() as *const c_char,
+        b"ModbusSource\0".as_ptr() as *const c_char,
+        b"InfluxSink\0".as_ptr() as *const c_char,
+        b"NatsSink\0".as_ptr() as *const c_char,
+        b"NatsSource\0".as_ptr() as *const c_char,
+        b"NetworkSink\0".as_ptr() as *const c_char,
+        b"NetworkSource\0".as_ptr() as *const c_char,
+        b"MqttSink\0".as_ptr() as *const c_char].as_ptr(),
+    provides_lengths: 9,
+};
+/// Creates the block `proc_block` named `block_name`, both passed by the host as UTF-8 bytes.
+///
+/// # Safety
+/// A non null `proc_block` and `block_name` must point to `proc_block_len` and `block_name_len`
+/// readable bytes that stay valid while the block exists.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_processor_modules(proc_block: *const u8, 
+    proc_block_len: usize, 
+    block_name: *const u8, 
+    block_name_len: usize) -> TraitObjectRepr {
+    let proc_block_str = block_template::ffi_name!(proc_block, proc_block_len);
+    let block_name_str = block_template::ffi_name!(block_name, block_name_len);
+    let proc: Box<dyn StreamProcessor>;
+    match proc_block_str {
+        "Ros2Source" => {
+            proc = Box::new(ros2_bridge::Ros2Source::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Ros2Sink" => {
+            proc = Box::new(ros2_bridge::Ros2Sink::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "ModbusSource" => {
+            proc = Box::new(modbus_source::ModbusSource::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "InfluxSink" => {
+            proc = Box::new(influx_sink::InfluxSink::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "NatsSink" => {
+            proc = Box::new(nats_stream::NatsSink::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "NatsSource" => {
+            proc = Box::new(nats_stream::NatsSource::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "NetworkSink" => {
+            proc = Box::new(network_stream::NetworkSink::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "NetworkSource" => {
+            proc = Box::new(network_stream::NetworkSource::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "MqttSink" => {
+            proc = Box::new(mqtt_sink::MqttSink::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        _ => {
+            eprintln!("Processor block {} not found", proc_block_str);
+            get_error_return(1)
+        }
+    }
+}
+
+// Schema of every block get_processor_modules creates, keyed by block type, see stream_block!.
+pub fn block_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "Ros2Source": ros2_bridge::Ros2Source::schema(),
+        "Ros2Sink": ros2_bridge::Ros2Sink::schema(),
+        "ModbusSource": modbus_source::ModbusSource::schema(),
+        "InfluxSink": influx_sink::InfluxSink::schema(),
+        "NatsSink": nats_stream::NatsSink::schema(),
+        "NatsSource": nats_stream::NatsSource::schema(),
+        "NetworkSink": network_stream::NetworkSink::schema(),
+        "NetworkSource": network_stream::NetworkSource::schema(),
+        "MqttSink": mqtt_sink::MqttSink::schema(),
+    })
+}
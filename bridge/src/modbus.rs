@@ -0,0 +1,134 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+// One process variable of the register map, value = raw * scale + offset. Multi-register
+// formats are read big endian, most significant word first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegisterEntry {
+    pub name: String,
+    #[serde(default = "default_table")]
+    pub table: String,
+    pub address: u16,
+    #[serde(default = "default_format")]
+    pub format: String,
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub offset: f64,
+}
+fn default_table() -> String {
+    "holding".to_string()
+}
+fn default_format() -> String {
+    "u16".to_string()
+}
+fn default_scale() -> f64 {
+    1.0
+}
+impl RegisterEntry {
+    // Function code of the table, holding registers (0x03) or input registers (0x04).
+    pub fn function(&self) -> Option<u8> {
+        match self.table.as_str() {
+            "holding" => Some(0x03),
+            "input" => Some(0x04),
+            _ => None,
+        }
+    }
+    pub fn register_count(&self) -> Option<u16> {
+        match self.format.as_str() {
+            "u16" | "i16" => Some(1),
+            "u32" | "i32" | "f32" => Some(2),
+            _ => None,
+        }
+    }
+    pub fn decode(&self, words: &[u16]) -> f64 {
+        let long = || ((words[0] as u32) << 16) | words[1] as u32;
+        let raw = match self.format.as_str() {
+            "u16" => words[0] as f64,
+            "i16" => words[0] as i16 as f64,
+            "u32" => long() as f64,
+            "i32" => long() as i32 as f64,
+            _ => f32::from_bits(long()) as f64,
+        };
+        raw * self.scale + self.offset
+    }
+}
+
+// Modbus TCP read request: MBAP header (transaction, protocol 0, length, unit) and the PDU.
+pub fn read_request(transaction: u16, unit: u8, function: u8, address: u16, count: u16) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(12);
+    frame.extend_from_slice(&transaction.to_be_bytes());
+    frame.extend_from_slice(&0u16.to_be_bytes());
+    frame.extend_from_slice(&6u16.to_be_bytes());
+    frame.push(unit);
+    frame.push(function);
+    frame.extend_from_slice(&address.to_be_bytes());
+    frame.extend_from_slice(&count.to_be_bytes());
+    frame
+}
+
+// Register words of a read response, checking that it answers the given request.
+pub fn parse_read_response(frame: &[u8], transaction: u16, function: u8, count: u16) -> Result<Vec<u16>, String> {
+    if frame.len() < 9 {
+        return Err("truncated response".to_string());
+    }
+    if u16::from_be_bytes([frame[0], frame[1]]) != transaction {
+        return Err("response to another transaction".to_string());
+    }
+    if frame[7] == function | 0x80 {
+        return Err(format!("exception code {}", frame[8]));
+    }
+    let bytes = frame[8] as usize;
+    if frame[7] != function || bytes != 2 * count as usize || frame.len() < 9 + bytes {
+        return Err("malformed response".to_string());
+    }
+    Ok(frame[9..9 + bytes].chunks_exact(2).map(|w| u16::from_be_bytes([w[0], w[1]])).collect())
+}
+
+pub struct ModbusClient {
+    stream: TcpStream,
+    unit: u8,
+    transaction: u16,
+}
+impl ModbusClient {
+    pub fn connect(address: &str, unit: u8, timeout: Duration) -> Result<Self, String> {
+        let socket = address.to_socket_addrs().map_err(|e| format!("{}: {}", address, e))?
+            .next().ok_or(format!("{}: no address", address))?;
+        let stream = TcpStream::connect_timeout(&socket, timeout).map_err(|e| format!("{}: {}", address, e))?;
+        stream.set_read_timeout(Some(timeout)).and_then(|_| stream.set_write_timeout(Some(timeout))).map_err(|e| e.to_string())?;
+        Ok(ModbusClient { stream, unit, transaction: 0 })
+    }
+    pub fn read_registers(&mut self, function: u8, address: u16, count: u16) -> Result<Vec<u16>, String> {
+        self.transaction = self.transaction.wrapping_add(1);
+        let request = read_request(self.transaction, self.unit, function, address, count);
+        self.stream.write_all(&request).map_err(|e| e.to_string())?;
+        // the MBAP length field counts the bytes after itself
+        let mut header = [0u8; 6];
+        self.stream.read_exact(&mut header).map_err(|e| e.to_string())?;
+        let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+        let mut frame = header.to_vec();
+        frame.resize(6 + length, 0);
+        self.stream.read_exact(&mut frame[6..]).map_err(|e| e.to_string())?;
+        parse_read_response(&frame, self.transaction, function, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_modbus_frames() {
+        assert_eq!(read_request(7, 1, 0x03, 100, 2), vec![0, 7, 0, 0, 0, 6, 1, 3, 0, 100, 0, 2]);
+        let response = [0, 7, 0, 0, 0, 7, 1, 3, 4, 0x41, 0x20, 0x00, 0x00];
+        let words = parse_read_response(&response, 7, 0x03, 2).unwrap();
+        assert_eq!(words, vec![0x4120, 0x0000]);
+        let entry: RegisterEntry = serde_json::from_str(r#"{"name": "flow", "address": 100, "format": "f32", "scale": 2.0}"#).unwrap();
+        assert_eq!((entry.function(), entry.register_count()), (Some(0x03), Some(2)));
+        assert_eq!(entry.decode(&words), 20.0);
+        let entry = RegisterEntry { format: "i16".to_string(), ..entry };
+        assert_eq!(entry.decode(&[0xFFFE]), -4.0);
+        assert!(parse_read_response(&[0, 7, 0, 0, 0, 3, 1, 0x83, 2], 7, 0x03, 2).unwrap_err().contains("exception"));
+    }
+}
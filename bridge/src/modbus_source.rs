@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::time::{Duration, Instant};
+use crate::modbus::{ModbusClient, RegisterEntry};
+
+stream_block! {
+    pub struct ModbusSource {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "address": String = "127.0.0.1:502".to_string(),
+            "unit_id": usize = 1,
+            "register_map": String = String::new(),
+            "sample_rate": f64 = 1.0,
+            "timeout_ms": usize = 1000,
+        },
+        state: { "registers": Vec<RegisterEntry> = Vec::<RegisterEntry>::new() },
+        fields: {
+            client: Option<ModbusClient> = None,
+            next_poll: Option<Instant> = None,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let address = self.get_statics::<String>("address")?.get_value();
+            let unit_id = self.get_statics::<usize>("unit_id")?.get_value();
+            let register_map = self.get_statics::<String>("register_map")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let timeout_ms = self.get_statics::<usize>("timeout_ms")?.get_value();
+            if address.is_empty() || unit_id > 255 || sample_rate.is_nan() || sample_rate <= 0.0 || timeout_ms == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            // the register map is a JSON array of {name, table, address, format, scale, offset}
+            let registers = match serde_json::from_str::<Vec<RegisterEntry>>(&register_map) {
+                Ok(registers) => registers,
+                Err(error) => {
+                    eprintln!("ModbusSource {}: invalid register map: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            };
+            if registers.is_empty() || registers.iter().any(|r| r.function().is_none() || r.register_count().is_none()) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("registers", registers)?;
+            self.client = None;
+            self.next_poll = None;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let address = self.get_statics::<String>("address")?.get_value();
+            let unit_id = self.get_statics::<usize>("unit_id")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let timeout = Duration::from_millis(self.get_statics::<usize>("timeout_ms")?.get_value() as u64);
+            let registers = self.get_state_value::<Vec<RegisterEntry>>("registers")?;
+            let interval = Duration::from_secs_f64(1.0 / sample_rate);
+            let now = Instant::now();
+            let deadline = self.next_poll.unwrap_or(now);
+            if deadline > now {
+                std::thread::sleep(deadline - now);
+            }
+            // polls keep a fixed schedule, a slow device skips the missed slots instead of bursting
+            let next_poll = deadline + interval;
+            self.next_poll = Some(if next_poll < Instant::now() { Instant::now() + interval } else { next_poll });
+            if self.client.is_none() {
+                match ModbusClient::connect(&address, unit_id as u8, timeout) {
+                    Ok(client) => self.client = Some(client),
+                    Err(error) => eprintln!("ModbusSource {}: {}", self.name, error),
+                }
+            }
+            // registers that cannot be read are reported as NaN and the connection is opened again
+            // on the next poll
+            let mut values = Vec::with_capacity(registers.len());
+            for register in registers.iter() {
+                let Some(client) = self.client.as_mut() else {
+                    values.push(f64::NAN);
+                    continue;
+                };
+                match client.read_registers(register.function().unwrap(), register.address, register.register_count().unwrap()) {
+                    Ok(words) => values.push(register.decode(&words)),
+                    Err(error) => {
+                        eprintln!("ModbusSource {}: {}: {}", self.name, register.name, error);
+                        self.client = None;
+                        values.push(f64::NAN);
+                    }
+                }
+            }
+            self.send_output::<Vec<f64>>("output", values)?;
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.client = None;
+            Ok(())
+        }
+    }
+}
+
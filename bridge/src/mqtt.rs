@@ -0,0 +1,197 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+// Topics published to cannot hold the + and # wildcards.
+pub fn valid_topic(topic: &str) -> bool {
+    !topic.is_empty() && topic.len() <= u16::MAX as usize && !topic.contains(['+', '#', '\0'])
+}
+
+// Fills {block}, {field} and {index} in a topic template.
+pub fn render_topic(template: &str, block: &str, field: &str, index: usize) -> String {
+    template.replace("{block}", block).replace("{field}", field).replace("{index}", &index.to_string())
+}
+
+// Reduces the values to one of mean, rms, std, min, max or peak (largest magnitude).
+pub fn statistic(values: &[f64], name: &str) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    match name {
+        "mean" => Some(mean),
+        "rms" => Some((values.iter().map(|v| v * v).sum::<f64>() / n).sqrt()),
+        "std" => Some((values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n).sqrt()),
+        "min" => values.iter().copied().reduce(f64::min),
+        "max" => values.iter().copied().reduce(f64::max),
+        "peak" => values.iter().map(|v| v.abs()).reduce(f64::max),
+        _ => None,
+    }
+}
+
+// The remaining length of the fixed header, 7 bits per byte with a continuation bit.
+pub fn encode_length(mut length: usize, packet: &mut Vec<u8>) {
+    loop {
+        let byte = (length % 128) as u8;
+        length /= 128;
+        packet.push(if length > 0 { byte | 0x80 } else { byte });
+        if length == 0 {
+            break;
+        }
+    }
+}
+
+fn encode_string(text: &str, packet: &mut Vec<u8>) {
+    packet.extend_from_slice(&(text.len() as u16).to_be_bytes());
+    packet.extend_from_slice(text.as_bytes());
+}
+
+fn with_header(kind: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut packet = vec![kind];
+    encode_length(body.len(), &mut packet);
+    packet.extend(body);
+    packet
+}
+
+// MQTT 3.1.1 CONNECT with a clean session and keep alive disabled, a sink may stay quiet for
+// longer than any keep alive interval.
+pub fn connect_packet(client_id: &str, username: &str, password: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    encode_string("MQTT", &mut body);
+    let flags = 0x02 | if username.is_empty() { 0 } else { 0x80 } | if password.is_empty() { 0 } else { 0x40 };
+    body.extend_from_slice(&[4, flags, 0, 0]);
+    encode_string(client_id, &mut body);
+    for field in [username, password].into_iter().filter(|f| !f.is_empty()) {
+        encode_string(field, &mut body);
+    }
+    with_header(0x10, body)
+}
+
+pub fn publish_packet(topic: &str, payload: &[u8], qos: u8, retain: bool, packet_id: u16) -> Vec<u8> {
+    let mut body = Vec::new();
+    encode_string(topic, &mut body);
+    if qos > 0 {
+        body.extend_from_slice(&packet_id.to_be_bytes());
+    }
+    body.extend_from_slice(payload);
+    with_header(0x30 | (qos << 1) | retain as u8, body)
+}
+
+// Minimal MQTT 3.1.1 publisher: QoS 0, 1 and 2, no subscriptions.
+pub struct MqttConnection {
+    stream: TcpStream,
+    packet_id: u16,
+}
+impl MqttConnection {
+    // `address` is host:port, with or without the mqtt:// scheme.
+    pub fn connect(address: &str, client_id: &str, username: &str, password: &str, timeout: Duration) -> Result<Self, String> {
+        let host = address.strip_prefix("mqtt://").unwrap_or(address);
+        let stream = TcpStream::connect(host).map_err(|e| format!("{}: {}", host, e))?;
+        stream.set_read_timeout(Some(timeout)).and_then(|_| stream.set_write_timeout(Some(timeout))).map_err(|e| e.to_string())?;
+        let mut connection = MqttConnection { stream, packet_id: 0 };
+        connection.send(&connect_packet(client_id, username, password))?;
+        let (kind, body) = connection.read_packet()?;
+        match (kind >> 4, body.get(1)) {
+            (2, Some(0)) => Ok(connection),
+            (2, Some(code)) => Err(format!("{}: connection refused, return code {}", host, code)),
+            _ => Err(format!("{}: unexpected packet {:#04x} instead of CONNACK", host, kind)),
+        }
+    }
+    fn send(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.stream.write_all(bytes).map_err(|e| e.to_string())
+    }
+    fn read_packet(&mut self) -> Result<(u8, Vec<u8>), String> {
+        let mut byte = [0u8; 1];
+        self.stream.read_exact(&mut byte).map_err(|e| e.to_string())?;
+        let kind = byte[0];
+        let (mut length, mut shift) = (0usize, 0);
+        loop {
+            self.stream.read_exact(&mut byte).map_err(|e| e.to_string())?;
+            length |= ((byte[0] & 0x7f) as usize) << shift;
+            shift += 7;
+            if byte[0] & 0x80 == 0 || shift > 21 {
+                break;
+            }
+        }
+        let mut body = vec![0u8; length];
+        self.stream.read_exact(&mut body).map_err(|e| e.to_string())?;
+        Ok((kind, body))
+    }
+    // Skips packets until the acknowledgement `kind` of `packet_id`.
+    fn wait_for(&mut self, kind: u8, packet_id: u16) -> Result<(), String> {
+        loop {
+            let (received, body) = self.read_packet()?;
+            if received >> 4 == kind && body.get(..2) == Some(&packet_id.to_be_bytes()[..]) {
+                return Ok(());
+            }
+        }
+    }
+    pub fn publish(&mut self, topic: &str, payload: &[u8], qos: u8, retain: bool) -> Result<(), String> {
+        // packet identifiers are non zero
+        self.packet_id = self.packet_id.wrapping_add(1).max(1);
+        let packet_id = self.packet_id;
+        self.send(&publish_packet(topic, payload, qos, retain, packet_id))?;
+        match qos {
+            1 => self.wait_for(4, packet_id),
+            2 => {
+                self.wait_for(5, packet_id)?;
+                let mut release = vec![0x62, 0x02];
+                release.extend_from_slice(&packet_id.to_be_bytes());
+                self.send(&release)?;
+                self.wait_for(7, packet_id)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+impl Drop for MqttConnection {
+    fn drop(&mut self) {
+        let _ = self.send(&[0xe0, 0x00]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    #[test]
+    fn test_mqtt_exchange() {
+        let mut length = Vec::new();
+        encode_length(321, &mut length);
+        assert_eq!(length, vec![0xc1, 0x02]);
+        assert_eq!(render_topic("plant/{block}/{field}/{index}", "pump", "rms", 2), "plant/pump/rms/2");
+        assert_eq!(statistic(&[3.0, -4.0], "rms"), Some(12.5f64.sqrt()));
+        assert_eq!(statistic(&[3.0, -4.0], "peak"), Some(4.0));
+        assert_eq!(statistic(&[], "mean"), None);
+        assert!(!valid_topic("plant/+/rms"));
+        // a scripted broker: CONNACK, then PUBACK for QoS 1 and PUBREC / PUBCOMP for QoS 2
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut broker = MqttConnection { stream, packet_id: 0 };
+            let mut received = vec![broker.read_packet().unwrap()];
+            broker.send(&[0x20, 0x02, 0x00, 0x00]).unwrap();
+            received.push(broker.read_packet().unwrap());
+            received.push(broker.read_packet().unwrap());
+            broker.send(&[0x40, 0x02, 0x00, 0x02]).unwrap();
+            received.push(broker.read_packet().unwrap());
+            broker.send(&[0x50, 0x02, 0x00, 0x03]).unwrap();
+            received.push(broker.read_packet().unwrap());
+            broker.send(&[0x70, 0x02, 0x00, 0x03]).unwrap();
+            received.push(broker.read_packet().unwrap());
+            received
+        });
+        let mut connection = MqttConnection::connect(&format!("mqtt://{}", address), "test", "user", "", Duration::from_secs(1)).unwrap();
+        connection.publish("a/b", b"1.5", 0, true).unwrap();
+        connection.publish("a/b", b"2", 1, false).unwrap();
+        connection.publish("a/b", b"3", 2, false).unwrap();
+        drop(connection);
+        let received = server.join().unwrap();
+        let kinds: Vec<u8> = received.iter().map(|(kind, _)| *kind).collect();
+        assert_eq!(kinds, vec![0x10, 0x31, 0x32, 0x34, 0x62, 0xe0]);
+        assert_eq!(received[0].1, connect_packet("test", "user", "")[2..].to_vec());
+        assert_eq!(received[1].1, [&[0x00, 0x03][..], b"a/b1.5"].concat());
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use serde_json::{Map, Value, json};
+use crate::mqtt::{MqttConnection, render_topic, statistic, valid_topic};
+
+// Publishes the input to an MQTT broker. Every value goes out as a field named after fields, or
+// value_<index>, unless statistics lists reductions (mean, rms, std, min, max, peak) of the
+// values received since the last publication, which then become the fields. A topic template
+// with {field} or {index} gets one message per field, "text" publishes the bare number and
+// "json" {"value": .., "timestamp_ns": ..}. Otherwise all the fields go in one JSON object.
+// publish_interval_ms limits the rate, frames in between only feed the statistics.
+stream_block! {
+    pub struct MqttSink {
+        inputs: { "input": Vec<f64> },
+        statics: {
+            "address": String = "mqtt://127.0.0.1:1883".to_string(),
+            "client_id": String = String::new(),
+            "username": String = String::new(),
+            "password": String = String::new(),
+            "topic": String = "signal/{block}/{field}".to_string(),
+            "fields": Vec<String> = Vec::<String>::new(),
+            "statistics": Vec<String> = Vec::<String>::new(),
+            "format": String = "text".to_string(),
+            "qos": usize = 0,
+            "retain": bool = false,
+            "publish_interval_ms": usize = 0,
+            "timeout_ms": usize = 1000,
+        },
+        state: { "published": usize = 0 },
+        fields: {
+            connection: Option<MqttConnection> = None,
+            pending: Vec<f64> = Vec::new(),
+            last_publish: Option<Instant> = None,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let address = self.get_statics::<String>("address")?.get_value();
+            let client_id = self.get_statics::<String>("client_id")?.get_value();
+            let username = self.get_statics::<String>("username")?.get_value();
+            let password = self.get_statics::<String>("password")?.get_value();
+            let template = self.get_statics::<String>("topic")?.get_value();
+            let statistics = self.get_statics::<Vec<String>>("statistics")?.get_value();
+            let format = self.get_statics::<String>("format")?.get_value();
+            let qos = self.get_statics::<usize>("qos")?.get_value();
+            let timeout_ms = self.get_statics::<usize>("timeout_ms")?.get_value();
+            // plain numbers need a topic per field, and MQTT 3.1.1 sends no password without a user name
+            let per_field = template.contains("{field}") || template.contains("{index}");
+            if address.is_empty() || !valid_topic(&render_topic(&template, self.name, "field", 0)) || !matches!(format.as_str(), "text" | "json")
+                || (format == "text" && !per_field) || statistics.iter().any(|name| statistic(&[0.0], name).is_none()) || qos > 2 || timeout_ms == 0
+                || (username.is_empty() && !password.is_empty()) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let client_id = if client_id.is_empty() { self.name.to_string() } else { client_id };
+            match MqttConnection::connect(&address, &client_id, &username, &password, Duration::from_millis(timeout_ms as u64)) {
+                Ok(connection) => self.connection = Some(connection),
+                Err(error) => {
+                    eprintln!("MqttSink {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.pending = Vec::new();
+            self.last_publish = None;
+            self.set_state_value("published", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let statistics = self.get_statics::<Vec<String>>("statistics")?.get_value();
+            let publish_interval = Duration::from_millis(self.get_statics::<usize>("publish_interval_ms")?.get_value() as u64);
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            {
+                let _lock = self.lock.lock().unwrap();
+                // the statistics cover every frame since the last publication, raw values only the latest
+                if statistics.is_empty() {
+                    self.pending = input_signal;
+                } else {
+                    self.pending.extend(input_signal);
+                }
+            }
+            if self.last_publish.is_none_or(|last| last.elapsed() >= publish_interval)
+                && let Err(error) = self.publish() {
+                self.stop()?;
+                return Err(error);
+            }
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            // statistics still pending go out before disconnecting
+            if self.connection.is_some() && !self.pending.is_empty() {
+                let _ = self.publish();
+            }
+            self.connection = None;
+            Ok(())
+        }
+    }
+}
+
+// Topic and payload of the messages for the named values, non finite values are left out.
+fn format_messages(template: &str, block: &str, format: &str, fields: &[(String, f64)], timestamp_ns: u128) -> Vec<(String, Vec<u8>)> {
+    let fields: Vec<(usize, &(String, f64))> = fields.iter().enumerate().filter(|(_, (_, value))| value.is_finite()).collect();
+    if template.contains("{field}") || template.contains("{index}") {
+        fields.into_iter().map(|(index, (field, value))| {
+            let payload = if format == "json" { json!({"value": value, "timestamp_ns": timestamp_ns as u64}).to_string() } else { value.to_string() };
+            (render_topic(template, block, field, index), payload.into_bytes())
+        }).collect()
+    } else if fields.is_empty() {
+        Vec::new()
+    } else {
+        let mut object = Map::new();
+        object.insert("timestamp_ns".to_string(), json!(timestamp_ns as u64));
+        for (_, (field, value)) in fields {
+            object.insert(field.clone(), json!(value));
+        }
+        vec![(render_topic(template, block, "", 0), Value::Object(object).to_string().into_bytes())]
+    }
+}
+
+impl MqttSink {
+    // Publishes the pending values, reduced to the statistics if any. A failed publication is
+    // reported here.
+    fn publish(&mut self) -> Result<(), StreamingError> {
+        let template = self.get_statics::<String>("topic")?.get_value();
+        let names = self.get_statics::<Vec<String>>("fields")?.get_value();
+        let statistics = self.get_statics::<Vec<String>>("statistics")?.get_value();
+        let format = self.get_statics::<String>("format")?.get_value();
+        let qos = self.get_statics::<usize>("qos")?.get_value();
+        let retain = self.get_statics::<bool>("retain")?.get_value();
+        let published = self.get_state_value::<usize>("published")?;
+        let timestamp_ns = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        self.last_publish = Some(Instant::now());
+        let values = std::mem::take(&mut self.pending);
+        if values.is_empty() {
+            return Ok(());
+        }
+        let fields: Vec<(String, f64)> = if statistics.is_empty() {
+            values.iter().enumerate().map(|(k, value)| (names.get(k).cloned().unwrap_or(format!("value_{}", k)), *value)).collect()
+        } else {
+            statistics.iter().filter_map(|name| statistic(&values, name).map(|value| (name.clone(), value))).collect()
+        };
+        let messages = format_messages(&template, self.name, &format, &fields, timestamp_ns);
+        let connection = self.connection.as_mut().ok_or(StreamingError::InvalidStateTransition)?;
+        if let Err(error) = messages.iter().try_for_each(|(topic, payload)| connection.publish(topic, payload, qos as u8, retain)) {
+            eprintln!("MqttSink {}: {}", self.name, error);
+            return Err(StreamingError::InvalidInput);
+        }
+        self.set_state_value("published", published + messages.len())?;
+        Ok(())
+    }
+}
+
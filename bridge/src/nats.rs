@@ -0,0 +1,126 @@
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+// "MSG <subject> <sid> [reply-to] <#bytes>", returns the payload size.
+pub fn parse_message_header(line: &str) -> Option<usize> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    match tokens.as_slice() {
+        ["MSG", _, _, size] | ["MSG", _, _, _, size] => size.parse().ok(),
+        _ => None,
+    }
+}
+
+// Minimal client of the NATS core text protocol: publish, one subscription, PING/PONG.
+pub struct NatsConnection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    line: String,
+}
+impl NatsConnection {
+    // `address` is host:port, with or without the nats:// scheme.
+    pub fn connect(address: &str, client_name: &str, timeout: Duration) -> Result<Self, String> {
+        let host = address.strip_prefix("nats://").unwrap_or(address);
+        let writer = TcpStream::connect(host).map_err(|e| format!("{}: {}", host, e))?;
+        writer.set_read_timeout(Some(timeout)).and_then(|_| writer.set_write_timeout(Some(timeout))).map_err(|e| e.to_string())?;
+        let reader = BufReader::new(writer.try_clone().map_err(|e| e.to_string())?);
+        let mut connection = NatsConnection { reader, writer, line: String::new() };
+        let info = connection.read_line()?;
+        if !info.starts_with("INFO") {
+            return Err(format!("{}: unexpected greeting {}", host, info));
+        }
+        let options = serde_json::json!({"verbose": false, "pedantic": false, "name": client_name, "lang": "rust", "protocol": 1});
+        connection.send(format!("CONNECT {}\r\nPING\r\n", options).as_bytes())?;
+        // the server answers the PING only once CONNECT has been accepted
+        loop {
+            let line = connection.read_line()?;
+            if line.starts_with("PONG") {
+                return Ok(connection);
+            }
+            if line.starts_with("-ERR") {
+                return Err(line);
+            }
+        }
+    }
+    fn send(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.writer.write_all(bytes).map_err(|e| e.to_string())
+    }
+    fn read_line(&mut self) -> Result<String, String> {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        if line.is_empty() {
+            return Err("connection closed".to_string());
+        }
+        Ok(line.trim_end().to_string())
+    }
+    pub fn publish(&mut self, subject: &str, payload: &[u8]) -> Result<(), String> {
+        let mut message = format!("PUB {} {}\r\n", subject, payload.len()).into_bytes();
+        message.extend_from_slice(payload);
+        message.extend_from_slice(b"\r\n");
+        self.send(&message)
+    }
+    pub fn subscribe(&mut self, subject: &str) -> Result<(), String> {
+        self.send(format!("SUB {} 1\r\n", subject).as_bytes())
+    }
+    // Next message payload, None when nothing arrived within `timeout`. A line cut by the
+    // timeout is kept and completed on the next call.
+    pub fn next_message(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, String> {
+        self.reader.get_ref().set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+        loop {
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return Err("connection closed".to_string()),
+                Ok(_) => {}
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return Ok(None),
+                Err(e) => return Err(e.to_string()),
+            }
+            let line = std::mem::take(&mut self.line);
+            let line = line.trim_end();
+            if line == "PING" {
+                self.send(b"PONG\r\n")?;
+            } else if line.starts_with("-ERR") {
+                return Err(line.to_string());
+            } else if let Some(size) = parse_message_header(line) {
+                let mut payload = vec![0u8; size + 2];
+                self.reader.read_exact(&mut payload).map_err(|e| e.to_string())?;
+                payload.truncate(size);
+                return Ok(Some(payload));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    #[test]
+    fn test_nats_exchange() {
+        assert_eq!(parse_message_header("MSG frames 1 11"), Some(11));
+        assert_eq!(parse_message_header("MSG frames 1 inbox.7 3"), Some(3));
+        assert_eq!(parse_message_header("PONG"), None);
+        // a scripted server: greeting, PONG to the connect PING, then a PING and one message
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"INFO {\"server_id\":\"test\"}\r\n").unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut received = String::new();
+            while !received.ends_with("PING\r\n") {
+                reader.read_line(&mut received).unwrap();
+            }
+            stream.write_all(b"PONG\r\nPING\r\nMSG frames 1 5\r\nhello\r\n").unwrap();
+            let mut rest = String::new();
+            while !rest.contains("PONG") || !rest.contains("hi\r\n") {
+                reader.read_line(&mut rest).unwrap();
+            }
+            rest
+        });
+        let mut connection = NatsConnection::connect(&format!("nats://{}", address), "test", Duration::from_secs(1)).unwrap();
+        connection.subscribe("frames").unwrap();
+        assert_eq!(connection.next_message(Duration::from_secs(1)).unwrap(), Some(b"hello".to_vec()));
+        connection.publish("frames", b"hi").unwrap();
+        let rest = server.join().unwrap();
+        assert!(rest.contains("SUB frames 1") && rest.contains("PUB frames 2"));
+    }
+}
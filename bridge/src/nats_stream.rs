@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::nats::NatsConnection;
+use codec::{StreamFrame, valid_format, encode_frame, decode_frame};
+
+stream_block! {
+    pub struct NatsSink {
+        inputs: { "input": Vec<f64> },
+        statics: {
+            "address": String = "nats://127.0.0.1:4222".to_string(),
+            "subject": String = String::new(),
+            "format": String = "json".to_string(),
+            "timeout_ms": usize = 1000,
+        },
+        state: { "sequence": usize = 0 },
+        fields: { connection: Option<NatsConnection> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let address = self.get_statics::<String>("address")?.get_value();
+            let subject = self.get_statics::<String>("subject")?.get_value();
+            let format = self.get_statics::<String>("format")?.get_value();
+            let timeout_ms = self.get_statics::<usize>("timeout_ms")?.get_value();
+            if address.is_empty() || !valid_subject(&subject, false) || !valid_format(&format) || timeout_ms == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            match NatsConnection::connect(&address, self.name, Duration::from_millis(timeout_ms as u64)) {
+                Ok(connection) => self.connection = Some(connection),
+                Err(error) => {
+                    eprintln!("NatsSink {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state_value("sequence", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let subject = self.get_statics::<String>("subject")?.get_value();
+            let format = self.get_statics::<String>("format")?.get_value();
+            let sequence = self.get_state_value::<usize>("sequence")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let timestamp_ns = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+            let result = {
+                let _lock = self.lock.lock().unwrap();
+                let frame = StreamFrame { sequence: sequence as u64, timestamp_ns, values: input_signal };
+                let connection = self.connection.as_mut().ok_or(StreamingError::InvalidStateTransition)?;
+                encode_frame(&frame, &format).and_then(|payload| connection.publish(&subject, &payload))
+            };
+            if let Err(error) = result {
+                eprintln!("NatsSink {}: {}", self.name, error);
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            self.set_state_value("sequence", sequence + 1)?;
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.connection = None;
+            Ok(())
+        }
+    }
+}
+
+stream_block! {
+    pub struct NatsSource {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "address": String = "nats://127.0.0.1:4222".to_string(),
+            "subject": String = String::new(),
+            "format": String = "json".to_string(),
+            "timeout_ms": usize = 1000,
+        },
+        state: { "expected_sequence": usize = 0 },
+        fields: { connection: Option<NatsConnection> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let address = self.get_statics::<String>("address")?.get_value();
+            let subject = self.get_statics::<String>("subject")?.get_value();
+            let format = self.get_statics::<String>("format")?.get_value();
+            let timeout_ms = self.get_statics::<usize>("timeout_ms")?.get_value();
+            if address.is_empty() || !valid_subject(&subject, true) || !valid_format(&format) || timeout_ms == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let connection = NatsConnection::connect(&address, self.name, Duration::from_millis(timeout_ms as u64))
+                .and_then(|mut connection| connection.subscribe(&subject).map(|_| connection));
+            match connection {
+                Ok(connection) => self.connection = Some(connection),
+                Err(error) => {
+                    eprintln!("NatsSource {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state_value("expected_sequence", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let format = self.get_statics::<String>("format")?.get_value();
+            let expected_sequence = self.get_state_value::<usize>("expected_sequence")?;
+            // the wait is bounded so that the run loop still sees a stop request on a silent subject
+            let message = self.connection.as_mut().ok_or(StreamingError::InvalidStateTransition)?.next_message(Duration::from_millis(100));
+            let payload = match message {
+                Ok(Some(payload)) => payload,
+                Ok(None) => return Ok(()),
+                Err(error) => {
+                    eprintln!("NatsSource {}: {}", self.name, error);
+                    self.stop()?;
+                    return Err(StreamingError::InvalidInput);
+                }
+            };
+            // a frame that does not decode is reported and skipped, it does not end the stream
+            let frame = match decode_frame(&payload, &format) {
+                Ok(frame) => frame,
+                Err(error) => {
+                    eprintln!("NatsSource {}: {}", self.name, error);
+                    return Ok(());
+                }
+            };
+            let sequence = frame.sequence as usize;
+            if expected_sequence > 0 && sequence > expected_sequence {
+                eprintln!("NatsSource {}: {} frames lost", self.name, sequence - expected_sequence);
+            }
+            self.set_state_value("expected_sequence", sequence + 1)?;
+            self.send_output::<Vec<f64>>("output", frame.values)?;
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.connection = None;
+            Ok(())
+        }
+    }
+}
+
+// Publishing to a wildcard subject is rejected by the server, subscriptions may use them.
+fn valid_subject(subject: &str, wildcards: bool) -> bool {
+    !subject.is_empty() && !subject.contains(char::is_whitespace)
+        && subject.split('.').all(|token| !token.is_empty() && (wildcards || (token != "*" && token != ">")))
+}
+
@@ -0,0 +1,234 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+// Largest payload of a UDP datagram over IPv4.
+pub const MAX_DATAGRAM: usize = 65507;
+// Guard against a corrupted length prefix, no frame is expected to come close.
+const MAX_MESSAGE: usize = 1 << 26;
+
+pub fn valid_protocol(protocol: &str) -> bool {
+    matches!(protocol, "udp" | "tcp")
+}
+
+pub fn valid_framing(framing: &str) -> bool {
+    matches!(framing, "length_prefixed" | "raw")
+}
+
+// Bytes per value of the codec formats usable without framing, where the frame size alone
+// delimits the messages.
+pub fn raw_value_size(format: &str) -> Option<usize> {
+    match format {
+        "raw_f32le" => Some(4),
+        "raw_f64le" => Some(8),
+        _ => None,
+    }
+}
+
+// Message as it goes on a TCP stream, "length_prefixed" puts the payload size before it as a
+// little endian u32.
+pub fn frame_message(payload: &[u8], framing: &str) -> Vec<u8> {
+    let mut message = Vec::with_capacity(payload.len() + 4);
+    if framing == "length_prefixed" {
+        message.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    }
+    message.extend_from_slice(payload);
+    message
+}
+
+// Removes the first complete message from the bytes received on a TCP stream, None while it is
+// still incomplete. "raw" messages are `raw_size` bytes long.
+pub fn take_message(pending: &mut Vec<u8>, framing: &str, raw_size: usize) -> Result<Option<Vec<u8>>, String> {
+    let (start, size) = if framing == "length_prefixed" {
+        if pending.len() < 4 {
+            return Ok(None);
+        }
+        let size = u32::from_le_bytes(pending[..4].try_into().unwrap()) as usize;
+        if size > MAX_MESSAGE {
+            return Err(format!("message length {} out of range, the stream is out of sync", size));
+        }
+        (4, size)
+    } else {
+        (0, raw_size)
+    };
+    if pending.len() < start + size {
+        return Ok(None);
+    }
+    let message = pending[start..start + size].to_vec();
+    pending.drain(..start + size);
+    Ok(Some(message))
+}
+
+// One end of a UDP or TCP link. UDP binds `address` to receive and sends to `address`, each
+// datagram is one message. TCP connects to `address`, or with `listen` accepts one peer at a
+// time on it and waits for the next one when the peer leaves.
+pub struct NetworkLink {
+    udp: Option<UdpSocket>,
+    listener: Option<TcpListener>,
+    stream: Option<TcpStream>,
+    framing: String,
+    raw_size: usize,
+    pending: Vec<u8>,
+}
+impl NetworkLink {
+    pub fn open(protocol: &str, address: &str, listen: bool, receive: bool, framing: &str, raw_size: usize, timeout: Duration) -> Result<Self, String> {
+        let socket = address.to_socket_addrs().map_err(|e| format!("{}: {}", address, e))?
+            .next().ok_or_else(|| format!("{}: no address", address))?;
+        let mut link = NetworkLink { udp: None, listener: None, stream: None, framing: framing.to_string(), raw_size, pending: Vec::new() };
+        match (protocol, listen) {
+            ("udp", _) if receive => link.udp = Some(UdpSocket::bind(socket).map_err(|e| format!("{}: {}", address, e))?),
+            ("udp", _) => {
+                let local: SocketAddr = if socket.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+                let udp = UdpSocket::bind(local).and_then(|udp| udp.connect(socket).map(|_| udp));
+                link.udp = Some(udp.map_err(|e| format!("{}: {}", address, e))?);
+            }
+            (_, true) => {
+                let listener = TcpListener::bind(socket).and_then(|l| l.set_nonblocking(true).map(|_| l));
+                link.listener = Some(listener.map_err(|e| format!("{}: {}", address, e))?);
+            }
+            (_, false) => {
+                let stream = TcpStream::connect_timeout(&socket, timeout).and_then(|s| s.set_write_timeout(Some(timeout)).map(|_| s));
+                link.stream = Some(stream.map_err(|e| format!("{}: {}", address, e))?);
+            }
+        }
+        Ok(link)
+    }
+    pub fn local_address(&self) -> Option<SocketAddr> {
+        match (&self.udp, &self.listener, &self.stream) {
+            (Some(udp), _, _) => udp.local_addr().ok(),
+            (_, Some(listener), _) => listener.local_addr().ok(),
+            (_, _, Some(stream)) => stream.local_addr().ok(),
+            _ => None,
+        }
+    }
+    // A listening link takes the next waiting peer, if any.
+    fn accept(&mut self) -> Result<bool, String> {
+        if self.stream.is_some() {
+            return Ok(true);
+        }
+        let Some(listener) = &self.listener else {
+            return Err("connection closed".to_string());
+        };
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+                self.stream = Some(stream);
+                self.pending.clear();
+                Ok(true)
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+    // The peer is gone, a listening link goes back to waiting for the next one.
+    fn disconnect(&mut self, error: String) -> Result<(), String> {
+        self.stream = None;
+        if self.listener.is_some() { Ok(()) } else { Err(error) }
+    }
+    // Next message, None when nothing complete arrived within `timeout`.
+    pub fn receive(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, String> {
+        if let Some(udp) = &self.udp {
+            let mut buffer = vec![0u8; MAX_DATAGRAM];
+            udp.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+            return match udp.recv(&mut buffer) {
+                Ok(size) => {
+                    buffer.truncate(size);
+                    Ok(Some(buffer))
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => Ok(None),
+                Err(e) => Err(e.to_string()),
+            };
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(message) = take_message(&mut self.pending, &self.framing, self.raw_size)? {
+                return Ok(Some(message));
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            if !self.accept()? {
+                std::thread::sleep(remaining.min(Duration::from_millis(10)));
+                continue;
+            }
+            let Some(stream) = self.stream.as_mut() else {
+                continue;
+            };
+            let mut buffer = [0u8; 65536];
+            let read = stream.set_read_timeout(Some(remaining)).and_then(|_| stream.read(&mut buffer));
+            match read {
+                Ok(0) => self.disconnect("connection closed".to_string())?,
+                Ok(size) => self.pending.extend_from_slice(&buffer[..size]),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) => self.disconnect(e.to_string())?,
+            }
+        }
+    }
+    // Returns false when the message was dropped because no peer is connected to a listening
+    // link.
+    pub fn send(&mut self, payload: &[u8]) -> Result<bool, String> {
+        if let Some(udp) = &self.udp {
+            if payload.len() > MAX_DATAGRAM {
+                return Err(format!("{} bytes do not fit a UDP datagram", payload.len()));
+            }
+            return udp.send(payload).map(|_| true).map_err(|e| e.to_string());
+        }
+        if !self.accept()? {
+            return Ok(false);
+        }
+        let message = frame_message(payload, &self.framing);
+        let stream = self.stream.as_mut().ok_or("connection closed")?;
+        match stream.write_all(&message) {
+            Ok(()) => Ok(true),
+            Err(e) => self.disconnect(e.to_string()).map(|_| false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_framing() {
+        let mut pending = frame_message(b"abc", "length_prefixed");
+        pending.extend_from_slice(&frame_message(b"de", "length_prefixed")[..5]);
+        assert_eq!(take_message(&mut pending, "length_prefixed", 0), Ok(Some(b"abc".to_vec())));
+        assert_eq!(take_message(&mut pending, "length_prefixed", 0), Ok(None));
+        pending.push(b'e');
+        assert_eq!(take_message(&mut pending, "length_prefixed", 0), Ok(Some(b"de".to_vec())));
+        let mut pending = vec![1, 2, 3, 4, 5];
+        assert_eq!(take_message(&mut pending, "raw", 4), Ok(Some(vec![1, 2, 3, 4])));
+        assert_eq!(take_message(&mut pending, "raw", 4), Ok(None));
+        assert!(take_message(&mut vec![255; 8], "length_prefixed", 0).is_err());
+    }
+    #[test]
+    fn test_links() {
+        let timeout = Duration::from_secs(1);
+        for protocol in ["udp", "tcp"] {
+            let mut receiver = NetworkLink::open(protocol, "127.0.0.1:0", true, true, "length_prefixed", 0, timeout).unwrap();
+            let address = receiver.local_address().unwrap().to_string();
+            let mut sender = NetworkLink::open(protocol, &address, false, false, "length_prefixed", 0, timeout).unwrap();
+            assert_eq!(sender.send(b"first"), Ok(true));
+            assert_eq!(sender.send(b"second"), Ok(true));
+            assert_eq!(receiver.receive(timeout), Ok(Some(b"first".to_vec())));
+            assert_eq!(receiver.receive(timeout), Ok(Some(b"second".to_vec())));
+            assert_eq!(receiver.receive(Duration::from_millis(50)), Ok(None));
+        }
+        // a listening TCP sender drops messages until a peer connects
+        let mut sender = NetworkLink::open("tcp", "127.0.0.1:0", true, false, "raw", 2, timeout).unwrap();
+        assert_eq!(sender.send(b"xx"), Ok(false));
+        let address = sender.local_address().unwrap().to_string();
+        let mut receiver = NetworkLink::open("tcp", &address, false, true, "raw", 2, timeout).unwrap();
+        let mut sent = false;
+        for _ in 0..100 {
+            sent = sender.send(b"ab").unwrap();
+            if sent {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(sent);
+        assert_eq!(receiver.receive(timeout), Ok(Some(b"ab".to_vec())));
+    }
+}
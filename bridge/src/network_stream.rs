@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::network::{NetworkLink, raw_value_size, valid_framing, valid_protocol};
+use codec::{StreamFrame, valid_format, encode_frame, decode_frame};
+
+// Sends the input frames over UDP, one datagram per frame, or TCP. On TCP "length_prefixed"
+// framing puts the payload size before each frame as a little endian u32, "raw" writes the bare
+// samples and needs a raw_f32le or raw_f64le format. With listen the sink waits for a TCP peer
+// on address and drops the frames while none is connected, otherwise it connects to address.
+stream_block! {
+    pub struct NetworkSink {
+        inputs: { "input": Vec<f64> },
+        statics: {
+            "protocol": String = "udp".to_string(),
+            "address": String = "127.0.0.1:5000".to_string(),
+            "listen": bool = false,
+            "framing": String = "length_prefixed".to_string(),
+            "format": String = "raw_f32le".to_string(),
+            "timeout_ms": usize = 1000,
+        },
+        state: {
+            "sequence": usize = 0,
+            "dropped": usize = 0,
+        },
+        fields: { link: Option<NetworkLink> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let protocol = self.get_statics::<String>("protocol")?.get_value();
+            let address = self.get_statics::<String>("address")?.get_value();
+            let listen = self.get_statics::<bool>("listen")?.get_value();
+            let framing = self.get_statics::<String>("framing")?.get_value();
+            let format = self.get_statics::<String>("format")?.get_value();
+            let timeout_ms = self.get_statics::<usize>("timeout_ms")?.get_value();
+            if !valid_link(&protocol, &address, &framing, &format, timeout_ms) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            match NetworkLink::open(&protocol, &address, listen, false, &framing, 0, Duration::from_millis(timeout_ms as u64)) {
+                Ok(link) => self.link = Some(link),
+                Err(error) => {
+                    eprintln!("NetworkSink {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state_value("sequence", 0usize)?;
+            self.set_state_value("dropped", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let format = self.get_statics::<String>("format")?.get_value();
+            let sequence = self.get_state_value::<usize>("sequence")?;
+            let dropped = self.get_state_value::<usize>("dropped")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let timestamp_ns = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+            let result = {
+                let _lock = self.lock.lock().unwrap();
+                let frame = StreamFrame { sequence: sequence as u64, timestamp_ns, values: input_signal };
+                let link = self.link.as_mut().ok_or(StreamingError::InvalidStateTransition)?;
+                encode_frame(&frame, &format).and_then(|payload| link.send(&payload))
+            };
+            match result {
+                Ok(true) => {}
+                Ok(false) => self.set_state_value("dropped", dropped + 1)?,
+                Err(error) => {
+                    eprintln!("NetworkSink {}: {}", self.name, error);
+                    self.stop()?;
+                    return Err(StreamingError::InvalidInput);
+                }
+            }
+            self.set_state_value("sequence", sequence + 1)?;
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.link = None;
+            Ok(())
+        }
+    }
+}
+
+// Receives frames over UDP, one datagram per frame, or TCP with the framing of NetworkSink. The
+// source binds address for UDP. On TCP it accepts one peer at a time on address with listen,
+// the default, or connects to it. "raw" framing cuts the byte stream in frames of frame_size
+// samples.
+stream_block! {
+    pub struct NetworkSource {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "protocol": String = "udp".to_string(),
+            "address": String = "0.0.0.0:5000".to_string(),
+            "listen": bool = true,
+            "framing": String = "length_prefixed".to_string(),
+            "format": String = "raw_f32le".to_string(),
+            "frame_size": usize = 1024,
+            "timeout_ms": usize = 1000,
+        },
+        state: { "expected_sequence": usize = 0 },
+        fields: { link: Option<NetworkLink> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let protocol = self.get_statics::<String>("protocol")?.get_value();
+            let address = self.get_statics::<String>("address")?.get_value();
+            let listen = self.get_statics::<bool>("listen")?.get_value();
+            let framing = self.get_statics::<String>("framing")?.get_value();
+            let format = self.get_statics::<String>("format")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let timeout_ms = self.get_statics::<usize>("timeout_ms")?.get_value();
+            if !valid_link(&protocol, &address, &framing, &format, timeout_ms) || frame_size == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let raw_size = frame_size * raw_value_size(&format).unwrap_or(0);
+            match NetworkLink::open(&protocol, &address, listen, true, &framing, raw_size, Duration::from_millis(timeout_ms as u64)) {
+                Ok(link) => self.link = Some(link),
+                Err(error) => {
+                    eprintln!("NetworkSource {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state_value("expected_sequence", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let format = self.get_statics::<String>("format")?.get_value();
+            let expected_sequence = self.get_state_value::<usize>("expected_sequence")?;
+            // the wait is bounded so that the run loop still sees a stop request on a silent link
+            let message = self.link.as_mut().ok_or(StreamingError::InvalidStateTransition)?.receive(Duration::from_millis(100));
+            let payload = match message {
+                Ok(Some(payload)) => payload,
+                Ok(None) => return Ok(()),
+                Err(error) => {
+                    eprintln!("NetworkSource {}: {}", self.name, error);
+                    self.stop()?;
+                    return Err(StreamingError::InvalidInput);
+                }
+            };
+            // a frame that does not decode is reported and skipped, it does not end the stream
+            let frame = match decode_frame(&payload, &format) {
+                Ok(frame) => frame,
+                Err(error) => {
+                    eprintln!("NetworkSource {}: {}", self.name, error);
+                    return Ok(());
+                }
+            };
+            let sequence = frame.sequence as usize;
+            if expected_sequence > 0 && sequence > expected_sequence {
+                eprintln!("NetworkSource {}: {} frames lost", self.name, sequence - expected_sequence);
+            }
+            self.set_state_value("expected_sequence", sequence + 1)?;
+            self.send_output::<Vec<f64>>("output", frame.values)?;
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.link = None;
+            Ok(())
+        }
+    }
+}
+
+// Raw framing leaves the frame boundaries to the sample format, only the raw ones qualify.
+fn valid_link(protocol: &str, address: &str, framing: &str, format: &str, timeout_ms: usize) -> bool {
+    valid_protocol(protocol) && !address.is_empty() && valid_framing(framing) && valid_format(format)
+        && (framing != "raw" || raw_value_size(format).is_some()) && timeout_ms > 0
+}
+
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::time::Duration;
+use crate::ros2_link::{Ros2Publisher, Ros2Subscriber, valid_message_type};
+
+stream_block! {
+    pub struct Ros2Source {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "node_name": String = "signal_processing".to_string(),
+            "topic": String = String::new(),
+            "message_type": String = "float64_multi_array".to_string(),
+        },
+        fields: { subscriber: Option<Ros2Subscriber> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let node_name = self.get_statics::<String>("node_name")?.get_value();
+            let topic = self.get_statics::<String>("topic")?.get_value();
+            let message_type = self.get_statics::<String>("message_type")?.get_value();
+            if node_name.is_empty() || topic.is_empty() || !valid_message_type(&message_type) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            match Ros2Subscriber::create(&node_name, &topic, &message_type) {
+                Ok(subscriber) => self.subscriber = Some(subscriber),
+                Err(error) => {
+                    eprintln!("Ros2Source {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            // the wait is bounded so that the run loop still sees a stop request on a silent topic
+            let frame = self.subscriber.as_ref().ok_or(StreamingError::InvalidStateTransition)?.receive(Duration::from_millis(100));
+            if let Some(frame) = frame {
+                self.send_output::<Vec<f64>>("output", frame)?;
+            }
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.subscriber = None;
+            Ok(())
+        }
+    }
+}
+
+stream_block! {
+    pub struct Ros2Sink {
+        inputs: { "input": Vec<f64> },
+        statics: {
+            "node_name": String = "signal_processing".to_string(),
+            "topic": String = String::new(),
+            "message_type": String = "float64_multi_array".to_string(),
+            "frame_id": String = String::new(),
+        },
+        fields: { publisher: Option<Ros2Publisher> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let node_name = self.get_statics::<String>("node_name")?.get_value();
+            let topic = self.get_statics::<String>("topic")?.get_value();
+            let message_type = self.get_statics::<String>("message_type")?.get_value();
+            let frame_id = self.get_statics::<String>("frame_id")?.get_value();
+            if node_name.is_empty() || topic.is_empty() || !valid_message_type(&message_type) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            match Ros2Publisher::create(&node_name, &topic, &message_type, &frame_id) {
+                Ok(publisher) => self.publisher = Some(publisher),
+                Err(error) => {
+                    eprintln!("Ros2Sink {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let result = {
+                let _lock = self.lock.lock().unwrap();
+                self.publisher.as_ref().ok_or(StreamingError::InvalidStateTransition)?.publish(&input_signal)
+            };
+            if let Err(error) = result {
+                eprintln!("Ros2Sink {}: {}", self.name, error);
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.publisher = None;
+            Ok(())
+        }
+    }
+}
+
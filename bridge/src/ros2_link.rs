@@ -0,0 +1,171 @@
+use std::time::Duration;
+#[cfg(feature = "ros2")]
+use std::sync::{Arc, Mutex, mpsc};
+#[cfg(feature = "ros2")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "ros2")]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "ros2")]
+use futures::{StreamExt, future, executor::LocalPool, task::LocalSpawnExt};
+#[cfg(feature = "ros2")]
+use r2r::QosProfile;
+#[cfg(feature = "ros2")]
+use r2r::std_msgs::msg::Float64MultiArray;
+#[cfg(feature = "ros2")]
+use r2r::sensor_msgs::msg::Imu;
+#[cfg(feature = "ros2")]
+use r2r::geometry_msgs::msg::{Quaternion, Vector3};
+
+// sensor_msgs/Imu travels as [qx, qy, qz, qw, wx, wy, wz, ax, ay, az], the covariances are not
+// carried and are published as zero (unknown).
+pub const IMU_FRAME_SIZE: usize = 10;
+
+pub fn valid_message_type(message_type: &str) -> bool {
+    matches!(message_type, "float64_multi_array" | "imu")
+}
+
+#[cfg(feature = "ros2")]
+fn imu_to_frame(message: &Imu) -> Vec<f64> {
+    let (q, w, a) = (&message.orientation, &message.angular_velocity, &message.linear_acceleration);
+    vec![q.x, q.y, q.z, q.w, w.x, w.y, w.z, a.x, a.y, a.z]
+}
+
+#[cfg(feature = "ros2")]
+fn frame_to_imu(frame: &[f64], frame_id: &str) -> Imu {
+    let mut message = Imu::default();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    message.header.stamp.sec = now.as_secs() as i32;
+    message.header.stamp.nanosec = now.subsec_nanos();
+    message.header.frame_id = frame_id.to_string();
+    message.orientation = Quaternion { x: frame[0], y: frame[1], z: frame[2], w: frame[3] };
+    message.angular_velocity = Vector3 { x: frame[4], y: frame[5], z: frame[6] };
+    message.linear_acceleration = Vector3 { x: frame[7], y: frame[8], z: frame[9] };
+    message
+}
+
+#[cfg(feature = "ros2")]
+enum TopicPublisher {
+    Array(r2r::Publisher<Float64MultiArray>),
+    Imu(r2r::Publisher<Imu>),
+}
+
+// Publishing does not need the node to spin, the node is only kept alive with the publisher.
+#[cfg(feature = "ros2")]
+pub struct Ros2Publisher {
+    _node: r2r::Node,
+    publisher: TopicPublisher,
+    frame_id: String,
+}
+#[cfg(feature = "ros2")]
+impl Ros2Publisher {
+    pub fn create(node_name: &str, topic: &str, message_type: &str, frame_id: &str) -> Result<Self, String> {
+        let context = r2r::Context::create().map_err(|e| e.to_string())?;
+        let mut node = r2r::Node::create(context, node_name, "").map_err(|e| e.to_string())?;
+        let publisher = match message_type {
+            "imu" => TopicPublisher::Imu(node.create_publisher::<Imu>(topic, QosProfile::default()).map_err(|e| e.to_string())?),
+            _ => TopicPublisher::Array(node.create_publisher::<Float64MultiArray>(topic, QosProfile::default()).map_err(|e| e.to_string())?),
+        };
+        Ok(Ros2Publisher { _node: node, publisher, frame_id: frame_id.to_string() })
+    }
+    pub fn publish(&self, frame: &[f64]) -> Result<(), String> {
+        match &self.publisher {
+            TopicPublisher::Array(publisher) => {
+                let message = Float64MultiArray { data: frame.to_vec(), ..Default::default() };
+                publisher.publish(&message).map_err(|e| e.to_string())
+            }
+            TopicPublisher::Imu(publisher) => {
+                if frame.len() != IMU_FRAME_SIZE {
+                    return Err(format!("imu frames have {} values, got {}", IMU_FRAME_SIZE, frame.len()));
+                }
+                publisher.publish(&frame_to_imu(frame, &self.frame_id)).map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+// The node, its subscription and the executor live on their own thread, the received messages
+// are converted to frames and queued for the block.
+#[cfg(feature = "ros2")]
+pub struct Ros2Subscriber {
+    receiver: Mutex<mpsc::Receiver<Vec<f64>>>,
+    running: Arc<AtomicBool>,
+}
+#[cfg(feature = "ros2")]
+impl Ros2Subscriber {
+    pub fn create(node_name: &str, topic: &str, message_type: &str) -> Result<Self, String> {
+        let (sender, receiver) = mpsc::channel();
+        let (ready_sender, ready_receiver) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let (node_name, topic, message_type) = (node_name.to_string(), topic.to_string(), message_type.to_string());
+        std::thread::spawn(move || {
+            let setup = || -> Result<(r2r::Node, LocalPool), String> {
+                let context = r2r::Context::create().map_err(|e| e.to_string())?;
+                let mut node = r2r::Node::create(context, &node_name, "").map_err(|e| e.to_string())?;
+                let pool = LocalPool::new();
+                let forward = move |frame: Vec<f64>| {
+                    let _ = sender.send(frame);
+                    future::ready(())
+                };
+                let spawned = if message_type == "imu" {
+                    let stream = node.subscribe::<Imu>(&topic, QosProfile::default()).map_err(|e| e.to_string())?;
+                    pool.spawner().spawn_local(stream.for_each(move |message| forward(imu_to_frame(&message))))
+                } else {
+                    let stream = node.subscribe::<Float64MultiArray>(&topic, QosProfile::default()).map_err(|e| e.to_string())?;
+                    pool.spawner().spawn_local(stream.for_each(move |message| forward(message.data)))
+                };
+                spawned.map_err(|e| e.to_string())?;
+                Ok((node, pool))
+            };
+            let (mut node, mut pool) = match setup() {
+                Ok(link) => {
+                    let _ = ready_sender.send(Ok(()));
+                    link
+                }
+                Err(error) => {
+                    let _ = ready_sender.send(Err(error));
+                    return;
+                }
+            };
+            while thread_running.load(Ordering::Relaxed) {
+                node.spin_once(Duration::from_millis(10));
+                pool.run_until_stalled();
+            }
+        });
+        ready_receiver.recv().map_err(|e| e.to_string())??;
+        Ok(Ros2Subscriber { receiver: Mutex::new(receiver), running })
+    }
+    pub fn receive(&self, timeout: Duration) -> Option<Vec<f64>> {
+        self.receiver.lock().unwrap().recv_timeout(timeout).ok()
+    }
+}
+#[cfg(feature = "ros2")]
+impl Drop for Ros2Subscriber {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+// Without the ros2 feature the blocks still load, init reports how to enable it.
+#[cfg(not(feature = "ros2"))]
+pub struct Ros2Publisher;
+#[cfg(not(feature = "ros2"))]
+impl Ros2Publisher {
+    pub fn create(_node_name: &str, _topic: &str, _message_type: &str, _frame_id: &str) -> Result<Self, String> {
+        Err("the bridge crate was built without the ros2 feature".to_string())
+    }
+    pub fn publish(&self, _frame: &[f64]) -> Result<(), String> {
+        Err("the bridge crate was built without the ros2 feature".to_string())
+    }
+}
+#[cfg(not(feature = "ros2"))]
+pub struct Ros2Subscriber;
+#[cfg(not(feature = "ros2"))]
+impl Ros2Subscriber {
+    pub fn create(_node_name: &str, _topic: &str, _message_type: &str) -> Result<Self, String> {
+        Err("the bridge crate was built without the ros2 feature".to_string())
+    }
+    pub fn receive(&self, _timeout: Duration) -> Option<Vec<f64>> {
+        None
+    }
+}
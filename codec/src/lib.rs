@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "arrow")]
+use std::collections::HashMap;
+#[cfg(feature = "arrow")]
+use std::sync::Arc;
+#[cfg(feature = "arrow")]
+use arrow_array::{Array, Float64Array, RecordBatch};
+#[cfg(feature = "arrow")]
+use arrow_ipc::{reader::StreamReader, writer::StreamWriter};
+#[cfg(feature = "arrow")]
+use arrow_schema::{DataType, Field, Schema};
+#[cfg(feature = "flatbuffers")]
+use flatbuffers::{FlatBufferBuilder, Follow, ForwardsUOffset, InvalidFlatbuffer, Table, Vector, Verifiable, Verifier};
+
+// Frame as it travels on a network link or in a file, the sequence lets consumers detect lost
+// frames. The raw formats carry the values only and decode with sequence and timestamp zero.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamFrame {
+    pub sequence: u64,
+    pub timestamp_ns: u64,
+    pub values: Vec<f64>,
+}
+
+// Formats available in this build, cbor, flatbuffers and arrow_ipc depend on the crate features.
+pub fn valid_format(format: &str) -> bool {
+    matches!(format, "raw_f32le" | "raw_f64le" | "json")
+        || (format == "cbor" && cfg!(feature = "cbor"))
+        || (format == "flatbuffers" && cfg!(feature = "flatbuffers"))
+        || (format == "arrow_ipc" && cfg!(feature = "arrow"))
+}
+
+pub fn encode_frame(frame: &StreamFrame, format: &str) -> Result<Vec<u8>, String> {
+    match format {
+        "raw_f32le" => Ok(frame.values.iter().flat_map(|v| (*v as f32).to_le_bytes()).collect()),
+        "raw_f64le" => Ok(frame.values.iter().flat_map(|v| v.to_le_bytes()).collect()),
+        "json" => serde_json::to_vec(frame).map_err(|e| e.to_string()),
+        #[cfg(feature = "cbor")]
+        "cbor" => {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(frame, &mut bytes).map_err(|e| e.to_string())?;
+            Ok(bytes)
+        }
+        #[cfg(feature = "flatbuffers")]
+        "flatbuffers" => Ok(encode_flatbuffer(frame)),
+        #[cfg(feature = "arrow")]
+        "arrow_ipc" => encode_arrow(frame).map_err(|e| e.to_string()),
+        _ => Err(format!("unsupported format {}", format)),
+    }
+}
+
+pub fn decode_frame(bytes: &[u8], format: &str) -> Result<StreamFrame, String> {
+    match format {
+        "raw_f32le" | "raw_f64le" => {
+            let size = if format == "raw_f32le" { 4 } else { 8 };
+            if !bytes.len().is_multiple_of(size) {
+                return Err(format!("{} bytes is not a whole number of {} samples", bytes.len(), format));
+            }
+            let values = bytes.chunks_exact(size)
+                .map(|b| if size == 4 { f32::from_le_bytes(b.try_into().unwrap()) as f64 } else { f64::from_le_bytes(b.try_into().unwrap()) })
+                .collect();
+            Ok(StreamFrame { values, ..Default::default() })
+        }
+        "json" => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+        #[cfg(feature = "cbor")]
+        "cbor" => ciborium::from_reader(bytes).map_err(|e| e.to_string()),
+        #[cfg(feature = "flatbuffers")]
+        "flatbuffers" => decode_flatbuffer(bytes),
+        #[cfg(feature = "arrow")]
+        "arrow_ipc" => decode_arrow(bytes),
+        _ => Err(format!("unsupported format {}", format)),
+    }
+}
+
+// FlatBuffers table equivalent to the schema
+//   table Frame { sequence: ulong; timestamp_ns: ulong; values: [double]; }
+//   root_type Frame;
+// written by hand so that the build does not need flatc, the vtable offsets follow the field order.
+#[cfg(feature = "flatbuffers")]
+const FRAME_SEQUENCE: u16 = 4;
+#[cfg(feature = "flatbuffers")]
+const FRAME_TIMESTAMP: u16 = 6;
+#[cfg(feature = "flatbuffers")]
+const FRAME_VALUES: u16 = 8;
+
+#[cfg(feature = "flatbuffers")]
+struct FrameTable<'a> {
+    table: Table<'a>,
+}
+#[cfg(feature = "flatbuffers")]
+impl<'a> Follow<'a> for FrameTable<'a> {
+    type Inner = FrameTable<'a>;
+    unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        FrameTable { table: unsafe { Table::new(buf, loc) } }
+    }
+}
+#[cfg(feature = "flatbuffers")]
+impl Verifiable for FrameTable<'_> {
+    fn run_verifier(verifier: &mut Verifier, pos: usize) -> Result<(), InvalidFlatbuffer> {
+        verifier.visit_table(pos)?
+            .visit_field::<u64>("sequence", FRAME_SEQUENCE, false)?
+            .visit_field::<u64>("timestamp_ns", FRAME_TIMESTAMP, false)?
+            .visit_field::<ForwardsUOffset<Vector<'_, f64>>>("values", FRAME_VALUES, false)?
+            .finish();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "flatbuffers")]
+fn encode_flatbuffer(frame: &StreamFrame) -> Vec<u8> {
+    let mut builder = FlatBufferBuilder::new();
+    let values = builder.create_vector(&frame.values);
+    let start = builder.start_table();
+    builder.push_slot::<u64>(FRAME_SEQUENCE, frame.sequence, 0);
+    builder.push_slot::<u64>(FRAME_TIMESTAMP, frame.timestamp_ns, 0);
+    builder.push_slot_always(FRAME_VALUES, values);
+    let root = builder.end_table(start);
+    builder.finish_minimal(root);
+    builder.finished_data().to_vec()
+}
+
+#[cfg(feature = "flatbuffers")]
+fn decode_flatbuffer(bytes: &[u8]) -> Result<StreamFrame, String> {
+    let root = flatbuffers::root::<FrameTable>(bytes).map_err(|e| e.to_string())?;
+    // the verifier above has checked every field access below
+    let (sequence, timestamp_ns, values) = unsafe {
+        (root.table.get::<u64>(FRAME_SEQUENCE, Some(0)).unwrap_or(0),
+         root.table.get::<u64>(FRAME_TIMESTAMP, Some(0)).unwrap_or(0),
+         root.table.get::<ForwardsUOffset<Vector<f64>>>(FRAME_VALUES, None))
+    };
+    Ok(StreamFrame { sequence, timestamp_ns, values: values.map(|v| v.iter().collect()).unwrap_or_default() })
+}
+
+// Arrow IPC stream with one Float64 column "values", sequence and timestamp go in the schema
+// metadata.
+#[cfg(feature = "arrow")]
+fn encode_arrow(frame: &StreamFrame) -> Result<Vec<u8>, arrow_schema::ArrowError> {
+    let metadata = HashMap::from([
+        ("sequence".to_string(), frame.sequence.to_string()),
+        ("timestamp_ns".to_string(), frame.timestamp_ns.to_string()),
+    ]);
+    let schema = Arc::new(Schema::new_with_metadata(vec![Field::new("values", DataType::Float64, false)], metadata));
+    let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(Float64Array::from(frame.values.clone()))])?;
+    let mut writer = StreamWriter::try_new(Vec::new(), &schema)?;
+    writer.write(&batch)?;
+    writer.into_inner()
+}
+
+#[cfg(feature = "arrow")]
+fn decode_arrow(bytes: &[u8]) -> Result<StreamFrame, String> {
+    let reader = StreamReader::try_new(bytes, None).map_err(|e| e.to_string())?;
+    let metadata = reader.schema().metadata().clone();
+    let number = |key: &str| metadata.get(key).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+    let mut frame = StreamFrame { sequence: number("sequence"), timestamp_ns: number("timestamp_ns"), values: Vec::new() };
+    for batch in reader {
+        let batch = batch.map_err(|e| e.to_string())?;
+        let column = batch.column_by_name("values").and_then(|c| c.as_any().downcast_ref::<Float64Array>())
+            .ok_or("missing Float64 column values".to_string())?;
+        frame.values.extend((0..column.len()).map(|k| column.value(k)));
+    }
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_frame_codecs() {
+        let frame = StreamFrame { sequence: 42, timestamp_ns: 1_700_000_000_000_000_000, values: vec![0.5, -1.25, 3.0] };
+        for format in ["json", "cbor", "flatbuffers", "arrow_ipc"] {
+            if !valid_format(format) {
+                continue;
+            }
+            let bytes = encode_frame(&frame, format).unwrap();
+            assert_eq!(decode_frame(&bytes, format).unwrap(), frame);
+        }
+        assert!(!valid_format("protobuf"));
+        let raw = StreamFrame { values: frame.values.clone(), ..Default::default() };
+        assert_eq!(decode_frame(&encode_frame(&frame, "raw_f32le").unwrap(), "raw_f32le").unwrap(), raw);
+        assert_eq!(decode_frame(&encode_frame(&frame, "raw_f64le").unwrap(), "raw_f64le").unwrap(), raw);
+        assert!(decode_frame(&[0u8; 6], "raw_f32le").is_err());
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct Calibration {
+        inputs: {
+            // one row per channel, as bundled by the Synchronizer
+            "input": Vec<Vec<f64>>,
+            "reference": Vec<Vec<f64>>,
+        },
+        outputs: { "output": Vec<Vec<f64>> },
+        statics: {
+            "mode": String = "apply".to_string(),
+            "method": String = "polynomial".to_string(),
+            "degree": usize = 1,
+            "table_points": usize = 16,
+            "learn_samples": usize = 1024,
+            "calibration_file": String = String::new(),
+        },
+        state: {
+            "calibration": CalibrationSet = CalibrationSet::default(),
+            "learning": bool = false,
+            "learn_input": Vec<Vec<f64>> = Vec::<Vec<f64>>::new(),
+            "learn_reference": Vec<Vec<f64>> = Vec::<Vec<f64>>::new(),
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            let method = self.get_statics::<String>("method")?.get_value();
+            let table_points = self.get_statics::<usize>("table_points")?.get_value();
+            let learn_samples = self.get_statics::<usize>("learn_samples")?.get_value();
+            let calibration_file = self.get_statics::<String>("calibration_file")?.get_value();
+            if calibration_file.is_empty() || (method != "polynomial" && method != "table") || table_points < 2 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            match mode.as_str() {
+                "apply" => {
+                    let calibration = match CalibrationSet::load(&calibration_file) {
+                        Ok(calibration) => calibration,
+                        Err(error) => {
+                            eprintln!("Calibration {}: {}", self.name, error);
+                            return Err(StreamingError::InvalidStatics)
+                        }
+                    };
+                    self.set_state_value("calibration", calibration)?;
+                    self.set_state_value("learning", false)?;
+                }
+                "learn" if learn_samples > 0 => {
+                    self.set_state_value("calibration", CalibrationSet::default())?;
+                    self.set_state_value("learning", true)?;
+                }
+                _ => return Err(StreamingError::InvalidStatics),
+            }
+            self.set_state_value("learn_input", Vec::<Vec<f64>>::new())?;
+            self.set_state_value("learn_reference", Vec::<Vec<f64>>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let input = self.recv_input::<Vec<Vec<f64>>>("input")?;
+            if self.get_state_value::<bool>("learning")? {
+                // frames pass through uncorrected until enough reference samples are collected
+                let reference = self.recv_input::<Vec<Vec<f64>>>("reference")?;
+                if reference.len() != input.len() || reference.iter().zip(input.iter()).any(|(r, x)| r.len() != x.len()) {
+                    self.stop()?;
+                    return Err(StreamingError::InvalidInput);
+                }
+                self.learn(&input, reference)?;
+                self.send_output::<Vec<Vec<f64>>>("output", input)?;
+                return Ok(());
+            }
+            let calibration = self.get_state_value::<CalibrationSet>("calibration")?;
+            if input.len() != calibration.channels.len() {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let output: Vec<Vec<f64>>;
+            {
+                let _lock = self.lock.lock().unwrap();
+                output = input.iter().zip(calibration.channels.iter())
+                    .map(|(channel, correction)| channel.iter().map(|x| correction.apply(*x)).collect())
+                    .collect();
+            }
+            self.send_output::<Vec<Vec<f64>>>("output", output)?;
+            Ok(())
+        }
+    }
+}
+
+// Correction of one channel. Polynomials are evaluated on (x - offset) / scale with ascending
+// coefficients, tables interpolate linearly between breakpoints and extend the end segments.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChannelCalibration {
+    pub offset: f64,
+    pub scale: f64,
+    pub polynomial: Vec<f64>,
+    pub table_input: Vec<f64>,
+    pub table_output: Vec<f64>,
+}
+impl ChannelCalibration {
+    pub fn apply(&self, x: f64) -> f64 {
+        if !self.polynomial.is_empty() {
+            let t = (x - self.offset) / self.scale;
+            return self.polynomial.iter().rev().fold(0.0, |acc, c| acc * t + c);
+        }
+        let points = self.table_input.len();
+        if points < 2 {
+            return self.table_output.first().copied().unwrap_or(x);
+        }
+        let segment = self.table_input[1..points - 1].partition_point(|breakpoint| *breakpoint <= x);
+        let (x0, x1) = (self.table_input[segment], self.table_input[segment + 1]);
+        let (y0, y1) = (self.table_output[segment], self.table_output[segment + 1]);
+        y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+    }
+}
+
+// Content of the calibration file, one entry per channel.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationSet {
+    pub method: String,
+    pub channels: Vec<ChannelCalibration>,
+}
+impl CalibrationSet {
+    pub fn load(path: &str) -> Result<CalibrationSet, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        serde_json::from_str(&text).map_err(|e| format!("invalid calibration file {}: {}", path, e))
+    }
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+}
+
+// Least squares fit of reference = p((input - offset) / scale), normal equations solved by
+// Gaussian elimination. The normalization keeps them well conditioned for raw ADC counts.
+pub fn fit_polynomial(input: &[f64], reference: &[f64], degree: usize) -> Option<ChannelCalibration> {
+    let count = input.len() as f64;
+    if input.len() <= degree {
+        return None;
+    }
+    let offset = input.iter().sum::<f64>() / count;
+    let scale = input.iter().map(|x| (x - offset).abs()).fold(0.0, f64::max);
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let size = degree + 1;
+    let mut system = vec![vec![0.0; size + 1]; size];
+    for (x, y) in input.iter().zip(reference.iter()) {
+        let t = (x - offset) / scale;
+        let powers: Vec<f64> = (0..size).scan(1.0, |power, _| { let current = *power; *power *= t; Some(current) }).collect();
+        for (row, p_row) in system.iter_mut().zip(powers.iter()) {
+            for (cell, p_column) in row.iter_mut().zip(powers.iter()) {
+                *cell += p_row * p_column;
+            }
+            row[size] += p_row * y;
+        }
+    }
+    for column in 0..size {
+        let pivot = (column..size).max_by(|a, b| system[*a][column].abs().total_cmp(&system[*b][column].abs()))?;
+        if system[pivot][column].abs() < 1.0e-12 * count {
+            return None;
+        }
+        system.swap(column, pivot);
+        for row in 0..size {
+            if row != column {
+                let factor = system[row][column] / system[column][column];
+                let pivot_row = system[column].clone();
+                for (cell, p) in system[row].iter_mut().zip(pivot_row.iter()) {
+                    *cell -= factor * p;
+                }
+            }
+        }
+    }
+    let polynomial = system.iter().enumerate().map(|(i, row)| row[size] / row[i]).collect();
+    Some(ChannelCalibration { offset, scale, polynomial, ..ChannelCalibration::default() })
+}
+
+// Lookup table with `points` breakpoints, each one the mean of an equal share of the samples
+// sorted by input value.
+pub fn fit_table(input: &[f64], reference: &[f64], points: usize) -> Option<ChannelCalibration> {
+    if points < 2 || input.len() < points {
+        return None;
+    }
+    let mut pairs: Vec<(f64, f64)> = input.iter().copied().zip(reference.iter().copied()).collect();
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut calibration = ChannelCalibration::default();
+    for bin in 0..points {
+        let share = &pairs[bin * pairs.len() / points..(bin + 1) * pairs.len() / points];
+        calibration.table_input.push(share.iter().map(|p| p.0).sum::<f64>() / share.len() as f64);
+        calibration.table_output.push(share.iter().map(|p| p.1).sum::<f64>() / share.len() as f64);
+    }
+    // breakpoints must be strictly increasing for the interpolation
+    if calibration.table_input.windows(2).any(|w| w[1] <= w[0]) {
+        return None;
+    }
+    Some(calibration)
+}
+
+impl Calibration {
+    fn learn(&mut self, input: &[Vec<f64>], reference: Vec<Vec<f64>>) -> Result<(), StreamingError> {
+        let method = self.get_statics::<String>("method")?.get_value();
+        let degree = self.get_statics::<usize>("degree")?.get_value();
+        let table_points = self.get_statics::<usize>("table_points")?.get_value();
+        let learn_samples = self.get_statics::<usize>("learn_samples")?.get_value();
+        let calibration_file = self.get_statics::<String>("calibration_file")?.get_value();
+        let mut learn_input = self.get_state_value::<Vec<Vec<f64>>>("learn_input")?;
+        let mut learn_reference = self.get_state_value::<Vec<Vec<f64>>>("learn_reference")?;
+        if learn_input.is_empty() {
+            learn_input = vec![Vec::new(); input.len()];
+            learn_reference = vec![Vec::new(); input.len()];
+        }
+        if learn_input.len() != input.len() {
+            self.stop()?;
+            return Err(StreamingError::InvalidInput);
+        }
+        for (buffer, channel) in learn_input.iter_mut().zip(input.iter()) {
+            buffer.extend_from_slice(channel);
+        }
+        for (buffer, channel) in learn_reference.iter_mut().zip(reference) {
+            buffer.extend(channel);
+        }
+        if learn_input[0].len() < learn_samples {
+            self.set_state_value("learn_input", learn_input)?;
+            self.set_state_value("learn_reference", learn_reference)?;
+            return Ok(());
+        }
+        let mut calibration = CalibrationSet { method: method.clone(), channels: Vec::new() };
+        for (channel_input, channel_reference) in learn_input.iter().zip(learn_reference.iter()) {
+            let fitted = if method == "polynomial" {
+                fit_polynomial(channel_input, channel_reference, degree)
+            } else {
+                fit_table(channel_input, channel_reference, table_points)
+            };
+            let Some(fitted) = fitted else {
+                eprintln!("Calibration {}: the learning data does not determine the {} fit", self.name, method);
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            };
+            calibration.channels.push(fitted);
+        }
+        if let Err(error) = calibration.save(&calibration_file) {
+            eprintln!("Calibration {}: {}", self.name, error);
+        }
+        self.set_state_value("calibration", calibration)?;
+        self.set_state_value("learning", false)?;
+        self.set_state_value("learn_input", Vec::<Vec<f64>>::new())?;
+        self.set_state_value("learn_reference", Vec::<Vec<f64>>::new())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_fits() {
+        let input: Vec<f64> = (0..200).map(|k| 30000.0 + 100.0 * k as f64).collect();
+        let reference: Vec<f64> = input.iter().map(|x| 0.5 - 2.0e-4 * x + 3.0e-9 * x * x).collect();
+        let polynomial = fit_polynomial(&input, &reference, 2).unwrap();
+        for (x, y) in input.iter().zip(reference.iter()) {
+            assert!((polynomial.apply(*x) - y).abs() < 1.0e-9);
+        }
+        let table = fit_table(&input, &reference, 20).unwrap();
+        assert_eq!(table.table_input.len(), 20);
+        assert!((table.apply(40000.0) - (0.5 - 8.0 + 4.8)).abs() < 1.0e-3);
+        assert!(fit_polynomial(&[1.0, 1.0, 1.0], &[0.0, 1.0, 2.0], 1).is_none());
+    }
+}
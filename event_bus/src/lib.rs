@@ -0,0 +1,116 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EventKind {
+    Trigger,
+    Anomaly,
+    FilterReset,
+    Divergence,
+    Quality,
+    Custom(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub source: String,
+    pub kind: EventKind,
+    pub payload: Value,
+    pub timestamp: SystemTime,
+}
+impl Event {
+    pub fn new(source: &str, kind: EventKind, payload: Value) -> Self {
+        Event { source: source.to_string(), kind, payload, timestamp: SystemTime::now() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventFilter {
+    All,
+    Source(String),
+    Kind(EventKind),
+    SourceAndKind(String, EventKind),
+}
+impl EventFilter {
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            EventFilter::All => true,
+            EventFilter::Source(source) => *source == event.source,
+            EventFilter::Kind(kind) => *kind == event.kind,
+            EventFilter::SourceAndKind(source, kind) => *source == event.source && *kind == event.kind,
+        }
+    }
+}
+
+pub struct EventBus {
+    subscribers: Mutex<Vec<(EventFilter, Sender<Event>)>>,
+}
+impl EventBus {
+    pub fn new() -> Self {
+        EventBus { subscribers: Mutex::new(Vec::new()) }
+    }
+    pub fn global() -> &'static EventBus {
+        static BUS: OnceLock<EventBus> = OnceLock::new();
+        BUS.get_or_init(EventBus::new)
+    }
+    pub fn subscribe(&self, filter: EventFilter) -> Receiver<Event> {
+        let (sender, receiver) = channel();
+        self.subscribers.lock().unwrap().push((filter, sender));
+        receiver
+    }
+    // Delivers the event to every matching subscriber and drops the ones whose receiver is gone.
+    // Returns the number of subscribers that received it.
+    pub fn publish(&self, event: Event) -> usize {
+        let mut delivered = 0;
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|(filter, sender)| {
+            if !filter.matches(&event) {
+                return true;
+            }
+            match sender.send(event.clone()) {
+                Ok(()) => {
+                    delivered += 1;
+                    true
+                }
+                Err(_) => false,
+            }
+        });
+        delivered
+    }
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+}
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus::new()
+    }
+}
+
+pub fn publish(source: &str, kind: EventKind, payload: Value) -> usize {
+    EventBus::global().publish(Event::new(source, kind, payload))
+}
+pub fn subscribe(filter: EventFilter) -> Receiver<Event> {
+    EventBus::global().subscribe(filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_filtered_delivery() {
+        let bus = EventBus::new();
+        let by_source = bus.subscribe(EventFilter::Source("kalman".to_string()));
+        let by_kind = bus.subscribe(EventFilter::Kind(EventKind::Anomaly));
+        assert_eq!(bus.publish(Event::new("kalman", EventKind::Divergence, Value::Null)), 1);
+        assert_eq!(bus.publish(Event::new("quality", EventKind::Anomaly, Value::Null)), 1);
+        assert_eq!(by_source.try_recv().unwrap().kind, EventKind::Divergence);
+        assert_eq!(by_kind.try_recv().unwrap().source, "quality");
+        drop(by_kind);
+        bus.publish(Event::new("quality", EventKind::Anomaly, Value::Null));
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct AdaptiveLms {
+        inputs: { "primary": Vec<f64>, "reference": Vec<f64> },
+        outputs: { "output": Vec<f64>, "error": Vec<f64> },
+        statics: {
+            "taps": usize = 32,
+            "step_size": f64 = 0.01,
+            "leakage": f64 = 0.0,
+            "normalized": bool = true,
+        },
+        state: { "filter": LmsFilter = LmsFilter::default() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let taps = self.get_statics::<usize>("taps")?.get_value();
+            let step_size = self.get_statics::<f64>("step_size")?.get_value();
+            let leakage = self.get_statics::<f64>("leakage")?.get_value();
+            let normalized = self.get_statics::<bool>("normalized")?.get_value();
+            // NLMS converges for 0 < step < 2, plain LMS bounds depend on the reference power
+            if taps == 0 || step_size.is_nan() || step_size <= 0.0 || (normalized && step_size >= 2.0)
+                || leakage.is_nan() || leakage < 0.0 || step_size * leakage >= 1.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("filter", LmsFilter::new(taps))?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let step_size = self.get_statics::<f64>("step_size")?.get_value();
+            let leakage = self.get_statics::<f64>("leakage")?.get_value();
+            let normalized = self.get_statics::<bool>("normalized")?.get_value();
+            let mut filter = self.get_state_value::<LmsFilter>("filter")?;
+            let primary = self.recv_input::<Vec<f64>>("primary")?;
+            let reference = self.recv_input::<Vec<f64>>("reference")?;
+            if primary.len() != reference.len() {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let (output_signal, error_signal): (Vec<f64>, Vec<f64>) = {
+                let _lock = self.lock.lock().unwrap();
+                primary.iter().zip(reference.iter())
+                    .map(|(d, x)| filter.step(*d, *x, step_size, leakage, normalized))
+                    .unzip()
+            };
+            self.set_state_value("filter", filter)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            self.send_output::<Vec<f64>>("error", error_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// Transversal filter adapted by (normalized) LMS. `memory` holds the reference samples,
+// newest first, the weight of tap k multiplies x(n-k).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LmsFilter {
+    pub weights: Vec<f64>,
+    memory: Vec<f64>,
+}
+impl LmsFilter {
+    pub fn new(taps: usize) -> Self {
+        LmsFilter { weights: vec![0.0; taps], memory: vec![0.0; taps] }
+    }
+    // Filters one reference sample and adapts towards `desired`, returns (output, error). The
+    // leakage shrinks the weights by (1 - step * leakage) on every update.
+    pub fn step(&mut self, desired: f64, reference: f64, step_size: f64, leakage: f64, normalized: bool) -> (f64, f64) {
+        self.memory.rotate_right(1);
+        self.memory[0] = reference;
+        let output: f64 = self.weights.iter().zip(self.memory.iter()).map(|(w, x)| w * x).sum();
+        let error = desired - output;
+        let gain = if normalized {
+            step_size / (1.0e-9 + self.memory.iter().map(|x| x * x).sum::<f64>())
+        } else {
+            step_size
+        };
+        let decay = 1.0 - step_size * leakage;
+        for (w, x) in self.weights.iter_mut().zip(self.memory.iter()) {
+            *w = decay * *w + gain * error * x;
+        }
+        (output, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_system_identification() {
+        let system = [0.5, -0.3, 0.2];
+        let mut filter = LmsFilter::new(4);
+        let mut history = [0.0; 3];
+        let mut seed = 12345u64;
+        let mut error = 0.0;
+        for _ in 0..4000 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let x = (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+            history.rotate_right(1);
+            history[0] = x;
+            let desired: f64 = system.iter().zip(history.iter()).map(|(h, x)| h * x).sum();
+            error = filter.step(desired, x, 0.5, 0.0, true).1;
+        }
+        assert!(error.abs() < 1.0e-6);
+        assert!(filter.weights.iter().zip(system.iter().chain([0.0].iter())).all(|(w, h)| (w - h).abs() < 1.0e-6));
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct AdaptiveRls {
+        inputs: { "primary": Vec<f64>, "reference": Vec<f64> },
+        outputs: { "output": Vec<f64>, "error": Vec<f64> },
+        statics: {
+            "taps": usize = 32,
+            "forgetting_factor": f64 = 0.99,
+            "regularization": f64 = 0.01,
+        },
+        state: { "filter": RlsFilter = RlsFilter::default() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let taps = self.get_statics::<usize>("taps")?.get_value();
+            let forgetting_factor = self.get_statics::<f64>("forgetting_factor")?.get_value();
+            let regularization = self.get_statics::<f64>("regularization")?.get_value();
+            // a small regularization trusts the first samples more and converges faster
+            if taps == 0 || forgetting_factor.is_nan() || forgetting_factor <= 0.0 || forgetting_factor > 1.0
+                || regularization.is_nan() || regularization <= 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("filter", RlsFilter::new(taps, regularization))?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let forgetting_factor = self.get_statics::<f64>("forgetting_factor")?.get_value();
+            let mut filter = self.get_state_value::<RlsFilter>("filter")?;
+            let primary = self.recv_input::<Vec<f64>>("primary")?;
+            let reference = self.recv_input::<Vec<f64>>("reference")?;
+            if primary.len() != reference.len() {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let (output_signal, error_signal): (Vec<f64>, Vec<f64>) = {
+                let _lock = self.lock.lock().unwrap();
+                primary.iter().zip(reference.iter())
+                    .map(|(d, x)| filter.step(*d, *x, forgetting_factor))
+                    .unzip()
+            };
+            self.set_state_value("filter", filter)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            self.send_output::<Vec<f64>>("error", error_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// Transversal filter adapted by exponentially weighted RLS. `memory` holds the reference samples,
+// newest first, `inverse_correlation` is the taps x taps matrix P in row-major order, started at
+// I / regularization.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RlsFilter {
+    pub weights: Vec<f64>,
+    memory: Vec<f64>,
+    inverse_correlation: Vec<f64>,
+}
+impl RlsFilter {
+    pub fn new(taps: usize, regularization: f64) -> Self {
+        let mut inverse_correlation = vec![0.0; taps * taps];
+        (0..taps).for_each(|k| inverse_correlation[k * taps + k] = 1.0 / regularization);
+        RlsFilter { weights: vec![0.0; taps], memory: vec![0.0; taps], inverse_correlation }
+    }
+    // Filters one reference sample and adapts towards `desired`, returns the a priori
+    // (output, error).
+    pub fn step(&mut self, desired: f64, reference: f64, forgetting_factor: f64) -> (f64, f64) {
+        let taps = self.weights.len();
+        self.memory.rotate_right(1);
+        self.memory[0] = reference;
+        let p = &mut self.inverse_correlation;
+        let projected: Vec<f64> = (0..taps)
+            .map(|i| p[i * taps..(i + 1) * taps].iter().zip(self.memory.iter()).map(|(a, x)| a * x).sum())
+            .collect();
+        let denominator = forgetting_factor + self.memory.iter().zip(projected.iter()).map(|(x, v)| x * v).sum::<f64>();
+        let gain: Vec<f64> = projected.iter().map(|v| v / denominator).collect();
+        let output: f64 = self.weights.iter().zip(self.memory.iter()).map(|(w, x)| w * x).sum();
+        let error = desired - output;
+        self.weights.iter_mut().zip(gain.iter()).for_each(|(w, g)| *w += g * error);
+        // P = (P - k v^T) / lambda, written symmetrically so rounding does not break the symmetry
+        for i in 0..taps {
+            for j in i..taps {
+                let value = (p[i * taps + j] - gain[i] * projected[j]) / forgetting_factor;
+                p[i * taps + j] = value;
+                p[j * taps + i] = value;
+            }
+        }
+        (output, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_fast_convergence() {
+        let system = [0.5, -0.3, 0.2];
+        let mut filter = RlsFilter::new(4, 1.0e-4);
+        let mut history = [0.0; 3];
+        let mut seed = 12345u64;
+        let mut error = 0.0;
+        // a few times the number of taps is enough, LMS needs thousands of samples here
+        for _ in 0..40 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let x = (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+            history.rotate_right(1);
+            history[0] = x;
+            let desired: f64 = system.iter().zip(history.iter()).map(|(h, x)| h * x).sum();
+            error = filter.step(desired, x, 0.99).1;
+        }
+        assert!(error.abs() < 1.0e-3);
+        assert!(filter.weights.iter().zip(system.iter().chain([0.0].iter())).all(|(w, h)| (w - h).abs() < 1.0e-3));
+    }
+}
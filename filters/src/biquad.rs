@@ -0,0 +1,193 @@
+use std::f64::consts::PI;
+use serde::{Deserialize, Serialize};
+use crate::fixed_point::{QFormat, QuantizationReport, quantize_coefficients};
+use crate::frequency_response::{FrequencyResponse, freqz};
+
+// Normalized second-order section b0 + b1 z^-1 + b2 z^-2 / 1 + a1 z^-1 + a2 z^-2.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Biquad {
+    pub b0: f64,
+    pub b1: f64,
+    pub b2: f64,
+    pub a1: f64,
+    pub a2: f64,
+}
+impl Biquad {
+    // Audio EQ cookbook (R. Bristow-Johnson) designs, gain in dB is used by the peaking and shelf types.
+    pub fn design(kind: &str, frequency: f64, gain: f64, q: f64, sample_rate: f64) -> Option<Biquad> {
+        if !(frequency > 0.0 && q > 0.0) || frequency >= sample_rate / 2.0 {
+            return None;
+        }
+        let amplitude = 10.0_f64.powf(gain / 40.0);
+        let omega = 2.0 * PI * frequency / sample_rate;
+        let (sin, cos) = omega.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let shelf = 2.0 * amplitude.sqrt() * alpha;
+        let (b0, b1, b2, a0, a1, a2) = match kind {
+            "peaking" => (1.0 + alpha * amplitude, -2.0 * cos, 1.0 - alpha * amplitude,
+                1.0 + alpha / amplitude, -2.0 * cos, 1.0 - alpha / amplitude),
+            "low_shelf" => (
+                amplitude * ((amplitude + 1.0) - (amplitude - 1.0) * cos + shelf),
+                2.0 * amplitude * ((amplitude - 1.0) - (amplitude + 1.0) * cos),
+                amplitude * ((amplitude + 1.0) - (amplitude - 1.0) * cos - shelf),
+                (amplitude + 1.0) + (amplitude - 1.0) * cos + shelf,
+                -2.0 * ((amplitude - 1.0) + (amplitude + 1.0) * cos),
+                (amplitude + 1.0) + (amplitude - 1.0) * cos - shelf),
+            "high_shelf" => (
+                amplitude * ((amplitude + 1.0) + (amplitude - 1.0) * cos + shelf),
+                -2.0 * amplitude * ((amplitude - 1.0) + (amplitude + 1.0) * cos),
+                amplitude * ((amplitude + 1.0) + (amplitude - 1.0) * cos - shelf),
+                (amplitude + 1.0) - (amplitude - 1.0) * cos + shelf,
+                2.0 * ((amplitude - 1.0) - (amplitude + 1.0) * cos),
+                (amplitude + 1.0) - (amplitude - 1.0) * cos - shelf),
+            "low_pass" => ((1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            "high_pass" => ((1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            "band_pass" => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            "notch" => (1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            _ => return None,
+        };
+        Some(Biquad { b0: b0 / a0, b1: b1 / a0, b2: b2 / a0, a1: a1 / a0, a2: a2 / a0 })
+    }
+    // One [b0, b1, b2, a0, a1, a2] row of an SOS matrix, as produced by scipy.signal and MATLAB
+    // zp2sos, normalized by a0.
+    pub fn from_sos_row(row: &[f64]) -> Option<Biquad> {
+        match *row {
+            [b0, b1, b2, a0, a1, a2] if a0 != 0.0 && row.iter().all(|c| c.is_finite()) => {
+                Some(Biquad { b0: b0 / a0, b1: b1 / a0, b2: b2 / a0, a1: a1 / a0, a2: a2 / a0 })
+            }
+            _ => None,
+        }
+    }
+    // Transposed direct form II, memory holds the two delay registers.
+    pub fn process(&self, memory: &mut [f64; 2], x: f64) -> f64 {
+        let y = self.b0 * x + memory[0];
+        memory[0] = self.b1 * x - self.a1 * y + memory[1];
+        memory[1] = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+// Section with its coefficients quantized to a coefficient format, a0 stays 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuantizedBiquad {
+    pub b0: i64,
+    pub b1: i64,
+    pub b2: i64,
+    pub a1: i64,
+    pub a2: i64,
+}
+impl QuantizedBiquad {
+    // The floating point section the quantized coefficients stand for.
+    pub fn to_biquad(&self, coefficient_format: &QFormat) -> Biquad {
+        Biquad {
+            b0: coefficient_format.to_f64(self.b0),
+            b1: coefficient_format.to_f64(self.b1),
+            b2: coefficient_format.to_f64(self.b2),
+            a1: coefficient_format.to_f64(self.a1),
+            a2: coefficient_format.to_f64(self.a2),
+        }
+    }
+    // Direct form I with a single wide accumulator, so only the output is rounded and saturated
+    // to the data format. memory holds the raw x(n-1), x(n-2), y(n-1), y(n-2).
+    pub fn process(&self, memory: &mut [i64; 4], x: i64, data_format: &QFormat, coefficient_format: &QFormat) -> i64 {
+        let accumulator = self.b0 as i128 * x as i128
+            + self.b1 as i128 * memory[0] as i128
+            + self.b2 as i128 * memory[1] as i128
+            - self.a1 as i128 * memory[2] as i128
+            - self.a2 as i128 * memory[3] as i128;
+        let y = data_format.from_accumulator(accumulator, coefficient_format.fractional_bits);
+        *memory = [x, memory[0], y, memory[2]];
+        y
+    }
+}
+
+// Runs one sample through a cascade, memory holds the delay registers of each section.
+pub fn cascade(sections: &[Biquad], memory: &mut [[f64; 2]], x: f64) -> f64 {
+    sections.iter().zip(memory.iter_mut()).fold(x, |value, (section, registers)| section.process(registers, value))
+}
+
+// Fixed point counterpart of cascade, the sample is quantized on the way in and back on the way out.
+pub fn fixed_cascade(sections: &[QuantizedBiquad], memory: &mut [[i64; 4]], x: f64, data_format: &QFormat, coefficient_format: &QFormat) -> f64 {
+    let raw = sections.iter().zip(memory.iter_mut()).fold(data_format.quantize(x), |value, (section, registers)| {
+        section.process(registers, value, data_format, coefficient_format)
+    });
+    data_format.to_f64(raw)
+}
+
+// Response of a cascade on the freqz grid: section magnitudes multiply, phases and group delays add.
+pub fn cascade_response(sections: &[Biquad], points: usize, sample_rate: f64) -> FrequencyResponse {
+    let mut response = freqz(&[1.0], &[1.0], points, sample_rate);
+    for section in sections {
+        let section_response = freqz(&[section.b0, section.b1, section.b2], &[1.0, section.a1, section.a2], points, sample_rate);
+        for k in 0..points {
+            response.magnitude[k] *= section_response.magnitude[k];
+            response.phase[k] += section_response.phase[k];
+            response.group_delay[k] += section_response.group_delay[k];
+        }
+    }
+    response
+}
+
+// Quantizes a cascade as a single coefficient set, so the report covers every section.
+pub fn quantize_sections(coefficient_format: &QFormat, sections: &[Biquad]) -> (Vec<QuantizedBiquad>, QuantizationReport) {
+    let coefficients: Vec<f64> = sections.iter().flat_map(|s| [s.b0, s.b1, s.b2, s.a1, s.a2]).collect();
+    let (quantized, report) = quantize_coefficients(coefficient_format, &coefficients);
+    let sections = quantized.chunks_exact(5)
+        .map(|q| QuantizedBiquad { b0: q[0], b1: q[1], b2: q[2], a1: q[3], a2: q[4] })
+        .collect();
+    (sections, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_peaking_gain_at_center() {
+        let section = Biquad::design("peaking", 1000.0, 6.0, 1.0, 48000.0).unwrap();
+        let omega = 2.0 * PI * 1000.0 / 48000.0;
+        let z = |k: f64| ((k * omega).cos(), -(k * omega).sin());
+        let num = (section.b0 + section.b1 * z(1.0).0 + section.b2 * z(2.0).0, section.b1 * z(1.0).1 + section.b2 * z(2.0).1);
+        let den = (1.0 + section.a1 * z(1.0).0 + section.a2 * z(2.0).0, section.a1 * z(1.0).1 + section.a2 * z(2.0).1);
+        let gain = ((num.0 * num.0 + num.1 * num.1) / (den.0 * den.0 + den.1 * den.1)).sqrt();
+        assert!((20.0 * gain.log10() - 6.0).abs() < 1.0e-9);
+        let dc = (section.b0 + section.b1 + section.b2) / (1.0 + section.a1 + section.a2);
+        assert!((dc - 1.0).abs() < 1.0e-3);
+    }
+    #[test]
+    fn test_cascade_response() {
+        let low = Biquad::design("low_pass", 2000.0, 0.0, 0.707, 48000.0).unwrap();
+        let peak = Biquad::design("peaking", 5000.0, 6.0, 2.0, 48000.0).unwrap();
+        let response = cascade_response(&[low, peak], 64, 48000.0);
+        // same as the response of the multiplied out transfer function
+        let b = [low.b0 * peak.b0, low.b0 * peak.b1 + low.b1 * peak.b0, low.b0 * peak.b2 + low.b1 * peak.b1 + low.b2 * peak.b0,
+            low.b1 * peak.b2 + low.b2 * peak.b1, low.b2 * peak.b2];
+        let a = [1.0, low.a1 + peak.a1, low.a2 + low.a1 * peak.a1 + peak.a2, low.a1 * peak.a2 + low.a2 * peak.a1, low.a2 * peak.a2];
+        let expected = freqz(&b, &a, 64, 48000.0);
+        for k in 0..64 {
+            assert!((response.magnitude[k] - expected.magnitude[k]).abs() < 1.0e-9);
+            // phase and group delay are undefined on the low pass zero at Nyquist
+            if expected.magnitude[k] > 1.0e-6 {
+                assert!((response.phase[k] - expected.phase[k]).abs() < 1.0e-9);
+                assert!((response.group_delay[k] - expected.group_delay[k]).abs() < 1.0e-6);
+            }
+        }
+        assert_eq!(response.frequencies, expected.frequencies);
+    }
+    #[test]
+    fn test_fixed_point_section() {
+        let section = Biquad::design("low_pass", 1000.0, 0.0, 0.707, 48000.0).unwrap();
+        let (data_format, coefficient_format) = (QFormat::parse("Q0.15").unwrap(), QFormat::parse("Q2.13").unwrap());
+        let (quantized, report) = quantize_sections(&coefficient_format, &[section]);
+        assert_eq!(report.saturated, 0);
+        assert!(report.max_error <= coefficient_format.resolution() / 2.0);
+        // the integer section settles where the quantized floating point section does
+        let reference = quantized[0].to_biquad(&coefficient_format);
+        let (mut memory, mut raw_memory) = ([0.0; 2], [0_i64; 4]);
+        let (mut y, mut raw) = (0.0, 0);
+        for _ in 0..2000 {
+            y = reference.process(&mut memory, 0.5);
+            raw = quantized[0].process(&mut raw_memory, data_format.quantize(0.5), &data_format, &coefficient_format);
+        }
+        assert!((data_format.to_f64(raw) - y).abs() < 1.0e-3);
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use flate2::read::ZlibDecoder;
+use serde::Deserialize;
+use crate::biquad::Biquad;
+
+// Arrays of a coefficient file by variable name, vectors are a single row.
+type Variables = HashMap<String, Vec<Vec<f64>>>;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoefficientSet {
+    pub b: Vec<f64>,
+    pub a: Vec<f64>,
+    pub sos: Vec<Vec<f64>>,
+}
+impl CoefficientSet {
+    // Numerator and denominator of the filter, None when the file only holds sections.
+    pub fn transfer_function(&self) -> Option<(Vec<f64>, Vec<f64>)> {
+        if self.b.is_empty() {
+            return None;
+        }
+        let a = if self.a.is_empty() { vec![1.0] } else { self.a.clone() };
+        Some((self.b.clone(), a))
+    }
+    // The second-order sections, kept as a cascade rather than multiplied out, which loses
+    // precision for high orders.
+    pub fn sections(&self) -> Option<Vec<Biquad>> {
+        if self.sos.is_empty() {
+            return None;
+        }
+        self.sos.iter().map(|row| Biquad::from_sos_row(row)).collect()
+    }
+}
+
+const B_NAMES: [&str; 5] = ["b", "num", "Num", "taps", "h"];
+const A_NAMES: [&str; 3] = ["a", "den", "Den"];
+const SOS_NAMES: [&str; 2] = ["sos", "SOS"];
+
+pub fn load_coefficients(path: &str) -> Result<CoefficientSet, String> {
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let variables = match extension.as_str() {
+        "json" => read_json(path)?,
+        "npz" => read_npz(path)?,
+        "npy" => {
+            let mut bytes = Vec::new();
+            File::open(path).and_then(|mut f| f.read_to_end(&mut bytes)).map_err(|e| format!("{}: {}", path, e))?;
+            HashMap::from([("b".to_string(), parse_npy(&bytes)?)])
+        }
+        "mat" => read_mat(path)?,
+        _ => return Err(format!("{}: unsupported coefficient file format", path)),
+    };
+    let find = |names: &[&str]| names.iter().find_map(|n| variables.get(*n).cloned());
+    let mut set = CoefficientSet::default();
+    if let Some(b) = find(&B_NAMES) {
+        set.b = b.into_iter().flatten().collect();
+    }
+    if let Some(a) = find(&A_NAMES) {
+        set.a = a.into_iter().flatten().collect();
+    }
+    if let Some(sos) = find(&SOS_NAMES) {
+        set.sos = sos;
+    }
+    if set.b.is_empty() && set.sos.is_empty() {
+        return Err(format!("{}: no b/num/taps or sos coefficients found", path));
+    }
+    Ok(set)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonValue {
+    Vector(Vec<f64>),
+    Matrix(Vec<Vec<f64>>),
+}
+
+fn read_json(path: &str) -> Result<Variables, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let values: HashMap<String, serde_json::Value> = serde_json::from_reader(file).map_err(|e| format!("{}: {}", path, e))?;
+    let mut ret = HashMap::new();
+    for (name, value) in values {
+        match serde_json::from_value::<JsonValue>(value) {
+            Ok(JsonValue::Vector(v)) => { ret.insert(name, vec![v]); }
+            Ok(JsonValue::Matrix(m)) => { ret.insert(name, m); }
+            Err(_) => {}
+        }
+    }
+    Ok(ret)
+}
+
+fn read_npz(path: &str) -> Result<Variables, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("{}: {}", path, e))?;
+    let mut ret = HashMap::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|e| format!("{}: {}", path, e))?;
+        let Some(name) = entry.name().strip_suffix(".npy").map(|n| n.to_string()) else {
+            continue;
+        };
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).map_err(|e| format!("{}: {}", path, e))?;
+        ret.insert(name, parse_npy(&bytes)?);
+    }
+    Ok(ret)
+}
+
+// NumPy .npy: magic, version, little-endian header length, then a python dict literal header.
+fn parse_npy(bytes: &[u8]) -> Result<Vec<Vec<f64>>, String> {
+    if bytes.len() < 10 || &bytes[0..6] != b"\x93NUMPY" {
+        return Err("not a .npy array".to_string());
+    }
+    let (header_len, header_start) = if bytes[6] == 1 {
+        (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10)
+    } else {
+        if bytes.len() < 12 {
+            return Err("truncated .npy header".to_string());
+        }
+        (u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize, 12)
+    };
+    let data_start = header_start + header_len;
+    if bytes.len() < data_start {
+        return Err("truncated .npy header".to_string());
+    }
+    let header = String::from_utf8_lossy(&bytes[header_start..data_start]);
+    let descr = header.split("'descr':").nth(1)
+        .and_then(|s| s.split('\'').nth(1))
+        .ok_or("missing descr in .npy header")?;
+    let fortran_order = header.contains("'fortran_order': True");
+    let shape: Vec<usize> = header.split("'shape':").nth(1)
+        .and_then(|s| s.split('(').nth(1))
+        .and_then(|s| s.split(')').next())
+        .ok_or("missing shape in .npy header")?
+        .split(',')
+        .filter_map(|d| d.trim().parse().ok())
+        .collect();
+    let data = &bytes[data_start..];
+    let values: Vec<f64> = match descr {
+        "<f8" => data.chunks_exact(8).map(|c| f64::from_le_bytes(c.try_into().unwrap())).collect(),
+        "<f4" => data.chunks_exact(4).map(|c| f32::from_le_bytes(c.try_into().unwrap()) as f64).collect(),
+        ">f8" => data.chunks_exact(8).map(|c| f64::from_be_bytes(c.try_into().unwrap())).collect(),
+        ">f4" => data.chunks_exact(4).map(|c| f32::from_be_bytes(c.try_into().unwrap()) as f64).collect(),
+        _ => return Err(format!("unsupported .npy dtype {}", descr)),
+    };
+    let (rows, cols) = match shape.as_slice() {
+        [] => (1, 1),
+        [n] => (1, *n),
+        [r, c] => (*r, *c),
+        _ => return Err("only 1-D and 2-D arrays are supported".to_string()),
+    };
+    reshape(values, rows, cols, !fortran_order)
+}
+
+fn reshape(values: Vec<f64>, rows: usize, cols: usize, row_major: bool) -> Result<Vec<Vec<f64>>, String> {
+    if values.len() < rows.checked_mul(cols).ok_or("array shape too large")? {
+        return Err("array data shorter than its shape".to_string());
+    }
+    if cols == 0 {
+        return Ok(Vec::new());
+    }
+    let mut ret = vec![vec![0.0; cols]; rows];
+    for r in 0..rows {
+        for c in 0..cols {
+            ret[r][c] = if row_major { values[r * cols + c] } else { values[c * rows + r] };
+        }
+    }
+    Ok(ret)
+}
+
+const MI_INT8: u32 = 1;
+const MI_UINT8: u32 = 2;
+const MI_INT16: u32 = 3;
+const MI_UINT16: u32 = 4;
+const MI_INT32: u32 = 5;
+const MI_UINT32: u32 = 6;
+const MI_SINGLE: u32 = 7;
+const MI_DOUBLE: u32 = 9;
+const MI_INT64: u32 = 12;
+const MI_UINT64: u32 = 13;
+const MI_MATRIX: u32 = 14;
+const MI_COMPRESSED: u32 = 15;
+
+// MATLAB level 5 MAT-file: 128 byte header followed by tagged data elements.
+fn read_mat(path: &str) -> Result<Variables, String> {
+    let mut bytes = Vec::new();
+    File::open(path).and_then(|mut f| f.read_to_end(&mut bytes)).map_err(|e| format!("{}: {}", path, e))?;
+    if bytes.len() < 128 || &bytes[126..128] != b"IM" {
+        return Err(format!("{}: not a little-endian level 5 MAT-file", path));
+    }
+    let mut ret = HashMap::new();
+    parse_mat_elements(&bytes[128..], &mut ret).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(ret)
+}
+
+const TRUNCATED: &str = "truncated MAT-file element";
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    let word = bytes.get(offset..offset.checked_add(4).ok_or(TRUNCATED)?).ok_or(TRUNCATED)?;
+    Ok(u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+}
+
+// The bytes from `offset` on, an error rather than a panic when the element sizes run past the data.
+fn tail(bytes: &[u8], offset: usize) -> Result<&[u8], String> {
+    bytes.get(offset..).ok_or(TRUNCATED.to_string())
+}
+
+// Returns (type, payload, total element size including padding).
+fn mat_element(bytes: &[u8]) -> Result<(u32, &[u8], usize), String> {
+    let first = read_u32(bytes, 0)?;
+    if first >> 16 != 0 {
+        // small data element, the payload is packed in the 4 bytes after the tag
+        let size = (first >> 16) as usize;
+        if size > 4 {
+            return Err("invalid MAT-file small data element".to_string());
+        }
+        let payload = bytes.get(4..4 + size).ok_or(TRUNCATED)?;
+        return Ok((first & 0xffff, payload, 8));
+    }
+    let size = read_u32(bytes, 4)? as usize;
+    let payload = bytes.get(8..size.checked_add(8).ok_or(TRUNCATED)?).ok_or(TRUNCATED)?;
+    let padded = if first == MI_COMPRESSED { size } else { size.div_ceil(8) * 8 };
+    Ok((first, payload, 8 + padded))
+}
+
+fn parse_mat_elements(mut bytes: &[u8], variables: &mut Variables) -> Result<(), String> {
+    while bytes.len() >= 8 {
+        let (data_type, payload, size) = mat_element(bytes)?;
+        match data_type {
+            MI_COMPRESSED => {
+                let mut inflated = Vec::new();
+                ZlibDecoder::new(payload).read_to_end(&mut inflated).map_err(|e| e.to_string())?;
+                parse_mat_elements(&inflated, variables)?;
+            }
+            MI_MATRIX => {
+                if let Some((name, value)) = parse_mat_matrix(payload)? {
+                    variables.insert(name, value);
+                }
+            }
+            _ => {}
+        }
+        bytes = bytes.get(size.min(bytes.len())..).unwrap_or(&[]);
+    }
+    Ok(())
+}
+
+// A numeric MAT-file variable and its name.
+type MatVariable = (String, Vec<Vec<f64>>);
+
+fn parse_mat_matrix(bytes: &[u8]) -> Result<Option<MatVariable>, String> {
+    let (_, flags, mut offset) = mat_element(bytes)?;
+    let class = flags.first().copied().unwrap_or(0);
+    let complex = flags.get(1).map(|f| f & 0x08 != 0).unwrap_or(false);
+    // numeric classes only: mxDOUBLE_CLASS (6) to mxUINT64_CLASS (15)
+    if !(6..=15).contains(&class) || complex {
+        return Ok(None);
+    }
+    let (_, dims, size) = mat_element(tail(bytes, offset)?)?;
+    offset = offset.checked_add(size).ok_or(TRUNCATED)?;
+    let dims: Vec<i32> = dims.chunks_exact(4).map(|c| i32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect();
+    if dims.iter().any(|d| *d < 0) {
+        return Err("negative MAT-file array dimension".to_string());
+    }
+    if dims.len() != 2 {
+        return Ok(None);
+    }
+    let (rows, cols) = (dims[0] as usize, dims[1] as usize);
+    let count = rows.checked_mul(cols).ok_or("MAT-file array too large")?;
+    let (_, name, size) = mat_element(tail(bytes, offset)?)?;
+    offset = offset.checked_add(size).ok_or(TRUNCATED)?;
+    let name = String::from_utf8_lossy(name).to_string();
+    let (data_type, real, _) = mat_element(tail(bytes, offset)?)?;
+    let values: Vec<f64> = match data_type {
+        MI_DOUBLE => real.chunks_exact(8).map(|c| f64::from_le_bytes(c.try_into().unwrap())).collect(),
+        MI_SINGLE => real.chunks_exact(4).map(|c| f32::from_le_bytes(c.try_into().unwrap()) as f64).collect(),
+        MI_INT8 => real.iter().map(|v| *v as i8 as f64).collect(),
+        MI_UINT8 => real.iter().map(|v| *v as f64).collect(),
+        MI_INT16 => real.chunks_exact(2).map(|c| i16::from_le_bytes(c.try_into().unwrap()) as f64).collect(),
+        MI_UINT16 => real.chunks_exact(2).map(|c| u16::from_le_bytes(c.try_into().unwrap()) as f64).collect(),
+        MI_INT32 => real.chunks_exact(4).map(|c| i32::from_le_bytes(c.try_into().unwrap()) as f64).collect(),
+        MI_UINT32 => real.chunks_exact(4).map(|c| u32::from_le_bytes(c.try_into().unwrap()) as f64).collect(),
+        MI_INT64 => real.chunks_exact(8).map(|c| i64::from_le_bytes(c.try_into().unwrap()) as f64).collect(),
+        MI_UINT64 => real.chunks_exact(8).map(|c| u64::from_le_bytes(c.try_into().unwrap()) as f64).collect(),
+        _ => return Ok(None),
+    };
+    // MATLAB stores column-major; row and column vectors both become a single row
+    let matrix = if rows == 1 || cols == 1 {
+        reshape(values, 1, count, true)?
+    } else {
+        reshape(values, rows, cols, false)?
+    };
+    Ok(Some((name, matrix)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn element(data_type: u32, payload: &[u8]) -> Vec<u8> {
+        let mut ret = [data_type.to_le_bytes(), (payload.len() as u32).to_le_bytes()].concat();
+        ret.extend_from_slice(payload);
+        ret.resize(ret.len().div_ceil(8) * 8, 0);
+        ret
+    }
+    fn matrix(dims: [i32; 2], values: &[f64]) -> Vec<u8> {
+        let dims: Vec<u8> = dims.iter().flat_map(|d| d.to_le_bytes()).collect();
+        let values: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        [element(MI_UINT32, &[6, 0, 0, 0, 0, 0, 0, 0]), element(MI_INT32, &dims), element(MI_INT8, b"b"), element(MI_DOUBLE, &values)].concat()
+    }
+    #[test]
+    fn test_mat_matrix() {
+        assert_eq!(parse_mat_matrix(&matrix([2, 1], &[1.0, 2.0])), Ok(Some(("b".to_string(), vec![vec![1.0, 2.0]]))));
+        assert!(parse_mat_matrix(&matrix([-1, 2], &[1.0, 2.0])).is_err());
+        assert!(parse_mat_matrix(&matrix([i32::MAX, i32::MAX], &[1.0, 2.0])).is_err());
+        // element sizes running past the data are errors, not panics
+        let bytes = matrix([2, 1], &[1.0, 2.0]);
+        for length in 0..bytes.len() {
+            assert!(parse_mat_matrix(&bytes[..length]).is_err());
+        }
+        assert!(mat_element(&[MI_DOUBLE as u8, 0, 0, 0, 0xff, 0xff, 0xff, 0xff]).is_err());
+        assert!(mat_element(&[MI_INT8 as u8, 0, 8, 0, 1, 2, 3, 4]).is_err());
+    }
+    #[test]
+    fn test_sections_kept() {
+        let set = CoefficientSet { sos: vec![vec![1.0, 2.0, 1.0, 2.0, 0.5, 0.25], vec![1.0, 0.0, -1.0, 1.0, 0.0, 0.5]], ..Default::default() };
+        assert_eq!(set.transfer_function(), None);
+        let sections = set.sections().unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0], Biquad { b0: 0.5, b1: 1.0, b2: 0.5, a1: 0.25, a2: 0.125 });
+        assert_eq!(CoefficientSet { sos: vec![vec![1.0, 2.0, 1.0]], ..Default::default() }.sections(), None);
+    }
+}
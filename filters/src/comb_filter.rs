@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct CombFilter {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "mode": String = "feedback".to_string(),
+            "fundamental": f64 = 50.0,
+            "q": f64 = 30.0,
+            "sample_rate": f64 = 1000.0,
+        },
+        state: {
+            "delay": usize = 0,
+            "pole": f64 = 0.0,
+            "position": usize = 0,
+            "inputs_memory": Vec<f64> = Vec::<f64>::new(),
+            "outputs_memory": Vec<f64> = Vec::<f64>::new(),
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            let fundamental = self.get_statics::<f64>("fundamental")?.get_value();
+            let q = self.get_statics::<f64>("q")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let Some((delay, pole)) = comb_design(&mode, fundamental, q, sample_rate) else {
+                return Err(StreamingError::InvalidStatics)
+            };
+            self.set_state_value("delay", delay)?;
+            self.set_state_value("pole", pole)?;
+            self.set_state_value("position", 0usize)?;
+            self.set_state_value("inputs_memory", vec![0.0; delay])?;
+            self.set_state_value("outputs_memory", vec![0.0; delay])?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let delay = self.get_state_value::<usize>("delay")?;
+            let pole = self.get_state_value::<f64>("pole")?;
+            let mut position = self.get_state_value::<usize>("position")?;
+            let mut input_memory = self.get_state_value::<Vec<f64>>("inputs_memory")?;
+            let mut output_memory = self.get_state_value::<Vec<f64>>("outputs_memory")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let gain = (1.0 + pole) / 2.0;
+            let mut output_signal = Vec::<f64>::with_capacity(input_signal.len());
+            {
+                let _lock = self.lock.lock().unwrap();
+                // memories are circular buffers of the last `delay` samples, position points at x(n-N)
+                for &x in input_signal.iter() {
+                    let y = gain * (x - input_memory[position]) + pole * output_memory[position];
+                    input_memory[position] = x;
+                    output_memory[position] = y;
+                    position = (position + 1) % delay;
+                    output_signal.push(y);
+                }
+            }
+            self.set_state_value("position", position)?;
+            self.set_state_value("inputs_memory", input_memory)?;
+            self.set_state_value("outputs_memory", output_memory)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// Delay length and pole radius of H(z) = (1+p)/2 * (1 - z^-N) / (1 - p*z^-N), which notches DC and
+// every multiple of fs/N with a -3 dB width of about fundamental/q. The dc_notch mode uses N = 1
+// and feedforward drops the pole.
+pub fn comb_design(mode: &str, fundamental: f64, q: f64, sample_rate: f64) -> Option<(usize, f64)> {
+    if !(fundamental > 0.0 && q > 0.0 && sample_rate > 0.0) || fundamental >= sample_rate / 2.0 {
+        return None;
+    }
+    let delay = match mode {
+        "feedforward" | "feedback" => (sample_rate / fundamental).round() as usize,
+        "dc_notch" => 1,
+        _ => return None,
+    };
+    if mode == "feedforward" {
+        return Some((delay, 0.0));
+    }
+    let pole = 1.0 - std::f64::consts::PI * fundamental / q * delay as f64 / sample_rate;
+    if !(0.0..1.0).contains(&pole) {
+        return None;
+    }
+    Some((delay, pole))
+}
+
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct PreEmphasis {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "coefficient": f64 = 0.97,
+            "preset": String = String::new(),
+            "sample_rate": f64 = 48000.0,
+        },
+        state: {
+            "alpha": f64 = 0.0,
+            "scale": f64 = 1.0,
+            "previous": f64 = 0.0,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let coefficient = self.get_statics::<f64>("coefficient")?.get_value();
+            let preset = self.get_statics::<String>("preset")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let Some((alpha, scale)) = emphasis_coefficient(&preset, coefficient, sample_rate) else {
+                return Err(StreamingError::InvalidStatics)
+            };
+            self.set_state_value("alpha", alpha)?;
+            self.set_state_value("scale", scale)?;
+            self.set_state_value("previous", 0.0)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let alpha = self.get_state_value::<f64>("alpha")?;
+            let scale = self.get_state_value::<f64>("scale")?;
+            let mut previous = self.get_state_value::<f64>("previous")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let output_signal: Vec<f64>;
+            {
+                let _lock = self.lock.lock().unwrap();
+                // y(n) = (x(n) - alpha*x(n-1)) / scale, previous holds x(n-1)
+                output_signal = input_signal.iter().map(|&x| {
+                    let y = (x - alpha * previous) / scale;
+                    previous = x;
+                    y
+                }).collect();
+            }
+            self.set_state_value("previous", previous)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+stream_block! {
+    pub struct DeEmphasis {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "coefficient": f64 = 0.97,
+            "preset": String = String::new(),
+            "sample_rate": f64 = 48000.0,
+        },
+        state: {
+            "alpha": f64 = 0.0,
+            "scale": f64 = 1.0,
+            "previous": f64 = 0.0,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let coefficient = self.get_statics::<f64>("coefficient")?.get_value();
+            let preset = self.get_statics::<String>("preset")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let Some((alpha, scale)) = emphasis_coefficient(&preset, coefficient, sample_rate) else {
+                return Err(StreamingError::InvalidStatics)
+            };
+            self.set_state_value("alpha", alpha)?;
+            self.set_state_value("scale", scale)?;
+            self.set_state_value("previous", 0.0)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let alpha = self.get_state_value::<f64>("alpha")?;
+            let scale = self.get_state_value::<f64>("scale")?;
+            let mut previous = self.get_state_value::<f64>("previous")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let output_signal: Vec<f64>;
+            {
+                let _lock = self.lock.lock().unwrap();
+                // y(n) = scale*x(n) + alpha*y(n-1), previous holds y(n-1)
+                output_signal = input_signal.iter().map(|&x| {
+                    previous = scale * x + alpha * previous;
+                    previous
+                }).collect();
+            }
+            self.set_state_value("previous", previous)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// Pole/zero position and gain shared by both blocks so that DeEmphasis inverts PreEmphasis.
+// Without a preset the plain speech pair x(n) - c*x(n-1) is used, the FM presets derive
+// the coefficient from the 50/75 us time constant and are normalized to unity gain at DC.
+pub fn emphasis_coefficient(preset: &str, coefficient: f64, sample_rate: f64) -> Option<(f64, f64)> {
+    let time_constant = match preset {
+        "" => {
+            if !(0.0..1.0).contains(&coefficient) {
+                return None;
+            }
+            return Some((coefficient, 1.0));
+        }
+        "fm50" => 50.0e-6,
+        "fm75" => 75.0e-6,
+        _ => return None,
+    };
+    if sample_rate.is_nan() || sample_rate <= 0.0 {
+        return None;
+    }
+    let alpha = (-1.0 / (time_constant * sample_rate)).exp();
+    Some((alpha, 1.0 - alpha))
+}
+
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::biquad::{Biquad, QuantizedBiquad, cascade, cascade_response, fixed_cascade, quantize_sections};
+use crate::fixed_point::{QFormat, QuantizationReport};
+use crate::frequency_response::FrequencyResponse;
+
+stream_block! {
+    pub struct Equalizer {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64>, "frequency_response": FrequencyResponse },
+        statics: {
+            "band_types": Vec<String> = Vec::<String>::new(),
+            "band_frequencies": Vec<f64> = Vec::<f64>::new(),
+            "band_gains": Vec<f64> = Vec::<f64>::new(),
+            "band_q": Vec<f64> = Vec::<f64>::new(),
+            "sample_rate": f64 = 48000.0,
+            "fixed_point": bool = false,
+            "data_format": String = "Q0.15".to_string(),
+            // a1 of a low frequency section is close to -2 and b0 of a boost exceeds 2
+            "coefficient_format": String = "Q2.13".to_string(),
+            // non zero sends the response of the cascade on frequency_response at start and after
+            // every gains update, the port then has to be connected
+            "response_points": usize = 0,
+        },
+        parameters: { "gains": Vec<f64> = Vec::<f64>::new() },
+        state: {
+            "sections": Vec<Biquad> = Vec::<Biquad>::new(),
+            "memory": Vec<[f64; 2]> = Vec::<[f64; 2]>::new(),
+            "applied_gains": Vec<f64> = Vec::<f64>::new(),
+            "quantized_sections": Vec<QuantizedBiquad> = Vec::<QuantizedBiquad>::new(),
+            "fixed_memory": Vec<[i64; 4]> = Vec::<[i64; 4]>::new(),
+            "quantization_report": QuantizationReport = QuantizationReport::default(),
+            "frequency_response": FrequencyResponse = FrequencyResponse::default(),
+            "response_pending": bool = false,
+            "rejected_gains": Vec<f64> = Vec::<f64>::new(),
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let band_gains = self.get_statics::<Vec<f64>>("band_gains")?.get_value();
+            let sections = self.compile(&band_gains)?;
+            self.set_state_value("memory", vec![[0.0; 2]; sections.len()])?;
+            self.set_state_value("fixed_memory", vec![[0_i64; 4]; sections.len()])?;
+            self.activate(sections)?;
+            self.set_state_value("applied_gains", band_gains)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            // the gains parameter overrides the band_gains statics while the stream runs, the
+            // cascade is redesigned only when it changes and keeps its memory across the update.
+            // Invalid gains are reported once and not compiled again until the parameter changes
+            let gains = self.get_parameter::<Vec<f64>>("gains")?.get_value();
+            if !gains.is_empty() && gains != self.get_state_value::<Vec<f64>>("applied_gains")?
+                && gains != self.get_state_value::<Vec<f64>>("rejected_gains")? {
+                match self.compile(&gains) {
+                    Ok(updated) => {
+                        self.activate(updated)?;
+                        self.set_state_value("applied_gains", gains)?;
+                        self.set_state_value("rejected_gains", Vec::<f64>::new())?;
+                    }
+                    Err(_) => {
+                        eprintln!("Equalizer {}: ignoring invalid gains {:?}", self.name, gains);
+                        self.set_state_value("rejected_gains", gains)?;
+                    }
+                }
+            }
+            let formats = if self.get_statics::<bool>("fixed_point")?.get_value() {
+                let data_format = QFormat::parse(&self.get_statics::<String>("data_format")?.get_value());
+                let coefficient_format = QFormat::parse(&self.get_statics::<String>("coefficient_format")?.get_value());
+                let (Some(data_format), Some(coefficient_format)) = (data_format, coefficient_format) else {
+                    return Err(StreamingError::InvalidStatics);
+                };
+                Some((data_format, coefficient_format))
+            } else {
+                None
+            };
+            if self.get_state_value::<bool>("response_pending")? {
+                let response = self.get_state_value::<FrequencyResponse>("frequency_response")?;
+                self.send_output::<FrequencyResponse>("frequency_response", response)?;
+                self.set_state_value("response_pending", false)?;
+            }
+            let sections = self.get_state_value::<Vec<Biquad>>("sections")?;
+            let quantized_sections = self.get_state_value::<Vec<QuantizedBiquad>>("quantized_sections")?;
+            let mut memory = self.get_state_value::<Vec<[f64; 2]>>("memory")?;
+            let mut fixed_memory = self.get_state_value::<Vec<[i64; 4]>>("fixed_memory")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let output_signal: Vec<f64>;
+            {
+                let _lock = self.lock.lock().unwrap();
+                output_signal = match formats {
+                    Some((data_format, coefficient_format)) => input_signal.iter()
+                        .map(|&x| fixed_cascade(&quantized_sections, &mut fixed_memory, x, &data_format, &coefficient_format))
+                        .collect(),
+                    None => input_signal.iter().map(|&x| cascade(&sections, &mut memory, x)).collect(),
+                };
+            }
+            self.set_state_value("memory", memory)?;
+            self.set_state_value("fixed_memory", fixed_memory)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+impl Equalizer {
+    fn compile(&self, gains: &[f64]) -> Result<Vec<Biquad>, StreamingError> {
+        let band_types = self.get_statics::<Vec<String>>("band_types")?.get_value();
+        let band_frequencies = self.get_statics::<Vec<f64>>("band_frequencies")?.get_value();
+        let band_q = self.get_statics::<Vec<f64>>("band_q")?.get_value();
+        let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+        if band_frequencies.len() != band_types.len() || band_q.len() != band_types.len() || gains.len() != band_types.len() {
+            return Err(StreamingError::InvalidStatics);
+        }
+        let mut sections = Vec::with_capacity(band_types.len());
+        for band in 0..band_types.len() {
+            match Biquad::design(&band_types[band], band_frequencies[band], gains[band], band_q[band], sample_rate) {
+                Some(section) => sections.push(section),
+                None => return Err(StreamingError::InvalidStatics),
+            }
+        }
+        Ok(sections)
+    }
+    // Makes `sections` the running cascade, quantized in fixed point mode, and refreshes the
+    // frequency response when one is requested.
+    fn activate(&mut self, sections: Vec<Biquad>) -> Result<(), StreamingError> {
+        let mut effective_sections = sections.clone();
+        if self.get_statics::<bool>("fixed_point")?.get_value() {
+            let data_format = QFormat::parse(&self.get_statics::<String>("data_format")?.get_value());
+            let coefficient_format = QFormat::parse(&self.get_statics::<String>("coefficient_format")?.get_value());
+            let (Some(_), Some(coefficient_format)) = (data_format, coefficient_format) else {
+                return Err(StreamingError::InvalidStatics);
+            };
+            let (quantized, report) = quantize_sections(&coefficient_format, &sections);
+            if report.saturated > 0 {
+                eprintln!("Equalizer {}: {} coefficients saturated in {:?}", self.name, report.saturated, coefficient_format);
+            }
+            effective_sections = quantized.iter().map(|q| q.to_biquad(&coefficient_format)).collect();
+            self.set_state_value("quantized_sections", quantized)?;
+            self.set_state_value("quantization_report", report)?;
+        }
+        self.set_state_value("sections", sections)?;
+        let response_points = self.get_statics::<usize>("response_points")?.get_value();
+        if response_points > 0 {
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            self.set_state_value("frequency_response", cascade_response(&effective_sections, response_points, sample_rate))?;
+        }
+        self.set_state_value("response_pending", response_points > 0)?;
+        Ok(())
+    }
+}
+
@@ -5,95 +5,204 @@ use std::sync::mpsc::SyncSender;
 use std::sync::{Arc, Mutex};
 use serde::Serialize;
 use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
 use data_model::streaming_data::{StreamingError, StreamingState};
 use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
 use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
 use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::fixed_point::{QFormat, QuantizationReport, quantize_coefficients};
+use crate::initial_conditions::initial_memory;
+use crate::frequency_response::{FrequencyResponse, freqz};
+use crate::coefficient_loader::load_coefficients;
+use crate::fir_design::design_fir;
 
-#[derive(StreamBlockMacro)]
-pub struct Fir {
-    name:       &'static str,
-    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
-    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
-    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
-    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
-    state:      HashMap<&'static str, Box<dyn DataTrait>>,
-    lock:       Arc<Mutex<()>>,
-    proc_state: Arc<Mutex<StreamingState>>,
-}
-impl Fir {
-    pub fn new(name: &'static str) -> Self {
-        let mut ret = Self {
-            name,
-            inputs: HashMap::new(),
-            outputs: HashMap::new(),
-            parameters: HashMap::new(),
-            statics: HashMap::new(),
-            state: HashMap::new(),
-            lock: Arc::new(Mutex::new(())),
-            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
-        };
-        ret.new_input::<Vec<f64>>("input");
-        ret.new_output::<Vec<f64>>("output");
-        ret.new_statics::<usize>("order", 0, None);
-        ret.new_statics::<Vec<f64>>("coefficient", Vec::<f64>::new(), None);
-        ret.new_state::<Vec<f64>>("inputs_memory", Vec::<f64>::new());
-        ret
+stream_block! {
+    pub struct Fir {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64>, "frequency_response": FrequencyResponse },
+        statics: {
+            "order": usize = 0,
+            "coefficient": Vec<f64> = Vec::<f64>::new(),
+            "coefficient_file": String = String::new(),
+            "filter_type": String = String::new(),
+            "cutoff": Vec<f64> = Vec::<f64>::new(),
+            "window": String = "hamming".to_string(),
+            "kaiser_beta": f64 = 8.6,
+            "fixed_point": bool = false,
+            "data_format": String = "Q0.15".to_string(),
+            "coefficient_format": String = "Q1.14".to_string(),
+            "initial_condition": String = "zero".to_string(),
+            "initial_inputs": Vec<f64> = Vec::<f64>::new(),
+            "steady_state_input": f64 = 0.0,
+            // non zero sends the response of the active coefficients on frequency_response at start
+            // and after every retune, the port then has to be connected
+            "response_points": usize = 0,
+            "sample_rate": f64 = 1.0,
+        },
+        parameters: { "coefficient": Vec<f64> = Vec::<f64>::new() },
+        state: {
+            "active_coefficient": Vec<f64> = Vec::<f64>::new(),
+            "inputs_memory": Vec<f64> = Vec::<f64>::new(),
+            "quantized_coefficient": Vec<i64> = Vec::<i64>::new(),
+            "quantization_report": QuantizationReport = QuantizationReport::default(),
+            "frequency_response": FrequencyResponse = FrequencyResponse::default(),
+            "response_pending": bool = false,
+            "rejected_coefficient": Vec<f64> = Vec::<f64>::new(),
+        },
     }
-}
-impl StreamProcessor for Fir {
-    fn init(&mut self) -> Result<(), StreamingError> {
-        if self.check_state(StreamingState::Running) {
-            return Err(StreamingError::InvalidStateTransition)
-        }
-        if !self.is_initialized() {
-            return Err(StreamingError::InvalidStatics)
-        }
-        let order = self.get_statics::<usize>("order")?.get_value();
-        let coefficient = self.get_statics::<Vec<f64>>("coefficient")?.get_value();
-        if coefficient.len() != order + 1 {
-            return Err(StreamingError::InvalidStatics);
-        }
-        let memory = vec![0.0; order];
-        self.set_state_value("inputs_memory", memory)?;
-        self.set_state(StreamingState::Initial);
-        Ok(())
-    }
-    fn run(&mut self) -> Result<(), StreamingError> {
-        if self.check_state(StreamingState::Stopped) {
-            return Err(StreamingError::InvalidStateTransition);
-        }
-        if !self.is_initialized() {
-            return Err(StreamingError::InvalidStatics)
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let coefficient_file = self.get_statics::<String>("coefficient_file")?.get_value();
+            let filter_type = self.get_statics::<String>("filter_type")?.get_value();
+            let coefficient = if !filter_type.is_empty() && coefficient_file.is_empty() {
+                // design mode, the taps come from the specification instead of the coefficient static
+                let order = self.get_statics::<usize>("order")?.get_value();
+                let cutoff = self.get_statics::<Vec<f64>>("cutoff")?.get_value();
+                let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+                let window = self.get_statics::<String>("window")?.get_value();
+                let kaiser_beta = self.get_statics::<f64>("kaiser_beta")?.get_value();
+                match design_fir(&filter_type, order, &cutoff, sample_rate, &window, kaiser_beta) {
+                    Some(coefficient) => coefficient,
+                    None => return Err(StreamingError::InvalidStatics),
+                }
+            } else if coefficient_file.is_empty() {
+                let order = self.get_statics::<usize>("order")?.get_value();
+                let coefficient = self.get_statics::<Vec<f64>>("coefficient")?.get_value();
+                if coefficient.len() != order + 1 {
+                    return Err(StreamingError::InvalidStatics);
+                }
+                coefficient
+            } else {
+                let set = match load_coefficients(&coefficient_file) {
+                    Ok(set) => set,
+                    Err(error) => {
+                        eprintln!("Fir {}: {}", self.name, error);
+                        return Err(StreamingError::InvalidStatics);
+                    }
+                };
+                match set.transfer_function() {
+                    Some((b, a)) if a.len() == 1 && a[0] != 0.0 && !b.is_empty() => b.iter().map(|v| v / a[0]).collect(),
+                    _ => return Err(StreamingError::InvalidStatics),
+                }
+            };
+            let order = coefficient.len() - 1;
+            self.activate(coefficient.clone())?;
+            let initial_condition = self.get_statics::<String>("initial_condition")?.get_value();
+            let initial_inputs = self.get_statics::<Vec<f64>>("initial_inputs")?.get_value();
+            let steady_state_input = self.get_statics::<f64>("steady_state_input")?.get_value();
+            let (memory, _) = match initial_memory(&initial_condition, &coefficient, &[1.0], order, initial_inputs, Vec::new(), steady_state_input) {
+                Some(memory) => memory,
+                None => return Err(StreamingError::InvalidStatics),
+            };
+            self.set_state_value("inputs_memory", memory)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
         }
-        self.set_state(StreamingState::Running);
-        while !self.check_state(StreamingState::Stopped) {
-            self.process()?;
+        fn process(&mut self) -> Result<(), StreamingError> {
+            // the coefficient parameter retunes the running filter, the taps are swapped between
+            // frames under the block lock and the memory is kept, so the order cannot change
+            let update = self.get_parameter::<Vec<f64>>("coefficient")?.get_value();
+            let active = self.get_state_value::<Vec<f64>>("active_coefficient")?;
+            if !update.is_empty() && update != active {
+                if update.len() == active.len() {
+                    let lock = self.lock.clone();
+                    let _lock = lock.lock().unwrap();
+                    self.activate(update)?;
+                    self.set_state_value("rejected_coefficient", Vec::<f64>::new())?;
+                } else if update != self.get_state_value::<Vec<f64>>("rejected_coefficient")? {
+                    // reported once, the parameter keeps the rejected set until it is set again
+                    eprintln!("Fir {}: ignoring {} coefficients for an order {} filter", self.name, update.len(), active.len() - 1);
+                    self.set_state_value("rejected_coefficient", update)?;
+                }
+            }
+            let coefficient = self.get_state_value::<Vec<f64>>("active_coefficient")?;
+            let order = coefficient.len() - 1;
+            // data_format and coefficient_format only matter, and are only validated at init, in fixed point mode
+            let formats = if self.get_statics::<bool>("fixed_point")?.get_value() {
+                let data_format = QFormat::parse(&self.get_statics::<String>("data_format")?.get_value());
+                let coefficient_format = QFormat::parse(&self.get_statics::<String>("coefficient_format")?.get_value());
+                let (Some(data_format), Some(coefficient_format)) = (data_format, coefficient_format) else {
+                    return Err(StreamingError::InvalidStatics);
+                };
+                Some((data_format, coefficient_format))
+            } else {
+                None
+            };
+            let quantized_coefficient = self.get_state_value::<Vec<i64>>("quantized_coefficient")?;
+            if self.get_state_value::<bool>("response_pending")? {
+                let response = self.get_state_value::<FrequencyResponse>("frequency_response")?;
+                self.send_output::<FrequencyResponse>("frequency_response", response)?;
+                self.set_state_value("response_pending", false)?;
+            }
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut input_memory = self.get_state_value::<Vec<f64>>("inputs_memory")?;
+            let mut output_signal = Vec::<f64>::with_capacity(input_signal.len());
+            for &sample in input_signal.iter() {
+                let _lock = self.lock.lock().unwrap();
+                // inputs_memory holds x(n-order)..x(n-1), oldest first
+                let value;
+                let input;
+                if let Some((data_format, coefficient_format)) = formats {
+                    let x = data_format.quantize(sample);
+                    let mut accumulator = quantized_coefficient[0] as i128 * x as i128;
+                    for index in 1..=order {
+                        accumulator += quantized_coefficient[index] as i128 * data_format.quantize(input_memory[order - index]) as i128;
+                    }
+                    value = data_format.to_f64(data_format.from_accumulator(accumulator, coefficient_format.fractional_bits));
+                    input = data_format.to_f64(x);
+                } else {
+                    let mut accumulator = coefficient[0]*sample;
+                    for index in 1..=order {
+                        accumulator += coefficient[index]*input_memory[order - index];
+                    }
+                    value = accumulator;
+                    input = sample;
+                }
+                output_signal.push(value);
+                if order > 0 {
+                    input_memory.remove(0);
+                    input_memory.push(input);
+                }
+            }
+            self.set_state_value("inputs_memory", input_memory)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
         }
-        Ok(())
     }
-    fn process(&mut self) -> Result<(), StreamingError> {
-        let coefficient = self.get_statics::<Vec<f64>>("coefficient")?.get_value();
-        let order = self.get_statics::<usize>("order")?.get_value();
-        let input_signal = self.recv_input::<Vec<f64>>("input")?;
-        let mut input_memory = self.get_state_value::<Vec<f64>>("inputs_memory")?;
-        let mut output_signal = Vec::<f64>::new();
-        for k in 0..input_signal.len() {
-            let _lock = self.lock.lock().unwrap();
-            let mut value = coefficient[0]*input_signal[k];
-            for index in 1..order {
-                value += coefficient[index]*input_memory[order - k];
+}
+impl Fir {
+    // Makes `coefficient` the running set, quantized in fixed point mode, and refreshes the
+    // frequency response when one is requested.
+    fn activate(&mut self, coefficient: Vec<f64>) -> Result<(), StreamingError> {
+        self.set_state_value("active_coefficient", coefficient.clone())?;
+        let fixed_point = self.get_statics::<bool>("fixed_point")?.get_value();
+        let data_format = QFormat::parse(&self.get_statics::<String>("data_format")?.get_value());
+        let coefficient_format = QFormat::parse(&self.get_statics::<String>("coefficient_format")?.get_value());
+        let mut effective_coefficient = coefficient.clone();
+        if fixed_point {
+            let (Some(_), Some(coefficient_format)) = (data_format, coefficient_format) else {
+                return Err(StreamingError::InvalidStatics);
+            };
+            let (quantized, report) = quantize_coefficients(&coefficient_format, &coefficient);
+            if report.saturated > 0 {
+                eprintln!("Fir {}: {} coefficients saturated in {:?}", self.name, report.saturated, coefficient_format);
             }
-            output_signal.push(value);
-            input_memory.remove(0);
-            input_memory.push(input_signal[k]);
+            effective_coefficient = quantized.iter().map(|q| coefficient_format.to_f64(*q)).collect();
+            self.set_state_value("quantized_coefficient", quantized)?;
+            self.set_state_value("quantization_report", report)?;
         }
-        self.set_state_value("inputs_memory", input_memory)?;
-        self.send_output::<Vec<f64>>("output", output_signal)?;
-        Ok(())
-    }
-    fn stop(&mut self) -> Result<(), StreamingError> {
-        self.set_state(StreamingState::Stopped);
+        let response_points = self.get_statics::<usize>("response_points")?.get_value();
+        if response_points > 0 {
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            self.set_state_value("frequency_response", freqz(&effective_coefficient, &[1.0], response_points, sample_rate))?;
+        }
+        self.set_state_value("response_pending", response_points > 0)?;
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,104 @@
+use std::f64::consts::PI;
+
+// Zeroth order modified Bessel function of the first kind, power series.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half = x / 2.0;
+    for k in 1..64 {
+        term *= (half / k as f64) * (half / k as f64);
+        sum += term;
+        if term < 1.0e-16 * sum {
+            break;
+        }
+    }
+    sum
+}
+
+// Symmetric design windows of `size` points (the periodic variants in the transform crate are
+// meant for spectral analysis, not for tap design).
+pub fn design_window(name: &str, size: usize, kaiser_beta: f64) -> Option<Vec<f64>> {
+    if size == 1 {
+        return Some(vec![1.0]);
+    }
+    let m = (size - 1) as f64;
+    let value = |n: usize| -> Option<f64> {
+        let x = 2.0 * PI * n as f64 / m;
+        match name {
+            "rectangular" => Some(1.0),
+            "hann" => Some(0.5 - 0.5 * x.cos()),
+            "hamming" => Some(0.54 - 0.46 * x.cos()),
+            "blackman" => Some(0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos()),
+            "kaiser" => {
+                let r = 2.0 * n as f64 / m - 1.0;
+                Some(bessel_i0(kaiser_beta * (1.0 - r * r).max(0.0).sqrt()) / bessel_i0(kaiser_beta))
+            }
+            _ => None,
+        }
+    };
+    (0..size).map(value).collect()
+}
+
+fn lowpass(cutoff: f64, order: usize) -> Vec<f64> {
+    let center = order as f64 / 2.0;
+    (0..=order).map(|n| {
+        let t = n as f64 - center;
+        if t == 0.0 { 2.0 * cutoff } else { (2.0 * PI * cutoff * t).sin() / (PI * t) }
+    }).collect()
+}
+
+// Window method design of an order `order` (order+1 taps) linear phase FIR. Cutoffs are in Hz,
+// one for lowpass/highpass and two for bandpass/bandstop. The taps are scaled to unit gain at DC
+// (lowpass, bandstop), Nyquist (highpass) or the band center (bandpass). Highpass and bandstop
+// need an even order, an odd order puts a zero at Nyquist.
+pub fn design_fir(filter_type: &str, order: usize, cutoff: &[f64], sample_rate: f64, window: &str, kaiser_beta: f64) -> Option<Vec<f64>> {
+    let normalized: Vec<f64> = cutoff.iter().map(|f| f / sample_rate).collect();
+    if sample_rate.is_nan() || sample_rate <= 0.0 || normalized.iter().any(|f| f.is_nan() || *f <= 0.0 || *f >= 0.5) {
+        return None;
+    }
+    let impulse = |order: usize| { let mut d = vec![0.0; order + 1]; d[order / 2] = 1.0; d };
+    let (mut taps, scale_frequency) = match (filter_type, normalized.as_slice()) {
+        ("lowpass", [f]) => (lowpass(*f, order), 0.0),
+        ("highpass", [f]) if order.is_multiple_of(2) => {
+            (impulse(order).iter().zip(lowpass(*f, order)).map(|(d, h)| d - h).collect(), 0.5)
+        }
+        ("bandpass", [f1, f2]) if f1 < f2 => {
+            (lowpass(*f2, order).iter().zip(lowpass(*f1, order)).map(|(h2, h1)| h2 - h1).collect(), (f1 + f2) / 2.0)
+        }
+        ("bandstop", [f1, f2]) if f1 < f2 && order.is_multiple_of(2) => {
+            let band: Vec<f64> = lowpass(*f2, order).iter().zip(lowpass(*f1, order)).map(|(h2, h1)| h2 - h1).collect();
+            (impulse(order).iter().zip(band).map(|(d, h)| d - h).collect(), 0.0)
+        }
+        _ => return None,
+    };
+    let weights = design_window(window, order + 1, kaiser_beta)?;
+    taps.iter_mut().zip(weights.iter()).for_each(|(tap, w)| *tap *= w);
+    let center = order as f64 / 2.0;
+    let gain: f64 = taps.iter().enumerate().map(|(n, tap)| tap * (2.0 * PI * scale_frequency * (n as f64 - center)).cos()).sum();
+    if gain.abs() < 1.0e-12 {
+        return None;
+    }
+    Some(taps.iter().map(|tap| tap / gain).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frequency_response::freqz;
+    #[test]
+    fn test_lowpass_and_bandstop() {
+        let taps = design_fir("lowpass", 64, &[1000.0], 8000.0, "hamming", 0.0).unwrap();
+        assert_eq!(taps.len(), 65);
+        assert!((taps[0] - taps[64]).abs() < 1.0e-15);
+        let response = freqz(&taps, &[1.0], 401, 8000.0);
+        assert!((response.magnitude[0] - 1.0).abs() < 1.0e-12);
+        // 500 Hz passband and 1500 Hz stopband, Hamming gives about -53 dB
+        assert!((response.magnitude[50] - 1.0).abs() < 0.01);
+        assert!(response.magnitude[150] < 0.003);
+        let notch = design_fir("bandstop", 100, &[700.0, 1300.0], 8000.0, "kaiser", 6.0).unwrap();
+        let response = freqz(&notch, &[1.0], 401, 8000.0);
+        assert!(response.magnitude[100] < 0.01);
+        assert!((response.magnitude[300] - 1.0).abs() < 0.01);
+        assert!(design_fir("highpass", 63, &[1000.0], 8000.0, "hamming", 0.0).is_none());
+    }
+}
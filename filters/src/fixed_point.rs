@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+// Signed Qm.n format: one sign bit, m integer bits and n fractional bits.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QFormat {
+    pub integer_bits: u32,
+    pub fractional_bits: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuantizationReport {
+    pub max_error: f64,
+    pub rms_error: f64,
+    pub saturated: usize,
+}
+
+impl QFormat {
+    pub fn new(integer_bits: u32, fractional_bits: u32) -> Option<Self> {
+        if 1 + integer_bits + fractional_bits > 64 {
+            return None;
+        }
+        Some(QFormat { integer_bits, fractional_bits })
+    }
+    // Parses "Qm.n" (e.g. "Q1.14") or "Qn" (e.g. "Q15", shorthand for "Q0.15").
+    pub fn parse(format: &str) -> Option<Self> {
+        let body = format.trim().strip_prefix('Q').or_else(|| format.trim().strip_prefix('q'))?;
+        match body.split_once('.') {
+            Some((m, n)) => QFormat::new(m.parse().ok()?, n.parse().ok()?),
+            None => QFormat::new(0, body.parse().ok()?),
+        }
+    }
+    pub fn word_length(&self) -> u32 {
+        1 + self.integer_bits + self.fractional_bits
+    }
+    pub fn max_raw(&self) -> i64 {
+        (((1_i128) << (self.word_length() - 1)) - 1) as i64
+    }
+    pub fn min_raw(&self) -> i64 {
+        (-((1_i128) << (self.word_length() - 1))) as i64
+    }
+    pub fn resolution(&self) -> f64 {
+        (-(self.fractional_bits as f64)).exp2()
+    }
+    pub fn saturate(&self, raw: i128) -> i64 {
+        raw.clamp(self.min_raw() as i128, self.max_raw() as i128) as i64
+    }
+    pub fn quantize(&self, value: f64) -> i64 {
+        if value.is_nan() {
+            return 0;
+        }
+        let scaled = (value * (self.fractional_bits as f64).exp2()).round();
+        if scaled >= self.max_raw() as f64 {
+            self.max_raw()
+        } else if scaled <= self.min_raw() as f64 {
+            self.min_raw()
+        } else {
+            scaled as i64
+        }
+    }
+    pub fn to_f64(&self, raw: i64) -> f64 {
+        raw as f64 * self.resolution()
+    }
+    // Rounds an accumulator carrying `shift` extra fractional bits back into this format.
+    pub fn from_accumulator(&self, accumulator: i128, shift: u32) -> i64 {
+        if shift == 0 {
+            return self.saturate(accumulator);
+        }
+        let rounding = 1_i128 << (shift - 1);
+        self.saturate((accumulator + rounding) >> shift)
+    }
+}
+
+pub fn quantize_coefficients(format: &QFormat, coefficients: &[f64]) -> (Vec<i64>, QuantizationReport) {
+    let mut report = QuantizationReport::default();
+    let mut quantized = Vec::with_capacity(coefficients.len());
+    let mut square_sum = 0.0;
+    for &c in coefficients {
+        let raw = format.quantize(c);
+        if raw == format.max_raw() || raw == format.min_raw() {
+            report.saturated += 1;
+        }
+        let error = (format.to_f64(raw) - c).abs();
+        report.max_error = report.max_error.max(error);
+        square_sum += error * error;
+        quantized.push(raw);
+    }
+    if !coefficients.is_empty() {
+        report.rms_error = (square_sum / coefficients.len() as f64).sqrt();
+    }
+    (quantized, report)
+}
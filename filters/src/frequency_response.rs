@@ -0,0 +1,65 @@
+use std::f64::consts::PI;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FrequencyResponse {
+    pub frequencies: Vec<f64>,
+    pub magnitude: Vec<f64>,
+    pub phase: Vec<f64>,
+    pub group_delay: Vec<f64>,
+}
+
+// Evaluates sum c(n)*e^{-jwn} and sum n*c(n)*e^{-jwn} as (re, im) pairs.
+fn polynomial_at(coefficients: &[f64], omega: f64) -> ((f64, f64), (f64, f64)) {
+    let mut value = (0.0, 0.0);
+    let mut ramp = (0.0, 0.0);
+    for (n, c) in coefficients.iter().enumerate() {
+        let (s, co) = (omega * n as f64).sin_cos();
+        value.0 += c * co;
+        value.1 -= c * s;
+        ramp.0 += n as f64 * c * co;
+        ramp.1 -= n as f64 * c * s;
+    }
+    (value, ramp)
+}
+
+fn divide(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    let den = b.0 * b.0 + b.1 * b.1;
+    ((a.0 * b.0 + a.1 * b.1) / den, (a.1 * b.0 - a.0 * b.1) / den)
+}
+
+// Magnitude, unwrapped phase (rad) and group delay (samples) of b(z)/a(z) on `points`
+// frequencies evenly spaced from 0 to Nyquist.
+pub fn freqz(b: &[f64], a: &[f64], points: usize, sample_rate: f64) -> FrequencyResponse {
+    let mut response = FrequencyResponse::default();
+    let mut previous_phase = 0.0;
+    let mut offset = 0.0;
+    let b_energy: f64 = b.iter().map(|c| c * c).sum();
+    for k in 0..points {
+        let omega = if points > 1 { PI * k as f64 / (points - 1) as f64 } else { 0.0 };
+        let (b_value, b_ramp) = polynomial_at(b, omega);
+        let (a_value, a_ramp) = polynomial_at(a, omega);
+        let h = divide(b_value, a_value);
+        let mut phase = h.1.atan2(h.0) + offset;
+        if k > 0 {
+            while phase - previous_phase > PI {
+                phase -= 2.0 * PI;
+                offset -= 2.0 * PI;
+            }
+            while phase - previous_phase < -PI {
+                phase += 2.0 * PI;
+                offset += 2.0 * PI;
+            }
+        }
+        previous_phase = phase;
+        // group delay is undefined on a zero of b(z), reported as 0 like scipy does
+        let b_magnitude = b_value.0 * b_value.0 + b_value.1 * b_value.1;
+        let b_delay = if b_magnitude <= 1.0e-20 * b_energy { 0.0 } else { divide(b_ramp, b_value).0 };
+        let a_delay = divide(a_ramp, a_value).0;
+        response.frequencies.push(omega / (2.0 * PI) * sample_rate);
+        response.magnitude.push((h.0 * h.0 + h.1 * h.1).sqrt());
+        response.phase.push(phase);
+        response.group_delay.push(b_delay - a_delay);
+    }
+    response
+}
@@ -5,105 +5,255 @@ use std::sync::mpsc::SyncSender;
 use std::sync::{Arc, Mutex};
 use serde::Serialize;
 use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
 use data_model::streaming_data::{StreamingError, StreamingState};
 use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
 use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
 use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::fixed_point::{QFormat, QuantizationReport, quantize_coefficients};
+use crate::initial_conditions::initial_memory;
+use crate::frequency_response::{FrequencyResponse, freqz};
+use crate::coefficient_loader::load_coefficients;
+use crate::iir_design::design_iir;
 
-#[derive(StreamBlockMacro)]
-pub struct Iir {
-    name:       &'static str,
-    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
-    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
-    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
-    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
-    state:      HashMap<&'static str, Box<dyn DataTrait>>,
-    lock:       Arc<Mutex<()>>,
-    proc_state: Arc<Mutex<StreamingState>>,
-}
-impl Iir {
-    pub fn new(name: &'static str) -> Self {
-        let mut ret = Self {
-            name,
-            inputs: HashMap::new(),
-            outputs: HashMap::new(),
-            parameters: HashMap::new(),
-            statics: HashMap::new(),
-            state: HashMap::new(),
-            lock: Arc::new(Mutex::new(())),
-            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
-        };
-        ret.new_input::<Vec<f64>>("input");
-        ret.new_output::<Vec<f64>>("output");
-        ret.new_statics::<usize>("order", 0, None);
-        ret.new_statics::<Vec<f64>>("a_coefficient", Vec::<f64>::new(), None);
-        ret.new_statics::<Vec<f64>>("b_coefficient", Vec::<f64>::new(), None);
-        ret.new_state::<Vec<f64>>("outputs_memory", Vec::<f64>::new());
-        ret.new_state::<Vec<f64>>("inputs_memory", Vec::<f64>::new());
-        ret
-    }
-}
-impl StreamProcessor for Iir {
-    fn init(&mut self) -> Result<(), StreamingError> {
-        if self.check_state(StreamingState::Running) {
-            return Err(StreamingError::InvalidStateTransition)
-        }
-        if !self.is_initialized() {
-            return Err(StreamingError::InvalidStatics)
-        }
-        let order = self.get_statics::<usize>("order")?.get_value();
-        let a_coefficient = self.get_statics::<Vec<f64>>("a_coefficient")?.get_value();
-        let b_coefficient = self.get_statics::<Vec<f64>>("b_coefficient")?.get_value();
-        if a_coefficient.len() != order + 1 || b_coefficient.len() != order {
-            return Err(StreamingError::InvalidStatics);
-        }
-        let memory = vec![0.0; order];
-        self.set_state_value("inputs_memory", memory.clone())?;
-        self.set_state_value("outputs_memory", memory)?;
-        self.set_state(StreamingState::Initial);
-        Ok(())
+stream_block! {
+    pub struct Iir {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64>, "frequency_response": FrequencyResponse },
+        statics: {
+            "order": usize = 0,
+            "a_coefficient": Vec<f64> = Vec::<f64>::new(),
+            "b_coefficient": Vec<f64> = Vec::<f64>::new(),
+            "coefficient_file": String = String::new(),
+            "design": String = String::new(),
+            "filter_type": String = "lowpass".to_string(),
+            "cutoff": Vec<f64> = Vec::<f64>::new(),
+            "ripple": f64 = 1.0,
+            "attenuation": f64 = 40.0,
+            "fixed_point": bool = false,
+            "data_format": String = "Q0.15".to_string(),
+            "coefficient_format": String = "Q1.14".to_string(),
+            "initial_condition": String = "zero".to_string(),
+            "initial_inputs": Vec<f64> = Vec::<f64>::new(),
+            "initial_outputs": Vec<f64> = Vec::<f64>::new(),
+            "steady_state_input": f64 = 0.0,
+            // non zero sends the response of the active coefficients on frequency_response at start
+            // and after every retune, the port then has to be connected
+            "response_points": usize = 0,
+            "sample_rate": f64 = 1.0,
+        },
+        parameters: {
+            "a_coefficient": Vec<f64> = Vec::<f64>::new(),
+            "b_coefficient": Vec<f64> = Vec::<f64>::new(),
+        },
+        state: {
+            "active_a_coefficient": Vec<f64> = Vec::<f64>::new(),
+            "active_b_coefficient": Vec<f64> = Vec::<f64>::new(),
+            "outputs_memory": Vec<f64> = Vec::<f64>::new(),
+            "inputs_memory": Vec<f64> = Vec::<f64>::new(),
+            "quantized_a_coefficient": Vec<i64> = Vec::<i64>::new(),
+            "quantized_b_coefficient": Vec<i64> = Vec::<i64>::new(),
+            "quantization_report": QuantizationReport = QuantizationReport::default(),
+            "frequency_response": FrequencyResponse = FrequencyResponse::default(),
+            "response_pending": bool = false,
+            "rejected_b_coefficient": Vec<f64> = Vec::<f64>::new(),
+            "rejected_a_coefficient": Vec<f64> = Vec::<f64>::new(),
+        },
     }
-    fn run(&mut self) -> Result<(), StreamingError> {
-        if self.check_state(StreamingState::Stopped) {
-            return Err(StreamingError::InvalidStateTransition);
-        }
-        if !self.is_initialized() {
-            return Err(StreamingError::InvalidStatics)
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let coefficient_file = self.get_statics::<String>("coefficient_file")?.get_value();
+            let design = self.get_statics::<String>("design")?.get_value();
+            let (b_coefficient, a_coefficient) = if !design.is_empty() && coefficient_file.is_empty() {
+                // order is the prototype order, band filters end up with twice as many coefficients
+                let order = self.get_statics::<usize>("order")?.get_value();
+                let filter_type = self.get_statics::<String>("filter_type")?.get_value();
+                let cutoff = self.get_statics::<Vec<f64>>("cutoff")?.get_value();
+                let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+                let ripple = self.get_statics::<f64>("ripple")?.get_value();
+                let attenuation = self.get_statics::<f64>("attenuation")?.get_value();
+                match design_iir(&design, &filter_type, order, &cutoff, sample_rate, ripple, attenuation) {
+                    Some(coefficients) => coefficients,
+                    None => return Err(StreamingError::InvalidStatics),
+                }
+            } else if coefficient_file.is_empty() {
+                let order = self.get_statics::<usize>("order")?.get_value();
+                let a_coefficient = self.get_statics::<Vec<f64>>("a_coefficient")?.get_value();
+                let b_coefficient = self.get_statics::<Vec<f64>>("b_coefficient")?.get_value();
+                if a_coefficient.len() != order + 1 || b_coefficient.len() != order + 1 {
+                    return Err(StreamingError::InvalidStatics);
+                }
+                (b_coefficient, a_coefficient)
+            } else {
+                let set = match load_coefficients(&coefficient_file) {
+                    Ok(set) => set,
+                    Err(error) => {
+                        eprintln!("Iir {}: {}", self.name, error);
+                        return Err(StreamingError::InvalidStatics);
+                    }
+                };
+                let Some((mut b, mut a)) = set.transfer_function() else {
+                    eprintln!("Iir {}: {} holds second-order sections, load it in a Sos block", self.name, coefficient_file);
+                    return Err(StreamingError::InvalidStatics);
+                };
+                let size = b.len().max(a.len());
+                b.resize(size, 0.0);
+                a.resize(size, 0.0);
+                (b, a)
+            };
+            if a_coefficient.is_empty() || a_coefficient[0] == 0.0 {
+                return Err(StreamingError::InvalidStatics);
+            }
+            let order = a_coefficient.len() - 1;
+            self.activate(b_coefficient.clone(), a_coefficient.clone())?;
+            let initial_condition = self.get_statics::<String>("initial_condition")?.get_value();
+            let initial_inputs = self.get_statics::<Vec<f64>>("initial_inputs")?.get_value();
+            let initial_outputs = self.get_statics::<Vec<f64>>("initial_outputs")?.get_value();
+            let steady_state_input = self.get_statics::<f64>("steady_state_input")?.get_value();
+            let (inputs_memory, outputs_memory) = match initial_memory(&initial_condition, &b_coefficient, &a_coefficient, order, initial_inputs, initial_outputs, steady_state_input) {
+                Some(memory) => memory,
+                None => return Err(StreamingError::InvalidStatics),
+            };
+            self.set_state_value("inputs_memory", inputs_memory)?;
+            self.set_state_value("outputs_memory", outputs_memory)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
         }
-        self.set_state(StreamingState::Running);
-        while !self.check_state(StreamingState::Stopped) {
-            self.process()?;
+        fn process(&mut self) -> Result<(), StreamingError> {
+            // the coefficient parameters retune the running filter, an empty one keeps the active
+            // set. The swap happens between frames under the block lock and keeps the memories, so
+            // the order cannot change
+            let mut update_b = self.get_parameter::<Vec<f64>>("b_coefficient")?.get_value();
+            let mut update_a = self.get_parameter::<Vec<f64>>("a_coefficient")?.get_value();
+            let active_b = self.get_state_value::<Vec<f64>>("active_b_coefficient")?;
+            let active_a = self.get_state_value::<Vec<f64>>("active_a_coefficient")?;
+            if update_b.is_empty() {
+                update_b = active_b.clone();
+            }
+            if update_a.is_empty() {
+                update_a = active_a.clone();
+            }
+            if update_b != active_b || update_a != active_a {
+                if update_b.len() == active_b.len() && update_a.len() == active_a.len() && update_a[0] != 0.0 {
+                    let lock = self.lock.clone();
+                    let _lock = lock.lock().unwrap();
+                    self.activate(update_b, update_a)?;
+                    self.set_state_value("rejected_b_coefficient", Vec::<f64>::new())?;
+                    self.set_state_value("rejected_a_coefficient", Vec::<f64>::new())?;
+                } else if update_b != self.get_state_value::<Vec<f64>>("rejected_b_coefficient")?
+                    || update_a != self.get_state_value::<Vec<f64>>("rejected_a_coefficient")? {
+                    // reported once, the parameters keep the rejected set until they are set again
+                    eprintln!("Iir {}: ignoring coefficients {:?} / {:?} for an order {} filter", self.name, update_b, update_a, active_a.len() - 1);
+                    self.set_state_value("rejected_b_coefficient", update_b)?;
+                    self.set_state_value("rejected_a_coefficient", update_a)?;
+                }
+            }
+            let a_coefficient = self.get_state_value::<Vec<f64>>("active_a_coefficient")?;
+            let b_coefficient = self.get_state_value::<Vec<f64>>("active_b_coefficient")?;
+            // data_format and coefficient_format only matter, and are only validated at init, in fixed point mode
+            let formats = if self.get_statics::<bool>("fixed_point")?.get_value() {
+                let data_format = QFormat::parse(&self.get_statics::<String>("data_format")?.get_value());
+                let coefficient_format = QFormat::parse(&self.get_statics::<String>("coefficient_format")?.get_value());
+                let (Some(data_format), Some(coefficient_format)) = (data_format, coefficient_format) else {
+                    return Err(StreamingError::InvalidStatics);
+                };
+                Some((data_format, coefficient_format))
+            } else {
+                None
+            };
+            let quantized_a = self.get_state_value::<Vec<i64>>("quantized_a_coefficient")?;
+            let quantized_b = self.get_state_value::<Vec<i64>>("quantized_b_coefficient")?;
+            let mut input_memory = self.get_state_value::<Vec<f64>>("inputs_memory")?;
+            let mut output_memory = self.get_state_value::<Vec<f64>>("outputs_memory")?;
+            let order = a_coefficient.len() - 1;
+            let mut output_signal = Vec::<f64>::new();
+            if self.get_state_value::<bool>("response_pending")? {
+                let response = self.get_state_value::<FrequencyResponse>("frequency_response")?;
+                self.send_output::<FrequencyResponse>("frequency_response", response)?;
+                self.set_state_value("response_pending", false)?;
+            }
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            for &sample in input_signal.iter() {
+                let _lock = self.lock.lock().unwrap();
+                // a0*y(n) = sum b(i)*x(n-i) - sum_{i>0} a(i)*y(n-i), memories hold the last `order` samples oldest first
+                let value;
+                let input;
+                if let Some((data_format, coefficient_format)) = formats {
+                    let x = data_format.quantize(sample);
+                    let mut accumulator = quantized_b[0] as i128 * x as i128;
+                    for index in 1..=order {
+                        accumulator += quantized_b[index] as i128 * data_format.quantize(input_memory[order - index]) as i128;
+                        accumulator -= quantized_a[index] as i128 * data_format.quantize(output_memory[order - index]) as i128;
+                    }
+                    value = data_format.to_f64(data_format.from_accumulator(accumulator, coefficient_format.fractional_bits));
+                    input = data_format.to_f64(x);
+                } else {
+                    let mut accumulator = b_coefficient[0]*sample;
+                    for index in 1..=order {
+                        accumulator += b_coefficient[index]*input_memory[order - index];
+                        accumulator -= a_coefficient[index]*output_memory[order - index];
+                    }
+                    value = accumulator / a_coefficient[0];
+                    input = sample;
+                }
+                output_signal.push(value);
+                if order > 0 {
+                    output_memory.remove(0);
+                    output_memory.push(value);
+                    input_memory.remove(0);
+                    input_memory.push(input);
+                }
+            }
+            self.set_state_value("inputs_memory", input_memory)?;
+            self.set_state_value("outputs_memory", output_memory)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
         }
-        Ok(())
     }
-    fn process(&mut self) -> Result<(), StreamingError> {
-        let a_coefficient = self.get_statics::<Vec<f64>>("a_coefficient")?.get_value();
-        let b_coefficient = self.get_statics::<Vec<f64>>("b_coefficient")?.get_value();
-        let mut input_memory = self.get_state_value::<Vec<f64>>("inputs_memory")?;
-        let mut output_memory = self.get_state_value::<Vec<f64>>("outputs_memory")?;
-        let order = self.get_statics::<usize>("order")?.get_value();
-        let mut output_signal = Vec::<f64>::new();
-        let input_signal = self.recv_input::<Vec<f64>>("input")?;
-        for k in 0..input_signal.len() {
-            let _lock = self.lock.lock().unwrap();
-            let mut value = b_coefficient[0]*input_signal[k];
-            for index in 1..order {
-                value += b_coefficient[index]*input_memory[order - k];
-                value += a_coefficient[index]*output_memory[order - k];
+}
+impl Iir {
+    // Makes (b, a) the running transfer function, quantized in fixed point mode, and refreshes
+    // the frequency response when one is requested.
+    fn activate(&mut self, b_coefficient: Vec<f64>, a_coefficient: Vec<f64>) -> Result<(), StreamingError> {
+        let order = a_coefficient.len() - 1;
+        self.set_state_value("active_b_coefficient", b_coefficient.clone())?;
+        self.set_state_value("active_a_coefficient", a_coefficient.clone())?;
+        let fixed_point = self.get_statics::<bool>("fixed_point")?.get_value();
+        let data_format = QFormat::parse(&self.get_statics::<String>("data_format")?.get_value());
+        let coefficient_format = QFormat::parse(&self.get_statics::<String>("coefficient_format")?.get_value());
+        let mut effective_b = b_coefficient.clone();
+        let mut effective_a = a_coefficient.clone();
+        if fixed_point {
+            let (Some(_), Some(coefficient_format)) = (data_format, coefficient_format) else {
+                return Err(StreamingError::InvalidStatics);
+            };
+            // quantize the a0-normalized difference equation as a single set
+            let a0 = a_coefficient[0];
+            let mut normalized: Vec<f64> = b_coefficient.iter().map(|b| b / a0).collect();
+            normalized.extend(a_coefficient.iter().map(|a| a / a0));
+            let (mut quantized, report) = quantize_coefficients(&coefficient_format, &normalized);
+            if report.saturated > 0 {
+                eprintln!("Iir {}: {} coefficients saturated in {:?}", self.name, report.saturated, coefficient_format);
             }
-            output_signal.push(value);
-            output_memory.remove(0);
-            output_memory.push(value);
-            input_memory.remove(0);
-            input_memory.push(input_signal[k]);
+            let quantized_a = quantized.split_off(order + 1);
+            effective_b = quantized.iter().map(|q| coefficient_format.to_f64(*q)).collect();
+            effective_a = quantized_a.iter().map(|q| coefficient_format.to_f64(*q)).collect();
+            self.set_state_value("quantized_b_coefficient", quantized)?;
+            self.set_state_value("quantized_a_coefficient", quantized_a)?;
+            self.set_state_value("quantization_report", report)?;
         }
-        self.set_state_value("inputs_memory", input_memory)?;
-        self.set_state_value("outputs_memory", output_memory)?;
-        self.send_output::<Vec<f64>>("output", output_signal)?;
-        Ok(())
-    }
-    fn stop(&mut self) -> Result<(), StreamingError> {
-        self.set_state(StreamingState::Stopped);
+        let response_points = self.get_statics::<usize>("response_points")?.get_value();
+        if response_points > 0 {
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            self.set_state_value("frequency_response", freqz(&effective_b, &effective_a, response_points, sample_rate))?;
+        }
+        self.set_state_value("response_pending", response_points > 0)?;
         Ok(())
     }
-}
\ No newline at end of file
+}
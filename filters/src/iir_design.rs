@@ -0,0 +1,276 @@
+use std::f64::consts::PI;
+use num_complex::Complex;
+
+// Zeros, poles and gain of a transfer function, analog (s plane) or digital (z plane).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Zpk {
+    pub zeros: Vec<Complex<f64>>,
+    pub poles: Vec<Complex<f64>>,
+    pub gain: f64,
+}
+
+fn product(values: &[Complex<f64>], shift: Complex<f64>) -> Complex<f64> {
+    values.iter().fold(Complex::new(1.0, 0.0), |acc, v| acc * (shift - v))
+}
+
+fn butterworth(order: usize) -> Zpk {
+    let n = order as f64;
+    let poles = (0..order).map(|k| Complex::from_polar(1.0, PI * (2.0 * k as f64 + n + 1.0) / (2.0 * n))).collect();
+    Zpk { zeros: Vec::new(), poles, gain: 1.0 }
+}
+
+fn chebyshev1(order: usize, ripple: f64) -> Zpk {
+    let n = order as f64;
+    let epsilon = (10f64.powf(ripple / 10.0) - 1.0).sqrt();
+    let mu = (1.0 / epsilon).asinh() / n;
+    let poles: Vec<Complex<f64>> = (0..order).map(|k| {
+        let theta = PI * (2.0 * k as f64 + 1.0) / (2.0 * n);
+        Complex::new(-mu.sinh() * theta.sin(), mu.cosh() * theta.cos())
+    }).collect();
+    let mut gain = product(&poles, Complex::new(0.0, 0.0)).re;
+    if order.is_multiple_of(2) {
+        gain /= (1.0 + epsilon * epsilon).sqrt();
+    }
+    Zpk { zeros: Vec::new(), poles, gain }
+}
+
+fn chebyshev2(order: usize, attenuation: f64) -> Zpk {
+    let n = order as f64;
+    let epsilon = 1.0 / (10f64.powf(attenuation / 10.0) - 1.0).sqrt();
+    let mu = (1.0 / epsilon).asinh() / n;
+    let mut zeros = Vec::new();
+    let mut poles = Vec::new();
+    for k in 0..order {
+        let theta = PI * (2.0 * k as f64 + 1.0) / (2.0 * n);
+        // the middle zero of an odd order sits at infinity
+        if 2 * k + 1 != order {
+            zeros.push(Complex::new(0.0, 1.0 / theta.cos()));
+        }
+        poles.push(1.0 / Complex::new(-mu.sinh() * theta.sin(), mu.cosh() * theta.cos()));
+    }
+    let gain = (product(&poles, Complex::new(0.0, 0.0)) / product(&zeros, Complex::new(0.0, 0.0))).re;
+    Zpk { zeros, poles, gain }
+}
+
+// Descending Landen sequence of elliptic moduli, used by the sn/cd evaluations below
+// (Orfanidis, "Lecture notes on elliptic filter design").
+fn landen(modulus: f64) -> Vec<f64> {
+    let mut k = modulus;
+    let mut sequence = Vec::new();
+    while k > 1.0e-15 && sequence.len() < 16 {
+        k = (k / (1.0 + (1.0 - k * k).sqrt())).powi(2);
+        sequence.push(k);
+    }
+    sequence
+}
+
+// Jacobi cd and sn for an argument u expressed in units of the quarter period K.
+fn cde(u: Complex<f64>, modulus: f64) -> Complex<f64> {
+    landen(modulus).iter().rev().fold((u * PI / 2.0).cos(), |w, v| (1.0 + v) * w / (1.0 + v * w * w))
+}
+fn sne(u: Complex<f64>, modulus: f64) -> Complex<f64> {
+    landen(modulus).iter().rev().fold((u * PI / 2.0).sin(), |w, v| (1.0 + v) * w / (1.0 + v * w * w))
+}
+
+// Inverse of sne, result in units of K.
+fn asne(w: Complex<f64>, modulus: f64) -> Complex<f64> {
+    let sequence = landen(modulus);
+    let mut previous = modulus;
+    let mut w = w;
+    for v in sequence {
+        w = w / (1.0 + (1.0 - w * w * previous * previous).sqrt()) * 2.0 / (1.0 + v);
+        previous = v;
+    }
+    w.asin() * 2.0 / PI
+}
+
+// Selectivity modulus k from the degree equation for the given order and discrimination k1.
+fn elliptic_degree(order: usize, k1: f64) -> f64 {
+    let k1_complement = (1.0 - k1 * k1).sqrt();
+    let product: f64 = (1..=order / 2).map(|i| {
+        let u = Complex::new((2.0 * i as f64 - 1.0) / order as f64, 0.0);
+        sne(u, k1_complement).re.powi(4)
+    }).product();
+    let k_complement = k1_complement.powi(order as i32) * product;
+    (1.0 - k_complement * k_complement).sqrt()
+}
+
+fn elliptic(order: usize, ripple: f64, attenuation: f64) -> Zpk {
+    let n = order as f64;
+    let epsilon_p = (10f64.powf(ripple / 10.0) - 1.0).sqrt();
+    let epsilon_s = (10f64.powf(attenuation / 10.0) - 1.0).sqrt();
+    let k1 = epsilon_p / epsilon_s;
+    let k = elliptic_degree(order, k1);
+    let v0 = asne(Complex::new(0.0, 1.0 / epsilon_p), k1) / Complex::new(0.0, n);
+    let mut zeros = Vec::new();
+    let mut poles = Vec::new();
+    for i in 1..=order / 2 {
+        let u = (2.0 * i as f64 - 1.0) / n;
+        let zeta = cde(Complex::new(u, 0.0), k);
+        let zero = Complex::new(0.0, 1.0) / (zeta * k);
+        let pole = Complex::new(0.0, 1.0) * cde(Complex::new(u, 0.0) - Complex::new(0.0, 1.0) * v0, k);
+        zeros.push(zero);
+        zeros.push(zero.conj());
+        poles.push(pole);
+        poles.push(pole.conj());
+    }
+    if order % 2 == 1 {
+        let pole = Complex::new(0.0, 1.0) * sne(Complex::new(0.0, 1.0) * v0, k);
+        poles.push(Complex::new(pole.re, 0.0));
+    }
+    let dc = if order % 2 == 1 { 1.0 } else { 1.0 / (1.0 + epsilon_p * epsilon_p).sqrt() };
+    let gain = dc * (product(&poles, Complex::new(0.0, 0.0)) / product(&zeros, Complex::new(0.0, 0.0))).re;
+    Zpk { zeros, poles, gain }
+}
+
+// Normalized analog lowpass prototype with its passband edge (stopband edge for chebyshev2)
+// at 1 rad/s. Ripple and attenuation are in dB.
+pub fn analog_prototype(prototype: &str, order: usize, ripple: f64, attenuation: f64) -> Option<Zpk> {
+    if order == 0 {
+        return None;
+    }
+    match prototype {
+        "butterworth" => Some(butterworth(order)),
+        "chebyshev1" if ripple > 0.0 => Some(chebyshev1(order, ripple)),
+        "chebyshev2" if attenuation > 0.0 => Some(chebyshev2(order, attenuation)),
+        "elliptic" if ripple > 0.0 && attenuation > ripple => Some(elliptic(order, ripple, attenuation)),
+        _ => None,
+    }
+}
+
+// Maps the prototype onto the requested band, edges in rad/s.
+pub fn transform_band(prototype: &Zpk, filter_type: &str, edges: &[f64]) -> Option<Zpk> {
+    let degree = prototype.poles.len() - prototype.zeros.len();
+    let zero = Complex::new(0.0, 0.0);
+    match (filter_type, edges) {
+        ("lowpass", [w]) => Some(Zpk {
+            zeros: prototype.zeros.iter().map(|z| z * w).collect(),
+            poles: prototype.poles.iter().map(|p| p * w).collect(),
+            gain: prototype.gain * w.powi(degree as i32),
+        }),
+        ("highpass", [w]) => {
+            let mut zeros: Vec<Complex<f64>> = prototype.zeros.iter().map(|z| w / z).collect();
+            zeros.extend(vec![zero; degree]);
+            Some(Zpk {
+                zeros,
+                poles: prototype.poles.iter().map(|p| w / p).collect(),
+                gain: prototype.gain * (product(&prototype.zeros, zero) / product(&prototype.poles, zero)).re,
+            })
+        }
+        ("bandpass", [w1, w2]) if w1 < w2 => {
+            let (bandwidth, center) = (w2 - w1, (w1 * w2).sqrt());
+            let split = |roots: &[Complex<f64>]| -> Vec<Complex<f64>> {
+                roots.iter().flat_map(|r| {
+                    let half = r * bandwidth / 2.0;
+                    let offset = (half * half - center * center).sqrt();
+                    [half + offset, half - offset]
+                }).collect()
+            };
+            let mut zeros = split(&prototype.zeros);
+            zeros.extend(vec![zero; degree]);
+            Some(Zpk { zeros, poles: split(&prototype.poles), gain: prototype.gain * bandwidth.powi(degree as i32) })
+        }
+        ("bandstop", [w1, w2]) if w1 < w2 => {
+            let (bandwidth, center) = (w2 - w1, (w1 * w2).sqrt());
+            let split = |roots: &[Complex<f64>]| -> Vec<Complex<f64>> {
+                roots.iter().flat_map(|r| {
+                    let half = bandwidth / 2.0 / r;
+                    let offset = (half * half - center * center).sqrt();
+                    [half + offset, half - offset]
+                }).collect()
+            };
+            let mut zeros = split(&prototype.zeros);
+            for _ in 0..degree {
+                zeros.push(Complex::new(0.0, center));
+                zeros.push(Complex::new(0.0, -center));
+            }
+            Some(Zpk {
+                zeros,
+                poles: split(&prototype.poles),
+                gain: prototype.gain * (product(&prototype.zeros, zero) / product(&prototype.poles, zero)).re,
+            })
+        }
+        _ => None,
+    }
+}
+
+pub fn bilinear(analog: &Zpk, sample_rate: f64) -> Zpk {
+    let fs2 = Complex::new(2.0 * sample_rate, 0.0);
+    let map = |r: &Complex<f64>| (fs2 + r) / (fs2 - r);
+    let mut zeros: Vec<Complex<f64>> = analog.zeros.iter().map(map).collect();
+    zeros.extend(vec![Complex::new(-1.0, 0.0); analog.poles.len() - analog.zeros.len()]);
+    Zpk {
+        zeros,
+        poles: analog.poles.iter().map(map).collect(),
+        gain: analog.gain * (product(&analog.zeros, fs2) / product(&analog.poles, fs2)).re,
+    }
+}
+
+fn polynomial(roots: &[Complex<f64>]) -> Vec<f64> {
+    let mut coefficients = vec![Complex::new(1.0, 0.0)];
+    for root in roots {
+        let mut next = vec![Complex::new(0.0, 0.0); coefficients.len() + 1];
+        for (i, c) in coefficients.iter().enumerate() {
+            next[i] += c;
+            next[i + 1] -= c * root;
+        }
+        coefficients = next;
+    }
+    coefficients.iter().map(|c| c.re).collect()
+}
+
+// Digital zpk design: prototype ("butterworth", "chebyshev1", "chebyshev2", "elliptic"),
+// filter_type ("lowpass", "highpass", "bandpass", "bandstop") and cutoffs in Hz, prewarped for
+// the bilinear transform. Band filters have twice the prototype order.
+pub fn design_zpk(prototype: &str, filter_type: &str, order: usize, cutoff: &[f64], sample_rate: f64, ripple: f64, attenuation: f64) -> Option<Zpk> {
+    if sample_rate.is_nan() || sample_rate <= 0.0 || cutoff.iter().any(|f| f.is_nan() || *f <= 0.0 || *f >= sample_rate / 2.0) {
+        return None;
+    }
+    let warped: Vec<f64> = cutoff.iter().map(|f| 2.0 * sample_rate * (PI * f / sample_rate).tan()).collect();
+    let analog = transform_band(&analog_prototype(prototype, order, ripple, attenuation)?, filter_type, &warped)?;
+    Some(bilinear(&analog, sample_rate))
+}
+
+// Same design as (b, a) polynomial coefficients in z^-1, a[0] = 1.
+pub fn design_iir(prototype: &str, filter_type: &str, order: usize, cutoff: &[f64], sample_rate: f64, ripple: f64, attenuation: f64) -> Option<(Vec<f64>, Vec<f64>)> {
+    let digital = design_zpk(prototype, filter_type, order, cutoff, sample_rate, ripple, attenuation)?;
+    let b = polynomial(&digital.zeros).iter().map(|c| c * digital.gain).collect();
+    Some((b, polynomial(&digital.poles)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frequency_response::freqz;
+    fn gain_db(b: &[f64], a: &[f64], frequency: f64, sample_rate: f64) -> f64 {
+        let points = 4001;
+        let response = freqz(b, a, points, sample_rate);
+        let index = (frequency / (sample_rate / 2.0) * (points - 1) as f64).round() as usize;
+        20.0 * response.magnitude[index].log10()
+    }
+    #[test]
+    fn test_band_edges() {
+        let fs = 8000.0;
+        let (b, a) = design_iir("butterworth", "lowpass", 4, &[1000.0], fs, 0.0, 0.0).unwrap();
+        assert!((gain_db(&b, &a, 0.0, fs)).abs() < 1.0e-9);
+        assert!((gain_db(&b, &a, 1000.0, fs) + 3.0103).abs() < 1.0e-3);
+        let (b, a) = design_iir("chebyshev1", "highpass", 5, &[1000.0], fs, 1.0, 0.0).unwrap();
+        assert!((gain_db(&b, &a, 1000.0, fs) + 1.0).abs() < 1.0e-3);
+        assert!(gain_db(&b, &a, 4000.0, fs).abs() < 1.0e-6);
+        let (b, a) = design_iir("chebyshev2", "lowpass", 6, &[1000.0], fs, 0.0, 40.0).unwrap();
+        assert!((gain_db(&b, &a, 1000.0, fs) + 40.0).abs() < 1.0e-3);
+        assert!(gain_db(&b, &a, 0.0, fs).abs() < 1.0e-6);
+        for order in [3, 4] {
+            let (b, a) = design_iir("elliptic", "lowpass", order, &[1000.0], fs, 0.5, 50.0).unwrap();
+            assert!((gain_db(&b, &a, 1000.0, fs) + 0.5).abs() < 1.0e-3);
+            assert!(gain_db(&b, &a, 3000.0, fs) < -50.0 + 1.0e-6);
+        }
+        let (b, a) = design_iir("butterworth", "bandpass", 3, &[800.0, 1200.0], fs, 0.0, 0.0).unwrap();
+        assert_eq!(a.len(), 7);
+        assert!((gain_db(&b, &a, 800.0, fs) + 3.0103).abs() < 1.0e-3);
+        assert!((gain_db(&b, &a, 1200.0, fs) + 3.0103).abs() < 1.0e-3);
+        let (b, a) = design_iir("butterworth", "bandstop", 2, &[800.0, 1200.0], fs, 0.0, 0.0).unwrap();
+        assert!(gain_db(&b, &a, 0.0, fs).abs() < 1.0e-9);
+        assert!((gain_db(&b, &a, 1200.0, fs) + 3.0103).abs() < 1.0e-3);
+    }
+}
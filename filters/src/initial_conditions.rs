@@ -0,0 +1,26 @@
+// Direct-form memories (oldest first) that a filter with numerator b and denominator a
+// would hold after settling on a constant input `level`. None if the DC gain is undefined.
+pub fn steady_state_memory(b: &[f64], a: &[f64], level: f64, order: usize) -> Option<(Vec<f64>, Vec<f64>)> {
+    let b_sum: f64 = b.iter().sum();
+    let a_sum: f64 = a.iter().sum();
+    if a_sum.abs() < 1.0e-12 {
+        return None;
+    }
+    let output = level * b_sum / a_sum;
+    Some((vec![level; order], vec![output; order]))
+}
+
+pub fn initial_memory(mode: &str, b: &[f64], a: &[f64], order: usize, initial_inputs: Vec<f64>, initial_outputs: Vec<f64>, level: f64) -> Option<(Vec<f64>, Vec<f64>)> {
+    match mode {
+        "zero" => Some((vec![0.0; order], vec![0.0; order])),
+        "explicit" => {
+            if initial_inputs.len() != order || (!initial_outputs.is_empty() && initial_outputs.len() != order) {
+                return None;
+            }
+            let outputs = if initial_outputs.is_empty() { vec![0.0; order] } else { initial_outputs };
+            Some((initial_inputs, outputs))
+        }
+        "steady_state" => steady_state_memory(b, a, level, order),
+        _ => None,
+    }
+}
@@ -2,6 +2,22 @@ pub mod iir;
 pub mod fir;
 pub mod moving_average;
 pub mod median_filter;
+pub mod percentile_filter;
+pub mod order_statistics;
+pub mod fixed_point;
+pub mod initial_conditions;
+pub mod frequency_response;
+pub mod coefficient_loader;
+pub mod comb_filter;
+pub mod emphasis;
+pub mod noise_gate;
+pub mod biquad;
+pub mod equalizer;
+pub mod fir_design;
+pub mod iir_design;
+pub mod adaptive_lms;
+pub mod adaptive_rls;
+pub mod sos;
 use std::ffi::c_char;
 use data_model::modules::{Version,ModuleStructFFI};
 use processor_engine::stream_processor::StreamProcessor;
@@ -18,23 +34,28 @@ pub static MODULE: ModuleStructFFI  = ModuleStructFFI {
     provides: std::ptr::null(),
     provides_lengths: 0,
 };
+/// Creates the block `proc_block` named `block_name`, both passed by the host as UTF-8 bytes.
+///
+/// # Safety
+/// A non null `proc_block` and `block_name` must point to `proc_block_len` and `block_name_len`
+/// readable bytes that stay valid while the block exists.
 #[unsafe(no_mangle)]
-pub extern "C" fn get_processor_modules(proc_block: *const u8, 
+pub unsafe extern "C" fn get_processor_modules(proc_block: *const u8, 
     proc_block_len: usize, 
     block_name: *const u8, 
     block_name_len: usize) -> TraitObjectRepr {
-    let proc_block_str = unsafe {
-        std::str::from_utf8(std::slice::from_raw_parts(proc_block, proc_block_len)).unwrap()
-    };
-    let block_name_str = unsafe {
-        std::str::from_utf8(std::slice::from_raw_parts(block_name, block_name_len)).unwrap()
-    };
+    let proc_block_str = block_template::ffi_name!(proc_block, proc_block_len);
+    let block_name_str = block_template::ffi_name!(block_name, block_name_len);
     let proc: Box<dyn StreamProcessor>;
     match proc_block_str {
         "MedianFilter" => {
             proc = Box::new(median_filter::MedianFilter::new(block_name_str));
             export_stream_processor(proc)
         }
+        "PercentileFilter" => {
+            proc = Box::new(percentile_filter::PercentileFilter::new(block_name_str));
+            export_stream_processor(proc)
+        }
         "MovingAverage" => {
             proc = Box::new(moving_average::MovingAverage::new(block_name_str));
             export_stream_processor(proc)
@@ -47,9 +68,60 @@ pub extern "C" fn get_processor_modules(proc_block: *const u8,
             proc = Box::new(iir::Iir::new(block_name_str));
             export_stream_processor(proc)
         }
+        "CombFilter" => {
+            proc = Box::new(comb_filter::CombFilter::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "PreEmphasis" => {
+            proc = Box::new(emphasis::PreEmphasis::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "DeEmphasis" => {
+            proc = Box::new(emphasis::DeEmphasis::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "NoiseGate" => {
+            proc = Box::new(noise_gate::NoiseGate::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Equalizer" => {
+            proc = Box::new(equalizer::Equalizer::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "AdaptiveLms" => {
+            proc = Box::new(adaptive_lms::AdaptiveLms::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "AdaptiveRls" => {
+            proc = Box::new(adaptive_rls::AdaptiveRls::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Sos" => {
+            proc = Box::new(sos::Sos::new(block_name_str));
+            export_stream_processor(proc)
+        }
         _ => {
             eprintln!("Processor block {} not found", proc_block_str);
             get_error_return(1)
         }
     }
-}
\ No newline at end of file
+}
+
+// Schema of every block get_processor_modules creates, keyed by block type, see stream_block!.
+pub fn block_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "MedianFilter": median_filter::MedianFilter::schema(),
+        "PercentileFilter": percentile_filter::PercentileFilter::schema(),
+        "MovingAverage": moving_average::MovingAverage::schema(),
+        "Fir": fir::Fir::schema(),
+        "Iir": iir::Iir::schema(),
+        "CombFilter": comb_filter::CombFilter::schema(),
+        "PreEmphasis": emphasis::PreEmphasis::schema(),
+        "DeEmphasis": emphasis::DeEmphasis::schema(),
+        "NoiseGate": noise_gate::NoiseGate::schema(),
+        "Equalizer": equalizer::Equalizer::schema(),
+        "AdaptiveLms": adaptive_lms::AdaptiveLms::schema(),
+        "AdaptiveRls": adaptive_rls::AdaptiveRls::schema(),
+        "Sos": sos::Sos::schema(),
+    })
+}
@@ -1,99 +1,103 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::any::Any;
 use std::fmt::Debug;
 use std::sync::mpsc::SyncSender;
 use std::sync::{Arc, Mutex};
 use serde::Serialize;
 use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
 use data_model::streaming_data::{StreamingError, StreamingState};
 use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
 use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
 use processor_engine::connectors::{ConnectorTrait, Input, Output};
 
-#[derive(StreamBlockMacro)]
-pub struct MovingAverage {
-    name:       &'static str,
-    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
-    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
-    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
-    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
-    state:      HashMap<&'static str, Box<dyn DataTrait>>,
-    lock:       Arc<Mutex<()>>,
-    proc_state: Arc<Mutex<StreamingState>>,
-}
-impl MovingAverage {
-    pub fn new(name: &'static str) -> Self {
-        let mut ret = Self {
-            name,
-            inputs: HashMap::new(),
-            outputs: HashMap::new(),
-            parameters: HashMap::new(),
-            statics: HashMap::new(),
-            state: HashMap::new(),
-            lock: Arc::new(Mutex::new(())),
-            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
-        };
-        ret.new_input::<Vec<f64>>("input");
-        ret.new_output::<Vec<f64>>("output");
-        ret.new_statics::<usize>("order", 0, None);
-        ret
-    }
-}
-impl StreamProcessor for MovingAverage {
-    fn init(&mut self) -> Result<(), StreamingError> {
-        if self.check_state(StreamingState::Running) {
-            return Err(StreamingError::InvalidStateTransition)
-        }
-        if !self.is_initialized() {
-            return Err(StreamingError::InvalidStatics)
-        }
-        self.set_state(StreamingState::Initial);
-        Ok(())
+// Moving average over `order` samples, streamed across frames of any length. "causal" averages
+// the last order samples, "exponential" uses alpha = 2 / (order + 1). "centered" is the causal
+// average delayed by (order - 1) / 2 samples, so the window is centered on the output sample and
+// the first output frames are that many samples shorter. Only the windows at the start of the
+// stream are truncated, they average over the samples received so far.
+stream_block! {
+    pub struct MovingAverage {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "order": usize = 0,
+            "mode": String = "centered".to_string(),
+        },
+        state: {
+            "average": f64 = 0.0,
+            "primed": bool = false,
+            "warmup": usize = 0,
+        },
+        fields: { window: VecDeque<f64> = VecDeque::new() },
     }
-    fn run(&mut self) -> Result<(), StreamingError> {
-        if self.check_state(StreamingState::Stopped) {
-            return Err(StreamingError::InvalidStateTransition);
-        }
-        if !self.is_initialized() {
-            return Err(StreamingError::InvalidStatics)
-        }
-        self.set_state(StreamingState::Running);
-        while !self.check_state(StreamingState::Stopped) {
-            self.process()?;
-        }
-        Ok(())
-    }
-    fn process(&mut self) -> Result<(), StreamingError> {
-        let order = self.get_statics::<usize>("order")?.get_value();
-        let mut output_signal = Vec::<f64>::new();
-        let half_order = order / 2;
-        let mut value_sum: f64 = 0.0;
-        let mut count: usize = 0;
-        let input_signal = self.recv_input::<Vec<f64>>("input")?;
-        {
-            let _lock = self.lock.lock().unwrap();
-            for k in 0..half_order {
-                value_sum += input_signal[k];
-                count += 1;
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
             }
-            for k in 0..input_signal.len() {
-                output_signal.push(value_sum / count as f64);
-                if k < order {
-                    value_sum += input_signal[k + half_order];
-                    count += 1;
-                } else if k > input_signal.len() - half_order - 1 {
-                    value_sum -= input_signal[k - half_order - 1];
-                    count -= 1;
+            let order = self.get_statics::<usize>("order")?.get_value();
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            if order < 1 || !["causal", "centered", "exponential"].contains(&mode.as_str()) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.window = VecDeque::with_capacity(order + 1);
+            self.set_state_value("average", 0.0)?;
+            self.set_state_value("primed", false)?;
+            self.set_state_value("warmup", if mode == "centered" { (order - 1) / 2 } else { 0 })?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let order = self.get_statics::<usize>("order")?.get_value();
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            let mut average = self.get_state_value::<f64>("average")?;
+            let mut primed = self.get_state_value::<bool>("primed")?;
+            let mut warmup = self.get_state_value::<usize>("warmup")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut output_signal = Vec::<f64>::with_capacity(input_signal.len());
+            {
+                let _lock = self.lock.lock().unwrap();
+                if mode == "exponential" {
+                    let alpha = 2.0 / (order as f64 + 1.0);
+                    for &x in input_signal.iter() {
+                        if !primed {
+                            average = x;
+                            primed = true;
+                        } else {
+                            average += alpha * (x - average);
+                        }
+                        output_signal.push(average);
+                    }
                 } else {
-                    value_sum += input_signal[k + half_order] - input_signal[k - half_order - 1];
+                    // the window keeps the last order samples across frames, the sum is rebuilt
+                    // once per frame so rounding errors do not pile up
+                    let mut sum: f64 = self.window.iter().sum();
+                    for &x in input_signal.iter() {
+                        self.window.push_back(x);
+                        sum += x;
+                        if self.window.len() > order {
+                            sum -= self.window.pop_front().unwrap_or(0.0);
+                        }
+                        // a centered output is ready once the samples after it have arrived
+                        if warmup > 0 {
+                            warmup -= 1;
+                        } else {
+                            output_signal.push(sum / self.window.len() as f64);
+                        }
+                    }
                 }
             }
+            self.set_state_value("average", average)?;
+            self.set_state_value("primed", primed)?;
+            self.set_state_value("warmup", warmup)?;
+            if !output_signal.is_empty() {
+                self.send_output::<Vec<f64>>("output", output_signal)?;
+            }
+            Ok(())
         }
-        self.send_output::<Vec<f64>>("output", output_signal)?;
-        Ok(())
-    }
-    fn stop(&mut self) -> Result<(), StreamingError> {
-        self.set_state(StreamingState::Stopped);
-        Ok(())
     }
-}
\ No newline at end of file
+}
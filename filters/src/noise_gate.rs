@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct NoiseGate {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "threshold": f64 = 0.01,
+            "hold": f64 = 0.05,
+            "attack": f64 = 0.001,
+            "release": f64 = 0.05,
+            "lookahead": f64 = 0.0,
+            "sample_rate": f64 = 48000.0,
+        },
+        state: {
+            "delay_line": Vec<f64> = Vec::<f64>::new(),
+            "position": usize = 0,
+            "hold_counter": usize = 0,
+            "gain": f64 = 0.0,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let threshold = self.get_statics::<f64>("threshold")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let mut times = Vec::new();
+            for name in ["hold", "attack", "release", "lookahead"] {
+                times.push(self.get_statics::<f64>(name)?.get_value());
+            }
+            if !(threshold >= 0.0 && sample_rate > 0.0) || times.iter().any(|t| t.is_nan() || *t < 0.0) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let lookahead = (times[3] * sample_rate).round() as usize;
+            self.set_state_value("delay_line", vec![0.0; lookahead])?;
+            self.set_state_value("position", 0usize)?;
+            self.set_state_value("hold_counter", 0usize)?;
+            self.set_state_value("gain", 0.0)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let threshold = self.get_statics::<f64>("threshold")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let hold = (self.get_statics::<f64>("hold")?.get_value() * sample_rate).round() as usize;
+            let attack = smoothing(self.get_statics::<f64>("attack")?.get_value(), sample_rate);
+            let release = smoothing(self.get_statics::<f64>("release")?.get_value(), sample_rate);
+            let mut delay_line = self.get_state_value::<Vec<f64>>("delay_line")?;
+            let mut position = self.get_state_value::<usize>("position")?;
+            let mut hold_counter = self.get_state_value::<usize>("hold_counter")?;
+            let mut gain = self.get_state_value::<f64>("gain")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut output_signal = Vec::<f64>::with_capacity(input_signal.len());
+            {
+                let _lock = self.lock.lock().unwrap();
+                // the detector sees x(n) while the output is x(n-lookahead), so the gate is already
+                // open when a transient leaves the delay line
+                for &x in input_signal.iter() {
+                    let target = if x.abs() >= threshold {
+                        hold_counter = hold;
+                        1.0
+                    } else if hold_counter > 0 {
+                        hold_counter -= 1;
+                        1.0
+                    } else {
+                        0.0
+                    };
+                    let coefficient = if target > gain { attack } else { release };
+                    gain = target + (gain - target) * coefficient;
+                    let delayed = if delay_line.is_empty() {
+                        x
+                    } else {
+                        let delayed = delay_line[position];
+                        delay_line[position] = x;
+                        position = (position + 1) % delay_line.len();
+                        delayed
+                    };
+                    output_signal.push(delayed * gain);
+                }
+            }
+            self.set_state_value("delay_line", delay_line)?;
+            self.set_state_value("position", position)?;
+            self.set_state_value("hold_counter", hold_counter)?;
+            self.set_state_value("gain", gain)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// One-pole smoothing coefficient for a time constant in seconds, 0 means an instant step.
+fn smoothing(time: f64, sample_rate: f64) -> f64 {
+    if time > 0.0 { (-1.0 / (time * sample_rate)).exp() } else { 0.0 }
+}
+
@@ -0,0 +1,66 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+
+// Sliding window kept both in arrival order and sorted, so any rank is read directly and
+// each update costs two binary searches plus a shift of the sorted buffer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrderStatistics {
+    capacity: usize,
+    window: VecDeque<f64>,
+    sorted: Vec<f64>,
+}
+impl OrderStatistics {
+    pub fn new(capacity: usize) -> Self {
+        OrderStatistics { capacity, window: VecDeque::with_capacity(capacity), sorted: Vec::with_capacity(capacity) }
+    }
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+    pub fn push(&mut self, value: f64) {
+        if self.window.len() >= self.capacity && let Some(oldest) = self.window.pop_front() {
+            let index = self.sorted.partition_point(|v| v.total_cmp(&oldest) == Ordering::Less);
+            self.sorted.remove(index);
+        }
+        let index = self.sorted.partition_point(|v| v.total_cmp(&value) != Ordering::Greater);
+        self.sorted.insert(index, value);
+        self.window.push_back(value);
+    }
+    // Linear interpolation between closest ranks, as numpy.percentile does by default.
+    pub fn percentile(&self, percentile: f64) -> Option<f64> {
+        if self.sorted.is_empty() {
+            return None;
+        }
+        let position = percentile.clamp(0.0, 100.0) / 100.0 * (self.sorted.len() - 1) as f64;
+        let lower = position.floor() as usize;
+        let upper = position.ceil() as usize;
+        let fraction = position - lower as f64;
+        Some(self.sorted[lower] + (self.sorted[upper] - self.sorted[lower]) * fraction)
+    }
+    pub fn median(&self) -> Option<f64> {
+        self.percentile(50.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_matches_sorted_window() {
+        let signal = [3.0, -1.0, 4.0, 1.0, -5.0, 9.0, 2.0, 6.0, 5.0, 3.0, 5.0, 8.0];
+        let mut statistics = OrderStatistics::new(4);
+        for (n, &x) in signal.iter().enumerate() {
+            statistics.push(x);
+            let mut window = signal[n.saturating_sub(3)..=n].to_vec();
+            window.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(statistics.percentile(0.0), Some(window[0]));
+            assert_eq!(statistics.percentile(100.0), Some(window[window.len() - 1]));
+            let mid = window.len() / 2;
+            let median = if window.len() % 2 == 1 { window[mid] } else { (window[mid - 1] + window[mid]) / 2.0 };
+            assert!((statistics.median().unwrap() - median).abs() < 1.0e-12);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::order_statistics::OrderStatistics;
+
+stream_block! {
+    pub struct PercentileFilter {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "order": usize = 0,
+            "percentile": f64 = 50.0,
+        },
+        state: { "window": OrderStatistics = OrderStatistics::default() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let order = self.get_statics::<usize>("order")?.get_value();
+            let percentile = self.get_statics::<f64>("percentile")?.get_value();
+            if order < 1 || !(0.0..=100.0).contains(&percentile) {
+                return Err(StreamingError::InvalidStatics);
+            }
+            self.set_state_value("window", OrderStatistics::new(order))?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let output_signal: Vec<f64>;
+            let percentile = self.get_statics::<f64>("percentile")?.get_value();
+            let mut window = self.get_state_value::<OrderStatistics>("window")?;
+            {
+                let input_signal = self.recv_input::<Vec<f64>>("input")?;
+                let _lock = self.lock.lock().unwrap();
+                output_signal = input_signal.iter().map(|&x| {
+                    window.push(x);
+                    window.percentile(percentile).unwrap_or(x)
+                }).collect();
+            }
+            self.set_state_value("window", window)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use utils::math::matrix::Matrix;
+use crate::biquad::{Biquad, QuantizedBiquad, cascade, cascade_response, fixed_cascade, quantize_sections};
+use crate::fixed_point::{QFormat, QuantizationReport};
+use crate::frequency_response::FrequencyResponse;
+use crate::coefficient_loader::load_coefficients;
+
+stream_block! {
+    pub struct Sos {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64>, "frequency_response": FrequencyResponse },
+        statics: {
+            // one [b0, b1, b2, a0, a1, a2] row per section, ignored when coefficient_file is set
+            "sos": Matrix<f64> = Matrix::<f64>::new(1, 6),
+            "coefficient_file": String = String::new(),
+            "fixed_point": bool = false,
+            "data_format": String = "Q0.15".to_string(),
+            "coefficient_format": String = "Q2.13".to_string(),
+            // non zero sends the response of the cascade once on frequency_response, the port then
+            // has to be connected
+            "response_points": usize = 0,
+            "sample_rate": f64 = 1.0,
+        },
+        state: {
+            "sections": Vec<Biquad> = Vec::<Biquad>::new(),
+            "memory": Vec<[f64; 2]> = Vec::<[f64; 2]>::new(),
+            "quantized_sections": Vec<QuantizedBiquad> = Vec::<QuantizedBiquad>::new(),
+            "fixed_memory": Vec<[i64; 4]> = Vec::<[i64; 4]>::new(),
+            "quantization_report": QuantizationReport = QuantizationReport::default(),
+            "frequency_response": FrequencyResponse = FrequencyResponse::default(),
+            "response_pending": bool = false,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let coefficient_file = self.get_statics::<String>("coefficient_file")?.get_value();
+            let sections = if coefficient_file.is_empty() {
+                let sos = self.get_statics::<Matrix<f64>>("sos")?.get_value();
+                sos.to_vec().iter().map(|row| Biquad::from_sos_row(row)).collect::<Option<Vec<Biquad>>>()
+            } else {
+                match load_coefficients(&coefficient_file) {
+                    Ok(set) => set.sections(),
+                    Err(error) => {
+                        eprintln!("Sos {}: {}", self.name, error);
+                        return Err(StreamingError::InvalidStatics);
+                    }
+                }
+            };
+            let Some(sections) = sections.filter(|sections| !sections.is_empty()) else {
+                return Err(StreamingError::InvalidStatics);
+            };
+            let mut effective_sections = sections.clone();
+            if self.get_statics::<bool>("fixed_point")?.get_value() {
+                let data_format = QFormat::parse(&self.get_statics::<String>("data_format")?.get_value());
+                let coefficient_format = QFormat::parse(&self.get_statics::<String>("coefficient_format")?.get_value());
+                let (Some(_), Some(coefficient_format)) = (data_format, coefficient_format) else {
+                    return Err(StreamingError::InvalidStatics);
+                };
+                let (quantized, report) = quantize_sections(&coefficient_format, &sections);
+                if report.saturated > 0 {
+                    eprintln!("Sos {}: {} coefficients saturated in {:?}", self.name, report.saturated, coefficient_format);
+                }
+                effective_sections = quantized.iter().map(|q| q.to_biquad(&coefficient_format)).collect();
+                self.set_state_value("quantized_sections", quantized)?;
+                self.set_state_value("quantization_report", report)?;
+            }
+            self.set_state_value("memory", vec![[0.0; 2]; sections.len()])?;
+            self.set_state_value("fixed_memory", vec![[0_i64; 4]; sections.len()])?;
+            self.set_state_value("sections", sections)?;
+            let response_points = self.get_statics::<usize>("response_points")?.get_value();
+            if response_points > 0 {
+                let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+                self.set_state_value("frequency_response", cascade_response(&effective_sections, response_points, sample_rate))?;
+            }
+            self.set_state_value("response_pending", response_points > 0)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            // data_format and coefficient_format only matter, and are only validated at init, in fixed point mode
+            let formats = if self.get_statics::<bool>("fixed_point")?.get_value() {
+                let data_format = QFormat::parse(&self.get_statics::<String>("data_format")?.get_value());
+                let coefficient_format = QFormat::parse(&self.get_statics::<String>("coefficient_format")?.get_value());
+                let (Some(data_format), Some(coefficient_format)) = (data_format, coefficient_format) else {
+                    return Err(StreamingError::InvalidStatics);
+                };
+                Some((data_format, coefficient_format))
+            } else {
+                None
+            };
+            if self.get_state_value::<bool>("response_pending")? {
+                let response = self.get_state_value::<FrequencyResponse>("frequency_response")?;
+                self.send_output::<FrequencyResponse>("frequency_response", response)?;
+                self.set_state_value("response_pending", false)?;
+            }
+            let sections = self.get_state_value::<Vec<Biquad>>("sections")?;
+            let quantized_sections = self.get_state_value::<Vec<QuantizedBiquad>>("quantized_sections")?;
+            let mut memory = self.get_state_value::<Vec<[f64; 2]>>("memory")?;
+            let mut fixed_memory = self.get_state_value::<Vec<[i64; 4]>>("fixed_memory")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let output_signal: Vec<f64>;
+            {
+                let _lock = self.lock.lock().unwrap();
+                output_signal = match formats {
+                    Some((data_format, coefficient_format)) => input_signal.iter()
+                        .map(|&x| fixed_cascade(&quantized_sections, &mut fixed_memory, x, &data_format, &coefficient_format))
+                        .collect(),
+                    None => input_signal.iter().map(|&x| cascade(&sections, &mut memory, x)).collect(),
+                };
+            }
+            self.set_state_value("memory", memory)?;
+            self.set_state_value("fixed_memory", fixed_memory)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+#[cfg(feature = "onnx")]
+use tract_onnx::prelude::*;
+
+stream_block! {
+    pub struct OnnxInference {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "model_path": String = String::new(),
+            "input_size": usize = 0,
+        },
+        fields: { model: Option<OnnxModel> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let model_path = self.get_statics::<String>("model_path")?.get_value();
+            let input_size = self.get_statics::<usize>("input_size")?.get_value();
+            if model_path.is_empty() || input_size == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            match OnnxModel::load(&model_path, input_size) {
+                Ok(model) => self.model = Some(model),
+                Err(error) => {
+                    eprintln!("OnnxInference {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let input_size = self.get_statics::<usize>("input_size")?.get_value();
+            let features = self.recv_input::<Vec<f64>>("input")?;
+            if features.len() != input_size {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let result;
+            {
+                let _lock = self.lock.lock().unwrap();
+                result = self.model.as_ref().unwrap().run(&features);
+            }
+            match result {
+                Ok(output) => self.send_output::<Vec<f64>>("output", output)?,
+                Err(error) => {
+                    eprintln!("OnnxInference {}: {}", self.name, error);
+                    self.stop()?;
+                    return Err(StreamingError::InvalidInput);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// Model taking a [1, input_size] f32 tensor, its first output is flattened into the output frame.
+#[cfg(feature = "onnx")]
+pub struct OnnxModel {
+    plan: TypedRunnableModel<TypedModel>,
+    input_size: usize,
+}
+#[cfg(feature = "onnx")]
+impl OnnxModel {
+    pub fn load(path: &str, input_size: usize) -> Result<OnnxModel, String> {
+        let plan = tract_onnx::onnx()
+            .model_for_path(path)
+            .and_then(|model| model.with_input_fact(0, f32::fact([1, input_size]).into()))
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(|e| e.to_string())?;
+        Ok(OnnxModel { plan, input_size })
+    }
+    pub fn run(&self, features: &[f64]) -> Result<Vec<f64>, String> {
+        let data: Vec<f32> = features.iter().map(|x| *x as f32).collect();
+        let input = Tensor::from_shape(&[1, self.input_size], &data).map_err(|e| e.to_string())?;
+        let outputs = self.plan.run(tvec!(input.into())).map_err(|e| e.to_string())?;
+        let view = outputs[0].to_array_view::<f32>().map_err(|e| e.to_string())?;
+        Ok(view.iter().map(|x| *x as f64).collect())
+    }
+}
+
+// Without the onnx feature the block still loads, init reports how to enable it.
+#[cfg(not(feature = "onnx"))]
+pub struct OnnxModel;
+#[cfg(not(feature = "onnx"))]
+impl OnnxModel {
+    pub fn load(_path: &str, _input_size: usize) -> Result<OnnxModel, String> {
+        Err("the inference crate was built without the onnx feature".to_string())
+    }
+    pub fn run(&self, _features: &[f64]) -> Result<Vec<f64>, String> {
+        Err("the inference crate was built without the onnx feature".to_string())
+    }
+}
+
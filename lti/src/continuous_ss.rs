@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use utils::math::matrix::Matrix;
+
+use crate::state_space::StateSpace;
+use crate::ode::IntegrationMethod;
+
+stream_block! {
+    pub struct ContinuousSs {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "A": Matrix<f64> = Matrix::<f64>::new(1,1),
+            "B": Matrix<f64> = Matrix::<f64>::new(1,1),
+            "C": Matrix<f64> = Matrix::<f64>::new(1,1),
+            "D": Matrix<f64> = Matrix::<f64>::new(1,1),
+            "x0": Matrix<f64> = Matrix::<f64>::new(1,1),
+            "sparse": bool = false,
+            "sample_time": f64 = 1.0,
+            "substeps": usize = 10,
+            "method": String = "rk4".to_string(),
+            "tolerance": f64 = 1.0e-6,
+        },
+        fields: {
+            model: StateSpace = StateSpace::new(Matrix::new(1,1), Matrix::new(1,1), Matrix::new(1,1), Matrix::new(1,1), Matrix::new(1,1)),
+            method: IntegrationMethod = IntegrationMethod::Rk4,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let A = self.get_statics::<Matrix<f64>>("A")?.get_value();
+            let B = self.get_statics::<Matrix<f64>>("B")?.get_value();
+            let C = self.get_statics::<Matrix<f64>>("C")?.get_value();
+            let D = self.get_statics::<Matrix<f64>>("D")?.get_value();
+            let x0 = self.get_statics::<Matrix<f64>>("x0")?.get_value();
+            if A.is_square() == false {
+                return Err(StreamingError::InvalidStatics)
+            }
+            if A.rows != B.rows {
+                return Err(StreamingError::InvalidStatics)
+            }
+            if A.cols != C.cols {
+                return Err(StreamingError::InvalidStatics)
+            }
+            if B.cols != D.cols {
+                return Err(StreamingError::InvalidStatics)
+            }
+            if C.rows != D.rows {
+                return Err(StreamingError::InvalidStatics)
+            }
+            if x0.rows != A.rows || x0.cols != 1 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sample_time = self.get_statics::<f64>("sample_time")?.get_value();
+            let substeps = self.get_statics::<usize>("substeps")?.get_value();
+            let tolerance = self.get_statics::<f64>("tolerance")?.get_value();
+            if sample_time <= 0.0 || substeps == 0 || tolerance <= 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.method = match IntegrationMethod::from_name(&self.get_statics::<String>("method")?.get_value()) {
+                Some(method) => method,
+                None => return Err(StreamingError::InvalidStatics),
+            };
+            let model = StateSpace::new(A, B, C, D, x0);
+            self.model = if self.get_statics::<bool>("sparse")?.get_value() { model.to_sparse() } else { model };
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let sample_time = self.get_statics::<f64>("sample_time")?.get_value();
+            let substeps = self.get_statics::<usize>("substeps")?.get_value();
+            let tolerance = self.get_statics::<f64>("tolerance")?.get_value();
+            let input = self.recv_input::<Vec<f64>>("input")?;
+            let u = Matrix::from_vec(input.into_iter().map(|v| vec![v]).collect());
+            if u.rows != self.model.get_input_size() || u.cols != 1 {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let y: Matrix<f64>;
+            {
+                let _guard = self.lock.lock().unwrap();
+                y = self.model.update_continuous(&u, sample_time, substeps, self.method, tolerance);
+            }
+            self.send_output::<Vec<f64>>("output", y.to_vec().into_iter().map(|v| v[0]).collect())?;
+            Ok(())
+        }
+    }
+}
@@ -1,6 +1,8 @@
 pub mod zpk;
 pub mod ss;
 pub mod tf;
+pub mod continuous_ss;
+pub mod ode;
 mod state_space;
 use std::ffi::c_char;
 use data_model::modules::{Version,ModuleStructFFI};
@@ -18,17 +20,18 @@ pub static MODULE: ModuleStructFFI  = ModuleStructFFI {
     provides: std::ptr::null(),
     provides_lengths: 0,
 };
+/// Creates the block `proc_block` named `block_name`, both passed by the host as UTF-8 bytes.
+///
+/// # Safety
+/// A non null `proc_block` and `block_name` must point to `proc_block_len` and `block_name_len`
+/// readable bytes that stay valid while the block exists.
 #[unsafe(no_mangle)]
-pub extern "C" fn get_processor_modules(proc_block: *const u8, 
+pub unsafe extern "C" fn get_processor_modules(proc_block: *const u8, 
     proc_block_len: usize, 
     block_name: *const u8, 
     block_name_len: usize) -> TraitObjectRepr {
-    let proc_block_str = unsafe {
-        std::str::from_utf8(std::slice::from_raw_parts(proc_block, proc_block_len)).unwrap()
-    };
-    let block_name_str = unsafe {
-        std::str::from_utf8(std::slice::from_raw_parts(block_name, block_name_len)).unwrap()
-    };
+    let proc_block_str = block_template::ffi_name!(proc_block, proc_block_len);
+    let block_name_str = block_template::ffi_name!(block_name, block_name_len);
     let proc: Box<dyn StreamProcessor>;
     match proc_block_str {
         "Zpk" => {
@@ -43,9 +46,23 @@ pub extern "C" fn get_processor_modules(proc_block: *const u8,
             proc = Box::new(tf::Tf::new(block_name_str));
             export_stream_processor(proc)
         }
+        "ContinuousSs" => {
+            proc = Box::new(continuous_ss::ContinuousSs::new(block_name_str));
+            export_stream_processor(proc)
+        }
         _ => {
             eprintln!("Processor block {} not found", proc_block_str);
             get_error_return(1)
         }
     }
-}
\ No newline at end of file
+}
+
+// Schema of every block get_processor_modules creates, keyed by block type, see stream_block!.
+pub fn block_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "Zpk": zpk::Zpk::schema(),
+        "Ss": ss::Ss::schema(),
+        "Tf": tf::Tf::schema(),
+        "ContinuousSs": continuous_ss::ContinuousSs::schema(),
+    })
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum IntegrationMethod {
+    Rk4,
+    Rk45,
+}
+impl IntegrationMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rk4" => Some(IntegrationMethod::Rk4),
+            "rk45" => Some(IntegrationMethod::Rk45),
+            _ => None,
+        }
+    }
+}
+
+fn axpy(x: &[f64], h: f64, k: &[&[f64]], c: &[f64]) -> Vec<f64> {
+    let mut ret = x.to_vec();
+    for (ki, ci) in k.iter().zip(c.iter()) {
+        if *ci != 0.0 {
+            for i in 0..ret.len() {
+                ret[i] += h * ci * ki[i];
+            }
+        }
+    }
+    ret
+}
+
+pub fn rk4_step<F>(f: &F, t: f64, x: &[f64], h: f64) -> Vec<f64>
+where F: Fn(f64, &[f64]) -> Vec<f64> {
+    let k1 = f(t, x);
+    let k2 = f(t + 0.5 * h, &axpy(x, h, &[&k1], &[0.5]));
+    let k3 = f(t + 0.5 * h, &axpy(x, h, &[&k2], &[0.5]));
+    let k4 = f(t + h, &axpy(x, h, &[&k3], &[1.0]));
+    axpy(x, h, &[&k1, &k2, &k3, &k4], &[1.0 / 6.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 6.0])
+}
+
+pub fn rk4<F>(f: &F, t0: f64, x0: &[f64], t1: f64, steps: usize) -> Vec<f64>
+where F: Fn(f64, &[f64]) -> Vec<f64> {
+    let steps = steps.max(1);
+    let h = (t1 - t0) / steps as f64;
+    let mut x = x0.to_vec();
+    for k in 0..steps {
+        x = rk4_step(f, t0 + k as f64 * h, &x, h);
+    }
+    x
+}
+
+// Dormand-Prince 5(4) embedded pair with step size control on the max-norm error.
+pub fn rk45<F>(f: &F, t0: f64, x0: &[f64], t1: f64, initial_step: f64, tolerance: f64) -> Vec<f64>
+where F: Fn(f64, &[f64]) -> Vec<f64> {
+    const C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+    const A2: [f64; 1] = [1.0 / 5.0];
+    const A3: [f64; 2] = [3.0 / 40.0, 9.0 / 40.0];
+    const A4: [f64; 3] = [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0];
+    const A5: [f64; 4] = [19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0];
+    const A6: [f64; 5] = [9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0];
+    const B5: [f64; 7] = [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0, 0.0];
+    const B4: [f64; 7] = [5179.0 / 57600.0, 0.0, 7571.0 / 16695.0, 393.0 / 640.0, -92097.0 / 339200.0, 187.0 / 2100.0, 1.0 / 40.0];
+    let span = t1 - t0;
+    if span <= 0.0 {
+        return x0.to_vec();
+    }
+    let min_step = span * 1.0e-12;
+    let mut h = if initial_step > 0.0 { initial_step.min(span) } else { span };
+    let mut t = t0;
+    let mut x = x0.to_vec();
+    while t < t1 {
+        if t + h > t1 {
+            h = t1 - t;
+        }
+        let k1 = f(t, &x);
+        let k2 = f(t + C[1] * h, &axpy(&x, h, &[&k1], &A2));
+        let k3 = f(t + C[2] * h, &axpy(&x, h, &[&k1, &k2], &A3));
+        let k4 = f(t + C[3] * h, &axpy(&x, h, &[&k1, &k2, &k3], &A4));
+        let k5 = f(t + C[4] * h, &axpy(&x, h, &[&k1, &k2, &k3, &k4], &A5));
+        let k6 = f(t + C[5] * h, &axpy(&x, h, &[&k1, &k2, &k3, &k4, &k5], &A6));
+        let x5 = axpy(&x, h, &[&k1, &k2, &k3, &k4, &k5, &k6], &B5[..6]);
+        let k7 = f(t + h, &x5);
+        let x4 = axpy(&x, h, &[&k1, &k2, &k3, &k4, &k5, &k6, &k7], &B4);
+        let mut error: f64 = 0.0;
+        for i in 0..x.len() {
+            let scale = tolerance * (1.0 + x[i].abs().max(x5[i].abs()));
+            error = error.max((x5[i] - x4[i]).abs() / scale);
+        }
+        if error <= 1.0 || h <= min_step {
+            t += h;
+            x = x5;
+        }
+        let factor = if error == 0.0 { 5.0 } else { (0.9 * error.powf(-0.2)).clamp(0.2, 5.0) };
+        h = (h * factor).max(min_step);
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_exponential_decay() {
+        let f = |_t: f64, x: &[f64]| vec![-x[0]];
+        let expected = (-1.0_f64).exp();
+        assert!((rk4(&f, 0.0, &[1.0], 1.0, 100)[0] - expected).abs() < 1.0e-9);
+        assert!((rk45(&f, 0.0, &[1.0], 1.0, 0.1, 1.0e-10)[0] - expected).abs() < 1.0e-8);
+    }
+}
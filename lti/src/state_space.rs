@@ -1,6 +1,8 @@
 use num_traits::zero;
 use serde::{Deserialize, Serialize};
 use utils::math::matrix::Matrix;
+use sparse::CsrMatrix;
+use crate::ode::{self, IntegrationMethod};
 
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,11 +12,41 @@ pub struct StateSpace {
     C: Matrix<f64>,
     D: Matrix<f64>,
     x: Matrix<f64>, // State vector
+    sparse: Option<SparseModel>,
+}
+
+// CSR copies of the model matrices, the updates use them instead of the dense products when set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SparseModel {
+    A: CsrMatrix,
+    B: CsrMatrix,
+    C: CsrMatrix,
+    D: CsrMatrix,
+}
+
+fn column(m: &Matrix<f64>) -> Vec<f64> {
+    m.to_vec().into_iter().map(|v| v[0]).collect()
+}
+fn from_column(v: Vec<f64>) -> Matrix<f64> {
+    Matrix::from_vec(v.into_iter().map(|v| vec![v]).collect())
+}
+fn add(a: Vec<f64>, b: Vec<f64>) -> Vec<f64> {
+    a.into_iter().zip(b).map(|(a, b)| a + b).collect()
 }
 
 impl StateSpace {
     pub fn new(A: Matrix<f64>, B: Matrix<f64>, C: Matrix<f64>, D: Matrix<f64>, x0: Matrix<f64>) -> Self {
-        StateSpace { A, B, C, D, x: x0 }
+        StateSpace { A, B, C, D, x: x0, sparse: None }
+    }
+    // Switches the updates to sparse products, for large models that are mostly zeros.
+    pub fn to_sparse(mut self) -> Self {
+        self.sparse = Some(SparseModel {
+            A: CsrMatrix::from_dense(&self.A.to_vec()),
+            B: CsrMatrix::from_dense(&self.B.to_vec()),
+            C: CsrMatrix::from_dense(&self.C.to_vec()),
+            D: CsrMatrix::from_dense(&self.D.to_vec()),
+        });
+        self
     }
     pub fn from_tf(num: Vec<f64>, den: Vec<f64>, x0: Vec<f64>) -> Self {
         let n = den.len() - 1;
@@ -36,7 +68,7 @@ impl StateSpace {
         for k in 1..num.len() {
             B.set(k - 1, 0, num[k] / den[0]).unwrap();
         }
-        StateSpace { A, B, C, D, x: Matrix::from_vec(x0.into_iter().map(|v| vec![v]).collect()) }
+        StateSpace { A, B, C, D, x: Matrix::from_vec(x0.into_iter().map(|v| vec![v]).collect()), sparse: None }
     }
     pub fn from_zpk(zeros: Vec<f64>, poles: Vec<f64>, gain: f64, x0: Vec<f64>) -> Self {
         let n = poles.len();
@@ -66,12 +98,44 @@ impl StateSpace {
         result
     }
     pub fn update(&mut self, u: &Matrix<f64>) -> Matrix<f64> {
+        if let Some(sparse) = &self.sparse {
+            let u = column(u);
+            let x = add(sparse.A.mul_vec(&column(&self.x)), sparse.B.mul_vec(&u));
+            let y = add(sparse.C.mul_vec(&x), sparse.D.mul_vec(&u));
+            self.x = from_column(x);
+            return from_column(y);
+        }
         // x(k+1) = A*x(k) + B*u(k)
         self.x = self.A.clone() * self.x.clone() + self.B.clone() * u.clone();
         // y(k) = C*x(k) + D*u(k)
         let y = self.C.clone() * self.x.clone() + self.D.clone() * u.clone();
         y
     }
+    pub fn update_continuous(&mut self, u: &Matrix<f64>, sample_time: f64, substeps: usize, method: IntegrationMethod, tolerance: f64) -> Matrix<f64> {
+        // dx/dt = A*x(t) + B*u(k), with u held constant over the sample interval
+        let Bu = match &self.sparse {
+            Some(sparse) => sparse.B.mul_vec(&column(u)),
+            None => column(&(self.B.clone() * u.clone())),
+        };
+        let derivative = |_t: f64, x: &[f64]| -> Vec<f64> {
+            let Ax = match &self.sparse {
+                Some(sparse) => sparse.A.mul_vec(x),
+                None => column(&(&self.A * &from_column(x.to_vec()))),
+            };
+            add(Ax, Bu.clone())
+        };
+        let x0: Vec<f64> = self.x.to_vec().into_iter().map(|v| v[0]).collect();
+        let x1 = match method {
+            IntegrationMethod::Rk4 => ode::rk4(&derivative, 0.0, &x0, sample_time, substeps),
+            IntegrationMethod::Rk45 => ode::rk45(&derivative, 0.0, &x0, sample_time, sample_time / substeps.max(1) as f64, tolerance),
+        };
+        self.x = from_column(x1);
+        // y(k) = C*x(k) + D*u(k)
+        match &self.sparse {
+            Some(sparse) => from_column(add(sparse.C.mul_vec(&column(&self.x)), sparse.D.mul_vec(&column(u)))),
+            None => self.C.clone() * self.x.clone() + self.D.clone() * u.clone(),
+        }
+    }
     pub fn get_input_size(&self) -> usize {
         self.B.cols
     }
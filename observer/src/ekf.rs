@@ -5,67 +5,113 @@ use std::sync::mpsc::SyncSender;
 use std::sync::{Arc, Mutex};
 use serde::Serialize;
 use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
 use data_model::streaming_data::{StreamingError, StreamingState};
 use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
 use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
 use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use utils::math::matrix::Matrix;
+use event_bus::EventKind;
+use serde_json::json;
+use crate::nonlinear_model::NonlinearModel;
 
-#[derive(StreamBlockMacro)]
-pub struct Ekf {
-    name:       &'static str,
-    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
-    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
-    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
-    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
-    state:      HashMap<&'static str, Box<dyn DataTrait>>,
-    lock:       Arc<Mutex<()>>,
-    proc_state: Arc<Mutex<StreamingState>>,
-}
-impl Ekf {
-    pub fn new(name: &'static str) -> Self {
-        let mut ret = Self {
-            name,
-            inputs: HashMap::new(),
-            outputs: HashMap::new(),
-            parameters: HashMap::new(),
-            statics: HashMap::new(),
-            state: HashMap::new(),
-            lock: Arc::new(Mutex::new(())),
-            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
-        };
-
-        ret
+stream_block! {
+    pub struct Ekf {
+        inputs: { "input": Vec<f64>, "control": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "model": String = String::new(),
+            "model_file": String = String::new(),
+            "dt": f64 = 1.0,
+            "Q": Matrix<f64> = Matrix::identity(1),
+            "R": Matrix<f64> = Matrix::identity(1),
+            "P0": Matrix<f64> = Matrix::identity(1),
+            "initial_state": Vec<f64> = vec![],
+            "use_control": bool = false,
+        },
+        state: {
+            "state": Vec<f64> = vec![],
+            "P": Matrix<f64> = Matrix::identity(1),
+        },
+        fields: { model: Option<NonlinearModel> = None },
     }
-}
-impl StreamProcessor for Ekf {
-    fn init(&mut self) -> Result<(), StreamingError> {
-        if self.check_state(StreamingState::Running) {
-            return Err(StreamingError::InvalidStateTransition)
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let initial_state = self.get_statics::<Vec<f64>>("initial_state")?.get_value();
+            let n = initial_state.len();
+            let Q = self.get_statics::<Matrix<f64>>("Q")?.get_value();
+            let R = self.get_statics::<Matrix<f64>>("R")?.get_value();
+            let P0 = self.get_statics::<Matrix<f64>>("P0")?.get_value();
+            let dt = self.get_statics::<f64>("dt")?.get_value();
+            if n == 0 || Q.rows != n || !Q.is_square() || P0.rows != n || !P0.is_square() || !R.is_square()
+                || dt.is_nan() || dt <= 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            // the model script comes inline or from model_file, see NonlinearModel for what it defines
+            let model_file = self.get_statics::<String>("model_file")?.get_value();
+            let source = if model_file.is_empty() {
+                self.get_statics::<String>("model")?.get_value()
+            } else {
+                match std::fs::read_to_string(&model_file) {
+                    Ok(source) => source,
+                    Err(error) => {
+                        eprintln!("Ekf {}: cannot read {}: {}", self.name, model_file, error);
+                        return Err(StreamingError::InvalidStatics)
+                    }
+                }
+            };
+            match NonlinearModel::new(&source, dt) {
+                Ok(model) => self.model = Some(model),
+                Err(error) => {
+                    eprintln!("Ekf {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state_value("state", initial_state)?;
+            self.set_state_value("P", P0)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
         }
-        if !self.is_initialized() {
-            return Err(StreamingError::InvalidStatics)
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let Q = self.get_statics::<Matrix<f64>>("Q")?.get_value().to_vec();
+            let R = self.get_statics::<Matrix<f64>>("R")?.get_value().to_vec();
+            let use_control = self.get_statics::<bool>("use_control")?.get_value();
+            let mut state = self.get_state_value::<Vec<f64>>("state")?;
+            let mut P = self.get_state_value::<Matrix<f64>>("P")?.to_vec();
+            let input = self.recv_input::<Vec<f64>>("input")?;
+            let control = if use_control {
+                self.recv_input::<Vec<f64>>("control")?
+            } else {
+                Vec::new()
+            };
+            if input.len() != R.len() {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let result = {
+                let _lock = self.lock.lock().unwrap();
+                let model = self.model.as_ref().ok_or(StreamingError::InvalidStateTransition)?;
+                model.step(&mut state, &mut P, &input, &control, &Q, &R)
+            };
+            if let Err(error) = result {
+                eprintln!("Ekf {}: {}", self.name, error);
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            if state.iter().any(|v| !v.is_finite()) {
+                event_bus::publish(self.name, EventKind::Divergence, json!({ "state": format!("{:?}", state) }));
+            }
+            self.set_state_value("state", state.clone())?;
+            self.set_state_value("P", Matrix::from_vec(P))?;
+            self.send_output("output", state)?;
+            Ok(())
         }
-        self.set_state(StreamingState::Initial);
-        Ok(())
     }
-    fn run(&mut self) -> Result<(), StreamingError> {
-        if self.check_state(StreamingState::Stopped) {
-            return Err(StreamingError::InvalidStateTransition);
-        }
-        if !self.is_initialized() {
-            return Err(StreamingError::InvalidStatics)
-        }
-        self.set_state(StreamingState::Running);
-        while !self.check_state(StreamingState::Stopped) {
-            self.process()?;
-        }
-        Ok(())
-    }
-    fn process(&mut self) -> Result<(), StreamingError> {
-        Ok(())
-    }
-    fn stop(&mut self) -> Result<(), StreamingError> {
-        self.set_state(StreamingState::Stopped);
-        Ok(())
-    }
-}
\ No newline at end of file
+}
+
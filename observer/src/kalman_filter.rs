@@ -5,133 +5,288 @@ use std::sync::mpsc::SyncSender;
 use std::sync::{Arc, Mutex};
 use serde::Serialize;
 use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
 use data_model::streaming_data::{StreamingError, StreamingState};
 use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
 use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
 use processor_engine::connectors::{ConnectorTrait, Input, Output};
 use utils::math::matrix::Matrix;
+use sparse::CsrMatrix;
+use event_bus::EventKind;
+use serde_json::json;
+use crate::{linalg, riccati};
 
 use std::time::SystemTime;
 
-#[derive(StreamBlockMacro)]
-pub struct KalmanFilter {
-    name:       &'static str,
-    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
-    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
-    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
-    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
-    state:      HashMap<&'static str, Box<dyn DataTrait>>,
-    lock:       Arc<Mutex<()>>,
-    proc_state: Arc<Mutex<StreamingState>>,
-}
-impl KalmanFilter {
-    pub fn new(name: &'static str) -> Self {
-        let mut ret = Self {
-            name,
-            inputs: HashMap::new(),
-            outputs: HashMap::new(),
-            parameters: HashMap::new(),
-            statics: HashMap::new(),
-            state: HashMap::new(),
-            lock: Arc::new(Mutex::new(())),
-            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
-        };
-        let _ = ret.new_input::<Vec<f64>>("input");
-        let _ = ret.new_output::<Vec<f64>>("output");
-        let _ = ret.new_statics::<Matrix<f64>>("A", Matrix::identity(1), None);
-        let _ = ret.new_statics::<Matrix<f64>>("B", Matrix::identity(1), None);
-        let _ = ret.new_statics::<Matrix<f64>>("H", Matrix::identity(1), None);
-        let _ = ret.new_statics::<Matrix<f64>>("Q", Matrix::identity(1), None);
-        let _ = ret.new_statics::<Matrix<f64>>("R", Matrix::identity(1), None);
-        let _ = ret.new_statics::<Matrix<f64>>("P0", Matrix::identity(1), None);
-        let _ = ret.new_statics::<Vec<f64>>("initial_state", vec![], None);
-        let _ = ret.new_state::<Vec<f64>>("state", vec![]);
-        let _ = ret.new_state::<Matrix<f64>>("P", Matrix::identity(1));
-        ret
-    }
-}
-impl StreamProcessor for KalmanFilter {
-    fn init(&mut self) -> Result<(), StreamingError> {
-        if self.check_state(StreamingState::Running) {
-            return Err(StreamingError::InvalidStateTransition)
-        }
-        if !self.is_initialized() {
-            return Err(StreamingError::InvalidStatics)
-        }
-        let A = self.get_statics::<Matrix<f64>>("A")?.get_value();
-        if !A.is_square() {
-            return Err(StreamingError::InvalidStatics)
-        }
-        let B = self.get_statics::<Matrix<f64>>("B")?.get_value();
-        if B.rows != A.rows {
-            return Err(StreamingError::InvalidStatics)
-        }
-        let H = self.get_statics::<Matrix<f64>>("H")?.get_value();
-        if H.cols != A.rows {
-            return Err(StreamingError::InvalidStatics)
-        }
-        let Q = self.get_statics::<Matrix<f64>>("Q")?.get_value();
-        if Q.rows != A.rows || !Q.is_square() {
-            return Err(StreamingError::InvalidStatics)
-        }
-        let R = self.get_statics::<Matrix<f64>>("R")?.get_value();
-        if R.rows != H.rows || !R.is_square() {
-            return Err(StreamingError::InvalidStatics)
-        }
-        let P0 = self.get_statics::<Matrix<f64>>("P0")?.get_value();
-        if P0.rows != A.rows || !P0.is_square() {
-            return Err(StreamingError::InvalidStatics)
-        }
-        let initial_state = self.get_statics::<Vec<f64>>("initial_state")?.get_value();
-        if initial_state.len() != A.rows {
-            return Err(StreamingError::InvalidStatics)
-        }
-        let _ = self.set_state_value("state", initial_state.clone());
-        let _ = self.set_state_value("P", P0.clone());
-        self.set_state(StreamingState::Initial);
-        Ok(())
+stream_block! {
+    pub struct KalmanFilter {
+        inputs: {
+            "input": Vec<f64>,
+            "control": Vec<f64>,
+            "A": Matrix<f64>,
+            "H": Matrix<f64>,
+            "schedule": f64,
+        },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "A": Matrix<f64> = Matrix::identity(1),
+            "B": Matrix<f64> = Matrix::identity(1),
+            "H": Matrix<f64> = Matrix::identity(1),
+            "Q": Matrix<f64> = Matrix::identity(1),
+            "R": Matrix<f64> = Matrix::identity(1),
+            "P0": Matrix<f64> = Matrix::identity(1),
+            "initial_state": Vec<f64> = vec![],
+            "steady_state": bool = false,
+            "use_control": bool = false,
+            "sparse": bool = false,
+            "use_A_input": bool = false,
+            "use_H_input": bool = false,
+            "use_schedule": bool = false,
+            "A_schedule": Matrix<f64> = Matrix::new(1, 1),
+            "H_schedule": Matrix<f64> = Matrix::new(1, 1),
+        },
+        parameters: {
+            "Q": Vec<Vec<f64>> = Vec::new(),
+            "R": Vec<Vec<f64>> = Vec::new(),
+        },
+        state: {
+            "state": Vec<f64> = vec![],
+            "P": Matrix<f64> = Matrix::identity(1),
+            "K": Matrix<f64> = Matrix::identity(1),
+            "Q": Matrix<f64> = Matrix::identity(1),
+            "R": Matrix<f64> = Matrix::identity(1),
+            "rejected_Q": Vec<Vec<f64>> = Vec::new(),
+            "rejected_R": Vec<Vec<f64>> = Vec::new(),
+        },
+        fields: { sparse: Option<SparseModel> = None },
     }
-    fn run(&mut self) -> Result<(), StreamingError> {
-        if self.check_state(StreamingState::Stopped) {
-            return Err(StreamingError::InvalidStateTransition);
-        }
-        if !self.is_initialized() {
-            return Err(StreamingError::InvalidStatics)
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let A = self.get_statics::<Matrix<f64>>("A")?.get_value();
+            if !A.is_square() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let B = self.get_statics::<Matrix<f64>>("B")?.get_value();
+            if B.rows != A.rows {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let H = self.get_statics::<Matrix<f64>>("H")?.get_value();
+            if H.cols != A.rows {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let Q = self.get_statics::<Matrix<f64>>("Q")?.get_value();
+            if Q.rows != A.rows || !Q.is_square() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let R = self.get_statics::<Matrix<f64>>("R")?.get_value();
+            if R.rows != H.rows || !R.is_square() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let P0 = self.get_statics::<Matrix<f64>>("P0")?.get_value();
+            if P0.rows != A.rows || !P0.is_square() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let initial_state = self.get_statics::<Vec<f64>>("initial_state")?.get_value();
+            if initial_state.len() != A.rows {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let steady_state = self.get_statics::<bool>("steady_state")?.get_value();
+            // the A and H inputs replace the statics at every step, the schedule input moves them
+            // along A_schedule and H_schedule. The steady state gain only holds for a fixed model.
+            let use_A_input = self.get_statics::<bool>("use_A_input")?.get_value();
+            let use_H_input = self.get_statics::<bool>("use_H_input")?.get_value();
+            let use_schedule = self.get_statics::<bool>("use_schedule")?.get_value();
+            if steady_state && (use_A_input || use_H_input || use_schedule) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            if use_schedule {
+                let A_schedule = self.get_statics::<Matrix<f64>>("A_schedule")?.get_value();
+                let H_schedule = self.get_statics::<Matrix<f64>>("H_schedule")?.get_value();
+                if A_schedule.rows != A.rows || A_schedule.cols != A.cols || H_schedule.rows != H.rows || H_schedule.cols != H.cols {
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            let sparse = self.get_statics::<bool>("sparse")?.get_value();
+            self.sparse = sparse.then(|| SparseModel::new(&A, &H));
+            self.set_state_value("state", initial_state.clone())?;
+            self.set_state_value("Q", Q.clone())?;
+            self.set_state_value("R", R.clone())?;
+            if steady_state {
+                let (P_ss, K_ss) = match riccati::steady_state_gain(&A.to_vec(), &H.to_vec(), &Q.to_vec(), &R.to_vec()) {
+                    Some(solution) => solution,
+                    None => return Err(StreamingError::InvalidStatics),
+                };
+                self.set_state_value("P", Matrix::from_vec(P_ss))?;
+                self.set_state_value("K", Matrix::from_vec(K_ss))?;
+            } else {
+                self.set_state_value("P", P0.clone())?;
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
         }
-        self.set_state(StreamingState::Running);
-        while !self.check_state(StreamingState::Stopped) {
-            self.process()?;
-        }
-        Ok(())
-    }
-    fn process(&mut self) -> Result<(), StreamingError> {
-        let A = self.get_statics::<Matrix<f64>>("A")?.get_value();
-        let B = self.get_statics::<Matrix<f64>>("B")?.get_value();
-        let H = self.get_statics::<Matrix<f64>>("H")?.get_value();
-        let Q = self.get_statics::<Matrix<f64>>("Q")?.get_value();
-        let R = self.get_statics::<Matrix<f64>>("R")?.get_value();
-        let mut P = self.get_state_value::<Matrix<f64>>("P")?;
-        let mut state = self.get_state_value::<Vec<f64>>("state")?;
-        let input = self.recv_input::<Vec<f64>>("input")?;
-        {
-            let _lock = self.lock.lock().unwrap();
-            let u = Matrix::from_vec(vec![input.clone()]).transpose();
-            let x_prior = &A * &Matrix::from_vec(vec![state.clone()]) + &B * &u;
-            let P_prior = &A * &P * A.transpose() + Q;
-            let y = &Matrix::from_vec(vec![input.clone()]).transpose() - &(&H * &x_prior);
-            let S = &H * &P_prior * H.transpose() + R;
-            let K = &P_prior * &H.transpose() * S.inverse().unwrap();
-            let x_post = &x_prior + &(&K * &y);
-            P = (Matrix::identity(K.rows) - &K * &H) * P_prior;
-            state = x_post.to_vec()[0].clone();
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let mut A = self.get_statics::<Matrix<f64>>("A")?.get_value();
+            let B = self.get_statics::<Matrix<f64>>("B")?.get_value();
+            let mut H = self.get_statics::<Matrix<f64>>("H")?.get_value();
+            let steady_state = self.get_statics::<bool>("steady_state")?.get_value();
+            let mut Q = self.get_state_value::<Matrix<f64>>("Q")?;
+            let mut R = self.get_state_value::<Matrix<f64>>("R")?;
+            let mut K_ss = self.get_state_value::<Matrix<f64>>("K")?;
+            let mut P = self.get_state_value::<Matrix<f64>>("P")?;
+            // the Q and R parameters retune the noise covariances while running, an empty one keeps
+            // the current matrix. A steady state filter solves the Riccati equation again.
+            let Q_update = self.get_parameter::<Vec<Vec<f64>>>("Q")?.get_value();
+            let R_update = self.get_parameter::<Vec<Vec<f64>>>("R")?.get_value();
+            let Q_update = if Q_update.is_empty() { Q.to_vec() } else { Q_update };
+            let R_update = if R_update.is_empty() { R.to_vec() } else { R_update };
+            // a rejected pair is reported once and not solved again until the parameters change
+            let rejected = Q_update == self.get_state_value::<Vec<Vec<f64>>>("rejected_Q")?
+                && R_update == self.get_state_value::<Vec<Vec<f64>>>("rejected_R")?;
+            if (Q_update != Q.to_vec() || R_update != R.to_vec()) && !rejected {
+                let fits = |m: &Vec<Vec<f64>>, size: usize| m.len() == size && m.iter().all(|row| row.len() == size);
+                let solution = if !fits(&Q_update, Q.rows) || !fits(&R_update, R.rows) {
+                    None
+                } else if steady_state {
+                    riccati::steady_state_gain(&A.to_vec(), &H.to_vec(), &Q_update, &R_update)
+                } else {
+                    Some((P.to_vec(), K_ss.to_vec()))
+                };
+                let (rejected_Q, rejected_R) = match solution {
+                    Some((P_next, K_next)) => {
+                        let _lock = self.lock.lock().unwrap();
+                        Q = Matrix::from_vec(Q_update);
+                        R = Matrix::from_vec(R_update);
+                        P = Matrix::from_vec(P_next);
+                        K_ss = Matrix::from_vec(K_next);
+                        (Vec::new(), Vec::new())
+                    }
+                    None => {
+                        eprintln!("KalmanFilter {}: ignoring Q {:?} / R {:?}", self.name, Q_update, R_update);
+                        (Q_update, R_update)
+                    }
+                };
+                self.set_state_value("rejected_Q", rejected_Q)?;
+                self.set_state_value("rejected_R", rejected_R)?;
+                self.set_state_value("Q", Q.clone())?;
+                self.set_state_value("R", R.clone())?;
+                self.set_state_value("P", P.clone())?;
+                self.set_state_value("K", K_ss.clone())?;
+            }
+            let mut state = self.get_state_value::<Vec<f64>>("state")?;
+            let use_control = self.get_statics::<bool>("use_control")?.get_value();
+            let input = self.recv_input::<Vec<f64>>("input")?;
+            // without a control connection the model is driven by u = 0
+            let control = if use_control {
+                self.recv_input::<Vec<f64>>("control")?
+            } else {
+                vec![0.0; B.cols]
+            };
+            if control.len() != B.cols {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let use_A_input = self.get_statics::<bool>("use_A_input")?.get_value();
+            let use_H_input = self.get_statics::<bool>("use_H_input")?.get_value();
+            let use_schedule = self.get_statics::<bool>("use_schedule")?.get_value();
+            let (A_shape, H_shape) = ((A.rows, A.cols), (H.rows, H.cols));
+            if use_A_input {
+                A = self.recv_input::<Matrix<f64>>("A")?;
+            }
+            if use_H_input {
+                H = self.recv_input::<Matrix<f64>>("H")?;
+            }
+            if use_schedule {
+                let schedule = self.recv_input::<f64>("schedule")?;
+                A = scheduled(&A, &self.get_statics::<Matrix<f64>>("A_schedule")?.get_value(), schedule);
+                H = scheduled(&H, &self.get_statics::<Matrix<f64>>("H_schedule")?.get_value(), schedule);
+            }
+            if (A.rows, A.cols) != A_shape || (H.rows, H.cols) != H_shape || input.len() != H.rows {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            if self.sparse.is_some() && (use_A_input || use_H_input || use_schedule) {
+                self.sparse = Some(SparseModel::new(&A, &H));
+            }
+            let updated = {
+                let _lock = self.lock.lock().unwrap();
+                let x = Matrix::from_vec(vec![state.clone()]).transpose();
+                let u = Matrix::from_vec(vec![control]).transpose();
+                let column = |v: Vec<f64>| Matrix::from_vec(vec![v]).transpose();
+                let x_prior = match &self.sparse {
+                    Some(sparse) => &column(sparse.A.mul_vec(&state)) + &(&B * &u),
+                    None => &A * &x + &B * &u,
+                };
+                let Hx = match &self.sparse {
+                    Some(sparse) => column(sparse.H.mul_vec(&x_prior.transpose().to_vec()[0])),
+                    None => &H * &x_prior,
+                };
+                let y = &column(input.clone()) - &Hx;
+                // None when the innovation covariance S cannot be inverted
+                let x_post = if steady_state {
+                    Some(&x_prior + &(&K_ss * &y))
+                } else if let Some(sparse) = &self.sparse {
+                    // (I - K H) P_prior is computed as P_prior - K (H P_prior)
+                    let P_prior = Matrix::from_vec(CsrMatrix::dense_mul(&sparse.A.mul_dense(&P.to_vec()), &sparse.At)) + Q.clone();
+                    let HP = Matrix::from_vec(sparse.H.mul_dense(&P_prior.to_vec()));
+                    let S = Matrix::from_vec(CsrMatrix::dense_mul(&HP.to_vec(), &sparse.Ht)) + R.clone();
+                    linalg::inverse(&S.to_vec()).map(|S_inv| {
+                        let K = Matrix::from_vec(CsrMatrix::dense_mul(&P_prior.to_vec(), &sparse.Ht)) * Matrix::from_vec(S_inv);
+                        P = &P_prior - &(&K * &HP);
+                        &x_prior + &(&K * &y)
+                    })
+                } else {
+                    let P_prior = &A * &P * A.transpose() + Q.clone();
+                    let S = &H * &P_prior * H.transpose() + R.clone();
+                    linalg::inverse(&S.to_vec()).map(|S_inv| {
+                        let K = &P_prior * &H.transpose() * Matrix::from_vec(S_inv);
+                        P = (Matrix::identity(K.rows) - &K * &H) * P_prior;
+                        &x_prior + &(&K * &y)
+                    })
+                };
+                x_post.map(|x_post| x_post.transpose().to_vec()[0].clone())
+            };
+            let Some(updated) = updated else {
+                event_bus::publish(self.name, EventKind::Divergence, json!({ "state": format!("{:?}", state), "innovation_covariance": "singular" }));
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            };
+            state = updated;
+            if state.iter().any(|v| !v.is_finite()) {
+                event_bus::publish(self.name, EventKind::Divergence, json!({ "state": format!("{:?}", state) }));
+            }
+            self.set_state_value("state", state.clone())?;
+            if !steady_state {
+                self.set_state_value("P", P)?;
+            }
+            self.send_output("output", state)?;
+            Ok(())
         }
-        let _ = self.set_state_value("state", state.clone());
-        let _ = self.set_state_value("P", P);
-        self.send_output("output", state)?;
-        Ok(())
     }
-    fn stop(&mut self) -> Result<(), StreamingError> {
-        self.set_state(StreamingState::Stopped);
-        Ok(())
+}
+// CSR copies of A and H with their transposes, used for the predict and update products of a
+// large model that is mostly zeros.
+struct SparseModel {
+    A:  CsrMatrix,
+    At: CsrMatrix,
+    H:  CsrMatrix,
+    Ht: CsrMatrix,
+}
+impl SparseModel {
+    fn new(A: &Matrix<f64>, H: &Matrix<f64>) -> Self {
+        let (A, H) = (CsrMatrix::from_dense(&A.to_vec()), CsrMatrix::from_dense(&H.to_vec()));
+        SparseModel { At: A.transpose(), Ht: H.transpose(), A, H }
     }
-}
\ No newline at end of file
+}
+
+// M + s * M1, the model matrix at the scheduling variable s.
+fn scheduled(M: &Matrix<f64>, M1: &Matrix<f64>, s: f64) -> Matrix<f64> {
+    Matrix::from_vec(M.to_vec().into_iter().zip(M1.to_vec())
+        .map(|(row, row1)| row.into_iter().zip(row1).map(|(m, m1)| m + s * m1).collect())
+        .collect())
+}
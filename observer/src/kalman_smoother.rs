@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct KalmanSmoother {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "process_noise": f64 = 1.0e-3,
+            "measurement_noise": f64 = 1.0,
+        },
+        state: {
+            "state": Vec<f64> = vec![0.0; 2],
+            "P": Vec<f64> = vec![0.0; 4],
+            "init": bool = false,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let process_noise = self.get_statics::<f64>("process_noise")?.get_value();
+            let measurement_noise = self.get_statics::<f64>("measurement_noise")?.get_value();
+            if !(process_noise >= 0.0 && measurement_noise > 0.0) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("state", vec![0.0; 2])?;
+            self.set_state_value("P", vec![0.0; 4])?;
+            self.set_state_value("init", false)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let q = self.get_statics::<f64>("process_noise")?.get_value();
+            let r = self.get_statics::<f64>("measurement_noise")?.get_value();
+            let mut state = self.get_state_value::<Vec<f64>>("state")?;
+            let mut covariance = self.get_state_value::<Vec<f64>>("P")?;
+            let mut init = self.get_state_value::<bool>("init")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut output_signal = Vec::<f64>::with_capacity(input_signal.len());
+            {
+                let _lock = self.lock.lock().unwrap();
+                for &z in input_signal.iter() {
+                    if init {
+                        constant_velocity_step(&mut state, &mut covariance, q, r, z);
+                    } else {
+                        // start on the first sample with an unknown velocity
+                        state = vec![z, 0.0];
+                        covariance = vec![r, 0.0, 0.0, r];
+                        init = true;
+                    }
+                    output_signal.push(state[0]);
+                }
+            }
+            self.set_state_value("state", state)?;
+            self.set_state_value("P", covariance)?;
+            self.set_state_value("init", init)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// One predict/update cycle of the constant-velocity model x = [position, velocity] with unit
+// sample time, white acceleration noise of variance q and measurement noise of variance r.
+// The covariance is stored row-major.
+pub fn constant_velocity_step(state: &mut [f64], covariance: &mut [f64], q: f64, r: f64, z: f64) {
+    let position = state[0] + state[1];
+    let velocity = state[1];
+    let p00 = covariance[0] + covariance[1] + covariance[2] + covariance[3] + q / 4.0;
+    let p01 = covariance[1] + covariance[3] + q / 2.0;
+    let p10 = covariance[2] + covariance[3] + q / 2.0;
+    let p11 = covariance[3] + q;
+    let s = p00 + r;
+    let k0 = p00 / s;
+    let k1 = p10 / s;
+    let innovation = z - position;
+    state[0] = position + k0 * innovation;
+    state[1] = velocity + k1 * innovation;
+    covariance[0] = (1.0 - k0) * p00;
+    covariance[1] = (1.0 - k0) * p01;
+    covariance[2] = p10 - k1 * p00;
+    covariance[3] = p11 - k1 * p01;
+}
+
@@ -1,7 +1,17 @@
 pub mod alpha_beta_gamma;
 pub mod kalman_filter;
+pub mod kalman_smoother;
 pub mod ekf;
 pub mod ukf;
+pub mod particle_filter;
+pub mod rts_smoother;
+pub mod fixed_lag_smoother;
+mod linalg;
+mod riccati;
+mod nonlinear_model;
+mod unscented;
+mod particles;
+mod rts;
 use std::ffi::c_char;
 use data_model::modules::{Version,ModuleStructFFI};
 use processor_engine::stream_processor::StreamProcessor;
@@ -18,17 +28,18 @@ pub static MODULE: ModuleStructFFI  = ModuleStructFFI {
     provides: std::ptr::null(),
     provides_lengths: 0,
 };
+/// Creates the block `proc_block` named `block_name`, both passed by the host as UTF-8 bytes.
+///
+/// # Safety
+/// A non null `proc_block` and `block_name` must point to `proc_block_len` and `block_name_len`
+/// readable bytes that stay valid while the block exists.
 #[unsafe(no_mangle)]
-pub extern "C" fn get_processor_modules(proc_block: *const u8, 
+pub unsafe extern "C" fn get_processor_modules(proc_block: *const u8, 
     proc_block_len: usize, 
     block_name: *const u8, 
     block_name_len: usize) -> TraitObjectRepr {
-    let proc_block_str = unsafe {
-        std::str::from_utf8(std::slice::from_raw_parts(proc_block, proc_block_len)).unwrap()
-    };
-    let block_name_str = unsafe {
-        std::str::from_utf8(std::slice::from_raw_parts(block_name, block_name_len)).unwrap()
-    };
+    let proc_block_str = block_template::ffi_name!(proc_block, proc_block_len);
+    let block_name_str = block_template::ffi_name!(block_name, block_name_len);
     let proc: Box<dyn StreamProcessor>;
     match proc_block_str {
         "AlphaBetaGamma" => {
@@ -39,6 +50,10 @@ pub extern "C" fn get_processor_modules(proc_block: *const u8,
             proc = Box::new(kalman_filter::KalmanFilter::new(block_name_str));
             export_stream_processor(proc)
         }
+        "KalmanSmoother" => {
+            proc = Box::new(kalman_smoother::KalmanSmoother::new(block_name_str));
+            export_stream_processor(proc)
+        }
         "Ekf" => {
             proc = Box::new(ekf::Ekf::new(block_name_str));
             export_stream_processor(proc)
@@ -47,9 +62,35 @@ pub extern "C" fn get_processor_modules(proc_block: *const u8,
             proc = Box::new(ukf::Ukf::new(block_name_str));
             export_stream_processor(proc)
         }
+        "ParticleFilter" => {
+            proc = Box::new(particle_filter::ParticleFilter::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "RtsSmoother" => {
+            proc = Box::new(rts_smoother::RtsSmoother::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "FixedLagSmoother" => {
+            proc = Box::new(fixed_lag_smoother::FixedLagSmoother::new(block_name_str));
+            export_stream_processor(proc)
+        }
         _ => {
             eprintln!("Processor block {} not found", proc_block_str);
             get_error_return(1)
         }
     }
-}
\ No newline at end of file
+}
+
+// Schema of every block get_processor_modules creates, keyed by block type, see stream_block!.
+pub fn block_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "AlphaBetaGamma": alpha_beta_gamma::AlphaBetaGamma::schema(),
+        "KalmanFilter": kalman_filter::KalmanFilter::schema(),
+        "KalmanSmoother": kalman_smoother::KalmanSmoother::schema(),
+        "Ekf": ekf::Ekf::schema(),
+        "Ukf": ukf::Ukf::schema(),
+        "ParticleFilter": particle_filter::ParticleFilter::schema(),
+        "RtsSmoother": rts_smoother::RtsSmoother::schema(),
+        "FixedLagSmoother": fixed_lag_smoother::FixedLagSmoother::schema(),
+    })
+}
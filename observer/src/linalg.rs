@@ -0,0 +1,83 @@
+pub fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect()
+}
+pub fn transpose(a: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    if a.is_empty() {
+        return Vec::new();
+    }
+    let mut ret = vec![vec![0.0; a.len()]; a[0].len()];
+    for i in 0..a.len() {
+        for j in 0..a[0].len() {
+            ret[j][i] = a[i][j];
+        }
+    }
+    ret
+}
+pub fn mul(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let cols = if b.is_empty() { 0 } else { b[0].len() };
+    let mut ret = vec![vec![0.0; cols]; a.len()];
+    for i in 0..a.len() {
+        for k in 0..b.len() {
+            let a_ik = a[i][k];
+            for j in 0..cols {
+                ret[i][j] += a_ik * b[k][j];
+            }
+        }
+    }
+    ret
+}
+pub fn add(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    a.iter().zip(b.iter())
+        .map(|(ra, rb)| ra.iter().zip(rb.iter()).map(|(x, y)| x + y).collect())
+        .collect()
+}
+pub fn sub(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    a.iter().zip(b.iter())
+        .map(|(ra, rb)| ra.iter().zip(rb.iter()).map(|(x, y)| x - y).collect())
+        .collect()
+}
+pub fn max_abs_diff(a: &[Vec<f64>], b: &[Vec<f64>]) -> f64 {
+    let mut ret: f64 = 0.0;
+    for (ra, rb) in a.iter().zip(b.iter()) {
+        for (x, y) in ra.iter().zip(rb.iter()) {
+            ret = ret.max((x - y).abs());
+        }
+    }
+    ret
+}
+// Gauss-Jordan elimination with partial pivoting, None if the matrix is singular.
+pub fn inverse(a: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let mut m = a.to_vec();
+    let mut inv = identity(n);
+    for col in 0..n {
+        let mut pivot = col;
+        for row in col + 1..n {
+            if m[row][col].abs() > m[pivot][col].abs() {
+                pivot = row;
+            }
+        }
+        if m[pivot][col].abs() < 1.0e-12 {
+            return None;
+        }
+        m.swap(col, pivot);
+        inv.swap(col, pivot);
+        let p = m[col][col];
+        for j in 0..n {
+            m[col][j] /= p;
+            inv[col][j] /= p;
+        }
+        for row in 0..n {
+            if row != col {
+                let factor = m[row][col];
+                if factor != 0.0 {
+                    for j in 0..n {
+                        m[row][j] -= factor * m[col][j];
+                        inv[row][j] -= factor * inv[col][j];
+                    }
+                }
+            }
+        }
+    }
+    Some(inv)
+}
@@ -0,0 +1,132 @@
+use rhai::{Array, Dynamic, Engine, FuncArgs, AST};
+use crate::linalg::{add, identity, inverse, mul, sub, transpose};
+
+// Nonlinear model of the Ekf and Ukf blocks, a Rhai script defining
+//   fn transition(x, u, dt)  the next state, n values
+//   fn measurement(x)        the predicted measurement, m values
+// and optionally the Jacobians transition_jacobian(x, u, dt) (n x n) and measurement_jacobian(x)
+// (m x n) as arrays of rows, used by the Ekf only. Jacobians the script leaves out are taken by
+// central differences.
+pub struct NonlinearModel {
+    engine: Engine,
+    ast: AST,
+    dt: f64,
+    transition_jacobian: bool,
+    measurement_jacobian: bool,
+}
+
+fn to_array(values: &[f64]) -> Dynamic {
+    Dynamic::from_array(values.iter().map(|x| Dynamic::from_float(*x)).collect())
+}
+
+fn to_vector(value: Dynamic) -> Option<Vec<f64>> {
+    value.try_cast::<Array>()?.into_iter().map(|v| v.as_float().ok().or_else(|| v.as_int().ok().map(|i| i as f64))).collect()
+}
+
+fn to_matrix(value: Dynamic) -> Option<Vec<Vec<f64>>> {
+    value.try_cast::<Array>()?.into_iter().map(to_vector).collect()
+}
+
+// Column k is (g(x + h e_k) - g(x - h e_k)) / 2h, the step scales with |x_k|.
+fn numerical_jacobian(g: impl Fn(&[f64]) -> Result<Vec<f64>, String>, x: &[f64]) -> Result<Vec<Vec<f64>>, String> {
+    let mut columns = Vec::with_capacity(x.len());
+    for k in 0..x.len() {
+        let h = 1.0e-6 * x[k].abs().max(1.0);
+        let mut forward = x.to_vec();
+        let mut backward = x.to_vec();
+        forward[k] += h;
+        backward[k] -= h;
+        let (upper, lower) = (g(&forward)?, g(&backward)?);
+        columns.push(upper.iter().zip(lower.iter()).map(|(a, b)| (a - b) / (2.0 * h)).collect());
+    }
+    Ok(transpose(&columns))
+}
+
+impl NonlinearModel {
+    pub fn new(source: &str, dt: f64) -> Result<NonlinearModel, String> {
+        let engine = Engine::new();
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        let defines = |name: &str, arity: usize| ast.iter_functions().any(|f| f.name == name && f.params.len() == arity);
+        if !defines("transition", 3) || !defines("measurement", 1) {
+            return Err("the model must define transition(x, u, dt) and measurement(x)".to_string());
+        }
+        let transition_jacobian = defines("transition_jacobian", 3);
+        let measurement_jacobian = defines("measurement_jacobian", 1);
+        Ok(NonlinearModel { engine, ast, dt, transition_jacobian, measurement_jacobian })
+    }
+    fn call(&self, name: &str, arguments: impl FuncArgs) -> Result<Dynamic, String> {
+        self.engine.call_fn::<Dynamic>(&mut rhai::Scope::new(), &self.ast, name, arguments).map_err(|e| format!("{}: {}", name, e))
+    }
+    pub fn transition(&self, x: &[f64], u: &[f64]) -> Result<Vec<f64>, String> {
+        to_vector(self.call("transition", (to_array(x), to_array(u), self.dt))?).ok_or("transition must return an array of numbers".to_string())
+    }
+    pub fn measurement(&self, x: &[f64]) -> Result<Vec<f64>, String> {
+        to_vector(self.call("measurement", (to_array(x),))?).ok_or("measurement must return an array of numbers".to_string())
+    }
+    pub fn transition_jacobian(&self, x: &[f64], u: &[f64]) -> Result<Vec<Vec<f64>>, String> {
+        if !self.transition_jacobian {
+            return numerical_jacobian(|x| self.transition(x, u), x);
+        }
+        to_matrix(self.call("transition_jacobian", (to_array(x), to_array(u), self.dt))?).ok_or("transition_jacobian must return an array of rows".to_string())
+    }
+    pub fn measurement_jacobian(&self, x: &[f64]) -> Result<Vec<Vec<f64>>, String> {
+        if !self.measurement_jacobian {
+            return numerical_jacobian(|x| self.measurement(x), x);
+        }
+        to_matrix(self.call("measurement_jacobian", (to_array(x),))?).ok_or("measurement_jacobian must return an array of rows".to_string())
+    }
+    // One predict/update cycle on the measurement `z` with control `u`, the state and its
+    // covariance are updated in place.
+    pub fn step(&self, x: &mut Vec<f64>, p: &mut Vec<Vec<f64>>, z: &[f64], u: &[f64], q: &[Vec<f64>], r: &[Vec<f64>]) -> Result<(), String> {
+        let n = x.len();
+        let shaped = |m: &[Vec<f64>], rows: usize| m.len() == rows && m.iter().all(|row| row.len() == n);
+        let f = self.transition_jacobian(x, u)?;
+        let x_prior = self.transition(x, u)?;
+        if x_prior.len() != n || !shaped(&f, n) {
+            return Err(format!("transition must give {} values and a {}x{} Jacobian", n, n, n));
+        }
+        let p_prior = add(&mul(&mul(&f, p), &transpose(&f)), q);
+        let h = self.measurement_jacobian(&x_prior)?;
+        let z_prior = self.measurement(&x_prior)?;
+        if z_prior.len() != z.len() || !shaped(&h, z.len()) {
+            return Err(format!("measurement must give {} values and a {}x{} Jacobian", z.len(), z.len(), n));
+        }
+        let s = add(&mul(&mul(&h, &p_prior), &transpose(&h)), r);
+        let s_inverse = inverse(&s).ok_or("singular innovation covariance".to_string())?;
+        let k = mul(&mul(&p_prior, &transpose(&h)), &s_inverse);
+        let innovation: Vec<Vec<f64>> = z.iter().zip(z_prior.iter()).map(|(a, b)| vec![a - b]).collect();
+        let correction = mul(&k, &innovation);
+        *x = x_prior.iter().zip(correction.iter()).map(|(value, c)| value + c[0]).collect();
+        *p = mul(&sub(&identity(n), &mul(&k, &h)), &p_prior);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_range_tracking() {
+        // constant velocity target observed through its range from a point 10 m off the track
+        let analytic = "
+            fn transition(x, u, dt) { [x[0] + dt * x[1], x[1]] }
+            fn measurement(x) { [sqrt(x[0] * x[0] + 100.0)] }
+            fn measurement_jacobian(x) { [[x[0] / sqrt(x[0] * x[0] + 100.0), 0.0]] }
+        ";
+        let model = NonlinearModel::new(analytic, 0.1).unwrap();
+        let x = [3.0, 1.0];
+        let numeric = numerical_jacobian(|x| model.measurement(x), &x).unwrap();
+        let exact = model.measurement_jacobian(&x).unwrap();
+        assert!((numeric[0][0] - exact[0][0]).abs() < 1.0e-8 && numeric[0][1].abs() < 1.0e-8);
+        assert_eq!(model.transition_jacobian(&x, &[]).unwrap().len(), 2);
+        let (mut estimate, mut p) = (vec![1.0, 0.0], vec![vec![10.0, 0.0], vec![0.0, 10.0]]);
+        let q = vec![vec![1.0e-6, 0.0], vec![0.0, 1.0e-6]];
+        let r = vec![vec![1.0e-4]];
+        for k in 1..=300 {
+            let position: f64 = 3.0 + 0.1 * k as f64;
+            model.step(&mut estimate, &mut p, &[(position * position + 100.0).sqrt()], &[], &q, &r).unwrap();
+        }
+        assert!((estimate[0] - 33.0).abs() < 1.0e-2 && (estimate[1] - 1.0).abs() < 1.0e-2);
+        assert!(NonlinearModel::new("fn measurement(x) { x }", 0.1).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use utils::math::matrix::Matrix;
+use event_bus::EventKind;
+use serde_json::json;
+use crate::nonlinear_model::NonlinearModel;
+use crate::particles::{ParticleSet, valid_strategy};
+use crate::unscented::cholesky;
+
+stream_block! {
+    pub struct ParticleFilter {
+        inputs: { "input": Vec<f64>, "control": Vec<f64> },
+        outputs: { "output": Vec<f64>, "effective_sample_size": f64 },
+        statics: {
+            "model": String = String::new(),
+            "model_file": String = String::new(),
+            "dt": f64 = 1.0,
+            "Q": Matrix<f64> = Matrix::identity(1),
+            "R": Matrix<f64> = Matrix::identity(1),
+            "P0": Matrix<f64> = Matrix::identity(1),
+            "initial_state": Vec<f64> = vec![],
+            "use_control": bool = false,
+            "particles": usize = 1000,
+            "resampling": String = "systematic".to_string(),
+            "resample_threshold": f64 = 0.5,
+            "seed": usize = 0,
+        },
+        state: {
+            "state": Vec<f64> = vec![],
+            "effective_sample_size": f64 = 0.0,
+        },
+        fields: {
+            model: Option<NonlinearModel> = None,
+            particles: Option<ParticleSet> = None,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let initial_state = self.get_statics::<Vec<f64>>("initial_state")?.get_value();
+            let n = initial_state.len();
+            let Q = self.get_statics::<Matrix<f64>>("Q")?.get_value();
+            let R = self.get_statics::<Matrix<f64>>("R")?.get_value();
+            let P0 = self.get_statics::<Matrix<f64>>("P0")?.get_value();
+            let dt = self.get_statics::<f64>("dt")?.get_value();
+            let count = self.get_statics::<usize>("particles")?.get_value();
+            let resampling = self.get_statics::<String>("resampling")?.get_value();
+            let resample_threshold = self.get_statics::<f64>("resample_threshold")?.get_value();
+            let seed = self.get_statics::<usize>("seed")?.get_value();
+            if n == 0 || Q.rows != n || !Q.is_square() || P0.rows != n || !P0.is_square() || !R.is_square()
+                || dt.is_nan() || dt <= 0.0 || count == 0 || !valid_strategy(&resampling)
+                || resample_threshold.is_nan() || !(0.0..=1.0).contains(&resample_threshold) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            // the noises are sampled through their Cholesky factors, P0 may be zero for a known start
+            let spread = match cholesky(&P0.to_vec()) {
+                Some(spread) => spread,
+                None if P0.to_vec().iter().flatten().all(|p| *p == 0.0) => P0.to_vec(),
+                None => return Err(StreamingError::InvalidStatics),
+            };
+            if cholesky(&Q.to_vec()).is_none() || cholesky(&R.to_vec()).is_none() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            // the model script comes inline or from model_file, see NonlinearModel for what it defines
+            let model_file = self.get_statics::<String>("model_file")?.get_value();
+            let source = if model_file.is_empty() {
+                self.get_statics::<String>("model")?.get_value()
+            } else {
+                match std::fs::read_to_string(&model_file) {
+                    Ok(source) => source,
+                    Err(error) => {
+                        eprintln!("ParticleFilter {}: cannot read {}: {}", self.name, model_file, error);
+                        return Err(StreamingError::InvalidStatics)
+                    }
+                }
+            };
+            match NonlinearModel::new(&source, dt) {
+                Ok(model) => self.model = Some(model),
+                Err(error) => {
+                    eprintln!("ParticleFilter {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.particles = Some(ParticleSet::new(&initial_state, &spread, count, seed as u64));
+            self.set_state_value("state", initial_state)?;
+            self.set_state_value("effective_sample_size", count as f64)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let process_factor = cholesky(&self.get_statics::<Matrix<f64>>("Q")?.get_value().to_vec()).ok_or(StreamingError::InvalidStatics)?;
+            let measurement_factor = cholesky(&self.get_statics::<Matrix<f64>>("R")?.get_value().to_vec()).ok_or(StreamingError::InvalidStatics)?;
+            let use_control = self.get_statics::<bool>("use_control")?.get_value();
+            let count = self.get_statics::<usize>("particles")?.get_value();
+            let resampling = self.get_statics::<String>("resampling")?.get_value();
+            let resample_threshold = self.get_statics::<f64>("resample_threshold")?.get_value();
+            let input = self.recv_input::<Vec<f64>>("input")?;
+            let control = if use_control {
+                self.recv_input::<Vec<f64>>("control")?
+            } else {
+                Vec::new()
+            };
+            if input.len() != measurement_factor.len() {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            // the estimate and the effective sample size are taken before resampling, which only
+            // runs once the weights have degenerated below resample_threshold * particles
+            let result = {
+                let _lock = self.lock.lock().unwrap();
+                let model = self.model.as_ref().ok_or(StreamingError::InvalidStateTransition)?;
+                let particles = self.particles.as_mut().ok_or(StreamingError::InvalidStateTransition)?;
+                particles.predict(model, &control, &process_factor)
+                    .and_then(|_| particles.update(model, &input, &measurement_factor))
+                    .map(|_| {
+                        let estimate = (particles.estimate(), particles.effective_sample_size());
+                        if estimate.1 < resample_threshold * count as f64 {
+                            particles.resample(&resampling);
+                        }
+                        estimate
+                    })
+            };
+            let (state, effective_sample_size) = match result {
+                Ok(estimate) => estimate,
+                Err(error) => {
+                    eprintln!("ParticleFilter {}: {}", self.name, error);
+                    self.stop()?;
+                    return Err(StreamingError::InvalidInput);
+                }
+            };
+            if state.iter().any(|v| !v.is_finite()) {
+                event_bus::publish(self.name, EventKind::Divergence, json!({ "state": format!("{:?}", state) }));
+            }
+            self.set_state_value("state", state.clone())?;
+            self.set_state_value("effective_sample_size", effective_sample_size)?;
+            self.send_output("output", state)?;
+            self.send_output("effective_sample_size", effective_sample_size)?;
+            Ok(())
+        }
+    }
+}
+
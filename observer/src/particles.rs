@@ -0,0 +1,141 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand_distr::StandardNormal;
+use crate::nonlinear_model::NonlinearModel;
+
+pub fn valid_strategy(strategy: &str) -> bool {
+    matches!(strategy, "systematic" | "stratified")
+}
+
+// Index of the particle drawn for each position in [0, 1), positions must be increasing.
+pub fn resample_indices(weights: &[f64], positions: &[f64]) -> Vec<usize> {
+    let mut indices = Vec::with_capacity(positions.len());
+    let mut cumulative = weights[0];
+    let mut index = 0;
+    for position in positions {
+        while *position >= cumulative && index + 1 < weights.len() {
+            index += 1;
+            cumulative += weights[index];
+        }
+        indices.push(index);
+    }
+    indices
+}
+
+// Weighted particle cloud of a bootstrap filter on a NonlinearModel. Particles move through the
+// transition plus Gaussian process noise and are weighted by the Gaussian likelihood of the
+// measurement residual, the noises are given by their lower Cholesky factors.
+pub struct ParticleSet {
+    particles: Vec<Vec<f64>>,
+    weights: Vec<f64>,
+    rng: StdRng,
+}
+impl ParticleSet {
+    // `seed` 0 draws the generator state from the operating system.
+    pub fn new(mean: &[f64], spread: &[Vec<f64>], count: usize, seed: u64) -> Self {
+        let rng = if seed == 0 { StdRng::from_os_rng() } else { StdRng::seed_from_u64(seed) };
+        let mut set = ParticleSet { particles: Vec::with_capacity(count), weights: vec![1.0 / count as f64; count], rng };
+        for _ in 0..count {
+            let noise = set.gaussian(spread);
+            set.particles.push(mean.iter().zip(noise.iter()).map(|(m, e)| m + e).collect());
+        }
+        set
+    }
+    fn gaussian(&mut self, factor: &[Vec<f64>]) -> Vec<f64> {
+        let normal: Vec<f64> = (0..factor.len()).map(|_| self.rng.sample(StandardNormal)).collect();
+        factor.iter().map(|row| row.iter().zip(normal.iter()).map(|(l, e)| l * e).sum()).collect()
+    }
+    pub fn predict(&mut self, model: &NonlinearModel, u: &[f64], process_factor: &[Vec<f64>]) -> Result<(), String> {
+        for k in 0..self.particles.len() {
+            let next = model.transition(&self.particles[k], u)?;
+            if next.len() != process_factor.len() {
+                return Err(format!("transition must give {} values", process_factor.len()));
+            }
+            let noise = self.gaussian(process_factor);
+            self.particles[k] = next.iter().zip(noise.iter()).map(|(x, e)| x + e).collect();
+        }
+        Ok(())
+    }
+    // Weights are updated in the log domain and shifted by their maximum before normalizing, so
+    // a far off measurement does not underflow every particle to zero.
+    pub fn update(&mut self, model: &NonlinearModel, z: &[f64], measurement_factor: &[Vec<f64>]) -> Result<(), String> {
+        let mut log_weights = Vec::with_capacity(self.particles.len());
+        for (particle, weight) in self.particles.iter().zip(self.weights.iter()) {
+            let predicted = model.measurement(particle)?;
+            if predicted.len() != z.len() {
+                return Err(format!("measurement must give {} values", z.len()));
+            }
+            // |L^-1 (z - h(x))|^2 by forward substitution
+            let mut y = vec![0.0; z.len()];
+            for i in 0..z.len() {
+                y[i] = (z[i] - predicted[i] - (0..i).map(|k| measurement_factor[i][k] * y[k]).sum::<f64>()) / measurement_factor[i][i];
+            }
+            log_weights.push(weight.ln() - 0.5 * y.iter().map(|v| v * v).sum::<f64>());
+        }
+        let max = log_weights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        if !max.is_finite() {
+            return Err("every particle has a zero or non-finite likelihood".to_string());
+        }
+        let weights: Vec<f64> = log_weights.iter().map(|w| (w - max).exp()).collect();
+        let total: f64 = weights.iter().sum();
+        self.weights = weights.iter().map(|w| w / total).collect();
+        Ok(())
+    }
+    pub fn estimate(&self) -> Vec<f64> {
+        let mut mean = vec![0.0; self.particles[0].len()];
+        for (particle, weight) in self.particles.iter().zip(self.weights.iter()) {
+            mean.iter_mut().zip(particle.iter()).for_each(|(m, x)| *m += weight * x);
+        }
+        mean
+    }
+    pub fn effective_sample_size(&self) -> f64 {
+        1.0 / self.weights.iter().map(|w| w * w).sum::<f64>()
+    }
+    // Systematic resampling shares one uniform offset between the N strata, stratified draws one
+    // per stratum. Both leave equal weights.
+    pub fn resample(&mut self, strategy: &str) {
+        let count = self.particles.len();
+        let shared: f64 = self.rng.random();
+        let positions: Vec<f64> = (0..count)
+            .map(|k| (k as f64 + if strategy == "stratified" { self.rng.random() } else { shared }) / count as f64)
+            .collect();
+        self.particles = resample_indices(&self.weights, &positions).into_iter().map(|k| self.particles[k].clone()).collect();
+        self.weights = vec![1.0 / count as f64; count];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_resample_indices() {
+        let weights = [0.1, 0.0, 0.6, 0.3];
+        let positions = [0.05, 0.3, 0.55, 0.8];
+        assert_eq!(resample_indices(&weights, &positions), vec![0, 2, 2, 3]);
+        // rounding may leave the last cumulative weight just below a position
+        assert_eq!(resample_indices(&[0.5, 0.49999], &[0.25, 0.999995]), vec![0, 1]);
+    }
+    #[test]
+    fn test_range_tracking() {
+        let source = "
+            fn transition(x, u, dt) { [x[0] + dt * x[1], x[1]] }
+            fn measurement(x) { [sqrt(x[0] * x[0] + 100.0)] }
+        ";
+        let model = NonlinearModel::new(source, 0.1).unwrap();
+        let process = vec![vec![1.0e-2, 0.0], vec![0.0, 1.0e-2]];
+        let measurement = vec![vec![0.1]];
+        for strategy in ["systematic", "stratified"] {
+            let mut set = ParticleSet::new(&[5.0, 0.0], &[vec![2.0, 0.0], vec![0.0, 1.0]], 500, 7);
+            for k in 1..=150 {
+                let position: f64 = 5.0 + 0.1 * k as f64;
+                set.predict(&model, &[], &process).unwrap();
+                set.update(&model, &[(position * position + 100.0).sqrt()], &measurement).unwrap();
+                if set.effective_sample_size() < 250.0 {
+                    set.resample(strategy);
+                }
+            }
+            let estimate = set.estimate();
+            assert!((estimate[0] - 20.0).abs() < 0.1 && (estimate[1] - 1.0).abs() < 0.1, "{} {:?}", strategy, estimate);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::linalg;
+
+const MAX_ITERATIONS: usize = 10000;
+const TOLERANCE: f64 = 1.0e-10;
+
+// Converged a-posteriori covariance and steady-state Kalman gain.
+pub type SteadyState = (Vec<Vec<f64>>, Vec<Vec<f64>>);
+
+// Iterates the discrete algebraic Riccati equation on the a-priori covariance
+// P = A*(P - P*H'*(H*P*H' + R)^-1*H*P)*A' + Q until convergence.
+pub fn solve_dare(a: &[Vec<f64>], h: &[Vec<f64>], q: &[Vec<f64>], r: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let a_t = linalg::transpose(a);
+    let h_t = linalg::transpose(h);
+    let mut p = q.to_vec();
+    for _ in 0..MAX_ITERATIONS {
+        let s = linalg::add(&linalg::mul(&linalg::mul(h, &p), &h_t), r);
+        let s_inv = linalg::inverse(&s)?;
+        let p_ht = linalg::mul(&p, &h_t);
+        let correction = linalg::mul(&linalg::mul(&p_ht, &s_inv), &linalg::transpose(&p_ht));
+        let p_post = linalg::sub(&p, &correction);
+        let p_next = linalg::add(&linalg::mul(&linalg::mul(a, &p_post), &a_t), q);
+        if p_next.iter().flatten().any(|v| !v.is_finite()) {
+            return None;
+        }
+        let delta = linalg::max_abs_diff(&p_next, &p);
+        p = p_next;
+        if delta < TOLERANCE {
+            return Some(p);
+        }
+    }
+    None
+}
+pub fn steady_state_gain(a: &[Vec<f64>], h: &[Vec<f64>], q: &[Vec<f64>], r: &[Vec<f64>]) -> Option<SteadyState> {
+    let p = solve_dare(a, h, q, r)?;
+    let h_t = linalg::transpose(h);
+    let s = linalg::add(&linalg::mul(&linalg::mul(h, &p), &h_t), r);
+    let k = linalg::mul(&linalg::mul(&p, &h_t), &linalg::inverse(&s)?);
+    let kh = linalg::mul(&k, h);
+    let p_post = linalg::mul(&linalg::sub(&linalg::identity(kh.len()), &kh), &p);
+    Some((p_post, k))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_scalar_random_walk_gain() {
+        // x(k+1) = x(k) + w, z = x + v with q = r = 1: P_prior = (1 + sqrt(5))/2
+        let (_, k) = steady_state_gain(&[vec![1.0]], &[vec![1.0]], &[vec![1.0]], &[vec![1.0]]).unwrap();
+        let p_prior = (1.0 + 5.0_f64.sqrt()) / 2.0;
+        assert!((k[0][0] - p_prior / (p_prior + 1.0)).abs() < 1.0e-8);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::linalg::{add, identity, inverse, mul, sub, transpose};
+
+// One Kalman filter step as the RTS recursion needs it, the prediction from the previous step
+// and the update on the measurement.
+#[derive(Clone, Debug)]
+pub struct FilteredStep {
+    pub x_prior: Vec<f64>,
+    pub p_prior: Vec<Vec<f64>>,
+    pub x: Vec<f64>,
+    pub p: Vec<Vec<f64>>,
+}
+
+fn apply(m: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    m.iter().map(|row| row.iter().zip(v.iter()).map(|(a, b)| a * b).sum()).collect()
+}
+
+fn offset(x: &[f64], m: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    x.iter().zip(apply(m, v)).map(|(a, b)| a + b).collect()
+}
+
+// Linear model x' = A x + B u + w, z = H x + v with noise covariances Q and R, filtered and
+// smoothed by the RtsSmoother and FixedLagSmoother blocks.
+pub struct LinearModel {
+    pub a: Vec<Vec<f64>>,
+    pub b: Vec<Vec<f64>>,
+    pub h: Vec<Vec<f64>>,
+    pub q: Vec<Vec<f64>>,
+    pub r: Vec<Vec<f64>>,
+}
+impl LinearModel {
+    // None when the innovation covariance is singular.
+    pub fn filter_step(&self, x: &[f64], p: &[Vec<f64>], z: &[f64], u: &[f64]) -> Option<FilteredStep> {
+        let x_prior: Vec<f64> = apply(&self.a, x).iter().zip(apply(&self.b, u)).map(|(ax, bu)| ax + bu).collect();
+        let p_prior = add(&mul(&mul(&self.a, p), &transpose(&self.a)), &self.q);
+        let s = add(&mul(&mul(&self.h, &p_prior), &transpose(&self.h)), &self.r);
+        let k = mul(&mul(&p_prior, &transpose(&self.h)), &inverse(&s)?);
+        let residual: Vec<f64> = z.iter().zip(apply(&self.h, &x_prior)).map(|(z, hx)| z - hx).collect();
+        let x = offset(&x_prior, &k, &residual);
+        let p = mul(&sub(&identity(x.len()), &mul(&k, &self.h)), &p_prior);
+        Some(FilteredStep { x_prior, p_prior, x, p })
+    }
+    // Rauch-Tung-Striebel backward pass over consecutive filter steps. The returned steps carry
+    // the smoothed state and covariance in x and p, the last one is the filtered estimate. None
+    // when a predicted covariance is singular.
+    pub fn smooth(&self, steps: &[FilteredStep]) -> Option<Vec<FilteredStep>> {
+        let mut smoothed = steps.to_vec();
+        let a_transpose = transpose(&self.a);
+        for k in (0..steps.len().saturating_sub(1)).rev() {
+            let next = &smoothed[k + 1];
+            let gain = mul(&mul(&steps[k].p, &a_transpose), &inverse(&next.p_prior)?);
+            let x_difference: Vec<f64> = next.x.iter().zip(next.x_prior.iter()).map(|(s, f)| s - f).collect();
+            let x = offset(&steps[k].x, &gain, &x_difference);
+            let p = add(&steps[k].p, &mul(&mul(&gain, &sub(&next.p, &next.p_prior)), &transpose(&gain)));
+            (smoothed[k].x, smoothed[k].p) = (x, p);
+        }
+        Some(smoothed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_smoothing() {
+        // constant velocity, position measured with noise variance 1
+        let model = LinearModel {
+            a: vec![vec![1.0, 1.0], vec![0.0, 1.0]],
+            b: vec![vec![0.0], vec![0.0]],
+            h: vec![vec![1.0, 0.0]],
+            q: vec![vec![1.0e-4, 0.0], vec![0.0, 1.0e-4]],
+            r: vec![vec![1.0]],
+        };
+        let noise = [0.9, -1.2, 0.4, 1.5, -0.7, -1.1, 0.3, 0.8, -0.2, -0.9, 1.3, -0.4, 0.6, -1.4, 0.2, 1.0, -0.6, 0.1, -0.3, 0.7];
+        let (mut x, mut p) = (vec![0.0, 0.0], vec![vec![100.0, 0.0], vec![0.0, 100.0]]);
+        let mut steps = Vec::new();
+        for (k, e) in noise.iter().enumerate() {
+            let step = model.filter_step(&x, &p, &[2.0 * k as f64 + e], &[0.0]).unwrap();
+            (x, p) = (step.x.clone(), step.p.clone());
+            steps.push(step);
+        }
+        let smoothed = model.smooth(&steps).unwrap();
+        assert_eq!(smoothed.len(), noise.len());
+        assert_eq!(smoothed[noise.len() - 1].x, steps[noise.len() - 1].x);
+        // the smoothed trajectory is closer to the truth and never less certain than the filter
+        let error = |k: usize, x: &Vec<f64>| (x[0] - 2.0 * k as f64).powi(2);
+        let filtered_error: f64 = steps.iter().enumerate().map(|(k, s)| error(k, &s.x)).sum();
+        let smoothed_error: f64 = smoothed.iter().enumerate().map(|(k, s)| error(k, &s.x)).sum();
+        assert!(smoothed_error < 0.5 * filtered_error);
+        assert!(smoothed.iter().zip(steps.iter()).all(|(s, f)| s.p[0][0] <= f.p[0][0] + 1.0e-12));
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use utils::math::matrix::Matrix;
+use event_bus::EventKind;
+use serde_json::json;
+use crate::rts::{FilteredStep, LinearModel};
+
+stream_block! {
+    pub struct RtsSmoother {
+        inputs: { "input": Vec<f64>, "control": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "A": Matrix<f64> = Matrix::identity(1),
+            "B": Matrix<f64> = Matrix::identity(1),
+            "H": Matrix<f64> = Matrix::identity(1),
+            "Q": Matrix<f64> = Matrix::identity(1),
+            "R": Matrix<f64> = Matrix::identity(1),
+            "P0": Matrix<f64> = Matrix::identity(1),
+            "initial_state": Vec<f64> = vec![],
+            "use_control": bool = false,
+            "window": usize = 100,
+        },
+        state: {
+            "state": Vec<f64> = vec![],
+            "P": Matrix<f64> = Matrix::identity(1),
+        },
+        fields: {
+            model: Option<LinearModel> = None,
+            history: Vec<FilteredStep> = Vec::new(),
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let A = self.get_statics::<Matrix<f64>>("A")?.get_value();
+            let B = self.get_statics::<Matrix<f64>>("B")?.get_value();
+            let H = self.get_statics::<Matrix<f64>>("H")?.get_value();
+            let Q = self.get_statics::<Matrix<f64>>("Q")?.get_value();
+            let R = self.get_statics::<Matrix<f64>>("R")?.get_value();
+            let P0 = self.get_statics::<Matrix<f64>>("P0")?.get_value();
+            let initial_state = self.get_statics::<Vec<f64>>("initial_state")?.get_value();
+            let window = self.get_statics::<usize>("window")?.get_value();
+            if !A.is_square() || B.rows != A.rows || H.cols != A.rows || Q.rows != A.rows || !Q.is_square()
+                || R.rows != H.rows || !R.is_square() || P0.rows != A.rows || !P0.is_square()
+                || initial_state.len() != A.rows || window == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.model = Some(LinearModel { a: A.to_vec(), b: B.to_vec(), h: H.to_vec(), q: Q.to_vec(), r: R.to_vec() });
+            self.history.clear();
+            self.set_state_value("state", initial_state)?;
+            self.set_state_value("P", P0)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let B = self.get_statics::<Matrix<f64>>("B")?.get_value();
+            let use_control = self.get_statics::<bool>("use_control")?.get_value();
+            let window = self.get_statics::<usize>("window")?.get_value();
+            let state = self.get_state_value::<Vec<f64>>("state")?;
+            let P = self.get_state_value::<Matrix<f64>>("P")?.to_vec();
+            let input = self.recv_input::<Vec<f64>>("input")?;
+            // without a control connection the model is driven by u = 0
+            let control = if use_control {
+                self.recv_input::<Vec<f64>>("control")?
+            } else {
+                vec![0.0; B.cols]
+            };
+            if control.len() != B.cols {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            // the filter runs forward on every frame, every `window` frames the backward pass smooths
+            // the buffered steps and they leave the block together
+            let result = {
+                let _lock = self.lock.lock().unwrap();
+                let model = self.model.as_ref().ok_or(StreamingError::InvalidStateTransition)?;
+                match model.filter_step(&state, &P, &input, &control) {
+                    Some(step) => {
+                        self.history.push(step.clone());
+                        let smoothed = if self.history.len() == window {
+                            let smoothed = model.smooth(&self.history);
+                            self.history.clear();
+                            smoothed
+                        } else {
+                            Some(Vec::new())
+                        };
+                        smoothed.map(|smoothed| (step, smoothed))
+                    }
+                    None => None,
+                }
+            };
+            let Some((filtered, smoothed)) = result else {
+                eprintln!("RtsSmoother {}: singular covariance", self.name);
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            };
+            if filtered.x.iter().any(|v| !v.is_finite()) {
+                event_bus::publish(self.name, EventKind::Divergence, json!({ "state": format!("{:?}", filtered.x) }));
+            }
+            self.set_state_value("state", filtered.x)?;
+            self.set_state_value("P", Matrix::from_vec(filtered.p))?;
+            for step in smoothed {
+                self.send_output("output", step.x)?;
+            }
+            Ok(())
+        }
+    }
+}
+
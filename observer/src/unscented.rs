@@ -0,0 +1,267 @@
+use crate::linalg::transpose;
+use crate::nonlinear_model::NonlinearModel;
+
+// Lower triangular L with L L^T = a, None when `a` is not positive definite.
+pub fn cholesky(a: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let mut l = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = a[i][j] - (0..j).map(|k| l[i][k] * l[j][k]).sum::<f64>();
+            if i == j {
+                if sum <= 0.0 {
+                    return None;
+                }
+                l[i][i] = sum.sqrt();
+            } else {
+                l[i][j] = sum / l[j][j];
+            }
+        }
+    }
+    Some(l)
+}
+
+// Lower triangular S with S S^T = sum of c c^T over `columns` (each of length n), from the
+// Householder QR of the matrix whose rows are the columns. The diagonal is made positive.
+pub fn triangular_factor(columns: &[Vec<f64>], n: usize) -> Vec<Vec<f64>> {
+    let mut a = columns.to_vec();
+    let m = a.len();
+    for k in 0..n.min(m) {
+        let norm = (k..m).map(|i| a[i][k] * a[i][k]).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            continue;
+        }
+        let mut v: Vec<f64> = (k..m).map(|i| a[i][k]).collect();
+        v[0] += if a[k][k] > 0.0 { norm } else { -norm };
+        let v_norm = v.iter().map(|x| x * x).sum::<f64>();
+        for j in k..n {
+            let factor = 2.0 * a[k..].iter().zip(v.iter()).map(|(row, vi)| vi * row[j]).sum::<f64>() / v_norm;
+            a[k..].iter_mut().zip(v.iter()).for_each(|(row, vi)| row[j] -= factor * vi);
+        }
+    }
+    let mut s = vec![vec![0.0; n]; n];
+    for j in 0..n.min(m) {
+        let sign = if a[j][j] < 0.0 { -1.0 } else { 1.0 };
+        (j..n).for_each(|i| s[i][j] = sign * a[j][i]);
+    }
+    s
+}
+
+// Rank one update of the lower factor, S S^T + weight x x^T, false when a downdate would make
+// the matrix indefinite.
+pub fn cholesky_update(s: &mut [Vec<f64>], x: &[f64], weight: f64) -> bool {
+    let sign = weight.signum();
+    let mut x: Vec<f64> = x.iter().map(|v| v * weight.abs().sqrt()).collect();
+    for k in 0..s.len() {
+        let r2 = s[k][k] * s[k][k] + sign * x[k] * x[k];
+        if r2 <= 0.0 || s[k][k] == 0.0 {
+            return false;
+        }
+        let r = r2.sqrt();
+        let (c, sn) = (r / s[k][k], x[k] / s[k][k]);
+        s[k][k] = r;
+        for i in k + 1..s.len() {
+            s[i][k] = (s[i][k] + sign * sn * x[i]) / c;
+            x[i] = c * x[i] - sn * s[i][k];
+        }
+    }
+    true
+}
+
+// Scaled unscented transform for an n-state filter, alpha spreads the sigma points, beta = 2 is
+// optimal for Gaussian priors and kappa is the secondary scaling. In square root mode the
+// covariance handed to step is the lower Cholesky factor S of P and is propagated as such, so P
+// stays positive definite by construction.
+pub struct Unscented {
+    gamma: f64,
+    mean_weights: Vec<f64>,
+    covariance_weights: Vec<f64>,
+    process_noise: Vec<Vec<f64>>,
+    measurement_noise: Vec<Vec<f64>>,
+    square_root: bool,
+}
+impl Unscented {
+    pub fn new(n: usize, alpha: f64, beta: f64, kappa: f64, q: &[Vec<f64>], r: &[Vec<f64>], square_root: bool) -> Result<Self, String> {
+        let lambda = alpha * alpha * (n as f64 + kappa) - n as f64;
+        if n as f64 + lambda <= 0.0 {
+            return Err(format!("alpha {} and kappa {} leave no spread for {} states", alpha, kappa, n));
+        }
+        let spread = n as f64 + lambda;
+        let mut mean_weights = vec![0.5 / spread; 2 * n + 1];
+        let mut covariance_weights = mean_weights.clone();
+        mean_weights[0] = lambda / spread;
+        covariance_weights[0] = lambda / spread + 1.0 - alpha * alpha + beta;
+        // the square root form carries the noise as Cholesky factors
+        let (process_noise, measurement_noise) = if square_root {
+            (cholesky(q).ok_or("Q is not positive definite")?, cholesky(r).ok_or("R is not positive definite")?)
+        } else {
+            (q.to_vec(), r.to_vec())
+        };
+        Ok(Unscented { gamma: spread.sqrt(), mean_weights, covariance_weights, process_noise, measurement_noise, square_root })
+    }
+    fn sigma_points(&self, x: &[f64], factor: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let mut points = vec![x.to_vec()];
+        let columns = transpose(factor);
+        for sign in [1.0, -1.0] {
+            for column in &columns {
+                points.push(x.iter().zip(column.iter()).map(|(v, c)| v + sign * self.gamma * c).collect());
+            }
+        }
+        points
+    }
+    fn mean(&self, points: &[Vec<f64>]) -> Vec<f64> {
+        let mut mean = vec![0.0; points[0].len()];
+        for (point, w) in points.iter().zip(self.mean_weights.iter()) {
+            mean.iter_mut().zip(point.iter()).for_each(|(m, p)| *m += w * p);
+        }
+        mean
+    }
+    fn deviations(points: &[Vec<f64>], mean: &[f64]) -> Vec<Vec<f64>> {
+        points.iter().map(|p| p.iter().zip(mean.iter()).map(|(a, b)| a - b).collect()).collect()
+    }
+    fn cross_covariance(&self, a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let mut c = vec![vec![0.0; b[0].len()]; a[0].len()];
+        for ((da, db), w) in a.iter().zip(b.iter()).zip(self.covariance_weights.iter()) {
+            for i in 0..da.len() {
+                for j in 0..db.len() {
+                    c[i][j] += w * da[i] * db[j];
+                }
+            }
+        }
+        c
+    }
+    // Covariance of the deviations plus the noise. In square root mode the factor comes from
+    // the QR of the weighted deviations and the noise factor, then the centre point, whose
+    // weight can be negative, is added by a rank one update.
+    fn spread_covariance(&self, deviations: &[Vec<f64>], noise: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, String> {
+        let size = deviations[0].len();
+        if !self.square_root {
+            let mut c = self.cross_covariance(deviations, deviations);
+            c.iter_mut().zip(noise.iter()).for_each(|(row, noise_row)| row.iter_mut().zip(noise_row.iter()).for_each(|(v, q)| *v += q));
+            return Ok(c);
+        }
+        let weight = self.covariance_weights[1].sqrt();
+        let mut columns: Vec<Vec<f64>> = deviations[1..].iter().map(|d| d.iter().map(|v| weight * v).collect()).collect();
+        columns.extend(transpose(noise));
+        let mut s = triangular_factor(&columns, size);
+        if !cholesky_update(&mut s, &deviations[0], self.covariance_weights[0]) {
+            return Err("the covariance factor update failed".to_string());
+        }
+        Ok(s)
+    }
+    fn factor(&self, covariance: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, String> {
+        if self.square_root {
+            Ok(covariance.to_vec())
+        } else {
+            cholesky(covariance).ok_or("the covariance is not positive definite".to_string())
+        }
+    }
+    // One predict/update cycle on the measurement `z` with control `u`. `covariance` is P, or
+    // its lower factor S in square root mode, and is updated in place with the state.
+    pub fn step(&self, model: &NonlinearModel, x: &mut Vec<f64>, covariance: &mut Vec<Vec<f64>>, z: &[f64], u: &[f64]) -> Result<(), String> {
+        let n = x.len();
+        let propagated = self.sigma_points(x, &self.factor(covariance)?).iter()
+            .map(|point| model.transition(point, u)).collect::<Result<Vec<Vec<f64>>, String>>()?;
+        if propagated.iter().any(|point| point.len() != n) {
+            return Err(format!("transition must give {} values", n));
+        }
+        let x_prior = self.mean(&propagated);
+        let prior = self.spread_covariance(&Self::deviations(&propagated, &x_prior), &self.process_noise)?;
+        // the sigma points are drawn again around the prediction for the measurement update
+        let points = self.sigma_points(&x_prior, &self.factor(&prior)?);
+        let predicted = points.iter().map(|point| model.measurement(point)).collect::<Result<Vec<Vec<f64>>, String>>()?;
+        if predicted.iter().any(|zp| zp.len() != z.len()) {
+            return Err(format!("measurement must give {} values", z.len()));
+        }
+        let z_prior = self.mean(&predicted);
+        let z_deviations = Self::deviations(&predicted, &z_prior);
+        let innovation = self.spread_covariance(&z_deviations, &self.measurement_noise)?;
+        let cross = self.cross_covariance(&Self::deviations(&points, &x_prior), &z_deviations);
+        // K = Pxz Pzz^-1 by forward and back substitution on the factor of Pzz
+        let sz = self.factor(&innovation)?;
+        let m = z.len();
+        let mut gain = vec![vec![0.0; m]; n];
+        for (row, cross_row) in gain.iter_mut().zip(cross.iter()) {
+            let mut y = vec![0.0; m];
+            for i in 0..m {
+                y[i] = (cross_row[i] - (0..i).map(|k| sz[i][k] * y[k]).sum::<f64>()) / sz[i][i];
+            }
+            for i in (0..m).rev() {
+                row[i] = (y[i] - (i + 1..m).map(|k| sz[k][i] * row[k]).sum::<f64>()) / sz[i][i];
+            }
+        }
+        let residual: Vec<f64> = z.iter().zip(z_prior.iter()).map(|(a, b)| a - b).collect();
+        *x = x_prior.iter().zip(gain.iter()).map(|(v, k)| v + k.iter().zip(residual.iter()).map(|(a, b)| a * b).sum::<f64>()).collect();
+        // P = P- - K Pzz K^T, the square root form downdates S- by the columns of K Sz
+        let u_columns: Vec<Vec<f64>> = (0..m).map(|j| gain.iter().map(|k| (0..m).map(|i| k[i] * sz[i][j]).sum()).collect()).collect();
+        if self.square_root {
+            let mut s = prior;
+            for column in &u_columns {
+                if !cholesky_update(&mut s, column, -1.0) {
+                    return Err("the covariance lost positive definiteness".to_string());
+                }
+            }
+            *covariance = s;
+        } else {
+            let mut p = prior;
+            for column in &u_columns {
+                for i in 0..n {
+                    for j in 0..n {
+                        p[i][j] -= column[i] * column[j];
+                    }
+                }
+            }
+            // keep P exactly symmetric, rounding otherwise accumulates into an indefinite matrix
+            *covariance = (0..n).map(|i| (0..n).map(|j| 0.5 * (p[i][j] + p[j][i])).collect()).collect();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::mul;
+    #[test]
+    fn test_factor_updates() {
+        let a = vec![vec![4.0, 2.0, 0.4], vec![2.0, 5.0, 1.0], vec![0.4, 1.0, 3.0]];
+        let l = cholesky(&a).unwrap();
+        let product = |s: &Vec<Vec<f64>>| mul(s, &transpose(s));
+        assert!(product(&l).iter().flatten().zip(a.iter().flatten()).all(|(x, y)| (x - y).abs() < 1.0e-12));
+        // the rows of l^T stacked give back l, whatever the column order
+        let columns = vec![transpose(&l)[2].clone(), transpose(&l)[0].clone(), transpose(&l)[1].clone()];
+        let s = triangular_factor(&columns, 3);
+        assert!(product(&s).iter().flatten().zip(a.iter().flatten()).all(|(x, y)| (x - y).abs() < 1.0e-12));
+        let mut updated = s.clone();
+        assert!(cholesky_update(&mut updated, &[1.0, -1.0, 0.5], 0.5));
+        assert!(cholesky_update(&mut updated, &[1.0, -1.0, 0.5], -0.5));
+        assert!(product(&updated).iter().flatten().zip(a.iter().flatten()).all(|(x, y)| (x - y).abs() < 1.0e-12));
+        assert!(!cholesky_update(&mut updated, &[10.0, 0.0, 0.0], -1.0));
+        assert!(cholesky(&[vec![1.0, 2.0], vec![2.0, 1.0]]).is_none());
+    }
+    #[test]
+    fn test_square_root_matches_standard() {
+        let source = "
+            fn transition(x, u, dt) { [x[0] + dt * x[1], x[1]] }
+            fn measurement(x) { [sqrt(x[0] * x[0] + 100.0)] }
+        ";
+        let model = NonlinearModel::new(source, 0.1).unwrap();
+        let q = vec![vec![1.0e-6, 0.0], vec![0.0, 1.0e-6]];
+        let r = vec![vec![1.0e-4]];
+        let standard = Unscented::new(2, 0.5, 2.0, 1.0, &q, &r, false).unwrap();
+        let square_root = Unscented::new(2, 0.5, 2.0, 1.0, &q, &r, true).unwrap();
+        let (mut x, mut p) = (vec![1.0, 0.0], vec![vec![10.0, 0.0], vec![0.0, 10.0]]);
+        let (mut x_sr, mut s) = (x.clone(), cholesky(&p).unwrap());
+        for k in 1..=300 {
+            let position: f64 = 3.0 + 0.1 * k as f64;
+            let z = [(position * position + 100.0).sqrt()];
+            standard.step(&model, &mut x, &mut p, &z, &[]).unwrap();
+            square_root.step(&model, &mut x_sr, &mut s, &z, &[]).unwrap();
+        }
+        assert!((x[0] - 33.0).abs() < 1.0e-2 && (x[1] - 1.0).abs() < 1.0e-2);
+        assert!(x.iter().zip(x_sr.iter()).all(|(a, b)| (a - b).abs() < 1.0e-6));
+        let p_sr = mul(&s, &transpose(&s));
+        assert!(p.iter().flatten().zip(p_sr.iter().flatten()).all(|(a, b)| (a - b).abs() < 1.0e-9));
+        assert!(Unscented::new(2, 1.0e-3, 2.0, -2.0, &q, &r, false).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::interpolation::{DriftEstimator, FractionalResampler};
+
+stream_block! {
+    pub struct AsyncResampler {
+        inputs: { "input": Vec<f64>, "timestamp": f64 },
+        outputs: { "output": Vec<f64>, "ratio": f64 },
+        statics: {
+            "input_rate": f64 = 48000.0,
+            "output_rate": f64 = 48000.0,
+            "bandwidth": f64 = 0.01,
+            "max_drift_ppm": f64 = 1000.0,
+        },
+        state: {
+            "drift": DriftEstimator = DriftEstimator::default(),
+            "resampler": FractionalResampler = FractionalResampler::default(),
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let input_rate = self.get_statics::<f64>("input_rate")?.get_value();
+            let output_rate = self.get_statics::<f64>("output_rate")?.get_value();
+            let bandwidth = self.get_statics::<f64>("bandwidth")?.get_value();
+            let max_drift_ppm = self.get_statics::<f64>("max_drift_ppm")?.get_value();
+            if !(input_rate > 0.0 && output_rate > 0.0 && bandwidth > 0.0 && bandwidth < 1.0 && max_drift_ppm >= 0.0) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("drift", DriftEstimator::default())?;
+            self.set_state_value("resampler", FractionalResampler::default())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let input_rate = self.get_statics::<f64>("input_rate")?.get_value();
+            let output_rate = self.get_statics::<f64>("output_rate")?.get_value();
+            let bandwidth = self.get_statics::<f64>("bandwidth")?.get_value();
+            let max_drift = self.get_statics::<f64>("max_drift_ppm")?.get_value() * 1.0e-6;
+            let mut drift = self.get_state_value::<DriftEstimator>("drift")?;
+            let mut resampler = self.get_state_value::<FractionalResampler>("resampler")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let timestamp = self.recv_input::<f64>("timestamp")?;
+            let arrival = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0);
+            let output_signal;
+            let step;
+            {
+                let _lock = self.lock.lock().unwrap();
+                // a positive drift means the producer clock runs slow: its input_rate samples span more
+                // than a consumer second, so fewer input samples are read per output sample
+                let clock_drift = drift.update(timestamp, arrival, 1.0 - bandwidth).clamp(-max_drift, max_drift);
+                step = input_rate / ((1.0 + clock_drift) * output_rate);
+                output_signal = resampler.process(&input_signal, step);
+            }
+            self.set_state_value("drift", drift)?;
+            self.set_state_value("resampler", resampler)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            self.send_output::<f64>("ratio", step)?;
+            Ok(())
+        }
+    }
+}
+
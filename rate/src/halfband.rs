@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use crate::polyphase::{PolyphaseResampler, lowpass};
+
+// Half-band low-pass of `length` = 4k - 1 taps: the center tap is 1/2 and every other tap is
+// zero, the response is antisymmetric around a quarter of the sample rate.
+pub fn halfband(length: usize, beta: f64) -> Vec<f64> {
+    let center = length / 2;
+    let mut taps = lowpass(length, 0.25, beta);
+    for (n, tap) in taps.iter_mut().enumerate() {
+        if n.abs_diff(center).is_multiple_of(2) {
+            *tap = 0.0;
+        }
+    }
+    // the odd taps are rescaled to add up to 1/2, keeping the DC gain at one
+    let sum: f64 = taps.iter().sum();
+    taps.iter_mut().for_each(|h| *h *= 0.5 / sum);
+    taps[center] = 0.5;
+    taps
+}
+
+// Integer rate change as a cascade of half-band stages for the factors of two, followed (when
+// decimating) or preceded (when interpolating) by one polyphase stage for the odd remainder.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateCascade {
+    stages: Vec<PolyphaseResampler>,
+}
+impl RateCascade {
+    pub fn new(factor: usize, interpolate: bool, length: usize, beta: f64) -> Self {
+        let mut odd = factor;
+        let mut halfbands = 0;
+        while odd > 1 && odd.is_multiple_of(2) {
+            odd /= 2;
+            halfbands += 1;
+        }
+        let taps = halfband(length, beta);
+        let (interp, decim) = if interpolate { (2, 1) } else { (1, 2) };
+        let mut stages: Vec<PolyphaseResampler> = (0..halfbands)
+            .map(|_| PolyphaseResampler::from_prototype(interp, decim, &taps))
+            .collect();
+        if odd > 1 {
+            // the prototype is about `length` taps per output sample of the slower side
+            let (interp, decim, taps_per_phase) = if interpolate { (odd, 1, length) } else { (1, odd, length * odd) };
+            let remainder = PolyphaseResampler::new(interp, decim, taps_per_phase, 0.9, beta);
+            if interpolate {
+                stages.insert(0, remainder);
+            } else {
+                stages.push(remainder);
+            }
+        }
+        RateCascade { stages }
+    }
+    pub fn process(&mut self, input: &[f64]) -> Vec<f64> {
+        self.stages.iter_mut().fold(input.to_vec(), |signal, stage| stage.process(&signal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    #[test]
+    fn test_halfband_cascade() {
+        let taps = halfband(23, 8.0);
+        assert!((taps[11] - 0.5).abs() < 1.0e-12);
+        assert!(taps[9] == 0.0 && taps[13] == 0.0 && taps[10] != 0.0);
+        let frequency = 0.01;
+        let input: Vec<f64> = (0..1200).map(|n| (2.0 * PI * frequency * n as f64).sin()).collect();
+        let mut decimator = RateCascade::new(12, false, 23, 8.0);
+        let mut decimated = Vec::new();
+        for chunk in input.chunks(50) {
+            decimated.extend(decimator.process(chunk));
+        }
+        assert_eq!(decimated.len(), 100);
+        let peak = decimated[50..].iter().fold(0.0f64, |a, b| a.max(b.abs()));
+        assert!((peak - 1.0).abs() < 0.02);
+        let mut interpolator = RateCascade::new(4, true, 23, 8.0);
+        let interpolated = interpolator.process(&input[..300]);
+        assert_eq!(interpolated.len(), 1200);
+        let peak = interpolated[400..].iter().fold(0.0f64, |a, b| a.max(b.abs()));
+        assert!((peak - 1.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::halfband::RateCascade;
+
+stream_block! {
+    pub struct Decimate {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "factor": usize = 2,
+            "filter_length": usize = 23,
+            "kaiser_beta": f64 = 8.0,
+        },
+        state: { "cascade": RateCascade = RateCascade::default() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let factor = self.get_statics::<usize>("factor")?.get_value();
+            let filter_length = self.get_statics::<usize>("filter_length")?.get_value();
+            let kaiser_beta = self.get_statics::<f64>("kaiser_beta")?.get_value();
+            if !valid_settings(factor, filter_length, kaiser_beta) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("cascade", RateCascade::new(factor, false, filter_length, kaiser_beta))?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let mut cascade = self.get_state_value::<RateCascade>("cascade")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let output_signal = {
+                let _lock = self.lock.lock().unwrap();
+                cascade.process(&input_signal)
+            };
+            self.set_state_value("cascade", cascade)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+stream_block! {
+    pub struct Interpolate {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "factor": usize = 2,
+            "filter_length": usize = 23,
+            "kaiser_beta": f64 = 8.0,
+        },
+        state: { "cascade": RateCascade = RateCascade::default() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let factor = self.get_statics::<usize>("factor")?.get_value();
+            let filter_length = self.get_statics::<usize>("filter_length")?.get_value();
+            let kaiser_beta = self.get_statics::<f64>("kaiser_beta")?.get_value();
+            if !valid_settings(factor, filter_length, kaiser_beta) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("cascade", RateCascade::new(factor, true, filter_length, kaiser_beta))?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let mut cascade = self.get_state_value::<RateCascade>("cascade")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let output_signal = {
+                let _lock = self.lock.lock().unwrap();
+                cascade.process(&input_signal)
+            };
+            self.set_state_value("cascade", cascade)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// Half-band filters have 4k - 1 taps, shorter ones would not have a stopband at all.
+fn valid_settings(factor: usize, filter_length: usize, kaiser_beta: f64) -> bool {
+    factor > 0 && filter_length >= 7 && (filter_length + 1).is_multiple_of(4) && kaiser_beta >= 0.0
+}
+
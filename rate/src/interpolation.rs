@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+// Catmull-Rom cubic through x(-1), x(0), x(1), x(2) evaluated at `fraction` between x(0) and x(1).
+pub fn hermite(points: &[f64], fraction: f64) -> f64 {
+    let (xm1, x0, x1, x2) = (points[0], points[1], points[2], points[3]);
+    let c1 = 0.5 * (x1 - xm1);
+    let c2 = xm1 - 2.5 * x0 + 2.0 * x1 - 0.5 * x2;
+    let c3 = 0.5 * (x2 - xm1) + 1.5 * (x0 - x1);
+    ((c3 * fraction + c2) * fraction + c1) * fraction + x0
+}
+
+// Streaming resampler reading the input with an arbitrary, possibly changing, step in input
+// samples per output sample. The last three input samples are kept to interpolate across frames.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FractionalResampler {
+    history: Vec<f64>,
+    position: f64,
+}
+impl Default for FractionalResampler {
+    fn default() -> Self {
+        FractionalResampler { history: vec![0.0; 3], position: 0.0 }
+    }
+}
+impl FractionalResampler {
+    pub fn process(&mut self, input: &[f64], step: f64) -> Vec<f64> {
+        let mut buffer = std::mem::take(&mut self.history);
+        buffer.extend_from_slice(input);
+        let mut output = Vec::new();
+        loop {
+            let index = 1 + self.position.floor() as usize;
+            if index + 2 >= buffer.len() {
+                break;
+            }
+            output.push(hermite(&buffer[index - 1..index + 3], self.position.fract()));
+            self.position += step;
+        }
+        let consumed = buffer.len() - 3;
+        self.position -= consumed as f64;
+        self.history = buffer.split_off(consumed);
+        output
+    }
+}
+
+// Slope of the consumer-minus-producer clock offset against producer time, by recursive least
+// squares with exponential forgetting. Times are taken relative to the first observation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DriftEstimator {
+    origin: Option<(f64, f64)>,
+    weight: f64,
+    time: f64,
+    offset: f64,
+    time_time: f64,
+    time_offset: f64,
+}
+impl DriftEstimator {
+    pub fn update(&mut self, producer_time: f64, consumer_time: f64, forgetting: f64) -> f64 {
+        let (producer_origin, consumer_origin) = *self.origin.get_or_insert((producer_time, consumer_time));
+        let t = producer_time - producer_origin;
+        let o = (consumer_time - consumer_origin) - t;
+        self.weight = forgetting * self.weight + 1.0;
+        self.time = forgetting * self.time + t;
+        self.offset = forgetting * self.offset + o;
+        self.time_time = forgetting * self.time_time + t * t;
+        self.time_offset = forgetting * self.time_offset + t * o;
+        let denominator = self.weight * self.time_time - self.time * self.time;
+        if denominator.abs() < 1.0e-12 {
+            return 0.0;
+        }
+        (self.weight * self.time_offset - self.time * self.offset) / denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_resampler_tracks_ramp() {
+        let mut resampler = FractionalResampler::default();
+        let ramp: Vec<f64> = (0..100).map(|n| n as f64).collect();
+        let mut output = Vec::new();
+        for chunk in ramp.chunks(7) {
+            output.extend(resampler.process(chunk, 0.75));
+        }
+        // two samples of latency from the zero history
+        for (k, value) in output.iter().enumerate().skip(4) {
+            assert!((value - (k as f64 * 0.75 - 2.0)).abs() < 1.0e-9);
+        }
+        let mut drift = DriftEstimator::default();
+        let mut slope = 0.0;
+        for n in 0..50 {
+            let producer = n as f64 * 0.01;
+            slope = drift.update(producer, 100.0 + producer * (1.0 + 200.0e-6), 0.99);
+        }
+        assert!((slope - 200.0e-6).abs() < 1.0e-9);
+    }
+}
@@ -0,0 +1,71 @@
+pub mod interpolation;
+pub mod async_resampler;
+pub mod polyphase;
+pub mod resample;
+pub mod halfband;
+pub mod integer_rate;
+use std::ffi::c_char;
+use data_model::modules::{Version,ModuleStructFFI};
+use processor_engine::stream_processor::StreamProcessor;
+use processor_engine::ffi::{TraitObjectRepr, export_stream_processor, get_error_return};
+#[unsafe(no_mangle)]
+pub static MODULE: ModuleStructFFI  = ModuleStructFFI {
+    name: b"Sample rate conversion\0".as_ptr() as *const c_char,
+    description: b"The library provides blocks to change and adapt stream sample rates.\0".as_ptr() as *const c_char,
+    authors: b"Sofia Silvestri\0".as_ptr() as *const c_char,
+    release_date: b"2026/10/16\0".as_ptr() as *const c_char,
+    version: Version{ major: 0,minor: 1,build: 0},
+    dependencies: std::ptr::null(),
+    dependency_number: 0,
+    provides: [b"AsyncResampler\0".as_ptr() as *const c_char,
+        b"Resample\0".as_ptr() as *const c_char,
+        b"Decimate\0".as_ptr() as *const c_char,
+        b"Interpolate\0".as_ptr() as *const c_char].as_ptr(),
+    provides_lengths: 4,
+};
+/// Creates the block `proc_block` named `block_name`, both passed by the host as UTF-8 bytes.
+///
+/// # Safety
+/// A non null `proc_block` and `block_name` must point to `proc_block_len` and `block_name_len`
+/// readable bytes that stay valid while the block exists.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_processor_modules(proc_block: *const u8, 
+    proc_block_len: usize, 
+    block_name: *const u8, 
+    block_name_len: usize) -> TraitObjectRepr {
+    let proc_block_str = block_template::ffi_name!(proc_block, proc_block_len);
+    let block_name_str = block_template::ffi_name!(block_name, block_name_len);
+    let proc: Box<dyn StreamProcessor>;
+    match proc_block_str {
+        "AsyncResampler" => {
+            proc = Box::new(async_resampler::AsyncResampler::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Resample" => {
+            proc = Box::new(resample::Resample::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Decimate" => {
+            proc = Box::new(integer_rate::Decimate::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Interpolate" => {
+            proc = Box::new(integer_rate::Interpolate::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        _ => {
+            eprintln!("Processor block {} not found", proc_block_str);
+            get_error_return(1)
+        }
+    }
+}
+
+// Schema of every block get_processor_modules creates, keyed by block type, see stream_block!.
+pub fn block_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "AsyncResampler": async_resampler::AsyncResampler::schema(),
+        "Resample": resample::Resample::schema(),
+        "Decimate": integer_rate::Decimate::schema(),
+        "Interpolate": integer_rate::Interpolate::schema(),
+    })
+}
@@ -0,0 +1,102 @@
+use std::f64::consts::PI;
+use serde::{Deserialize, Serialize};
+
+pub fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > 1.0e-12 * sum {
+        term *= (x / (2.0 * k)).powi(2);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+// Kaiser windowed sinc low-pass with `cutoff` in cycles per sample and unit DC gain.
+pub fn lowpass(length: usize, cutoff: f64, beta: f64) -> Vec<f64> {
+    let center = (length - 1) as f64 / 2.0;
+    let taps: Vec<f64> = (0..length).map(|n| {
+        let t = n as f64 - center;
+        let sinc = if t == 0.0 { 2.0 * cutoff } else { (2.0 * PI * cutoff * t).sin() / (PI * t) };
+        let ratio = if center > 0.0 { t / center } else { 0.0 };
+        sinc * bessel_i0(beta * (1.0 - ratio * ratio).max(0.0).sqrt()) / bessel_i0(beta)
+    }).collect();
+    let sum: f64 = taps.iter().sum();
+    taps.iter().map(|h| h / sum).collect()
+}
+
+// Streaming L/M resampler. The prototype filter runs at L times the input rate and is split
+// into L phases of `taps_per_phase` coefficients, only the phase hit by each output is computed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolyphaseResampler {
+    interp: usize,
+    decim: usize,
+    phases: Vec<Vec<f64>>,
+    history: Vec<f64>,
+    time: usize,
+}
+impl PolyphaseResampler {
+    // `cutoff` is the fraction of the lower of the two Nyquist frequencies kept by the filter.
+    pub fn new(interp: usize, decim: usize, taps_per_phase: usize, cutoff: f64, beta: f64) -> Self {
+        let divisor = gcd(interp, decim);
+        let (interp, decim) = (interp / divisor, decim / divisor);
+        let prototype = lowpass(interp * taps_per_phase, cutoff * 0.5 / interp.max(decim) as f64, beta);
+        Self::from_prototype(interp, decim, &prototype)
+    }
+    // The prototype runs at `interp` times the input rate, its length is padded with zeros to a
+    // multiple of `interp`.
+    pub fn from_prototype(interp: usize, decim: usize, prototype: &[f64]) -> Self {
+        let taps_per_phase = prototype.len().div_ceil(interp);
+        let phases = (0..interp)
+            .map(|p| (0..taps_per_phase).map(|j| prototype.get(p + j * interp).unwrap_or(&0.0) * interp as f64).collect())
+            .collect();
+        PolyphaseResampler { interp, decim, phases, history: vec![0.0; taps_per_phase - 1], time: 0 }
+    }
+    pub fn ratio(&self) -> (usize, usize) {
+        (self.interp, self.decim)
+    }
+    pub fn process(&mut self, input: &[f64]) -> Vec<f64> {
+        let taps = self.history.len() + 1;
+        let mut buffer = std::mem::take(&mut self.history);
+        buffer.extend_from_slice(input);
+        let mut output = Vec::new();
+        // `time` counts upsampled samples from the first sample of the current frame
+        while self.time / self.interp < input.len() {
+            let index = self.time / self.interp + taps - 1;
+            let phase = &self.phases[self.time % self.interp];
+            output.push(phase.iter().enumerate().map(|(j, h)| h * buffer[index - j]).sum());
+            self.time += self.decim;
+        }
+        self.time -= input.len() * self.interp;
+        self.history = buffer.split_off(buffer.len() - (taps - 1));
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_rational_resampling() {
+        let mut resampler = PolyphaseResampler::new(6, 4, 24, 0.9, 8.0);
+        assert_eq!(resampler.ratio(), (3, 2));
+        let frequency = 0.02;
+        let input: Vec<f64> = (0..600).map(|n| (2.0 * PI * frequency * n as f64).sin()).collect();
+        let mut output = Vec::new();
+        for chunk in input.chunks(37) {
+            output.extend(resampler.process(chunk));
+        }
+        assert_eq!(output.len(), 900);
+        // the prototype delays by (3 * 24 - 1) / 2 upsampled samples
+        let delay = (3.0 * 24.0 - 1.0) / 2.0;
+        for (m, value) in output.iter().enumerate().skip(100) {
+            let time = (m as f64 * 2.0 - delay) / 3.0;
+            assert!((value - (2.0 * PI * frequency * time).sin()).abs() < 1.0e-3, "{} {}", m, value);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::polyphase::PolyphaseResampler;
+
+stream_block! {
+    pub struct Resample {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "interp": usize = 1,
+            "decim": usize = 1,
+            "cutoff": f64 = 0.9,
+            "taps_per_phase": usize = 16,
+            "kaiser_beta": f64 = 8.0,
+        },
+        state: { "resampler": PolyphaseResampler = PolyphaseResampler::default() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let interp = self.get_statics::<usize>("interp")?.get_value();
+            let decim = self.get_statics::<usize>("decim")?.get_value();
+            let cutoff = self.get_statics::<f64>("cutoff")?.get_value();
+            let taps_per_phase = self.get_statics::<usize>("taps_per_phase")?.get_value();
+            let kaiser_beta = self.get_statics::<f64>("kaiser_beta")?.get_value();
+            if interp == 0 || decim == 0 || taps_per_phase == 0 || !(cutoff > 0.0 && cutoff <= 1.0) || kaiser_beta.is_nan() || kaiser_beta < 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("resampler", PolyphaseResampler::new(interp, decim, taps_per_phase, cutoff, kaiser_beta))?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let mut resampler = self.get_state_value::<PolyphaseResampler>("resampler")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let output_signal = {
+                let _lock = self.lock.lock().unwrap();
+                resampler.process(&input_signal)
+            };
+            self.set_state_value("resampler", resampler)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
@@ -0,0 +1,175 @@
+use std::ops::Range;
+
+pub fn valid_file_format(format: &str) -> bool {
+    matches!(format, "wav" | "csv" | "raw")
+}
+
+// Bytes per value of the supported binary encodings, all little endian. Integers map the full
+// scale to [-1, 1), u8 being offset binary as in 8 bit WAV files.
+pub fn encoding_size(encoding: &str) -> Option<usize> {
+    match encoding {
+        "u8" => Some(1),
+        "i16le" => Some(2),
+        "i24le" => Some(3),
+        "i32le" | "f32le" => Some(4),
+        "f64le" => Some(8),
+        _ => None,
+    }
+}
+
+pub fn decode_value(bytes: &[u8], encoding: &str) -> f64 {
+    match encoding {
+        "u8" => (bytes[0] as f64 - 128.0) / 128.0,
+        "i16le" => i16::from_le_bytes([bytes[0], bytes[1]]) as f64 / 32768.0,
+        // sign extended through the top byte of an i32
+        "i24le" => (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f64 / 8388608.0,
+        "i32le" => i32::from_le_bytes(bytes[..4].try_into().unwrap()) as f64 / 2147483648.0,
+        "f32le" => f32::from_le_bytes(bytes[..4].try_into().unwrap()) as f64,
+        _ => f64::from_le_bytes(bytes[..8].try_into().unwrap()),
+    }
+}
+
+// Integer encodings saturate values outside [-1, 1).
+pub fn encode_value(value: f64, encoding: &str, bytes: &mut Vec<u8>) {
+    match encoding {
+        "u8" => bytes.push(((value * 128.0).round().clamp(-128.0, 127.0) + 128.0) as u8),
+        "i16le" => bytes.extend_from_slice(&((value * 32768.0).round().clamp(-32768.0, 32767.0) as i16).to_le_bytes()),
+        "i24le" => bytes.extend_from_slice(&((value * 8388608.0).round().clamp(-8388608.0, 8388607.0) as i32).to_le_bytes()[..3]),
+        "i32le" => bytes.extend_from_slice(&((value * 2147483648.0).round().clamp(-2147483648.0, 2147483647.0) as i32).to_le_bytes()),
+        "f32le" => bytes.extend_from_slice(&(value as f32).to_le_bytes()),
+        _ => bytes.extend_from_slice(&value.to_le_bytes()),
+    }
+}
+
+// Sample layout of a WAV file, the samples of the channels are interleaved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavLayout {
+    pub encoding: String,
+    pub channels: usize,
+    pub sample_rate: u32,
+}
+
+// Reads the fmt chunk and locates the data chunk of a RIFF/WAVE file, PCM and IEEE float
+// including their WAVE_FORMAT_EXTENSIBLE form. A data size past the end of the file, as left
+// by an interrupted recording, is cut to the end of the file.
+pub fn parse_wav(bytes: &[u8]) -> Result<(WavLayout, Range<usize>), String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("not a RIFF/WAVE file".to_string());
+    }
+    let mut layout = None;
+    let mut position = 12;
+    while position + 8 <= bytes.len() {
+        let id = &bytes[position..position + 4];
+        let size = u32::from_le_bytes(bytes[position + 4..position + 8].try_into().unwrap()) as usize;
+        let body = position + 8;
+        if id == b"fmt " {
+            if size < 16 || body + 16 > bytes.len() {
+                return Err("truncated fmt chunk".to_string());
+            }
+            let field = |offset: usize| u16::from_le_bytes([bytes[body + offset], bytes[body + offset + 1]]);
+            let mut tag = field(0);
+            // the extensible format carries the actual tag in the first bytes of its subformat
+            if tag == 0xFFFE && size >= 26 && body + 26 <= bytes.len() {
+                tag = field(24);
+            }
+            let bits = field(14);
+            let encoding = match (tag, bits) {
+                (1, 8) => "u8",
+                (1, 16) => "i16le",
+                (1, 24) => "i24le",
+                (1, 32) => "i32le",
+                (3, 32) => "f32le",
+                (3, 64) => "f64le",
+                _ => return Err(format!("unsupported WAV format {} with {} bits", tag, bits)),
+            };
+            let channels = field(2) as usize;
+            if channels == 0 {
+                return Err("WAV file without channels".to_string());
+            }
+            let sample_rate = u32::from_le_bytes(bytes[body + 4..body + 8].try_into().unwrap());
+            layout = Some(WavLayout { encoding: encoding.to_string(), channels, sample_rate });
+        } else if id == b"data" {
+            let layout = layout.ok_or("data chunk before the fmt chunk")?;
+            return Ok((layout, body..body.saturating_add(size).min(bytes.len())));
+        }
+        // chunks are padded to an even size
+        position = body.saturating_add(size).saturating_add(size & 1);
+    }
+    Err("no data chunk".to_string())
+}
+
+// Canonical 44 byte header for `data_size` bytes of samples, sizes beyond 4 GiB saturate.
+pub fn wav_header(layout: &WavLayout, data_size: usize) -> Vec<u8> {
+    let size = encoding_size(&layout.encoding).unwrap_or(8);
+    let tag: u16 = if layout.encoding.starts_with('f') { 3 } else { 1 };
+    let block_align = (size * layout.channels) as u16;
+    let data_size = data_size.min(u32::MAX as usize - 36) as u32;
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(data_size + 36).to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&tag.to_le_bytes());
+    header.extend_from_slice(&(layout.channels as u16).to_le_bytes());
+    header.extend_from_slice(&layout.sample_rate.to_le_bytes());
+    header.extend_from_slice(&(layout.sample_rate * block_align as u32).to_le_bytes());
+    header.extend_from_slice(&block_align.to_le_bytes());
+    header.extend_from_slice(&(8 * size as u16).to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_size.to_le_bytes());
+    header
+}
+
+// Values of one column of a delimited text file. Empty lines and lines starting with '#' are
+// skipped, as is a first line whose field is not a number (the header).
+pub fn parse_csv(text: &str, column: usize, delimiter: char) -> Result<Vec<f64>, String> {
+    let mut values = Vec::new();
+    let lines = text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'));
+    for (k, (number, line)) in lines.enumerate() {
+        let field = line.split(delimiter).nth(column).map(str::trim);
+        match field.map(str::parse::<f64>) {
+            Some(Ok(value)) => values.push(value),
+            _ if k == 0 => {}
+            _ => return Err(format!("line {}: no number in column {}", number + 1, column)),
+        }
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_encodings() {
+        for encoding in ["u8", "i16le", "i24le", "i32le", "f32le", "f64le"] {
+            let mut bytes = Vec::new();
+            for value in [0.5, -0.25, -1.0, 2.0] {
+                encode_value(value, encoding, &mut bytes);
+            }
+            let size = encoding_size(encoding).unwrap();
+            assert_eq!(bytes.len(), 4 * size);
+            let decoded: Vec<f64> = bytes.chunks_exact(size).map(|c| decode_value(c, encoding)).collect();
+            assert_eq!(&decoded[..3], &[0.5, -0.25, -1.0], "{}", encoding);
+            // integers saturate just below full scale
+            assert!(decoded[3] > 0.99 && decoded[3] <= 2.0);
+        }
+    }
+    #[test]
+    fn test_wav_and_csv() {
+        let layout = WavLayout { encoding: "i24le".to_string(), channels: 2, sample_rate: 48000 };
+        let mut bytes = wav_header(&layout, 12);
+        assert_eq!(bytes.len(), 44);
+        bytes.extend_from_slice(&[0; 12]);
+        assert_eq!(parse_wav(&bytes), Ok((layout.clone(), 44..56)));
+        // extra chunk before the data, odd sized and padded, and a data size past the end
+        let mut bytes = wav_header(&layout, 1000);
+        bytes.splice(36..36, b"LIST\x03\x00\x00\x00abc\x00".iter().copied());
+        bytes.extend_from_slice(&[0; 6]);
+        assert_eq!(parse_wav(&bytes), Ok((layout, 56..62)));
+        assert!(parse_wav(b"RIFF\x00\x00\x00\x00WAVE").is_err());
+        let text = "time,value\n0.0,1.5\n\n# comment\n0.1, -2e-3\n";
+        assert_eq!(parse_csv(text, 1, ','), Ok(vec![1.5, -2.0e-3]));
+        assert_eq!(parse_csv("1 2\n3 4\n", 0, ' '), Ok(vec![1.0, 3.0]));
+        assert!(parse_csv("a\n1\nb\n", 0, ',').is_err());
+    }
+}
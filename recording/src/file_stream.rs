@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use memmap2::Mmap;
+use crate::file_format::{WavLayout, decode_value, encode_value, encoding_size, parse_csv, parse_wav, valid_file_format, wav_header};
+use crate::sigmf_stream::replay_indices;
+
+// Writes the input frames to a WAV file, a CSV file with one value per line or a raw binary
+// file. encoding applies to WAV and raw files, the WAV header sizes are filled in on stop. The
+// sample_rate only goes into the WAV header.
+stream_block! {
+    pub struct FileSink {
+        inputs: { "input": Vec<f64> },
+        statics: {
+            "path": String = String::new(),
+            "format": String = "wav".to_string(),
+            "encoding": String = "f32le".to_string(),
+            "sample_rate": f64 = 1.0,
+            "header": String = String::new(),
+        },
+        state: { "samples_written": usize = 0 },
+        fields: { writer: Option<BufWriter<File>> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let path = self.get_statics::<String>("path")?.get_value();
+            let format = self.get_statics::<String>("format")?.get_value();
+            let encoding = self.get_statics::<String>("encoding")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let header = self.get_statics::<String>("header")?.get_value();
+            if path.is_empty() || !valid_file_format(&format) || (format != "csv" && encoding_size(&encoding).is_none())
+                || sample_rate.is_nan() || sample_rate < 1.0 || sample_rate > u32::MAX as f64 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            // the WAV header is written with empty sizes, stop() fills them in
+            let preamble = match format.as_str() {
+                "wav" => wav_header(&WavLayout { encoding, channels: 1, sample_rate: sample_rate.round() as u32 }, 0),
+                "csv" if !header.is_empty() => format!("{}\n", header).into_bytes(),
+                _ => Vec::new(),
+            };
+            let file = File::create(&path).and_then(|file| {
+                let mut writer = BufWriter::new(file);
+                writer.write_all(&preamble)?;
+                Ok(writer)
+            });
+            match file {
+                Ok(writer) => self.writer = Some(writer),
+                Err(error) => {
+                    eprintln!("FileSink {}: cannot create {}: {}", self.name, path, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state_value("samples_written", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let format = self.get_statics::<String>("format")?.get_value();
+            let encoding = self.get_statics::<String>("encoding")?.get_value();
+            let samples_written = self.get_state_value::<usize>("samples_written")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut bytes = Vec::new();
+            let result = {
+                let _lock = self.lock.lock().unwrap();
+                if format == "csv" {
+                    input_signal.iter().for_each(|v| bytes.extend_from_slice(format!("{}\n", v).as_bytes()));
+                } else {
+                    input_signal.iter().for_each(|v| encode_value(*v, &encoding, &mut bytes));
+                }
+                self.writer.as_mut().ok_or(StreamingError::InvalidStateTransition)?.write_all(&bytes)
+            };
+            if let Err(error) = result {
+                eprintln!("FileSink {}: {}", self.name, error);
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            self.set_state_value("samples_written", samples_written + input_signal.len())?;
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            if let Some(writer) = self.writer.take() {
+                let format = self.get_statics::<String>("format")?.get_value();
+                let encoding = self.get_statics::<String>("encoding")?.get_value();
+                let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+                let samples_written = self.get_state_value::<usize>("samples_written")?;
+                let size = samples_written * encoding_size(&encoding).unwrap_or(0);
+                let header = (format == "wav").then(|| wav_header(&WavLayout { encoding, channels: 1, sample_rate: sample_rate.round() as u32 }, size));
+                if let Err(error) = finish_file(writer, header) {
+                    eprintln!("FileSink {}: {}", self.name, error);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// Streams one channel of a WAV file, one column of a CSV file or one channel of an interleaved
+// raw binary file in frames of frame_size samples. With repeat the file is replayed from the
+// start, otherwise the block stops after the last frame.
+stream_block! {
+    pub struct FileSource {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "path": String = String::new(),
+            "format": String = "wav".to_string(),
+            "encoding": String = "f64le".to_string(),
+            "channels": usize = 1,
+            "channel": usize = 0,
+            "column": usize = 0,
+            "delimiter": String = ",".to_string(),
+            "frame_size": usize = 1024,
+            "repeat": bool = false,
+        },
+        state: {
+            "file_encoding": String = String::new(),
+            "file_channels": usize = 1,
+            "sample_rate": f64 = 0.0,
+            "data_start": usize = 0,
+            "position": usize = 0,
+            "end": usize = 0,
+        },
+        fields: { mapping: Option<Mmap> = None, values: Vec<f64> = Vec::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let path = self.get_statics::<String>("path")?.get_value();
+            let format = self.get_statics::<String>("format")?.get_value();
+            let channel = self.get_statics::<usize>("channel")?.get_value();
+            let delimiter = self.get_statics::<String>("delimiter")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            if path.is_empty() || !valid_file_format(&format) || delimiter.chars().count() != 1 || frame_size == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            if format == "csv" {
+                let column = self.get_statics::<usize>("column")?.get_value();
+                let values = std::fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path, e))
+                    .and_then(|text| parse_csv(&text, column, delimiter.chars().next().unwrap_or(',')));
+                match values {
+                    Ok(values) => self.values = values,
+                    Err(error) => {
+                        eprintln!("FileSource {}: {}", self.name, error);
+                        return Err(StreamingError::InvalidStatics)
+                    }
+                }
+                self.mapping = None;
+                self.set_state_value("end", self.values.len())?;
+            } else {
+                // binary files are mapped like the SigMF recordings, only the replayed pages are loaded
+                let mapping = match File::open(&path).and_then(|file| unsafe { Mmap::map(&file) }) {
+                    Ok(mapping) => mapping,
+                    Err(error) => {
+                        eprintln!("FileSource {}: cannot map {}: {}", self.name, path, error);
+                        return Err(StreamingError::InvalidStatics)
+                    }
+                };
+                let layout = if format == "wav" {
+                    parse_wav(&mapping).map(|(layout, data)| (layout.encoding, layout.channels, layout.sample_rate as f64, data))
+                } else {
+                    let encoding = self.get_statics::<String>("encoding")?.get_value();
+                    let channels = self.get_statics::<usize>("channels")?.get_value();
+                    Ok((encoding, channels, 0.0, 0..mapping.len()))
+                };
+                let (encoding, channels, sample_rate, data) = match layout {
+                    Ok(layout) => layout,
+                    Err(error) => {
+                        eprintln!("FileSource {}: {}: {}", self.name, path, error);
+                        return Err(StreamingError::InvalidStatics)
+                    }
+                };
+                let Some(size) = encoding_size(&encoding) else {
+                    return Err(StreamingError::InvalidStatics)
+                };
+                if channels == 0 || channel >= channels {
+                    eprintln!("FileSource {}: channel {} out of the {} channels of {}", self.name, channel, channels, path);
+                    return Err(StreamingError::InvalidStatics)
+                }
+                self.mapping = Some(mapping);
+                self.values = Vec::new();
+                self.set_state_value("file_encoding", encoding)?;
+                self.set_state_value("file_channels", channels)?;
+                self.set_state_value("sample_rate", sample_rate)?;
+                self.set_state_value("data_start", data.start)?;
+                self.set_state_value("end", data.len() / (size * channels))?;
+            }
+            self.set_state_value("position", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let channel = self.get_statics::<usize>("channel")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let repeat = self.get_statics::<bool>("repeat")?.get_value();
+            let encoding = self.get_state_value::<String>("file_encoding")?;
+            let channels = self.get_state_value::<usize>("file_channels")?;
+            let data_start = self.get_state_value::<usize>("data_start")?;
+            let position = self.get_state_value::<usize>("position")?;
+            let end = self.get_state_value::<usize>("end")?;
+            let (indices, next_position) = replay_indices(position, frame_size, 1, 0, end, repeat);
+            if indices.is_empty() {
+                // end of the file
+                self.stop()?;
+                return Ok(());
+            }
+            let output_signal: Vec<f64> = {
+                let _lock = self.lock.lock().unwrap();
+                match self.mapping.as_ref() {
+                    Some(mapping) => {
+                        let size = encoding_size(&encoding).ok_or(StreamingError::InvalidStateTransition)?;
+                        indices.iter().map(|k| {
+                            let offset = data_start + (k * channels + channel) * size;
+                            decode_value(&mapping[offset..offset + size], &encoding)
+                        }).collect()
+                    }
+                    None => indices.iter().map(|k| self.values[*k]).collect(),
+                }
+            };
+            self.set_state_value("position", next_position)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.mapping = None;
+            Ok(())
+        }
+    }
+}
+
+// Flushes the buffered samples and writes `header` over the start of the file, the WAV header
+// with the final sizes.
+fn finish_file(writer: BufWriter<File>, header: Option<Vec<u8>>) -> std::io::Result<()> {
+    let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+    if let Some(header) = header {
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&header)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use num_complex::Complex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SigmfGlobal {
+    #[serde(rename = "core:datatype")]
+    pub datatype: String,
+    #[serde(rename = "core:sample_rate", default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f64>,
+    #[serde(rename = "core:version")]
+    pub version: String,
+    #[serde(rename = "core:description", default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(rename = "core:author", default, skip_serializing_if = "String::is_empty")]
+    pub author: String,
+    #[serde(rename = "core:hw", default, skip_serializing_if = "String::is_empty")]
+    pub hw: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SigmfCapture {
+    #[serde(rename = "core:sample_start")]
+    pub sample_start: u64,
+    #[serde(rename = "core:frequency", default, skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<f64>,
+    #[serde(rename = "core:datetime", default, skip_serializing_if = "Option::is_none")]
+    pub datetime: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SigmfAnnotation {
+    #[serde(rename = "core:sample_start")]
+    pub sample_start: u64,
+    #[serde(rename = "core:sample_count", default, skip_serializing_if = "Option::is_none")]
+    pub sample_count: Option<u64>,
+    #[serde(rename = "core:label", default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
+    #[serde(rename = "core:comment", default, skip_serializing_if = "String::is_empty")]
+    pub comment: String,
+    #[serde(rename = "core:freq_lower_edge", default, skip_serializing_if = "Option::is_none")]
+    pub freq_lower_edge: Option<f64>,
+    #[serde(rename = "core:freq_upper_edge", default, skip_serializing_if = "Option::is_none")]
+    pub freq_upper_edge: Option<f64>,
+}
+
+// Contents of a .sigmf-meta file. Fields outside the core namespace are dropped on load.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SigmfMetadata {
+    pub global: SigmfGlobal,
+    #[serde(default)]
+    pub captures: Vec<SigmfCapture>,
+    #[serde(default)]
+    pub annotations: Vec<SigmfAnnotation>,
+}
+impl SigmfMetadata {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        serde_json::from_str(&text).map_err(|e| format!("cannot parse {}: {}", path, e))
+    }
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+    // Center frequency of the capture segment holding `sample`, captures are sorted by start.
+    pub fn frequency_at(&self, sample: u64) -> Option<f64> {
+        self.captures.iter().rev().find(|c| c.sample_start <= sample).and_then(|c| c.frequency)
+    }
+}
+
+// Meta and data file names of a recording, `base` is the path without the SigMF extension.
+pub fn recording_paths(base: &str) -> (String, String) {
+    (format!("{}.sigmf-meta", base), format!("{}.sigmf-data", base))
+}
+
+// Bytes per complex sample of the supported little endian datatypes.
+pub fn sample_size(datatype: &str) -> Option<usize> {
+    match datatype {
+        "cf64_le" => Some(16),
+        "cf32_le" => Some(8),
+        "ci16_le" => Some(4),
+        "ci8" => Some(2),
+        _ => None,
+    }
+}
+
+// Integer datatypes map the full scale to [-1, 1), values outside are saturated.
+pub fn encode_samples(samples: &[Complex<f64>], datatype: &str, bytes: &mut Vec<u8>) {
+    for sample in samples {
+        for value in [sample.re, sample.im] {
+            match datatype {
+                "cf64_le" => bytes.extend_from_slice(&value.to_le_bytes()),
+                "cf32_le" => bytes.extend_from_slice(&(value as f32).to_le_bytes()),
+                "ci16_le" => bytes.extend_from_slice(&((value * 32768.0).round().clamp(-32768.0, 32767.0) as i16).to_le_bytes()),
+                "ci8" => bytes.push((value * 128.0).round().clamp(-128.0, 127.0) as i8 as u8),
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn decode_samples(bytes: &[u8], datatype: &str) -> Vec<Complex<f64>> {
+    let Some(size) = sample_size(datatype) else {
+        return Vec::new();
+    };
+    let component = size / 2;
+    let values: Vec<f64> = bytes.chunks_exact(component).map(|c| match datatype {
+        "cf64_le" => f64::from_le_bytes(c.try_into().unwrap()),
+        "cf32_le" => f32::from_le_bytes(c.try_into().unwrap()) as f64,
+        "ci16_le" => i16::from_le_bytes(c.try_into().unwrap()) as f64 / 32768.0,
+        _ => c[0] as i8 as f64 / 128.0,
+    }).collect();
+    values.chunks_exact(2).map(|v| Complex::new(v[0], v[1])).collect()
+}
+
+// ISO 8601 UTC timestamp as SigMF expects it, from seconds since the Unix epoch.
+pub fn utc_datetime(seconds: u64) -> String {
+    let days = (seconds / 86400) as i64;
+    let time = seconds % 86400;
+    // civil date from the day count, Howard Hinnant's algorithm
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, time / 3600, time % 3600 / 60, time % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_sigmf_encoding() {
+        let samples = vec![Complex::new(0.5, -0.25), Complex::new(-1.0, 0.999)];
+        for datatype in ["cf64_le", "cf32_le", "ci16_le", "ci8"] {
+            let mut bytes = Vec::new();
+            encode_samples(&samples, datatype, &mut bytes);
+            assert_eq!(bytes.len(), 2 * sample_size(datatype).unwrap());
+            let decoded = decode_samples(&bytes, datatype);
+            assert!((decoded[0] - samples[0]).norm() < 1.0e-2);
+            assert!((decoded[1] - samples[1]).norm() < 1.0e-2);
+        }
+        assert_eq!(utc_datetime(0), "1970-01-01T00:00:00Z");
+        assert_eq!(utc_datetime(1_792_152_000), "2026-10-16T12:00:00Z");
+        let meta = SigmfMetadata {
+            global: SigmfGlobal { datatype: "cf32_le".to_string(), sample_rate: Some(1.0e6), version: "1.0.0".to_string(), ..Default::default() },
+            captures: vec![SigmfCapture { sample_start: 0, frequency: Some(100.0e6), datetime: None },
+                SigmfCapture { sample_start: 1000, frequency: Some(101.0e6), datetime: None }],
+            annotations: Vec::new(),
+        };
+        let text = serde_json::to_string(&meta).unwrap();
+        assert!(text.contains("\"core:sample_rate\":1000000.0"));
+        assert_eq!(serde_json::from_str::<SigmfMetadata>(&text).unwrap(), meta);
+        assert_eq!(meta.frequency_at(999), Some(100.0e6));
+        assert_eq!(meta.frequency_at(1000), Some(101.0e6));
+    }
+}
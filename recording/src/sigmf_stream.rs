@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+use num_complex::Complex;
+use memmap2::Mmap;
+use crate::sigmf::{SigmfAnnotation, SigmfCapture, SigmfGlobal, SigmfMetadata, decode_samples, encode_samples, recording_paths, sample_size, utc_datetime};
+
+stream_block! {
+    pub struct SigmfSink {
+        inputs: { "input": Vec<Complex<f64>> },
+        statics: {
+            "path": String = String::new(),
+            "datatype": String = "cf32_le".to_string(),
+            "sample_rate": f64 = 1.0,
+            "center_frequency": f64 = 0.0,
+            "description": String = String::new(),
+            "author": String = String::new(),
+            "hw": String = String::new(),
+            "annotations": String = String::new(),
+        },
+        state: { "samples_written": usize = 0 },
+        fields: { writer: Option<BufWriter<File>> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let path = self.get_statics::<String>("path")?.get_value();
+            let datatype = self.get_statics::<String>("datatype")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let annotations = self.get_statics::<String>("annotations")?.get_value();
+            if path.is_empty() || sample_size(&datatype).is_none() || sample_rate.is_nan() || sample_rate <= 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            // annotations are given as a JSON array of SigMF annotation objects
+            let annotations = if annotations.is_empty() {
+                Vec::new()
+            } else {
+                match serde_json::from_str::<Vec<SigmfAnnotation>>(&annotations) {
+                    Ok(annotations) => annotations,
+                    Err(error) => {
+                        eprintln!("SigmfSink {}: invalid annotations: {}", self.name, error);
+                        return Err(StreamingError::InvalidStatics)
+                    }
+                }
+            };
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            let metadata = SigmfMetadata {
+                global: SigmfGlobal {
+                    datatype,
+                    sample_rate: Some(sample_rate),
+                    version: "1.0.0".to_string(),
+                    description: self.get_statics::<String>("description")?.get_value(),
+                    author: self.get_statics::<String>("author")?.get_value(),
+                    hw: self.get_statics::<String>("hw")?.get_value(),
+                },
+                captures: vec![SigmfCapture {
+                    sample_start: 0,
+                    frequency: Some(self.get_statics::<f64>("center_frequency")?.get_value()),
+                    datetime: Some(utc_datetime(now)),
+                }],
+                annotations,
+            };
+            let (meta_path, data_path) = recording_paths(&path);
+            let file = metadata.save(&meta_path).and_then(|_| File::create(&data_path).map_err(|e| format!("cannot create {}: {}", data_path, e)));
+            match file {
+                Ok(file) => self.writer = Some(BufWriter::new(file)),
+                Err(error) => {
+                    eprintln!("SigmfSink {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state_value("samples_written", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let datatype = self.get_statics::<String>("datatype")?.get_value();
+            let samples_written = self.get_state_value::<usize>("samples_written")?;
+            let input_signal = self.recv_input::<Vec<Complex<f64>>>("input")?;
+            let mut bytes = Vec::new();
+            let result = {
+                let _lock = self.lock.lock().unwrap();
+                encode_samples(&input_signal, &datatype, &mut bytes);
+                self.writer.as_mut().ok_or(StreamingError::InvalidStateTransition)?.write_all(&bytes)
+            };
+            if let Err(error) = result {
+                eprintln!("SigmfSink {}: {}", self.name, error);
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            self.set_state_value("samples_written", samples_written + input_signal.len())?;
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            if let Some(mut writer) = self.writer.take()
+                && let Err(error) = writer.flush() {
+                eprintln!("SigmfSink {}: {}", self.name, error);
+            }
+            Ok(())
+        }
+    }
+}
+
+stream_block! {
+    pub struct SigmfSource {
+        outputs: { "output": Vec<Complex<f64>>, "center_frequency": f64 },
+        statics: {
+            "path": String = String::new(),
+            "frame_size": usize = 1024,
+            "repeat": bool = false,
+            "start_offset": usize = 0,
+            "stop_offset": usize = 0,
+            "stride": usize = 1,
+        },
+        state: {
+            "metadata": SigmfMetadata = SigmfMetadata::default(),
+            "position": usize = 0,
+            "end": usize = 0,
+        },
+        fields: { mapping: Option<Mmap> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let path = self.get_statics::<String>("path")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let start_offset = self.get_statics::<usize>("start_offset")?.get_value();
+            let stop_offset = self.get_statics::<usize>("stop_offset")?.get_value();
+            let stride = self.get_statics::<usize>("stride")?.get_value();
+            if path.is_empty() || frame_size == 0 || stride == 0 || (stop_offset > 0 && stop_offset <= start_offset) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let (meta_path, data_path) = recording_paths(&path);
+            let metadata = match SigmfMetadata::load(&meta_path) {
+                Ok(metadata) if sample_size(&metadata.global.datatype).is_some() => metadata,
+                Ok(metadata) => {
+                    eprintln!("SigmfSource {}: unsupported datatype {}", self.name, metadata.global.datatype);
+                    return Err(StreamingError::InvalidStatics)
+                }
+                Err(error) => {
+                    eprintln!("SigmfSource {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            };
+            // the data file is mapped rather than read, only the pages of the replayed frames are
+            // loaded so captures larger than the memory can be replayed
+            let mapping = File::open(&data_path).and_then(|file| unsafe { Mmap::map(&file) });
+            let mapping = match mapping {
+                Ok(mapping) => mapping,
+                Err(error) => {
+                    eprintln!("SigmfSource {}: cannot map {}: {}", self.name, data_path, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            };
+            // offsets are in samples, a stop_offset of zero replays up to the end of the file
+            let size = sample_size(&metadata.global.datatype).ok_or(StreamingError::InvalidStatics)?;
+            let samples = mapping.len() / size;
+            let end = if stop_offset == 0 { samples } else { stop_offset.min(samples) };
+            if start_offset >= end {
+                eprintln!("SigmfSource {}: start_offset {} is past the {} samples of {}", self.name, start_offset, end, data_path);
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.mapping = Some(mapping);
+            self.set_state_value("metadata", metadata)?;
+            self.set_state_value("position", start_offset)?;
+            self.set_state_value("end", end)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let repeat = self.get_statics::<bool>("repeat")?.get_value();
+            let start_offset = self.get_statics::<usize>("start_offset")?.get_value();
+            let stride = self.get_statics::<usize>("stride")?.get_value();
+            let metadata = self.get_state_value::<SigmfMetadata>("metadata")?;
+            let position = self.get_state_value::<usize>("position")?;
+            let end = self.get_state_value::<usize>("end")?;
+            let datatype = metadata.global.datatype.clone();
+            let size = sample_size(&datatype).ok_or(StreamingError::InvalidStatics)?;
+            let (indices, next_position) = replay_indices(position, frame_size, stride, start_offset, end, repeat);
+            if indices.is_empty() {
+                // end of the recording
+                self.stop()?;
+                return Ok(());
+            }
+            let mut bytes = Vec::with_capacity(indices.len() * size);
+            {
+                let _lock = self.lock.lock().unwrap();
+                let mapping = self.mapping.as_ref().ok_or(StreamingError::InvalidStateTransition)?;
+                indices.iter().for_each(|k| bytes.extend_from_slice(&mapping[k * size..(k + 1) * size]));
+            }
+            let output_signal = decode_samples(&bytes, &datatype);
+            let frequency = metadata.frequency_at(indices[0] as u64).unwrap_or(0.0);
+            self.set_state_value("position", next_position)?;
+            self.send_output::<f64>("center_frequency", frequency)?;
+            self.send_output::<Vec<Complex<f64>>>("output", output_signal)?;
+            Ok(())
+        }
+        fn teardown(&mut self) -> Result<(), StreamingError> {
+            self.mapping = None;
+            Ok(())
+        }
+    }
+}
+
+// Sample indices of the next frame, `count` samples `stride` apart from `position` inside
+// [start, end). With `repeat` the replay wraps to `start`, otherwise the frame stops short at
+// `end`. Returns the indices and the position following the frame.
+pub fn replay_indices(mut position: usize, count: usize, stride: usize, start: usize, end: usize, repeat: bool) -> (Vec<usize>, usize) {
+    let mut indices = Vec::with_capacity(count);
+    while indices.len() < count {
+        if position >= end {
+            if !repeat || start >= end {
+                break;
+            }
+            position = start;
+        }
+        indices.push(position);
+        position += stride;
+    }
+    (indices, position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_replay_indices() {
+        assert_eq!(replay_indices(2, 3, 2, 2, 7, false), (vec![2, 4, 6], 8));
+        assert_eq!(replay_indices(8, 3, 2, 2, 7, false), (vec![], 8));
+        assert_eq!(replay_indices(5, 4, 1, 2, 7, false), (vec![5, 6], 7));
+        assert_eq!(replay_indices(5, 4, 1, 2, 7, true), (vec![5, 6, 2, 3], 4));
+        assert_eq!(replay_indices(6, 3, 3, 0, 8, true), (vec![6, 0, 3], 6));
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct BitPack {
+        inputs: { "input": Vec<u8> },
+        outputs: { "output": Vec<u8> },
+        statics: { "msb_first": bool = true },
+        state: { "pending_bits": Vec<u8> = Vec::<u8>::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("pending_bits", Vec::<u8>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let msb_first = self.get_statics::<bool>("msb_first")?.get_value();
+            let mut bits = self.get_state_value::<Vec<u8>>("pending_bits")?;
+            let input_bits = self.recv_input::<Vec<u8>>("input")?;
+            let (bytes, remainder) = {
+                let _lock = self.lock.lock().unwrap();
+                bits.extend_from_slice(&input_bits);
+                pack_bits(&bits, msb_first)
+            };
+            self.set_state_value("pending_bits", remainder)?;
+            if !bytes.is_empty() {
+                self.send_output::<Vec<u8>>("output", bytes)?;
+            }
+            Ok(())
+        }
+    }
+}
+stream_block! {
+    pub struct BitUnpack {
+        inputs: { "input": Vec<u8> },
+        outputs: { "output": Vec<u8> },
+        statics: { "msb_first": bool = true },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let msb_first = self.get_statics::<bool>("msb_first")?.get_value();
+            let input_bytes = self.recv_input::<Vec<u8>>("input")?;
+            let bits = {
+                let _lock = self.lock.lock().unwrap();
+                unpack_bits(&input_bytes, msb_first)
+            };
+            self.send_output::<Vec<u8>>("output", bits)?;
+            Ok(())
+        }
+    }
+}
+
+// Packs one bit per element (any non zero value is a 1) into bytes. The bits that do not
+// fill a whole byte are returned so that they can be carried over to the next frame.
+pub fn pack_bits(bits: &[u8], msb_first: bool) -> (Vec<u8>, Vec<u8>) {
+    let chunks = bits.chunks_exact(8);
+    let remainder = chunks.remainder().to_vec();
+    let bytes = chunks.map(|chunk| {
+        chunk.iter().enumerate().fold(0u8, |byte, (k, bit)| {
+            let position = if msb_first { 7 - k } else { k };
+            byte | (((*bit != 0) as u8) << position)
+        })
+    }).collect();
+    (bytes, remainder)
+}
+
+pub fn unpack_bits(bytes: &[u8], msb_first: bool) -> Vec<u8> {
+    bytes.iter().flat_map(|byte| {
+        (0..8).map(move |k| {
+            let position = if msb_first { 7 - k } else { k };
+            (byte >> position) & 1
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_pack_round_trip() {
+        let bits = vec![1, 0, 1, 1, 0, 0, 0, 1, 1, 1, 0];
+        let (bytes, remainder) = pack_bits(&bits, true);
+        assert_eq!(bytes, vec![0b1011_0001]);
+        assert_eq!(remainder, vec![1, 1, 0]);
+        let (bytes, _) = pack_bits(&bits, false);
+        assert_eq!(bytes, vec![0b1000_1101]);
+        assert_eq!(unpack_bits(&bytes, false), bits[..8].to_vec());
+        assert_eq!(unpack_bits(&[0b1011_0001], true), bits[..8].to_vec());
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use utils::math::matrix::Matrix;
+
+// Gain matrices of the named mixes, M output rows by N input columns. "balance" attenuates one
+// side of a stereo pair, -1 keeps the left channel only and 1 the right one. "mid_side" encodes
+// L/R to M = (L + R) / 2, S = (L - R) / 2 and "left_right" decodes it back.
+pub fn preset_gain(preset: &str, balance: f64) -> Option<Vec<Vec<f64>>> {
+    match preset {
+        "downmix" => Some(vec![vec![0.5, 0.5]]),
+        "balance" if (-1.0..=1.0).contains(&balance) => Some(vec![vec![(1.0 - balance).min(1.0), 0.0], vec![0.0, (1.0 + balance).min(1.0)]]),
+        "mid_side" => Some(vec![vec![0.5, 0.5], vec![0.5, -0.5]]),
+        "left_right" => Some(vec![vec![1.0, 1.0], vec![1.0, -1.0]]),
+        _ => None,
+    }
+}
+
+// Mixes interleaved frames of gain[0].len() channels into frames of gain.len() channels.
+pub fn mix_channels(input: &[f64], gain: &[Vec<f64>]) -> Vec<f64> {
+    let inputs = gain.first().map_or(0, |row| row.len());
+    input.chunks_exact(inputs.max(1))
+        .flat_map(|frame| gain.iter().map(move |row| row.iter().zip(frame).map(|(g, x)| g * x).sum::<f64>()))
+        .collect()
+}
+
+// Maps interleaved frames of N channels to M channels through an M x N gain matrix, set directly
+// or by a preset: "downmix" (stereo to mono), "balance" (stereo, after the balance static),
+// "mid_side" (L/R to M/S) or "left_right" (M/S back to L/R).
+stream_block! {
+    pub struct ChannelMap {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "gain": Matrix<f64> = Matrix::identity(2),
+            "preset": String = String::new(),
+            "balance": f64 = 0.0,
+        },
+        state: { "gain": Vec<Vec<f64>> = Vec::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let preset = self.get_statics::<String>("preset")?.get_value();
+            let balance = self.get_statics::<f64>("balance")?.get_value();
+            let gain = if preset.is_empty() {
+                self.get_statics::<Matrix<f64>>("gain")?.get_value().to_vec()
+            } else {
+                preset_gain(&preset, balance).ok_or(StreamingError::InvalidStatics)?
+            };
+            if gain.is_empty() || gain[0].is_empty() || gain.iter().any(|row| row.len() != gain[0].len() || row.iter().any(|g| !g.is_finite())) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("gain", gain)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let gain = self.get_state_value::<Vec<Vec<f64>>>("gain")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            if !input_signal.len().is_multiple_of(gain[0].len()) {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let output_signal;
+            {
+                let _lock = self.lock.lock().unwrap();
+                output_signal = mix_channels(&input_signal, &gain);
+            }
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_mix_channels() {
+        let stereo = [1.0, 0.0, 0.5, 0.5, 0.25, -0.25];
+        assert_eq!(mix_channels(&stereo, &preset_gain("downmix", 0.0).unwrap()), vec![0.5, 0.5, 0.0]);
+        assert_eq!(mix_channels(&stereo, &preset_gain("balance", 0.5).unwrap()), vec![0.5, 0.0, 0.25, 0.5, 0.125, -0.25]);
+        // mid/side and back is the identity
+        let mid_side = mix_channels(&stereo, &preset_gain("mid_side", 0.0).unwrap());
+        assert_eq!(mid_side, vec![0.5, 0.5, 0.5, 0.0, 0.0, 0.25]);
+        assert_eq!(mix_channels(&mid_side, &preset_gain("left_right", 0.0).unwrap()), stereo.to_vec());
+        // mono to three channels
+        assert_eq!(mix_channels(&[1.0, 2.0], &[vec![1.0], vec![0.5], vec![-1.0]]), vec![1.0, 0.5, -1.0, 2.0, 1.0, -2.0]);
+        assert!(preset_gain("balance", 1.5).is_none());
+    }
+}
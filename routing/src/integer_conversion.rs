@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct IntegerToFloat {
+        inputs: {
+            "input_u8": Vec<u8>,
+            "input_u16": Vec<u16>,
+            "input_i16": Vec<i16>,
+            "input_i32": Vec<i32>,
+        },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "format": String = "i16".to_string(),
+            "scale": f64 = 1.0,
+            "offset": f64 = 0.0,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let format = self.get_statics::<String>("format")?.get_value();
+            let scale = self.get_statics::<f64>("scale")?.get_value();
+            if integer_range(&format).is_none() || !scale.is_finite() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let format = self.get_statics::<String>("format")?.get_value();
+            let scale = self.get_statics::<f64>("scale")?.get_value();
+            let offset = self.get_statics::<f64>("offset")?.get_value();
+            let codes: Vec<i64> = match format.as_str() {
+                "u8" => self.recv_input::<Vec<u8>>("input_u8")?.into_iter().map(i64::from).collect(),
+                "u16" => self.recv_input::<Vec<u16>>("input_u16")?.into_iter().map(i64::from).collect(),
+                "i16" => self.recv_input::<Vec<i16>>("input_i16")?.into_iter().map(i64::from).collect(),
+                "i32" => self.recv_input::<Vec<i32>>("input_i32")?.into_iter().map(i64::from).collect(),
+                _ => return Err(StreamingError::InvalidStatics),
+            };
+            let output_signal = {
+                let _lock = self.lock.lock().unwrap();
+                codes.iter().map(|code| *code as f64 * scale + offset).collect()
+            };
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+stream_block! {
+    pub struct FloatToInteger {
+        inputs: { "input": Vec<f64> },
+        outputs: {
+            "output_u8": Vec<u8>,
+            "output_u16": Vec<u16>,
+            "output_i16": Vec<i16>,
+            "output_i32": Vec<i32>,
+        },
+        statics: {
+            "format": String = "i16".to_string(),
+            "scale": f64 = 1.0,
+            "offset": f64 = 0.0,
+        },
+        state: { "saturated": usize = 0 },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let format = self.get_statics::<String>("format")?.get_value();
+            let scale = self.get_statics::<f64>("scale")?.get_value();
+            if integer_range(&format).is_none() || !scale.is_finite() || scale == 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("saturated", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let format = self.get_statics::<String>("format")?.get_value();
+            let scale = self.get_statics::<f64>("scale")?.get_value();
+            let offset = self.get_statics::<f64>("offset")?.get_value();
+            let range = integer_range(&format).ok_or(StreamingError::InvalidStatics)?;
+            let mut saturated = self.get_state_value::<usize>("saturated")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let codes: Vec<i64> = {
+                let _lock = self.lock.lock().unwrap();
+                input_signal.iter().map(|value| {
+                    let (code, clipped) = quantize(*value, scale, offset, range);
+                    saturated += clipped as usize;
+                    code
+                }).collect()
+            };
+            self.set_state_value("saturated", saturated)?;
+            // the codes are already inside the format range, the narrowing casts are lossless
+            match format.as_str() {
+                "u8" => self.send_output::<Vec<u8>>("output_u8", codes.iter().map(|c| *c as u8).collect())?,
+                "u16" => self.send_output::<Vec<u16>>("output_u16", codes.iter().map(|c| *c as u16).collect())?,
+                "i16" => self.send_output::<Vec<i16>>("output_i16", codes.iter().map(|c| *c as i16).collect())?,
+                "i32" => self.send_output::<Vec<i32>>("output_i32", codes.iter().map(|c| *c as i32).collect())?,
+                _ => return Err(StreamingError::InvalidStatics),
+            }
+            Ok(())
+        }
+    }
+}
+
+// Range of the integer formats, only the port matching the format is read or written.
+pub fn integer_range(format: &str) -> Option<(i64, i64)> {
+    match format {
+        "u8" => Some((u8::MIN as i64, u8::MAX as i64)),
+        "u16" => Some((u16::MIN as i64, u16::MAX as i64)),
+        "i16" => Some((i16::MIN as i64, i16::MAX as i64)),
+        "i32" => Some((i32::MIN as i64, i32::MAX as i64)),
+        _ => None,
+    }
+}
+
+// Inverse of value = code * scale + offset, rounded and saturated to the format range.
+// The flag is set when the value had to be clipped.
+pub fn quantize(value: f64, scale: f64, offset: f64, range: (i64, i64)) -> (i64, bool) {
+    let code = ((value - offset) / scale).round();
+    if code.is_nan() {
+        return (0, true);
+    }
+    if code < range.0 as f64 {
+        (range.0, true)
+    } else if code > range.1 as f64 {
+        (range.1, true)
+    } else {
+        (code as i64, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_quantize() {
+        let range = integer_range("i16").unwrap();
+        assert_eq!(quantize(0.5, 1.0 / 32768.0, 0.0, range), (16384, false));
+        assert_eq!(quantize(1.0, 1.0 / 32768.0, 0.0, range), (32767, true));
+        assert_eq!(quantize(-2.0, 1.0 / 32768.0, 0.0, range), (-32768, true));
+        let range = integer_range("u16").unwrap();
+        assert_eq!(quantize(0.0, 10.0 / 65536.0, -5.0, range), (32768, false));
+        assert!(integer_range("i64").is_none());
+    }
+}
@@ -0,0 +1,143 @@
+pub mod synchronizer;
+pub mod matrix_conversion;
+pub mod probe;
+pub mod scope_sink;
+pub mod unit_delay;
+pub mod bit_packing;
+pub mod integer_conversion;
+pub mod channel_map;
+use std::ffi::c_char;
+use data_model::modules::{Version,ModuleStructFFI};
+use processor_engine::stream_processor::StreamProcessor;
+use processor_engine::ffi::{TraitObjectRepr, export_stream_processor, get_error_return};
+#[unsafe(no_mangle)]
+pub static MODULE: ModuleStructFFI  = ModuleStructFFI {
+    name: b"Signal routing\0".as_ptr() as *const c_char,
+    description: b"The library provides blocks to route, align and bundle streams.\0".as_ptr() as *const c_char,
+    authors: b"Sofia Silvestri\0".as_ptr() as *const c_char,
+    release_date: b"2026/10/16\0".as_ptr() as *const c_char,
+    version: Version{ major: 0,minor: 1,build: 0},
+    dependencies: std::ptr::null(),
+    dependency_number: 0,
+    provides: [b"Synchronizer\0".as_ptr() as *const c_char,
+        b"VectorsToMatrix\0".as_ptr() as *const c_char,
+        b"MatrixToVectors\0".as_ptr() as *const c_char,
+        b"Probe\0".as_ptr() as *const c_char,
+        b"ScopeSink\0".as_ptr() as *const c_char,
+        b"UnitDelay\0".as_ptr() as *const c_char,
+        b"BitPack\0".as_ptr() as *const c_char,
+        b"BitUnpack\0".as_ptr() as *const c_char,
+        b"IntegerToFloat\0".as_ptr() as *const c_char,
+        b"FloatToInteger\0".as_ptr() as *const c_char,
+        b"ChannelMap\0".as_ptr() as *const c_char].as_ptr(),
+    provides_lengths: 11,
+};
+/// Creates the block `proc_block` named `block_name`, both passed by the host as UTF-8 bytes.
+///
+/// # Safety
+/// A non null `proc_block` and `block_name` must point to `proc_block_len` and `block_name_len`
+/// readable bytes that stay valid while the block exists.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_processor_modules(proc_block: *const u8, 
+    proc_block_len: usize, 
+    block_name: *const u8, 
+    block_name_len: usize) -> TraitObjectRepr {
+    let proc_block_str = block_template::ffi_name!(proc_block, proc_block_len);
+    let block_name_str = block_template::ffi_name!(block_name, block_name_len);
+    let proc: Box<dyn StreamProcessor>;
+    match proc_block_str {
+        "Synchronizer" => {
+            proc = Box::new(synchronizer::Synchronizer::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "VectorsToMatrix" => {
+            proc = Box::new(matrix_conversion::VectorsToMatrix::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "MatrixToVectors" => {
+            proc = Box::new(matrix_conversion::MatrixToVectors::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Probe" => {
+            proc = Box::new(probe::Probe::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "ScopeSink" => {
+            proc = Box::new(scope_sink::ScopeSink::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "UnitDelay" => {
+            proc = Box::new(unit_delay::UnitDelay::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "BitPack" => {
+            proc = Box::new(bit_packing::BitPack::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "BitUnpack" => {
+            proc = Box::new(bit_packing::BitUnpack::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "IntegerToFloat" => {
+            proc = Box::new(integer_conversion::IntegerToFloat::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "FloatToInteger" => {
+            proc = Box::new(integer_conversion::FloatToInteger::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "ChannelMap" => {
+            proc = Box::new(channel_map::ChannelMap::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        _ => {
+            eprintln!("Processor block {} not found", proc_block_str);
+            get_error_return(1)
+        }
+    }
+}
+
+/// Copies the JSON encoded snapshot of a probe (array of frames, oldest first) into `buffer`.
+/// Returns the full encoded length, the copy only happens when it fits in `capacity`, so the
+/// host can retry with a larger buffer. Returns 0 when no probe has that name or the name is
+/// not valid UTF-8.
+///
+/// # Safety
+/// A non null `probe_name` must point to `probe_name_len` readable bytes, a non null `buffer` to
+/// `capacity` writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn probe_snapshot(probe_name: *const u8,
+    probe_name_len: usize,
+    buffer: *mut u8,
+    capacity: usize) -> usize {
+    let Ok(probe_name_str) = (unsafe { block_template::ffi_name(probe_name, probe_name_len) }) else {
+        return 0;
+    };
+    let Some(frames) = probe::snapshot(probe_name_str) else {
+        return 0;
+    };
+    let encoded = serde_json::to_vec(&frames).unwrap_or_default();
+    if encoded.len() <= capacity && !buffer.is_null() {
+        unsafe {
+            std::ptr::copy_nonoverlapping(encoded.as_ptr(), buffer, encoded.len());
+        }
+    }
+    encoded.len()
+}
+
+// Schema of every block get_processor_modules creates, keyed by block type, see stream_block!.
+pub fn block_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "Synchronizer": synchronizer::Synchronizer::schema(),
+        "VectorsToMatrix": matrix_conversion::VectorsToMatrix::schema(),
+        "MatrixToVectors": matrix_conversion::MatrixToVectors::schema(),
+        "Probe": probe::Probe::schema(),
+        "ScopeSink": scope_sink::ScopeSink::schema(),
+        "UnitDelay": unit_delay::UnitDelay::schema(),
+        "BitPack": bit_packing::BitPack::schema(),
+        "BitUnpack": bit_packing::BitUnpack::schema(),
+        "IntegerToFloat": integer_conversion::IntegerToFloat::schema(),
+        "FloatToInteger": integer_conversion::FloatToInteger::schema(),
+        "ChannelMap": channel_map::ChannelMap::schema(),
+    })
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use utils::math::matrix::Matrix;
+
+pub const MAX_PORTS: usize = 8;
+const INPUT_NAMES: [&str; MAX_PORTS] = ["input_0", "input_1", "input_2", "input_3", "input_4", "input_5", "input_6", "input_7"];
+const OUTPUT_NAMES: [&str; MAX_PORTS] = ["output_0", "output_1", "output_2", "output_3", "output_4", "output_5", "output_6", "output_7"];
+
+stream_block! {
+    pub struct VectorsToMatrix {
+        inputs: {
+            "input_0": Vec<f64>,
+            "input_1": Vec<f64>,
+            "input_2": Vec<f64>,
+            "input_3": Vec<f64>,
+            "input_4": Vec<f64>,
+            "input_5": Vec<f64>,
+            "input_6": Vec<f64>,
+            "input_7": Vec<f64>,
+        },
+        outputs: { "output": Matrix<f64> },
+        statics: {
+            "mode": String = "channels".to_string(),
+            "input_number": usize = 2,
+            "frame_count": usize = 1,
+        },
+        state: { "rows": Vec<Vec<f64>> = Vec::<Vec<f64>>::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            let input_number = self.get_statics::<usize>("input_number")?.get_value();
+            let frame_count = self.get_statics::<usize>("frame_count")?.get_value();
+            if !valid_mode(&mode) || input_number == 0 || input_number > MAX_PORTS || frame_count == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("rows", Vec::<Vec<f64>>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            let input_number = self.get_statics::<usize>("input_number")?.get_value();
+            let frame_count = self.get_statics::<usize>("frame_count")?.get_value();
+            let mut rows = self.get_state_value::<Vec<Vec<f64>>>("rows")?;
+            if mode == "channels" {
+                for input_name in INPUT_NAMES.iter().take(input_number) {
+                    rows.push(self.recv_input::<Vec<f64>>(input_name)?);
+                }
+            } else {
+                rows.push(self.recv_input::<Vec<f64>>(INPUT_NAMES[0])?);
+            }
+            let expected = if mode == "channels" { input_number } else { frame_count };
+            if rows.iter().any(|row| row.len() != rows[0].len()) {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            if rows.len() < expected {
+                self.set_state_value("rows", rows)?;
+                return Ok(());
+            }
+            let matrix;
+            {
+                let _lock = self.lock.lock().unwrap();
+                matrix = Matrix::from_vec(rows);
+            }
+            self.set_state_value("rows", Vec::<Vec<f64>>::new())?;
+            self.send_output::<Matrix<f64>>("output", matrix)?;
+            Ok(())
+        }
+    }
+}
+stream_block! {
+    pub struct MatrixToVectors {
+        inputs: { "input": Matrix<f64> },
+        outputs: {
+            "output_0": Vec<f64>,
+            "output_1": Vec<f64>,
+            "output_2": Vec<f64>,
+            "output_3": Vec<f64>,
+            "output_4": Vec<f64>,
+            "output_5": Vec<f64>,
+            "output_6": Vec<f64>,
+            "output_7": Vec<f64>,
+        },
+        statics: {
+            "mode": String = "channels".to_string(),
+            "output_number": usize = 2,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            let output_number = self.get_statics::<usize>("output_number")?.get_value();
+            if !valid_mode(&mode) || output_number == 0 || output_number > MAX_PORTS {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            let output_number = self.get_statics::<usize>("output_number")?.get_value();
+            let matrix = self.recv_input::<Matrix<f64>>("input")?;
+            if mode == "channels" && matrix.rows != output_number {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let rows;
+            {
+                let _lock = self.lock.lock().unwrap();
+                rows = matrix.to_vec();
+            }
+            for (index, row) in rows.into_iter().enumerate() {
+                let output_name = if mode == "channels" { OUTPUT_NAMES[index] } else { OUTPUT_NAMES[0] };
+                self.send_output::<Vec<f64>>(output_name, row)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// In "channels" mode each row of the matrix is one port, in "frames" mode each row is one
+// successive frame of the first port.
+fn valid_mode(mode: &str) -> bool {
+    mode == "channels" || mode == "frames"
+}
+
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+stream_block! {
+    pub struct Probe {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: { "depth": usize = 8 },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let depth = self.get_statics::<usize>("depth")?.get_value();
+            if depth == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            ProbeRegistry::global().clear(self.name);
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let depth = self.get_statics::<usize>("depth")?.get_value();
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            ProbeRegistry::global().record(self.name, input_signal.clone(), depth);
+            self.send_output::<Vec<f64>>("output", input_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// Last frames seen by every probe, keyed by block name. Shared between the processing threads
+// and whoever queries it, each probe only ever writes its own entry.
+pub struct ProbeRegistry {
+    frames: Mutex<HashMap<String, VecDeque<Vec<f64>>>>,
+}
+impl ProbeRegistry {
+    pub fn new() -> Self {
+        ProbeRegistry { frames: Mutex::new(HashMap::new()) }
+    }
+    pub fn global() -> &'static ProbeRegistry {
+        static REGISTRY: OnceLock<ProbeRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(ProbeRegistry::new)
+    }
+    pub fn record(&self, probe: &str, frame: Vec<f64>, depth: usize) {
+        let mut frames = self.frames.lock().unwrap();
+        let history = frames.entry(probe.to_string()).or_default();
+        history.push_back(frame);
+        while history.len() > depth {
+            history.pop_front();
+        }
+    }
+    pub fn clear(&self, probe: &str) {
+        self.frames.lock().unwrap().insert(probe.to_string(), VecDeque::new());
+    }
+    // Oldest frame first, None if no probe with that name was initialized.
+    pub fn snapshot(&self, probe: &str) -> Option<Vec<Vec<f64>>> {
+        self.frames.lock().unwrap().get(probe).map(|history| history.iter().cloned().collect())
+    }
+    pub fn probes(&self) -> Vec<String> {
+        self.frames.lock().unwrap().keys().cloned().collect()
+    }
+}
+impl Default for ProbeRegistry {
+    fn default() -> Self {
+        ProbeRegistry::new()
+    }
+}
+
+pub fn snapshot(probe: &str) -> Option<Vec<Vec<f64>>> {
+    ProbeRegistry::global().snapshot(probe)
+}
+
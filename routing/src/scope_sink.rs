@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+stream_block! {
+    pub struct ScopeSink {
+        inputs: { "input": Vec<f64> },
+        statics: {
+            "sample_rate": f64 = 1000.0,
+            "display_rate": f64 = 100.0,
+            "mode": String = "minmax".to_string(),
+            "history": usize = 1000,
+        },
+        state: { "decimator": ScopeDecimator = ScopeDecimator::default() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let display_rate = self.get_statics::<f64>("display_rate")?.get_value();
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            let history = self.get_statics::<usize>("history")?.get_value();
+            if !(sample_rate > 0.0 && display_rate > 0.0) || (mode != "minmax" && mode != "decimate") || history == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let factor = ((sample_rate / display_rate).ceil() as usize).max(1);
+            self.set_state_value("decimator", ScopeDecimator::new(factor))?;
+            ScopeRegistry::global().clear(self.name);
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let minmax = self.get_statics::<String>("mode")?.get_value() == "minmax";
+            let history = self.get_statics::<usize>("history")?.get_value();
+            let mut decimator = self.get_state_value::<ScopeDecimator>("decimator")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let trace;
+            {
+                let _lock = self.lock.lock().unwrap();
+                trace = decimator.process(&input_signal, minmax, sample_rate);
+            }
+            self.set_state_value("decimator", decimator)?;
+            if !trace.minimum.is_empty() {
+                ScopeRegistry::global().push(self.name, &trace, history);
+            }
+            Ok(())
+        }
+    }
+}
+
+// Display points produced from one input frame. In "minmax" mode each point covers a bucket of
+// input samples and keeps its extremes so spikes stay visible, in "decimate" mode minimum and
+// maximum both hold the first sample of the bucket.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScopeTrace {
+    pub start_time: f64,
+    pub point_duration: f64,
+    pub minimum: Vec<f64>,
+    pub maximum: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScopeDecimator {
+    factor: usize,
+    position: usize,
+    count: usize,
+    minimum: f64,
+    maximum: f64,
+}
+impl ScopeDecimator {
+    pub fn new(factor: usize) -> Self {
+        ScopeDecimator { factor, ..ScopeDecimator::default() }
+    }
+    pub fn process(&mut self, signal: &[f64], minmax: bool, sample_rate: f64) -> ScopeTrace {
+        let mut trace = ScopeTrace {
+            start_time: (self.position - self.count) as f64 / sample_rate,
+            point_duration: self.factor as f64 / sample_rate,
+            ..ScopeTrace::default()
+        };
+        for &x in signal {
+            if self.count == 0 {
+                self.minimum = x;
+                self.maximum = x;
+            } else if minmax {
+                self.minimum = self.minimum.min(x);
+                self.maximum = self.maximum.max(x);
+            }
+            self.count += 1;
+            self.position += 1;
+            if self.count == self.factor {
+                trace.minimum.push(self.minimum);
+                trace.maximum.push(self.maximum);
+                self.count = 0;
+            }
+        }
+        trace
+    }
+}
+
+type ScopeCallback = Box<dyn Fn(&str, &ScopeTrace) + Send + Sync>;
+
+// Delivery side of the scopes: callbacks registered for a scope name are invoked from the
+// processing thread, the last `history` points of every scope are also kept for polling.
+pub struct ScopeRegistry {
+    callbacks: Mutex<Vec<(String, ScopeCallback)>>,
+    buffers: Mutex<HashMap<String, ScopeTrace>>,
+}
+impl ScopeRegistry {
+    pub fn new() -> Self {
+        ScopeRegistry { callbacks: Mutex::new(Vec::new()), buffers: Mutex::new(HashMap::new()) }
+    }
+    pub fn global() -> &'static ScopeRegistry {
+        static REGISTRY: OnceLock<ScopeRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(ScopeRegistry::new)
+    }
+    pub fn register_callback(&self, scope: &str, callback: ScopeCallback) {
+        self.callbacks.lock().unwrap().push((scope.to_string(), callback));
+    }
+    pub fn clear(&self, scope: &str) {
+        self.buffers.lock().unwrap().remove(scope);
+    }
+    pub fn push(&self, scope: &str, trace: &ScopeTrace, history: usize) {
+        {
+            let mut buffers = self.buffers.lock().unwrap();
+            let buffer = buffers.entry(scope.to_string()).or_insert_with(|| ScopeTrace {
+                start_time: trace.start_time,
+                point_duration: trace.point_duration,
+                ..ScopeTrace::default()
+            });
+            buffer.minimum.extend_from_slice(&trace.minimum);
+            buffer.maximum.extend_from_slice(&trace.maximum);
+            let excess = buffer.minimum.len().saturating_sub(history);
+            buffer.minimum.drain(..excess);
+            buffer.maximum.drain(..excess);
+            buffer.start_time += excess as f64 * buffer.point_duration;
+        }
+        for (name, callback) in self.callbacks.lock().unwrap().iter() {
+            if name == scope {
+                callback(scope, trace);
+            }
+        }
+    }
+    pub fn latest(&self, scope: &str) -> Option<ScopeTrace> {
+        self.buffers.lock().unwrap().get(scope).cloned()
+    }
+}
+impl Default for ScopeRegistry {
+    fn default() -> Self {
+        ScopeRegistry::new()
+    }
+}
+
+pub fn register_callback(scope: &str, callback: ScopeCallback) {
+    ScopeRegistry::global().register_callback(scope, callback)
+}
+pub fn latest(scope: &str) -> Option<ScopeTrace> {
+    ScopeRegistry::global().latest(scope)
+}
+
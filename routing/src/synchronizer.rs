@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+pub const MAX_INPUTS: usize = 8;
+const INPUT_NAMES: [&str; MAX_INPUTS] = ["input_0", "input_1", "input_2", "input_3", "input_4", "input_5", "input_6", "input_7"];
+
+// Bundles input_number inputs into one frame per input. Frames carry no sequence number or
+// timestamp, so the inputs are aligned by stream position only: sample k of every input goes in
+// the same bundle, and a sample lost upstream shifts that input for good. With a frame_size every
+// bundle holds frame_size samples per input, with 0 it holds whatever all the inputs have
+// received. At most max_backlog samples are kept per input, the oldest beyond that are dropped
+// and counted.
+stream_block! {
+    pub struct Synchronizer {
+        inputs: {
+            "input_0": Vec<f64>,
+            "input_1": Vec<f64>,
+            "input_2": Vec<f64>,
+            "input_3": Vec<f64>,
+            "input_4": Vec<f64>,
+            "input_5": Vec<f64>,
+            "input_6": Vec<f64>,
+            "input_7": Vec<f64>,
+        },
+        outputs: { "output": Vec<Vec<f64>> },
+        statics: {
+            "input_number": usize = 2,
+            "frame_size": usize = 0,
+            "max_backlog": usize = 65536,
+        },
+        state: {
+            "buffers": Vec<Vec<f64>> = Vec::<Vec<f64>>::new(),
+            "dropped": usize = 0,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let input_number = self.get_statics::<usize>("input_number")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let max_backlog = self.get_statics::<usize>("max_backlog")?.get_value();
+            if input_number == 0 || input_number > MAX_INPUTS || max_backlog < frame_size.max(1) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("buffers", vec![Vec::<f64>::new(); input_number])?;
+            self.set_state_value("dropped", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let input_number = self.get_statics::<usize>("input_number")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let max_backlog = self.get_statics::<usize>("max_backlog")?.get_value();
+            let mut buffers = self.get_state_value::<Vec<Vec<f64>>>("buffers")?;
+            let mut dropped = self.get_state_value::<usize>("dropped")?;
+            // an input is only read while it holds less than a frame, a faster input waits in
+            // its connector and leftovers are carried to the next bundle
+            for index in 0..input_number {
+                while buffers[index].len() < frame_size.max(1) {
+                    let frame = self.recv_input::<Vec<f64>>(INPUT_NAMES[index])?;
+                    buffers[index].extend(frame);
+                }
+                let excess = buffers[index].len().saturating_sub(max_backlog);
+                buffers[index].drain(..excess);
+                dropped += excess;
+            }
+            let mut bundles: Vec<Vec<Vec<f64>>> = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                let available = buffers.iter().map(|b| b.len()).min().unwrap_or(0);
+                match available.checked_div(frame_size) {
+                    Some(frames) => {
+                        for _ in 0..frames {
+                            bundles.push(buffers.iter_mut().map(|b| b.drain(..frame_size).collect()).collect());
+                        }
+                    }
+                    // a zero frame_size bundles whatever every input has received
+                    None if available > 0 => {
+                        bundles.push(buffers.iter_mut().map(|b| b.drain(..available).collect()).collect());
+                    }
+                    None => {}
+                }
+            }
+            self.set_state_value("buffers", buffers)?;
+            self.set_state_value("dropped", dropped)?;
+            for bundle in bundles {
+                self.send_output::<Vec<Vec<f64>>>("output", bundle)?;
+            }
+            Ok(())
+        }
+    }
+}
+
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+stream_block! {
+    pub struct UnitDelay {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "initial_value": f64 = 0.0,
+            "frame_size": usize = 1,
+            "delay": usize = 1,
+        },
+        state: { "primed": bool = false },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let delay = self.get_statics::<usize>("delay")?.get_value();
+            if frame_size == 0 || delay == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("primed", false)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            // the initial frames go out before anything is received, this is what lets a
+            // feedback loop start without every block waiting on its predecessor
+            if !self.get_state_value::<bool>("primed")? {
+                let initial_value = self.get_statics::<f64>("initial_value")?.get_value();
+                let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+                let delay = self.get_statics::<usize>("delay")?.get_value();
+                for _ in 0..delay {
+                    self.send_output::<Vec<f64>>("output", vec![initial_value; frame_size])?;
+                }
+                self.set_state_value("primed", true)?;
+            }
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            self.send_output::<Vec<f64>>("output", input_signal)?;
+            Ok(())
+        }
+    }
+}
+
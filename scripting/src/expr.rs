@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::expression::Expression;
+
+pub const MAX_INPUTS: usize = 8;
+const INPUT_NAMES: [&str; MAX_INPUTS] = ["input_0", "input_1", "input_2", "input_3", "input_4", "input_5", "input_6", "input_7"];
+
+stream_block! {
+    pub struct Expr {
+        inputs: {
+            "input_0": Vec<f64>,
+            "input_1": Vec<f64>,
+            "input_2": Vec<f64>,
+            "input_3": Vec<f64>,
+            "input_4": Vec<f64>,
+            "input_5": Vec<f64>,
+            "input_6": Vec<f64>,
+            "input_7": Vec<f64>,
+        },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            // variable i of the expression reads input_i
+            "variables": Vec<String> = vec!["a".to_string()],
+            "expression": String = "a".to_string(),
+        },
+        fields: { program: Option<Expression> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let variables = self.get_statics::<Vec<String>>("variables")?.get_value();
+            let expression = self.get_statics::<String>("expression")?.get_value();
+            if variables.is_empty() || variables.len() > MAX_INPUTS {
+                return Err(StreamingError::InvalidStatics)
+            }
+            match Expression::parse(&expression, &variables) {
+                Ok(program) => self.program = Some(program),
+                Err(error) => {
+                    eprintln!("Expr {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let variables = self.get_statics::<Vec<String>>("variables")?.get_value();
+            let mut inputs = Vec::with_capacity(variables.len());
+            for input_name in INPUT_NAMES.iter().take(variables.len()) {
+                inputs.push(self.recv_input::<Vec<f64>>(input_name)?);
+            }
+            let length = inputs[0].len();
+            if inputs.iter().any(|input| input.len() != length) {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let mut output_signal = Vec::<f64>::with_capacity(length);
+            {
+                let _lock = self.lock.lock().unwrap();
+                let program = self.program.as_ref().unwrap();
+                let mut values = vec![0.0; inputs.len()];
+                for k in 0..length {
+                    for (value, input) in values.iter_mut().zip(inputs.iter()) {
+                        *value = input[k];
+                    }
+                    output_signal.push(program.evaluate(&values));
+                }
+            }
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
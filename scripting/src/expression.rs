@@ -0,0 +1,216 @@
+// Arithmetic expressions over named variables, parsed once and evaluated per sample.
+// Grammar: sum = product (('+'|'-') product)*, product = unary (('*'|'/'|'%') unary)*,
+// unary = '-' unary | power, power = primary ('^' unary)?, primary = number | name | name '(' args ')' | '(' sum ')'.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(f64),
+    Variable(usize),
+    Negate(Box<Expression>),
+    Binary(char, Box<Expression>, Box<Expression>),
+    Call(String, Vec<Expression>),
+}
+
+const FUNCTIONS: [(&str, usize); 19] = [
+    ("sqrt", 1), ("abs", 1), ("exp", 1), ("ln", 1), ("log10", 1), ("sin", 1), ("cos", 1), ("tan", 1),
+    ("asin", 1), ("acos", 1), ("atan", 1), ("floor", 1), ("ceil", 1), ("round", 1), ("sign", 1),
+    ("atan2", 2), ("pow", 2), ("min", 2), ("max", 2),
+];
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    position: usize,
+    variables: &'a [String],
+}
+impl Parser<'_> {
+    fn skip_spaces(&mut self) {
+        while self.position < self.chars.len() && self.chars[self.position].is_whitespace() {
+            self.position += 1;
+        }
+    }
+    fn peek(&mut self) -> Option<char> {
+        self.skip_spaces();
+        self.chars.get(self.position).copied()
+    }
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at position {}", expected, self.position))
+        }
+    }
+    fn sum(&mut self) -> Result<Expression, String> {
+        let mut left = self.product()?;
+        while let Some(op) = self.peek().filter(|c| *c == '+' || *c == '-') {
+            self.position += 1;
+            left = Expression::Binary(op, Box::new(left), Box::new(self.product()?));
+        }
+        Ok(left)
+    }
+    fn product(&mut self) -> Result<Expression, String> {
+        let mut left = self.unary()?;
+        while let Some(op) = self.peek().filter(|c| *c == '*' || *c == '/' || *c == '%') {
+            self.position += 1;
+            left = Expression::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+    fn power(&mut self) -> Result<Expression, String> {
+        let base = self.primary()?;
+        if self.peek() == Some('^') {
+            self.position += 1;
+            return Ok(Expression::Binary('^', Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+    fn unary(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some('-') => {
+                self.position += 1;
+                Ok(Expression::Negate(Box::new(self.unary()?)))
+            }
+            Some('+') => {
+                self.position += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+    fn primary(&mut self) -> Result<Expression, String> {
+        let start = self.position;
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let inner = self.sum()?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let start = self.position;
+                while self.position < self.chars.len() && (self.chars[self.position].is_ascii_digit() || self.chars[self.position] == '.') {
+                    self.position += 1;
+                }
+                // exponent part, e.g. 1.5e-3
+                if self.position < self.chars.len() && (self.chars[self.position] == 'e' || self.chars[self.position] == 'E') {
+                    let mark = self.position;
+                    self.position += 1;
+                    if self.position < self.chars.len() && (self.chars[self.position] == '+' || self.chars[self.position] == '-') {
+                        self.position += 1;
+                    }
+                    if self.position < self.chars.len() && self.chars[self.position].is_ascii_digit() {
+                        while self.position < self.chars.len() && self.chars[self.position].is_ascii_digit() {
+                            self.position += 1;
+                        }
+                    } else {
+                        self.position = mark;
+                    }
+                }
+                let text: String = self.chars[start..self.position].iter().collect();
+                text.parse::<f64>().map(Expression::Number).map_err(|_| format!("invalid number '{}'", text))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let start = self.position;
+                while self.position < self.chars.len() && (self.chars[self.position].is_alphanumeric() || self.chars[self.position] == '_') {
+                    self.position += 1;
+                }
+                let name: String = self.chars[start..self.position].iter().collect();
+                if self.peek() == Some('(') {
+                    self.position += 1;
+                    let mut arguments = vec![self.sum()?];
+                    while self.peek() == Some(',') {
+                        self.position += 1;
+                        arguments.push(self.sum()?);
+                    }
+                    self.expect(')')?;
+                    return match FUNCTIONS.iter().find(|(function, _)| *function == name) {
+                        Some((_, arity)) if *arity == arguments.len() => Ok(Expression::Call(name, arguments)),
+                        Some((_, arity)) => Err(format!("{} takes {} arguments", name, arity)),
+                        None => Err(format!("unknown function '{}'", name)),
+                    };
+                }
+                if let Some(index) = self.variables.iter().position(|v| *v == name) {
+                    return Ok(Expression::Variable(index));
+                }
+                match name.as_str() {
+                    "pi" => Ok(Expression::Number(std::f64::consts::PI)),
+                    "e" => Ok(Expression::Number(std::f64::consts::E)),
+                    _ => Err(format!("unknown variable '{}'", name)),
+                }
+            }
+            _ => Err(format!("unexpected input at position {}", start)),
+        }
+    }
+}
+
+impl Expression {
+    // Variables are resolved to their index in `variables`, which is also the order of the
+    // values passed to evaluate.
+    pub fn parse(text: &str, variables: &[String]) -> Result<Expression, String> {
+        let mut parser = Parser { chars: text.chars().collect(), position: 0, variables };
+        let expression = parser.sum()?;
+        if parser.peek().is_some() {
+            return Err(format!("unexpected input at position {}", parser.position));
+        }
+        Ok(expression)
+    }
+    pub fn evaluate(&self, values: &[f64]) -> f64 {
+        match self {
+            Expression::Number(value) => *value,
+            Expression::Variable(index) => values[*index],
+            Expression::Negate(inner) => -inner.evaluate(values),
+            Expression::Binary(op, left, right) => {
+                let (a, b) = (left.evaluate(values), right.evaluate(values));
+                match op {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    '/' => a / b,
+                    '%' => a % b,
+                    _ => a.powf(b),
+                }
+            }
+            Expression::Call(name, arguments) => {
+                let x = arguments[0].evaluate(values);
+                let y = arguments.get(1).map_or(0.0, |argument| argument.evaluate(values));
+                match name.as_str() {
+                    "sqrt" => x.sqrt(),
+                    "abs" => x.abs(),
+                    "exp" => x.exp(),
+                    "ln" => x.ln(),
+                    "log10" => x.log10(),
+                    "sin" => x.sin(),
+                    "cos" => x.cos(),
+                    "tan" => x.tan(),
+                    "asin" => x.asin(),
+                    "acos" => x.acos(),
+                    "atan" => x.atan(),
+                    "floor" => x.floor(),
+                    "ceil" => x.ceil(),
+                    "round" => x.round(),
+                    "sign" => if x == 0.0 { 0.0 } else { x.signum() },
+                    "atan2" => x.atan2(y),
+                    "pow" => x.powf(y),
+                    "min" => x.min(y),
+                    _ => x.max(y),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_parse_and_evaluate() {
+        let variables = vec!["a".to_string(), "b".to_string()];
+        let magnitude = Expression::parse("sqrt(a^2 + b^2)", &variables).unwrap();
+        assert_eq!(magnitude.evaluate(&[3.0, 4.0]), 5.0);
+        let precedence = Expression::parse("-2^2 + 3*b - a/2 + 1.5e1", &variables).unwrap();
+        assert_eq!(precedence.evaluate(&[4.0, 2.0]), -4.0 + 6.0 - 2.0 + 15.0);
+        assert_eq!(Expression::parse("2^3^2", &variables).unwrap().evaluate(&[0.0, 0.0]), 512.0);
+        assert!(Expression::parse("max(a)", &variables).is_err());
+        assert!(Expression::parse("a + c", &variables).is_err());
+        assert!(Expression::parse("(a + b", &variables).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+
+pub const MAX_PORTS: usize = 8;
+const INPUT_NAMES: [&str; MAX_PORTS] = ["input_0", "input_1", "input_2", "input_3", "input_4", "input_5", "input_6", "input_7"];
+const OUTPUT_NAMES: [&str; MAX_PORTS] = ["output_0", "output_1", "output_2", "output_3", "output_4", "output_5", "output_6", "output_7"];
+
+stream_block! {
+    pub struct Script {
+        inputs: {
+            "input_0": Vec<f64>,
+            "input_1": Vec<f64>,
+            "input_2": Vec<f64>,
+            "input_3": Vec<f64>,
+            "input_4": Vec<f64>,
+            "input_5": Vec<f64>,
+            "input_6": Vec<f64>,
+            "input_7": Vec<f64>,
+        },
+        outputs: {
+            "output_0": Vec<f64>,
+            "output_1": Vec<f64>,
+            "output_2": Vec<f64>,
+            "output_3": Vec<f64>,
+            "output_4": Vec<f64>,
+            "output_5": Vec<f64>,
+            "output_6": Vec<f64>,
+            "output_7": Vec<f64>,
+        },
+        statics: {
+            "script": String = String::new(),
+            "script_file": String = String::new(),
+            "settings": String = String::new(),
+            "input_number": usize = 1,
+            "output_number": usize = 1,
+        },
+        state: { "script_state": String = String::new() },
+        fields: { runtime: Option<ScriptRuntime> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let input_number = self.get_statics::<usize>("input_number")?.get_value();
+            let output_number = self.get_statics::<usize>("output_number")?.get_value();
+            if input_number == 0 || input_number > MAX_PORTS || output_number > MAX_PORTS {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let script_file = self.get_statics::<String>("script_file")?.get_value();
+            let source = if script_file.is_empty() {
+                self.get_statics::<String>("script")?.get_value()
+            } else {
+                match std::fs::read_to_string(&script_file) {
+                    Ok(source) => source,
+                    Err(error) => {
+                        eprintln!("Script {}: cannot read {}: {}", self.name, script_file, error);
+                        return Err(StreamingError::InvalidStatics)
+                    }
+                }
+            };
+            let settings = self.get_statics::<String>("settings")?.get_value();
+            let runtime = match ScriptRuntime::new(&source, &settings) {
+                Ok(runtime) => runtime,
+                Err(error) => {
+                    eprintln!("Script {}: {}", self.name, error);
+                    return Err(StreamingError::InvalidStatics)
+                }
+            };
+            self.set_state_value("script_state", runtime.state_json())?;
+            self.runtime = Some(runtime);
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let input_number = self.get_statics::<usize>("input_number")?.get_value();
+            let output_number = self.get_statics::<usize>("output_number")?.get_value();
+            let mut inputs = Vec::with_capacity(input_number);
+            for input_name in INPUT_NAMES.iter().take(input_number) {
+                inputs.push(self.recv_input::<Vec<f64>>(input_name)?);
+            }
+            let result;
+            let script_state;
+            {
+                let _lock = self.lock.lock().unwrap();
+                let runtime = self.runtime.as_mut().unwrap();
+                result = runtime.process(&inputs);
+                script_state = runtime.state_json();
+            }
+            let outputs = match result {
+                Ok(outputs) if outputs.len() == output_number => outputs,
+                Ok(outputs) => {
+                    eprintln!("Script {}: process returned {} outputs, {} expected", self.name, outputs.len(), output_number);
+                    self.stop()?;
+                    return Err(StreamingError::InvalidInput);
+                }
+                Err(error) => {
+                    eprintln!("Script {}: {}", self.name, error);
+                    self.stop()?;
+                    return Err(StreamingError::InvalidInput);
+                }
+            };
+            self.set_state_value("script_state", script_state)?;
+            for (output_name, output) in OUTPUT_NAMES.iter().zip(outputs) {
+                self.send_output::<Vec<f64>>(output_name, output)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Rhai script driving the block. The script may define `fn init(settings)` and must define
+// `fn process(inputs, settings)`; both run with `this` bound to a map that persists between
+// calls. `settings` is the JSON object from the settings static, `inputs` holds one array per
+// input port and process returns one array per output port.
+pub struct ScriptRuntime {
+    engine: Engine,
+    ast: AST,
+    settings: Dynamic,
+    state: Dynamic,
+}
+
+fn to_array(signal: &[f64]) -> Dynamic {
+    Dynamic::from_array(signal.iter().map(|x| Dynamic::from_float(*x)).collect())
+}
+
+fn to_signal(value: Dynamic) -> Option<Vec<f64>> {
+    value.try_cast::<Array>()?.into_iter().map(|v| v.as_float().ok().or_else(|| v.as_int().ok().map(|i| i as f64))).collect()
+}
+
+impl ScriptRuntime {
+    pub fn new(source: &str, settings: &str) -> Result<ScriptRuntime, String> {
+        let engine = Engine::new();
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        if !ast.iter_functions().any(|f| f.name == "process" && f.params.len() == 2) {
+            return Err("the script does not define process(inputs, settings)".to_string());
+        }
+        let settings = if settings.trim().is_empty() { Map::new() } else { engine.parse_json(settings, true).map_err(|e| e.to_string())? };
+        let mut runtime = ScriptRuntime { engine, ast, settings: Dynamic::from_map(settings), state: Dynamic::from_map(Map::new()) };
+        if runtime.ast.iter_functions().any(|f| f.name == "init" && f.params.len() == 1) {
+            let options = CallFnOptions::new().bind_this_ptr(&mut runtime.state);
+            let _ = runtime.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &runtime.ast, "init", (runtime.settings.clone(),))
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(runtime)
+    }
+    pub fn process(&mut self, inputs: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, String> {
+        let arguments: Array = inputs.iter().map(|input| to_array(input)).collect();
+        let options = CallFnOptions::new().bind_this_ptr(&mut self.state);
+        let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, "process", (Dynamic::from_array(arguments), self.settings.clone()))
+            .map_err(|e| e.to_string())?;
+        let outputs = result.try_cast::<Array>().ok_or("process must return an array of outputs")?;
+        outputs.into_iter().map(|output| to_signal(output).ok_or_else(|| "outputs must be arrays of numbers".to_string())).collect()
+    }
+    pub fn state_json(&self) -> String {
+        self.state.clone().try_cast::<Map>().map(|state| rhai::format_map_as_json(&state)).unwrap_or_default()
+    }
+}
+
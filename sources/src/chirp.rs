@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::generators::{chirp_phase, valid_sweep};
+
+// Frequency sweep from f0 to f1 over `duration` seconds. With repeat the sweep starts over,
+// otherwise the block stops after the frame holding the last sample of the sweep.
+stream_block! {
+    pub struct Chirp {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "f0": f64 = 1.0,
+            "f1": f64 = 100.0,
+            "duration": f64 = 1.0,
+            "method": String = "linear".to_string(),
+            "amplitude": f64 = 1.0,
+            "repeat": bool = true,
+            "sample_rate": f64 = 1000.0,
+            "frame_size": usize = 1024,
+        },
+        state: { "sample_index": usize = 0 },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let f0 = self.get_statics::<f64>("f0")?.get_value();
+            let f1 = self.get_statics::<f64>("f1")?.get_value();
+            let duration = self.get_statics::<f64>("duration")?.get_value();
+            let method = self.get_statics::<String>("method")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            // an exponential sweep needs both ends on the same side of zero
+            if !valid_sweep(&method) || !f0.is_finite() || !f1.is_finite() || (method == "exponential" && f0 * f1 <= 0.0)
+                || duration.is_nan() || duration <= 0.0 || sample_rate.is_nan() || sample_rate <= 0.0 || frame_size == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("sample_index", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let f0 = self.get_statics::<f64>("f0")?.get_value();
+            let f1 = self.get_statics::<f64>("f1")?.get_value();
+            let duration = self.get_statics::<f64>("duration")?.get_value();
+            let method = self.get_statics::<String>("method")?.get_value();
+            let amplitude = self.get_statics::<f64>("amplitude")?.get_value();
+            let repeat = self.get_statics::<bool>("repeat")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let mut sample_index = self.get_state_value::<usize>("sample_index")?;
+            let sweep_length = ((duration * sample_rate).round() as usize).max(1);
+            let mut frame = Vec::with_capacity(frame_size);
+            {
+                let _lock = self.lock.lock().unwrap();
+                while frame.len() < frame_size && (repeat || sample_index < sweep_length) {
+                    let t = (sample_index % sweep_length) as f64 / sample_rate;
+                    frame.push(amplitude * (2.0 * std::f64::consts::PI * chirp_phase(t, f0, f1, duration, &method)).sin());
+                    sample_index = if repeat { (sample_index + 1) % sweep_length } else { sample_index + 1 };
+                }
+            }
+            self.set_state_value("sample_index", sample_index)?;
+            self.send_output::<Vec<f64>>("output", frame)?;
+            if !repeat && sample_index >= sweep_length {
+                self.stop()?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::f64::consts::PI;
+
+pub fn valid_waveform(waveform: &str) -> bool {
+    matches!(waveform, "sine" | "square" | "triangle" | "sawtooth")
+}
+
+// Unit amplitude periodic waveform at `phase` cycles. The square wave is high for the first
+// `duty_cycle` of the period, the triangle and sawtooth start at -1 like the square wave.
+pub fn periodic_sample(waveform: &str, phase: f64, duty_cycle: f64) -> f64 {
+    let phase = phase.rem_euclid(1.0);
+    match waveform {
+        "square" => if phase < duty_cycle { 1.0 } else { -1.0 },
+        "triangle" => if phase < 0.5 { 4.0 * phase - 1.0 } else { 3.0 - 4.0 * phase },
+        "sawtooth" => 2.0 * phase - 1.0,
+        _ => (2.0 * PI * phase).sin(),
+    }
+}
+
+pub fn valid_sweep(method: &str) -> bool {
+    matches!(method, "linear" | "exponential")
+}
+
+// Phase in cycles at time t of a sweep from f0 to f1 over `duration` seconds, the instantaneous
+// frequency f0 + (f1 - f0) t / T for "linear" and f0 (f1 / f0)^(t / T) for "exponential".
+pub fn chirp_phase(t: f64, f0: f64, f1: f64, duration: f64, method: &str) -> f64 {
+    if method == "exponential" && f0 != f1 {
+        let ratio = f1 / f0;
+        f0 * duration / ratio.ln() * (ratio.powf(t / duration) - 1.0)
+    } else {
+        f0 * t + (f1 - f0) * t * t / (2.0 * duration)
+    }
+}
+
+// Second tap m of the maximal length polynomial x^n + x^m + 1 for the supported orders n.
+pub fn prbs_taps(order: u32) -> Option<u32> {
+    match order {
+        7 => Some(6),
+        9 => Some(5),
+        11 => Some(9),
+        15 => Some(14),
+        20 => Some(17),
+        23 => Some(18),
+        31 => Some(28),
+        _ => None,
+    }
+}
+
+// Advances the Fibonacci register by one bit and returns it, the register must not be zero.
+pub fn prbs_next(register: &mut u64, order: u32, tap: u32) -> bool {
+    let bit = ((*register >> (order - 1)) ^ (*register >> (tap - 1))) & 1;
+    *register = ((*register << 1) | bit) & ((1u64 << order) - 1);
+    bit == 1
+}
+
+// Paul Kellet's filter turning white noise into -3 dB/octave pink noise, within 0.05 dB above
+// 9.2 Hz at 44.1 kHz. The output is scaled back to about unit variance for unit white noise.
+#[derive(Clone, Debug, Default)]
+pub struct PinkFilter {
+    b: [f64; 7],
+}
+impl PinkFilter {
+    pub fn next(&mut self, white: f64) -> f64 {
+        let b = &mut self.b;
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.1538520;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+        b[6] = white * 0.115926;
+        pink * 0.331
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_waveforms() {
+        assert!((periodic_sample("sine", 0.25, 0.5) - 1.0).abs() < 1.0e-12);
+        assert_eq!(periodic_sample("square", 0.2, 0.25), 1.0);
+        assert_eq!(periodic_sample("square", 1.3, 0.25), -1.0);
+        assert_eq!(periodic_sample("triangle", 0.5, 0.5), 1.0);
+        assert_eq!(periodic_sample("triangle", 0.75, 0.5), 0.0);
+        assert_eq!(periodic_sample("sawtooth", -0.25, 0.5), 0.5);
+        // the derivative of the phase is the instantaneous frequency
+        for method in ["linear", "exponential"] {
+            let (t, h) = (0.7, 1.0e-6);
+            let frequency = (chirp_phase(t + h, 10.0, 1000.0, 2.0, method) - chirp_phase(t - h, 10.0, 1000.0, 2.0, method)) / (2.0 * h);
+            let expected = if method == "linear" { 10.0 + 990.0 * t / 2.0 } else { 10.0 * 100.0f64.powf(t / 2.0) };
+            assert!((frequency - expected).abs() < 1.0e-3, "{} {}", method, frequency);
+        }
+    }
+    #[test]
+    fn test_prbs_period() {
+        for order in [7, 9, 11, 15] {
+            let tap = prbs_taps(order).unwrap();
+            let mut register = 1u64;
+            let mut ones = 0;
+            let period = (1usize << order) - 1;
+            for _ in 0..period {
+                ones += prbs_next(&mut register, order, tap) as usize;
+            }
+            // maximal length: back to the seed after 2^n - 1 bits, with one more one than zeros
+            assert_eq!(register, 1);
+            assert_eq!(ones, period / 2 + 1);
+        }
+        assert!(prbs_taps(8).is_none());
+    }
+}
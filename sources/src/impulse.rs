@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+// Unit impulse of height `amplitude` at sample `delay`, repeated every `period` samples when the
+// period is not zero.
+stream_block! {
+    pub struct Impulse {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "amplitude": f64 = 1.0,
+            "delay": usize = 0,
+            "period": usize = 0,
+            "sample_rate": f64 = 1.0,
+            "frame_size": usize = 1024,
+        },
+        state: { "sample_index": usize = 0 },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            if sample_rate.is_nan() || sample_rate <= 0.0 || frame_size == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("sample_index", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let amplitude = self.get_statics::<f64>("amplitude")?.get_value();
+            let delay = self.get_statics::<usize>("delay")?.get_value();
+            let period = self.get_statics::<usize>("period")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let start = self.get_state_value::<usize>("sample_index")?;
+            let frame = (start..start + frame_size)
+                .map(|k| {
+                    let fires = k == delay || (period > 0 && k > delay && (k - delay).is_multiple_of(period));
+                    if fires { amplitude } else { 0.0 }
+                })
+                .collect();
+            self.set_state_value("sample_index", start + frame_size)?;
+            self.send_output::<Vec<f64>>("output", frame)?;
+            Ok(())
+        }
+    }
+}
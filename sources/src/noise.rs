@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand_distr::StandardNormal;
+use crate::generators::PinkFilter;
+
+// Gaussian noise of standard deviation `amplitude` around `mean`, white or pink (-3 dB/octave).
+// A zero seed draws the generator state from the operating system.
+stream_block! {
+    pub struct Noise {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "color": String = "white".to_string(),
+            "amplitude": f64 = 1.0,
+            "mean": f64 = 0.0,
+            "seed": usize = 0,
+            "sample_rate": f64 = 1.0,
+            "frame_size": usize = 1024,
+        },
+        fields: { rng: Option<StdRng> = None, pink: PinkFilter = PinkFilter::default() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let color = self.get_statics::<String>("color")?.get_value();
+            let amplitude = self.get_statics::<f64>("amplitude")?.get_value();
+            let seed = self.get_statics::<usize>("seed")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            if (color != "white" && color != "pink") || amplitude.is_nan() || amplitude < 0.0
+                || sample_rate.is_nan() || sample_rate <= 0.0 || frame_size == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.rng = Some(if seed == 0 { StdRng::from_os_rng() } else { StdRng::seed_from_u64(seed as u64) });
+            self.pink = PinkFilter::default();
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let pink = self.get_statics::<String>("color")?.get_value() == "pink";
+            let amplitude = self.get_statics::<f64>("amplitude")?.get_value();
+            let mean = self.get_statics::<f64>("mean")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let mut frame = Vec::with_capacity(frame_size);
+            {
+                let _lock = self.lock.lock().unwrap();
+                let rng = self.rng.as_mut().ok_or(StreamingError::InvalidStateTransition)?;
+                for _ in 0..frame_size {
+                    let white: f64 = rng.sample(StandardNormal);
+                    let value = if pink { self.pink.next(white) } else { white };
+                    frame.push(mean + amplitude * value);
+                }
+            }
+            self.send_output::<Vec<f64>>("output", frame)?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::generators::{periodic_sample, valid_waveform};
+
+// Periodic waveform source. The phase static is in radians, the running phase is kept in cycles
+// so consecutive frames join without a discontinuity.
+stream_block! {
+    pub struct Oscillator {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "waveform": String = "sine".to_string(),
+            "frequency": f64 = 1.0,
+            "amplitude": f64 = 1.0,
+            "offset": f64 = 0.0,
+            "phase": f64 = 0.0,
+            "duty_cycle": f64 = 0.5,
+            "sample_rate": f64 = 1.0,
+            "frame_size": usize = 1024,
+        },
+        state: { "phase": f64 = 0.0 },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let waveform = self.get_statics::<String>("waveform")?.get_value();
+            let frequency = self.get_statics::<f64>("frequency")?.get_value();
+            let phase = self.get_statics::<f64>("phase")?.get_value();
+            let duty_cycle = self.get_statics::<f64>("duty_cycle")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            if !valid_waveform(&waveform) || !frequency.is_finite() || !phase.is_finite() || !(0.0..=1.0).contains(&duty_cycle)
+                || sample_rate.is_nan() || sample_rate <= 0.0 || frame_size == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("phase", (phase / (2.0 * std::f64::consts::PI)).rem_euclid(1.0))?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let waveform = self.get_statics::<String>("waveform")?.get_value();
+            let frequency = self.get_statics::<f64>("frequency")?.get_value();
+            let amplitude = self.get_statics::<f64>("amplitude")?.get_value();
+            let offset = self.get_statics::<f64>("offset")?.get_value();
+            let duty_cycle = self.get_statics::<f64>("duty_cycle")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let mut phase = self.get_state_value::<f64>("phase")?;
+            let mut frame = Vec::with_capacity(frame_size);
+            {
+                let _lock = self.lock.lock().unwrap();
+                for _ in 0..frame_size {
+                    frame.push(offset + amplitude * periodic_sample(&waveform, phase, duty_cycle));
+                    phase = (phase + frequency / sample_rate).rem_euclid(1.0);
+                }
+            }
+            self.set_state_value("phase", phase)?;
+            self.send_output::<Vec<f64>>("output", frame)?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::generators::{prbs_next, prbs_taps};
+
+// Pseudo random binary sequence of the given order, a maximal length LFSR repeating every
+// 2^order - 1 bits. Each bit lasts samples_per_bit samples at +amplitude or -amplitude.
+stream_block! {
+    pub struct Prbs {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "order": usize = 7,
+            "amplitude": f64 = 1.0,
+            "samples_per_bit": usize = 1,
+            "seed": usize = 1,
+            "sample_rate": f64 = 1.0,
+            "frame_size": usize = 1024,
+        },
+        state: { "register": usize = 1, "sample_index": usize = 0, "level": f64 = 0.0 },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let order = self.get_statics::<usize>("order")?.get_value();
+            let samples_per_bit = self.get_statics::<usize>("samples_per_bit")?.get_value();
+            let seed = self.get_statics::<usize>("seed")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            // the all zero register would lock up the sequence
+            let register = seed & ((1usize << order.min(63)) - 1);
+            if prbs_taps(order as u32).is_none() || register == 0 || samples_per_bit == 0
+                || sample_rate.is_nan() || sample_rate <= 0.0 || frame_size == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("register", register)?;
+            self.set_state_value("sample_index", 0usize)?;
+            self.set_state_value("level", 0.0)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let order = self.get_statics::<usize>("order")?.get_value() as u32;
+            let amplitude = self.get_statics::<f64>("amplitude")?.get_value();
+            let samples_per_bit = self.get_statics::<usize>("samples_per_bit")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let tap = prbs_taps(order).ok_or(StreamingError::InvalidStatics)?;
+            let mut register = self.get_state_value::<usize>("register")? as u64;
+            let mut sample_index = self.get_state_value::<usize>("sample_index")?;
+            let mut level = self.get_state_value::<f64>("level")?;
+            let mut frame = Vec::with_capacity(frame_size);
+            {
+                let _lock = self.lock.lock().unwrap();
+                for _ in 0..frame_size {
+                    if sample_index == 0 {
+                        level = if prbs_next(&mut register, order, tap) { amplitude } else { -amplitude };
+                    }
+                    frame.push(level);
+                    sample_index = (sample_index + 1) % samples_per_bit;
+                }
+            }
+            self.set_state_value("register", register as usize)?;
+            self.set_state_value("sample_index", sample_index)?;
+            self.set_state_value("level", level)?;
+            self.send_output::<Vec<f64>>("output", frame)?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+
+// Step from initial_value to final_value at sample `delay`.
+stream_block! {
+    pub struct Step {
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "initial_value": f64 = 0.0,
+            "final_value": f64 = 1.0,
+            "delay": usize = 0,
+            "sample_rate": f64 = 1.0,
+            "frame_size": usize = 1024,
+        },
+        state: { "sample_index": usize = 0 },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            if sample_rate.is_nan() || sample_rate <= 0.0 || frame_size == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("sample_index", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let initial_value = self.get_statics::<f64>("initial_value")?.get_value();
+            let final_value = self.get_statics::<f64>("final_value")?.get_value();
+            let delay = self.get_statics::<usize>("delay")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let start = self.get_state_value::<usize>("sample_index")?;
+            let frame = (start..start + frame_size).map(|k| if k < delay { initial_value } else { final_value }).collect();
+            // once past the step the index no longer matters, saturating keeps it from wrapping
+            self.set_state_value("sample_index", start.saturating_add(frame_size))?;
+            self.send_output::<Vec<f64>>("output", frame)?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+// Compressed sparse row matrix. The non zero values of row i are values[row_ptr[i]..row_ptr[i + 1]],
+// in column order, at the columns col_idx[row_ptr[i]..row_ptr[i + 1]].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsrMatrix {
+    pub rows: usize,
+    pub cols: usize,
+    row_ptr: Vec<usize>,
+    col_idx: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl CsrMatrix {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        CsrMatrix { rows, cols, row_ptr: vec![0; rows + 1], col_idx: Vec::new(), values: Vec::new() }
+    }
+    pub fn identity(n: usize) -> Self {
+        CsrMatrix { rows: n, cols: n, row_ptr: (0..=n).collect(), col_idx: (0..n).collect(), values: vec![1.0; n] }
+    }
+    // Keeps the non zero entries of a dense row major matrix.
+    pub fn from_dense(dense: &[Vec<f64>]) -> Self {
+        let cols = dense.first().map_or(0, |row| row.len());
+        let mut ret = CsrMatrix::new(0, cols);
+        for row in dense {
+            for (j, v) in row.iter().enumerate().filter(|(_, v)| **v != 0.0) {
+                ret.col_idx.push(j);
+                ret.values.push(*v);
+            }
+            ret.row_ptr.push(ret.values.len());
+            ret.rows += 1;
+        }
+        ret
+    }
+    // Builds the matrix from (row, column, value) entries in any order, duplicates are summed.
+    pub fn from_triplets(rows: usize, cols: usize, triplets: &[(usize, usize, f64)]) -> Result<Self, String> {
+        if let Some((i, j, _)) = triplets.iter().find(|(i, j, _)| *i >= rows || *j >= cols) {
+            return Err(format!("entry ({}, {}) out of a {}x{} matrix", i, j, rows, cols));
+        }
+        let mut sorted = triplets.to_vec();
+        sorted.sort_by_key(|(i, j, _)| (*i, *j));
+        let mut ret = CsrMatrix::new(rows, cols);
+        let mut last = None;
+        for (i, j, v) in sorted {
+            if last == Some((i, j)) {
+                *ret.values.last_mut().unwrap() += v;
+            } else {
+                ret.col_idx.push(j);
+                ret.values.push(v);
+                ret.row_ptr[i + 1] += 1;
+                last = Some((i, j));
+            }
+        }
+        for i in 0..rows {
+            ret.row_ptr[i + 1] += ret.row_ptr[i];
+        }
+        Ok(ret)
+    }
+    pub fn to_dense(&self) -> Vec<Vec<f64>> {
+        let mut ret = vec![vec![0.0; self.cols]; self.rows];
+        for (i, row) in ret.iter_mut().enumerate() {
+            for (j, v) in self.row(i) {
+                row[j] = v;
+            }
+        }
+        ret
+    }
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+    pub fn row(&self, i: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let range = self.row_ptr[i]..self.row_ptr[i + 1];
+        self.col_idx[range.clone()].iter().copied().zip(self.values[range].iter().copied())
+    }
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        let range = self.row_ptr[i]..self.row_ptr[i + 1];
+        match self.col_idx[range.clone()].binary_search(&j) {
+            Ok(k) => self.values[range.start + k],
+            Err(_) => 0.0,
+        }
+    }
+    pub fn transpose(&self) -> Self {
+        let mut counts = vec![0; self.cols + 1];
+        for j in &self.col_idx {
+            counts[j + 1] += 1;
+        }
+        for j in 0..self.cols {
+            counts[j + 1] += counts[j];
+        }
+        let mut ret = CsrMatrix { rows: self.cols, cols: self.rows, row_ptr: counts.clone(), col_idx: vec![0; self.nnz()], values: vec![0.0; self.nnz()] };
+        // rows are visited in order, so the columns of the transpose stay sorted
+        for i in 0..self.rows {
+            for (j, v) in self.row(i) {
+                ret.col_idx[counts[j]] = i;
+                ret.values[counts[j]] = v;
+                counts[j] += 1;
+            }
+        }
+        ret
+    }
+    // self * x
+    pub fn mul_vec(&self, x: &[f64]) -> Vec<f64> {
+        (0..self.rows).map(|i| self.row(i).map(|(j, v)| v * x[j]).sum()).collect()
+    }
+    // self * m, m dense with self.cols rows
+    pub fn mul_dense(&self, m: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let cols = m.first().map_or(0, |row| row.len());
+        (0..self.rows).map(|i| {
+            let mut ret = vec![0.0; cols];
+            for (k, v) in self.row(i) {
+                ret.iter_mut().zip(&m[k]).for_each(|(r, m_kj)| *r += v * m_kj);
+            }
+            ret
+        }).collect()
+    }
+    // m * self, m dense with self.rows columns
+    pub fn dense_mul(m: &[Vec<f64>], s: &CsrMatrix) -> Vec<Vec<f64>> {
+        m.iter().map(|m_row| {
+            let mut ret = vec![0.0; s.cols];
+            for (k, m_ik) in m_row.iter().enumerate().filter(|(_, v)| **v != 0.0) {
+                for (j, v) in s.row(k) {
+                    ret[j] += m_ik * v;
+                }
+            }
+            ret
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_csr() {
+        let dense = vec![vec![1.0, 0.0, 2.0], vec![0.0, 0.0, 0.0], vec![0.0, 3.0, 0.0]];
+        let a = CsrMatrix::from_dense(&dense);
+        assert_eq!(a.nnz(), 3);
+        assert_eq!(a.to_dense(), dense);
+        assert_eq!(a.get(0, 2), 2.0);
+        assert_eq!(a.get(1, 1), 0.0);
+        let b = CsrMatrix::from_triplets(3, 3, &[(2, 1, 1.0), (0, 2, 2.0), (0, 0, 1.0), (2, 1, 2.0)]).unwrap();
+        assert_eq!(a, b);
+        assert!(CsrMatrix::from_triplets(3, 3, &[(3, 0, 1.0)]).is_err());
+        assert_eq!(a.transpose().to_dense(), vec![vec![1.0, 0.0, 0.0], vec![0.0, 0.0, 3.0], vec![2.0, 0.0, 0.0]]);
+        assert_eq!(a.mul_vec(&[1.0, 2.0, 3.0]), vec![7.0, 0.0, 6.0]);
+        let m = vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]];
+        assert_eq!(a.mul_dense(&m), vec![vec![11.0, 14.0], vec![0.0, 0.0], vec![9.0, 12.0]]);
+        assert_eq!(CsrMatrix::dense_mul(&a.transpose().mul_dense(&m), &CsrMatrix::identity(2)), vec![vec![1.0, 2.0], vec![15.0, 18.0], vec![2.0, 4.0]]);
+        assert_eq!(CsrMatrix::dense_mul(&[vec![1.0, 1.0, 1.0]], &a), vec![vec![1.0, 3.0, 2.0]]);
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use rustfft::{FftPlanner, Fft, num_complex::Complex};
+use crate::psd::detrend;
+
+stream_block! {
+    pub struct ArSpectrum {
+        inputs: { "input": Vec<f64> },
+        outputs: { "psd": Vec<f64>, "frequencies": Vec<f64>, "coefficients": Vec<f64> },
+        statics: {
+            "order": usize = 8,
+            "method": String = "burg".to_string(),
+            "frame_size": usize = 256,
+            "nfft": usize = 512,
+            "detrend": String = "constant".to_string(),
+            "sample_rate": f64 = 1.0,
+            "onesided": bool = true,
+        },
+        state: { "pending": Vec<f64> = Vec::<f64>::new() },
+        fields: { fft_core: Option<Arc<dyn Fft<f64>>> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let order = self.get_statics::<usize>("order")?.get_value();
+            let method = self.get_statics::<String>("method")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let nfft = self.get_statics::<usize>("nfft")?.get_value();
+            let detrend = self.get_statics::<String>("detrend")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            if order == 0 || frame_size <= order || nfft <= order || sample_rate.is_nan() || sample_rate <= 0.0
+                || (method != "burg" && method != "yule_walker")
+                || !["none", "constant", "linear"].contains(&detrend.as_str()) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.fft_core = Some(FftPlanner::new().plan_fft_forward(nfft));
+            self.set_state_value("pending", Vec::<f64>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let order = self.get_statics::<usize>("order")?.get_value();
+            let method = self.get_statics::<String>("method")?.get_value();
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let nfft = self.get_statics::<usize>("nfft")?.get_value();
+            let detrend_mode = self.get_statics::<String>("detrend")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let onesided = self.get_statics::<bool>("onesided")?.get_value();
+            let mut pending = self.get_state_value::<Vec<f64>>("pending")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut estimates = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                let fft = self.fft_core.as_ref().unwrap();
+                pending.extend_from_slice(&input_signal);
+                while pending.len() >= frame_size {
+                    let mut frame: Vec<f64> = pending.drain(..frame_size).collect();
+                    detrend(&mut frame, &detrend_mode);
+                    let (coefficients, variance) = if method == "burg" { burg(&frame, order) } else { yule_walker(&frame, order) };
+                    let psd = ar_psd(&coefficients, variance, fft, sample_rate, onesided);
+                    estimates.push((coefficients, psd));
+                }
+            }
+            self.set_state_value("pending", pending)?;
+            for (coefficients, psd) in estimates {
+                let resolution = sample_rate / nfft as f64;
+                let frequencies = (0..psd.len())
+                    .map(|k| if onesided || k <= nfft / 2 { k as f64 * resolution } else { (k as f64 - nfft as f64) * resolution })
+                    .collect();
+                self.send_output::<Vec<f64>>("frequencies", frequencies)?;
+                self.send_output::<Vec<f64>>("coefficients", coefficients)?;
+                self.send_output::<Vec<f64>>("psd", psd)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Burg estimate of the AR model x[n] + a1 x[n-1] + .. + ap x[n-p] = e[n]. Each stage picks the
+// reflection coefficient minimizing the sum of the forward and backward prediction errors, so the
+// model is always stable. Returns a1..ap and the variance of e.
+pub fn burg(x: &[f64], order: usize) -> (Vec<f64>, f64) {
+    let n = x.len();
+    let mut a = vec![1.0];
+    let mut error = x.iter().map(|v| v * v).sum::<f64>() / n as f64;
+    let mut forward = x.to_vec();
+    let mut backward = x.to_vec();
+    for m in 1..=order.min(n.saturating_sub(1)) {
+        let (mut num, mut den) = (0.0, 0.0);
+        for i in m..n {
+            num += forward[i] * backward[i - 1];
+            den += forward[i] * forward[i] + backward[i - 1] * backward[i - 1];
+        }
+        if den <= 0.0 {
+            break;
+        }
+        let k = -2.0 * num / den;
+        // errors of order m from those of order m - 1, backward[i - 1] is read before it is replaced
+        for i in (m..n).rev() {
+            let f = forward[i];
+            forward[i] += k * backward[i - 1];
+            backward[i] = backward[i - 1] + k * f;
+        }
+        a.push(0.0);
+        a = (0..=m).map(|i| a[i] + k * a[m - i]).collect();
+        error *= 1.0 - k * k;
+    }
+    a.resize(order + 1, 0.0);
+    (a.split_off(1), error)
+}
+
+// Yule-Walker estimate from the biased autocorrelation, solved by the Levinson-Durbin recursion.
+pub fn yule_walker(x: &[f64], order: usize) -> (Vec<f64>, f64) {
+    let n = x.len();
+    let r: Vec<f64> = (0..=order).map(|lag| {
+        if lag >= n { 0.0 } else { x[lag..].iter().zip(x).map(|(a, b)| a * b).sum::<f64>() / n as f64 }
+    }).collect();
+    let mut a = vec![1.0];
+    let mut error = r[0];
+    for m in 1..=order {
+        if error <= 0.0 {
+            break;
+        }
+        let k = -(0..m).map(|i| a[i] * r[m - i]).sum::<f64>() / error;
+        a.push(0.0);
+        a = (0..=m).map(|i| a[i] + k * a[m - i]).collect();
+        error *= 1.0 - k * k;
+    }
+    a.resize(order + 1, 0.0);
+    (a.split_off(1), error)
+}
+
+// Power spectral density variance / (fs |A(f)|^2) of the model on an nfft point grid, onesided
+// spectra fold the negative frequencies as the Psd block does.
+pub fn ar_psd(coefficients: &[f64], variance: f64, fft: &Arc<dyn Fft<f64>>, sample_rate: f64, onesided: bool) -> Vec<f64> {
+    let n = fft.len();
+    let mut buffer = vec![Complex::new(0.0, 0.0); n];
+    buffer[0].re = 1.0;
+    for (b, a) in buffer[1..].iter_mut().zip(coefficients) {
+        b.re = *a;
+    }
+    fft.process(&mut buffer);
+    let mut power: Vec<f64> = buffer.iter().map(|c| variance / (sample_rate * c.norm_sqr().max(f64::MIN_POSITIVE))).collect();
+    if onesided {
+        power.truncate(n / 2 + 1);
+        let last = if n.is_multiple_of(2) { n / 2 } else { n / 2 + 1 };
+        power[1..last].iter_mut().for_each(|p| *p *= 2.0);
+    }
+    power
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_ar_estimates() {
+        // AR(2) x[n] = 1.5 x[n-1] - 0.8 x[n-2] + e[n], driven by a deterministic pseudo random e
+        let mut seed = 12345u64;
+        let mut noise = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        };
+        let mut x = vec![0.0; 4096];
+        for n in 2..x.len() {
+            x[n] = 1.5 * x[n - 1] - 0.8 * x[n - 2] + noise();
+        }
+        for (a, variance) in [burg(&x, 2), yule_walker(&x, 2)] {
+            assert!((a[0] + 1.5).abs() < 0.05 && (a[1] - 0.8).abs() < 0.05);
+            assert!((variance - 1.0 / 12.0).abs() < 0.01);
+        }
+        // a pure tone is a lossless AR(2) with its poles on the unit circle, Burg finds them on a
+        // short frame and the spectrum peaks at the tone frequency
+        let tone: Vec<f64> = (0..32).map(|n| (2.0 * std::f64::consts::PI * 0.1 * n as f64 + 0.3).cos()).collect();
+        let (a, variance) = burg(&tone, 2);
+        assert!((a[0] + 2.0 * (2.0 * std::f64::consts::PI * 0.1).cos()).abs() < 1.0e-2 && (a[1] - 1.0).abs() < 1.0e-3);
+        assert!(variance < 1.0e-3);
+        let fft = FftPlanner::new().plan_fft_forward(200);
+        let psd = ar_psd(&a, variance, &fft, 1.0, true);
+        assert_eq!(psd.len(), 101);
+        let peak = (0..psd.len()).max_by(|i, j| psd[*i].total_cmp(&psd[*j])).unwrap();
+        assert_eq!(peak, 20);
+        assert_eq!(burg(&[1.0, 2.0], 4).0.len(), 4);
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use rustdct::{DctPlanner, TransformType2And3, TransformType4};
+
+stream_block! {
+    pub struct Dct {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "size": usize = 256,
+            "transform_type": usize = 2,
+            "normalization": String = "ortho".to_string(),
+        },
+        fields: { plan: Option<TrigonometricPlan> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let size = self.get_statics::<usize>("size")?.get_value();
+            let transform_type = self.get_statics::<usize>("transform_type")?.get_value();
+            let normalization = self.get_statics::<String>("normalization")?.get_value();
+            if size == 0 || (normalization != "ortho" && normalization != "none") {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.plan = TrigonometricPlan::new(transform_type, size);
+            if self.plan.is_none() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let size = self.get_statics::<usize>("size")?.get_value();
+            let transform_type = self.get_statics::<usize>("transform_type")?.get_value();
+            let ortho = self.get_statics::<String>("normalization")?.get_value() == "ortho";
+            let mut input_signal = self.recv_input::<Vec<f64>>("input")?;
+            if input_signal.len() != size {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            {
+                let _lock = self.lock.lock().unwrap();
+                self.plan.as_ref().unwrap().process(transform_type, false, ortho, &mut input_signal);
+            }
+            self.send_output::<Vec<f64>>("output", input_signal)?;
+            Ok(())
+        }
+    }
+}
+stream_block! {
+    pub struct Dst {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "size": usize = 256,
+            "transform_type": usize = 2,
+            "normalization": String = "ortho".to_string(),
+        },
+        fields: { plan: Option<TrigonometricPlan> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let size = self.get_statics::<usize>("size")?.get_value();
+            let transform_type = self.get_statics::<usize>("transform_type")?.get_value();
+            let normalization = self.get_statics::<String>("normalization")?.get_value();
+            if size == 0 || (normalization != "ortho" && normalization != "none") {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.plan = TrigonometricPlan::new(transform_type, size);
+            if self.plan.is_none() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let size = self.get_statics::<usize>("size")?.get_value();
+            let transform_type = self.get_statics::<usize>("transform_type")?.get_value();
+            let ortho = self.get_statics::<String>("normalization")?.get_value() == "ortho";
+            let mut input_signal = self.recv_input::<Vec<f64>>("input")?;
+            if input_signal.len() != size {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            {
+                let _lock = self.lock.lock().unwrap();
+                self.plan.as_ref().unwrap().process(transform_type, true, ortho, &mut input_signal);
+            }
+            self.send_output::<Vec<f64>>("output", input_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// Planned transform, types II and III share the same rustdct plan.
+pub enum TrigonometricPlan {
+    Type23(Arc<dyn TransformType2And3<f64>>),
+    Type4(Arc<dyn TransformType4<f64>>),
+}
+impl TrigonometricPlan {
+    pub fn new(transform_type: usize, size: usize) -> Option<Self> {
+        let mut planner = DctPlanner::new();
+        match transform_type {
+            2 | 3 => Some(TrigonometricPlan::Type23(planner.plan_dct2(size))),
+            4 => Some(TrigonometricPlan::Type4(planner.plan_dct4(size))),
+            _ => None,
+        }
+    }
+    // Runs the cosine (or with `sine` the sine) transform in place. The "ortho" normalization
+    // makes the matrix orthonormal so that types II and III invert each other and type IV is
+    // its own inverse, "none" keeps the rustdct scaling.
+    pub fn process(&self, transform_type: usize, sine: bool, ortho: bool, buffer: &mut [f64]) {
+        let n = buffer.len();
+        if n == 0 {
+            return;
+        }
+        // the DC term of DCT-III (last term of DST-III) enters rustdct halved
+        let edge = if sine { n - 1 } else { 0 };
+        if ortho && transform_type == 3 {
+            buffer[edge] *= std::f64::consts::SQRT_2;
+        }
+        match (self, transform_type, sine) {
+            (TrigonometricPlan::Type23(plan), 2, false) => plan.process_dct2(buffer),
+            (TrigonometricPlan::Type23(plan), 2, true) => plan.process_dst2(buffer),
+            (TrigonometricPlan::Type23(plan), 3, false) => plan.process_dct3(buffer),
+            (TrigonometricPlan::Type23(plan), 3, true) => plan.process_dst3(buffer),
+            (TrigonometricPlan::Type4(plan), _, false) => plan.process_dct4(buffer),
+            (TrigonometricPlan::Type4(plan), _, true) => plan.process_dst4(buffer),
+            _ => {}
+        }
+        if ortho {
+            let scale = (2.0 / n as f64).sqrt();
+            buffer.iter_mut().for_each(|x| *x *= scale);
+            if transform_type == 2 {
+                buffer[edge] *= std::f64::consts::FRAC_1_SQRT_2;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    #[test]
+    fn test_orthonormal_transforms() {
+        let n = 12;
+        let signal: Vec<f64> = (0..n).map(|k| (k as f64 * 0.7).sin() + 0.1 * k as f64).collect();
+        let forward = TrigonometricPlan::new(2, n).unwrap();
+        let mut buffer = signal.clone();
+        forward.process(2, false, true, &mut buffer);
+        for (k, value) in buffer.iter().enumerate() {
+            let weight = if k == 0 { (1.0 / n as f64).sqrt() } else { (2.0 / n as f64).sqrt() };
+            let direct: f64 = signal.iter().enumerate()
+                .map(|(m, x)| x * (PI * k as f64 * (2 * m + 1) as f64 / (2 * n) as f64).cos()).sum();
+            assert!((value - weight * direct).abs() < 1.0e-9);
+        }
+        let energy = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>();
+        assert!((energy(&buffer) - energy(&signal)).abs() < 1.0e-9);
+        forward.process(3, false, true, &mut buffer);
+        assert!(buffer.iter().zip(signal.iter()).all(|(a, b)| (a - b).abs() < 1.0e-9));
+        buffer.clone_from(&signal);
+        forward.process(2, true, true, &mut buffer);
+        assert!((energy(&buffer) - energy(&signal)).abs() < 1.0e-9);
+        forward.process(3, true, true, &mut buffer);
+        assert!(buffer.iter().zip(signal.iter()).all(|(a, b)| (a - b).abs() < 1.0e-9));
+        let fourth = TrigonometricPlan::new(4, n).unwrap();
+        for sine in [false, true] {
+            buffer.clone_from(&signal);
+            fourth.process(4, sine, true, &mut buffer);
+            fourth.process(4, sine, true, &mut buffer);
+            assert!(buffer.iter().zip(signal.iter()).all(|(a, b)| (a - b).abs() < 1.0e-9));
+        }
+    }
+}
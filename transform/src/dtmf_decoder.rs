@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::goertzel::goertzel_power;
+
+stream_block! {
+    pub struct DtmfDecoder {
+        inputs: { "input": Vec<f64> },
+        outputs: { "digits": Vec<DtmfDigit> },
+        statics: {
+            "sample_rate": f64 = 8000.0,
+            "block_size": usize = 205,
+            "min_energy": f64 = 1.0e-6,
+            "tone_ratio": f64 = 0.7,
+            "normal_twist": f64 = 8.0,
+            "reverse_twist": f64 = 4.0,
+            "min_blocks": usize = 2,
+        },
+        state: { "detector": DtmfDetector = DtmfDetector::default() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let settings = self.settings()?;
+            // the highest column tone must stay below Nyquist
+            if settings.sample_rate <= 2.0 * COLUMN_FREQUENCIES[3] || settings.block_size < 2 || settings.min_blocks < 1
+                || settings.min_energy < 0.0 || settings.tone_ratio.is_nan() || settings.tone_ratio <= 0.0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("detector", DtmfDetector::default())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let settings = self.settings()?;
+            let mut detector = self.get_state_value::<DtmfDetector>("detector")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let digits;
+            {
+                let _lock = self.lock.lock().unwrap();
+                digits = detector.process(&input_signal, &settings);
+            }
+            self.set_state_value("detector", detector)?;
+            if !digits.is_empty() {
+                self.send_output::<Vec<DtmfDigit>>("digits", digits)?;
+            }
+            Ok(())
+        }
+    }
+}
+impl DtmfDecoder {
+    fn settings(&self) -> Result<DtmfSettings, StreamingError> {
+        Ok(DtmfSettings {
+            sample_rate: self.get_statics::<f64>("sample_rate")?.get_value(),
+            block_size: self.get_statics::<usize>("block_size")?.get_value(),
+            min_energy: self.get_statics::<f64>("min_energy")?.get_value(),
+            tone_ratio: self.get_statics::<f64>("tone_ratio")?.get_value(),
+            normal_twist: self.get_statics::<f64>("normal_twist")?.get_value(),
+            reverse_twist: self.get_statics::<f64>("reverse_twist")?.get_value(),
+            min_blocks: self.get_statics::<usize>("min_blocks")?.get_value(),
+        })
+    }
+}
+
+pub const ROW_FREQUENCIES: [f64; 4] = [697.0, 770.0, 852.0, 941.0];
+pub const COLUMN_FREQUENCIES: [f64; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+const KEYPAD: [[char; 4]; 4] = [
+    ['1', '2', '3', 'A'],
+    ['4', '5', '6', 'B'],
+    ['7', '8', '9', 'C'],
+    ['*', '0', '#', 'D'],
+];
+
+// A key press, emitted once the key is released. Times are in seconds from the stream start.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DtmfDigit {
+    pub digit: char,
+    pub start: f64,
+    pub duration: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DtmfSettings {
+    pub sample_rate: f64,
+    pub block_size: usize,
+    pub min_energy: f64,
+    pub tone_ratio: f64,
+    pub normal_twist: f64,
+    pub reverse_twist: f64,
+    pub min_blocks: usize,
+}
+
+fn strongest(block: &[f64], frequencies: &[f64; 4], sample_rate: f64) -> (usize, f64) {
+    frequencies.iter().enumerate()
+        .map(|(index, frequency)| (index, goertzel_power(block, *frequency, sample_rate)))
+        .fold((0, f64::MIN), |best, candidate| if candidate.1 > best.1 { candidate } else { best })
+}
+
+// Digit present in one block: strongest row and column tones together must carry `tone_ratio`
+// of the block energy and their level difference must stay within the twist limits (dB, positive
+// twist means the column tone is weaker).
+pub fn detect_digit(block: &[f64], settings: &DtmfSettings) -> Option<char> {
+    let energy: f64 = block.iter().map(|x| x * x).sum();
+    if energy <= settings.min_energy * block.len() as f64 {
+        return None;
+    }
+    let (row, row_power) = strongest(block, &ROW_FREQUENCIES, settings.sample_rate);
+    let (column, column_power) = strongest(block, &COLUMN_FREQUENCIES, settings.sample_rate);
+    // a full scale tone of amplitude A has power (A*N/2)^2 against an energy of A^2*N/2
+    let scale = 2.0 / (block.len() as f64 * energy);
+    if (row_power + column_power) * scale < settings.tone_ratio || column_power <= 0.0 {
+        return None;
+    }
+    let twist = 10.0 * (row_power / column_power).log10();
+    if twist > settings.normal_twist || -twist > settings.reverse_twist {
+        return None;
+    }
+    Some(KEYPAD[row][column])
+}
+
+// Block detector with debouncing: a key goes down or up only after `min_blocks` consecutive
+// blocks agree, so short glitches and the gaps inside a block boundary are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DtmfDetector {
+    buffer: Vec<f64>,
+    position: usize,
+    candidate: Option<char>,
+    run: usize,
+    active: Option<(char, usize)>,
+}
+impl DtmfDetector {
+    pub fn process(&mut self, signal: &[f64], settings: &DtmfSettings) -> Vec<DtmfDigit> {
+        let mut digits = Vec::new();
+        self.buffer.extend_from_slice(signal);
+        let block_size = settings.block_size;
+        let blocks = self.buffer.len() / block_size;
+        for index in 0..blocks {
+            let block = &self.buffer[index * block_size..(index + 1) * block_size];
+            let detected = detect_digit(block, settings);
+            if detected == self.candidate {
+                self.run += 1;
+            } else {
+                self.candidate = detected;
+                self.run = 1;
+            }
+            if self.run == settings.min_blocks {
+                let onset = self.position - (settings.min_blocks - 1) * block_size;
+                if let Some((digit, start)) = self.active.take() {
+                    if Some(digit) == self.candidate {
+                        self.active = Some((digit, start));
+                    } else {
+                        digits.push(DtmfDigit {
+                            digit,
+                            start: start as f64 / settings.sample_rate,
+                            duration: (onset - start) as f64 / settings.sample_rate,
+                        });
+                    }
+                }
+                if self.active.is_none() {
+                    self.active = self.candidate.map(|digit| (digit, onset));
+                }
+            }
+            self.position += block_size;
+        }
+        self.buffer.drain(..blocks * block_size);
+        digits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    #[test]
+    fn test_decodes_sequence() {
+        let sample_rate = 8000.0;
+        let mut signal = Vec::new();
+        for (row, column) in [(0, 0), (1, 1), (2, 2), (3, 2)] {
+            for k in 0..800 {
+                let t = k as f64 / sample_rate;
+                signal.push(0.4 * (2.0 * PI * ROW_FREQUENCIES[row] * t).sin() + 0.3 * (2.0 * PI * COLUMN_FREQUENCIES[column] * t).sin());
+            }
+            signal.extend(vec![0.0; 600]);
+        }
+        let settings = DtmfSettings {
+            sample_rate,
+            block_size: 205,
+            min_energy: 1.0e-6,
+            tone_ratio: 0.7,
+            normal_twist: 8.0,
+            reverse_twist: 4.0,
+            min_blocks: 2,
+        };
+        let mut detector = DtmfDetector::default();
+        let mut digits = Vec::new();
+        for frame in signal.chunks(160) {
+            digits.extend(detector.process(frame, &settings));
+        }
+        let decoded: String = digits.iter().map(|d| d.digit).collect();
+        assert_eq!(decoded, "159#");
+        assert!((digits[1].start - 0.175).abs() < 2.0 * 205.0 / sample_rate);
+        assert!((digits[0].duration - 0.1).abs() < 2.0 * 205.0 / sample_rate);
+    }
+}
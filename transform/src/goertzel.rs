@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::f64::consts::PI;
+
+stream_block! {
+    pub struct Goertzel {
+        inputs: { "input": Vec<f64> },
+        outputs: { "magnitudes": Vec<f64> },
+        statics: {
+            "frequencies": Vec<f64> = Vec::<f64>::new(),
+            "sample_rate": f64 = 8000.0,
+            "block_size": usize = 205,
+        },
+        state: { "pending": Vec<f64> = Vec::<f64>::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let frequencies = self.get_statics::<Vec<f64>>("frequencies")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let block_size = self.get_statics::<usize>("block_size")?.get_value();
+            if frequencies.is_empty() || block_size == 0 || sample_rate.is_nan() || sample_rate <= 0.0
+                || frequencies.iter().any(|f| f.is_nan() || *f < 0.0 || *f > sample_rate / 2.0) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("pending", Vec::<f64>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let frequencies = self.get_statics::<Vec<f64>>("frequencies")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let block_size = self.get_statics::<usize>("block_size")?.get_value();
+            let mut pending = self.get_state_value::<Vec<f64>>("pending")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut magnitudes = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                pending.extend_from_slice(&input_signal);
+                while pending.len() >= block_size {
+                    let block: Vec<f64> = pending.drain(..block_size).collect();
+                    // 2|X|/N is the amplitude of a sinusoid at the target frequency
+                    magnitudes.push(frequencies.iter()
+                        .map(|frequency| 2.0 * goertzel_power(&block, *frequency, sample_rate).max(0.0).sqrt() / block_size as f64)
+                        .collect::<Vec<f64>>());
+                }
+            }
+            self.set_state_value("pending", pending)?;
+            for magnitude in magnitudes {
+                self.send_output::<Vec<f64>>("magnitudes", magnitude)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Squared magnitude of the DFT of `block` at `frequency`, second order Goertzel recursion. The
+// frequency does not have to fall on a bin of the block length.
+pub fn goertzel_power(block: &[f64], frequency: f64, sample_rate: f64) -> f64 {
+    let omega = 2.0 * PI * frequency / sample_rate;
+    let coefficient = 2.0 * omega.cos();
+    let (mut s1, mut s2) = (0.0, 0.0);
+    for x in block {
+        let s0 = x + coefficient * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    s1 * s1 + s2 * s2 - coefficient * s1 * s2
+}
+
@@ -1,4 +1,15 @@
 pub mod fft;
+pub mod window_functions;
+pub mod spectral;
+pub mod spectral_subtraction;
+pub mod phase_vocoder;
+pub mod goertzel;
+pub mod dtmf_decoder;
+pub mod stft;
+pub mod windowing;
+pub mod psd;
+pub mod dct;
+pub mod ar_spectrum;
 use std::ffi::c_char;
 use data_model::modules::{Version,ModuleStructFFI};
 use processor_engine::stream_processor::StreamProcessor;
@@ -12,29 +23,103 @@ pub static MODULE: ModuleStructFFI  = ModuleStructFFI {
     version: Version{ major: 1,minor: 0,build: 0},
     dependencies: std::ptr::null(),
     dependency_number: 0,
-    provides: [b"Fft\0".as_ptr() as *const c_char].as_ptr(),
-    provides_lengths: 1,
+    provides: [b"Fft\0".as_ptr() as *const c_char,
+        b"SpectralSubtraction\0".as_ptr() as *const c_char,
+        b"PhaseVocoder\0".as_ptr() as *const c_char,
+        b"DtmfDecoder\0".as_ptr() as *const c_char,
+        b"Stft\0".as_ptr() as *const c_char,
+        b"Istft\0".as_ptr() as *const c_char,
+        b"Window\0".as_ptr() as *const c_char,
+        b"Psd\0".as_ptr() as *const c_char,
+        b"Dct\0".as_ptr() as *const c_char,
+        b"Dst\0".as_ptr() as *const c_char,
+        b"Goertzel\0".as_ptr() as *const c_char,
+        b"ArSpectrum\0".as_ptr() as *const c_char].as_ptr(),
+    provides_lengths: 12,
 };
+/// Creates the block `proc_block` named `block_name`, both passed by the host as UTF-8 bytes.
+///
+/// # Safety
+/// A non null `proc_block` and `block_name` must point to `proc_block_len` and `block_name_len`
+/// readable bytes that stay valid while the block exists.
 #[unsafe(no_mangle)]
-pub extern "C" fn get_processor_modules(proc_block: *const u8, 
+pub unsafe extern "C" fn get_processor_modules(proc_block: *const u8, 
     proc_block_len: usize, 
     block_name: *const u8, 
     block_name_len: usize) -> TraitObjectRepr {
-    let proc_block_str = unsafe {
-        std::str::from_utf8(std::slice::from_raw_parts(proc_block, proc_block_len)).unwrap()
-    };
-    let block_name_str = unsafe {
-        std::str::from_utf8(std::slice::from_raw_parts(block_name, block_name_len)).unwrap()
-    };
+    let proc_block_str = block_template::ffi_name!(proc_block, proc_block_len);
+    let block_name_str = block_template::ffi_name!(block_name, block_name_len);
     let proc: Box<dyn StreamProcessor>;
     match proc_block_str {
         "Fft" => {
             proc = Box::new(fft::FftProcessor::new(block_name_str));
             export_stream_processor(proc)
         }
+        "SpectralSubtraction" => {
+            proc = Box::new(spectral_subtraction::SpectralSubtraction::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "PhaseVocoder" => {
+            proc = Box::new(phase_vocoder::PhaseVocoder::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "DtmfDecoder" => {
+            proc = Box::new(dtmf_decoder::DtmfDecoder::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Stft" => {
+            proc = Box::new(stft::Stft::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Istft" => {
+            proc = Box::new(stft::Istft::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Window" => {
+            proc = Box::new(windowing::Window::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Psd" => {
+            proc = Box::new(psd::Psd::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Dct" => {
+            proc = Box::new(dct::Dct::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Dst" => {
+            proc = Box::new(dct::Dst::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "Goertzel" => {
+            proc = Box::new(goertzel::Goertzel::new(block_name_str));
+            export_stream_processor(proc)
+        }
+        "ArSpectrum" => {
+            proc = Box::new(ar_spectrum::ArSpectrum::new(block_name_str));
+            export_stream_processor(proc)
+        }
         _ => {
             eprintln!("Processor block {} not found", proc_block_str);
             get_error_return(1)
         }
     }
-}
\ No newline at end of file
+}
+
+// Schema of every block get_processor_modules creates, keyed by block type, see stream_block!.
+pub fn block_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "Fft": fft::FftProcessor::schema(),
+        "SpectralSubtraction": spectral_subtraction::SpectralSubtraction::schema(),
+        "PhaseVocoder": phase_vocoder::PhaseVocoder::schema(),
+        "DtmfDecoder": dtmf_decoder::DtmfDecoder::schema(),
+        "Stft": stft::Stft::schema(),
+        "Istft": stft::Istft::schema(),
+        "Window": windowing::Window::schema(),
+        "Psd": psd::Psd::schema(),
+        "Dct": dct::Dct::schema(),
+        "Dst": dct::Dst::schema(),
+        "Goertzel": goertzel::Goertzel::schema(),
+        "ArSpectrum": ar_spectrum::ArSpectrum::schema(),
+    })
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use rustfft::num_complex::Complex;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use std::f64::consts::PI;
+use crate::spectral::StftEngine;
+use crate::window_functions::window;
+
+stream_block! {
+    pub struct PhaseVocoder {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "frame_size": usize = 2048,
+            "hop_size": usize = 512,
+            "window": String = "hann".to_string(),
+            "stretch": f64 = 1.0,
+            "pitch_shift": f64 = 0.0,
+            "phase_locking": bool = true,
+        },
+        state: {
+            "previous_phase": Vec<f64> = Vec::<f64>::new(),
+            "synthesis_phase": Vec<f64> = Vec::<f64>::new(),
+            "first_frame": bool = true,
+            "resample_position": f64 = 1.0,
+            "resample_last": f64 = 0.0,
+        },
+        fields: { stft: Option<StftEngine> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let hop_size = self.get_statics::<usize>("hop_size")?.get_value();
+            let window_name = self.get_statics::<String>("window")?.get_value();
+            let stretch = self.get_statics::<f64>("stretch")?.get_value();
+            let pitch_shift = self.get_statics::<f64>("pitch_shift")?.get_value();
+            if !(stretch > 0.0 && pitch_shift.is_finite()) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            // pitch shifting stretches by the pitch ratio and resamples back to the requested duration
+            let pitch_ratio = 2.0_f64.powf(pitch_shift / 12.0);
+            let synthesis_hop = (hop_size as f64 * stretch * pitch_ratio).round() as usize;
+            let Some(window) = window(&window_name, frame_size) else {
+                return Err(StreamingError::InvalidStatics)
+            };
+            self.stft = StftEngine::new(frame_size, hop_size, window).and_then(|stft| stft.with_synthesis_hop(synthesis_hop));
+            if self.stft.is_none() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("previous_phase", vec![0.0; frame_size / 2 + 1])?;
+            self.set_state_value("synthesis_phase", vec![0.0; frame_size / 2 + 1])?;
+            self.set_state_value("first_frame", true)?;
+            self.set_state_value("resample_position", 1.0)?;
+            self.set_state_value("resample_last", 0.0)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let pitch_shift = self.get_statics::<f64>("pitch_shift")?.get_value();
+            let phase_locking = self.get_statics::<bool>("phase_locking")?.get_value();
+            let mut previous_phase = self.get_state_value::<Vec<f64>>("previous_phase")?;
+            let mut synthesis_phase = self.get_state_value::<Vec<f64>>("synthesis_phase")?;
+            let mut first_frame = self.get_state_value::<bool>("first_frame")?;
+            let mut resample_position = self.get_state_value::<f64>("resample_position")?;
+            let mut resample_last = self.get_state_value::<f64>("resample_last")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut stretched = Vec::<f64>::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                let Some(stft) = self.stft.as_mut() else {
+                    return Err(StreamingError::InvalidStatics)
+                };
+                let frame_size = stft.frame_size;
+                let bins = frame_size / 2 + 1;
+                for spectrum in stft.analyze(&input_signal) {
+                    let magnitude: Vec<f64> = spectrum[..bins].iter().map(|c| c.norm()).collect();
+                    let phase: Vec<f64> = spectrum[..bins].iter().map(|c| c.arg()).collect();
+                    if first_frame {
+                        synthesis_phase.copy_from_slice(&phase);
+                        first_frame = false;
+                    } else {
+                        advance_phases(&magnitude, &phase, &previous_phase, &mut synthesis_phase,
+                            frame_size, (stft.hop_size, stft.synthesis_hop), phase_locking);
+                    }
+                    previous_phase = phase;
+                    let mut modified = vec![Complex::new(0.0, 0.0); frame_size];
+                    for k in 0..bins {
+                        modified[k] = Complex::from_polar(magnitude[k], synthesis_phase[k]);
+                        if k > 0 && k < frame_size - k {
+                            modified[frame_size - k] = modified[k].conj();
+                        }
+                    }
+                    stretched.extend(stft.synthesize(modified));
+                }
+            }
+            let output_signal = if pitch_shift == 0.0 {
+                stretched
+            } else {
+                resample_linear(&stretched, 2.0_f64.powf(pitch_shift / 12.0), &mut resample_position, &mut resample_last)
+            };
+            self.set_state_value("previous_phase", previous_phase)?;
+            self.set_state_value("synthesis_phase", synthesis_phase)?;
+            self.set_state_value("first_frame", first_frame)?;
+            self.set_state_value("resample_position", resample_position)?;
+            self.set_state_value("resample_last", resample_last)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
+fn wrap_phase(phase: f64) -> f64 {
+    phase - 2.0 * PI * (phase / (2.0 * PI)).round()
+}
+
+// Advances the synthesis phases of bins 0..=N/2 from one analysis frame to the next. Peak bins
+// follow their instantaneous frequency, with identity phase locking (Laroche and Dolson) the other
+// bins keep their analysis phase offset to the peak of their region.
+pub fn advance_phases(magnitude: &[f64], phase: &[f64], previous_phase: &[f64], synthesis_phase: &mut [f64],
+    frame_size: usize, (analysis_hop, synthesis_hop): (usize, usize), phase_locking: bool) {
+    let bins = magnitude.len();
+    let is_peak = |k: usize| {
+        let lower = k.saturating_sub(2);
+        let upper = (k + 2).min(bins - 1);
+        (lower..=upper).all(|j| j == k || magnitude[j] < magnitude[k])
+    };
+    let peaks: Vec<usize> = if phase_locking { (0..bins).filter(|&k| is_peak(k)).collect() } else { (0..bins).collect() };
+    let mut advanced = synthesis_phase.to_vec();
+    for &k in peaks.iter() {
+        let omega = 2.0 * PI * k as f64 / frame_size as f64;
+        let deviation = wrap_phase(phase[k] - previous_phase[k] - omega * analysis_hop as f64);
+        let frequency = omega + deviation / analysis_hop as f64;
+        advanced[k] = synthesis_phase[k] + frequency * synthesis_hop as f64;
+    }
+    if phase_locking && !peaks.is_empty() {
+        let mut region = 0;
+        for k in 0..bins {
+            // each bin belongs to the closest peak
+            while region + 1 < peaks.len() && k.abs_diff(peaks[region + 1]) < k.abs_diff(peaks[region]) {
+                region += 1;
+            }
+            let peak = peaks[region];
+            if k != peak {
+                advanced[k] = advanced[peak] + phase[k] - phase[peak];
+            }
+        }
+    }
+    synthesis_phase.copy_from_slice(&advanced);
+}
+
+// Linear interpolation reading the stream with a step of `ratio` samples. `last` is the final
+// sample of the previous frame and `position` is relative to it, both carry across frames.
+pub fn resample_linear(input: &[f64], ratio: f64, position: &mut f64, last: &mut f64) -> Vec<f64> {
+    let mut output = Vec::new();
+    let sample = |index: usize| if index == 0 { *last } else { input[index - 1] };
+    while *position < input.len() as f64 {
+        let index = position.floor() as usize;
+        let fraction = *position - index as f64;
+        output.push(sample(index) + (sample(index + 1) - sample(index)) * fraction);
+        *position += ratio;
+    }
+    *position -= input.len() as f64;
+    if let Some(value) = input.last() {
+        *last = *value;
+    }
+    output
+}
+
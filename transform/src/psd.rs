@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use rustfft::{FftPlanner, Fft, num_complex::Complex};
+use crate::window_functions::window;
+
+stream_block! {
+    pub struct Psd {
+        inputs: { "input": Vec<f64> },
+        outputs: { "psd": Vec<f64>, "frequencies": Vec<f64> },
+        statics: {
+            "segment_size": usize = 256,
+            "overlap": usize = 128,
+            "window": String = "hann".to_string(),
+            "detrend": String = "constant".to_string(),
+            "sample_rate": f64 = 1.0,
+            "averages": usize = 8,
+            "scaling": String = "density".to_string(),
+            "onesided": bool = true,
+        },
+        state: {
+            "window_coefficients": Vec<f64> = Vec::<f64>::new(),
+            "pending": Vec<f64> = Vec::<f64>::new(),
+            "accumulator": Vec<f64> = Vec::<f64>::new(),
+            "segment_count": usize = 0,
+        },
+        fields: { fft_core: Option<Arc<dyn Fft<f64>>> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let segment_size = self.get_statics::<usize>("segment_size")?.get_value();
+            let overlap = self.get_statics::<usize>("overlap")?.get_value();
+            let window_name = self.get_statics::<String>("window")?.get_value();
+            let detrend = self.get_statics::<String>("detrend")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let averages = self.get_statics::<usize>("averages")?.get_value();
+            let scaling = self.get_statics::<String>("scaling")?.get_value();
+            if segment_size < 2 || overlap >= segment_size || averages == 0 || sample_rate.is_nan() || sample_rate <= 0.0
+                || !["none", "constant", "linear"].contains(&detrend.as_str())
+                || (scaling != "density" && scaling != "spectrum") {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let Some(coefficients) = window(&window_name, segment_size) else {
+                return Err(StreamingError::InvalidStatics)
+            };
+            self.fft_core = Some(FftPlanner::new().plan_fft_forward(segment_size));
+            self.set_state_value("window_coefficients", coefficients)?;
+            self.set_state_value("pending", Vec::<f64>::new())?;
+            self.set_state_value("accumulator", Vec::<f64>::new())?;
+            self.set_state_value("segment_count", 0usize)?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let segment_size = self.get_statics::<usize>("segment_size")?.get_value();
+            let overlap = self.get_statics::<usize>("overlap")?.get_value();
+            let detrend_mode = self.get_statics::<String>("detrend")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let averages = self.get_statics::<usize>("averages")?.get_value();
+            let scaling = self.get_statics::<String>("scaling")?.get_value();
+            let onesided = self.get_statics::<bool>("onesided")?.get_value();
+            let coefficients = self.get_state_value::<Vec<f64>>("window_coefficients")?;
+            let mut pending = self.get_state_value::<Vec<f64>>("pending")?;
+            let mut accumulator = self.get_state_value::<Vec<f64>>("accumulator")?;
+            let mut segment_count = self.get_state_value::<usize>("segment_count")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            // density is in units^2/Hz, spectrum gives the power of a bin-centered tone
+            let scale = if scaling == "density" {
+                1.0 / (sample_rate * coefficients.iter().map(|w| w * w).sum::<f64>())
+            } else {
+                1.0 / coefficients.iter().sum::<f64>().powi(2)
+            };
+            let mut estimates = Vec::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                let fft = self.fft_core.as_ref().unwrap();
+                pending.extend_from_slice(&input_signal);
+                while pending.len() >= segment_size {
+                    let mut segment = pending[..segment_size].to_vec();
+                    detrend(&mut segment, &detrend_mode);
+                    let power = segment_power(&segment, &coefficients, fft, onesided);
+                    if accumulator.len() != power.len() {
+                        accumulator = vec![0.0; power.len()];
+                    }
+                    accumulator.iter_mut().zip(power.iter()).for_each(|(a, p)| *a += p);
+                    segment_count += 1;
+                    pending.drain(..segment_size - overlap);
+                    if segment_count == averages {
+                        estimates.push(accumulator.iter().map(|a| a * scale / averages as f64).collect::<Vec<f64>>());
+                        accumulator.iter_mut().for_each(|a| *a = 0.0);
+                        segment_count = 0;
+                    }
+                }
+            }
+            self.set_state_value("pending", pending)?;
+            self.set_state_value("accumulator", accumulator)?;
+            self.set_state_value("segment_count", segment_count)?;
+            for estimate in estimates {
+                let resolution = sample_rate / segment_size as f64;
+                // two sided spectra keep the FFT bin order, the upper half is the negative frequencies
+                let frequencies = (0..estimate.len())
+                    .map(|k| if onesided || k <= segment_size / 2 { k as f64 * resolution } else { (k as f64 - segment_size as f64) * resolution })
+                    .collect();
+                self.send_output::<Vec<f64>>("frequencies", frequencies)?;
+                self.send_output::<Vec<f64>>("psd", estimate)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Removes the mean ("constant") or the least squares line ("linear") from a segment.
+pub fn detrend(segment: &mut [f64], mode: &str) {
+    let n = segment.len() as f64;
+    match mode {
+        "constant" => {
+            let mean = segment.iter().sum::<f64>() / n;
+            segment.iter_mut().for_each(|x| *x -= mean);
+        }
+        "linear" if segment.len() > 1 => {
+            let center = (n - 1.0) / 2.0;
+            let mean = segment.iter().sum::<f64>() / n;
+            let variance: f64 = (0..segment.len()).map(|k| (k as f64 - center).powi(2)).sum();
+            let slope = segment.iter().enumerate().map(|(k, x)| (k as f64 - center) * x).sum::<f64>() / variance;
+            segment.iter_mut().enumerate().for_each(|(k, x)| *x -= mean + slope * (k as f64 - center));
+        }
+        _ => {}
+    }
+}
+
+// Squared magnitude of the windowed segment spectrum, onesided spectra fold the negative
+// frequencies onto bins 1..N/2 (Nyquist excluded for even N).
+pub fn segment_power(segment: &[f64], window: &[f64], fft: &Arc<dyn Fft<f64>>, onesided: bool) -> Vec<f64> {
+    let mut buffer: Vec<Complex<f64>> = segment.iter().zip(window.iter()).map(|(x, w)| Complex::new(x * w, 0.0)).collect();
+    fft.process(&mut buffer);
+    let n = buffer.len();
+    let mut power: Vec<f64> = buffer.iter().map(|c| c.norm_sqr()).collect();
+    if onesided {
+        power.truncate(n / 2 + 1);
+        let last = if n.is_multiple_of(2) { n / 2 } else { n / 2 + 1 };
+        power[1..last].iter_mut().for_each(|p| *p *= 2.0);
+    }
+    power
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    #[test]
+    fn test_tone_power() {
+        let size = 64;
+        let fft = FftPlanner::new().plan_fft_forward(size);
+        let window = vec![1.0; size];
+        let mut line: Vec<f64> = (0..size).map(|k| 3.0 + 0.1 * k as f64).collect();
+        detrend(&mut line, "linear");
+        assert!(line.iter().all(|x| x.abs() < 1.0e-12));
+        let mut segment: Vec<f64> = (0..size).map(|k| 3.0 + 2.0 * (2.0 * PI * 8.0 * k as f64 / size as f64).cos()).collect();
+        detrend(&mut segment, "constant");
+        let power = segment_power(&segment, &window, &fft, true);
+        // "spectrum" scaling 1/(sum w)^2 turns the bin into the tone power A^2/2
+        let scale = 1.0 / (size * size) as f64;
+        assert!((power[8] * scale - 2.0).abs() < 1.0e-9);
+        assert!(power[0] * scale < 1.0e-20);
+    }
+}
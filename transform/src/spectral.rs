@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use rustfft::{FftPlanner, Fft, num_complex::Complex};
+
+// Streaming short-time Fourier analysis and weighted overlap-add synthesis. Samples are
+// accumulated until a full frame is available, each frame advances by `hop_size` samples
+// and synthesized frames by `synthesis_hop` samples (equal unless the signal is stretched).
+pub struct StftEngine {
+    pub frame_size: usize,
+    pub hop_size: usize,
+    pub synthesis_hop: usize,
+    window: Vec<f64>,
+    forward: Arc<dyn Fft<f64>>,
+    inverse: Arc<dyn Fft<f64>>,
+    pending: Vec<f64>,
+    overlap: Vec<f64>,
+    normalization: f64,
+}
+impl StftEngine {
+    pub fn new(frame_size: usize, hop_size: usize, window: Vec<f64>) -> Option<Self> {
+        if frame_size == 0 || hop_size == 0 || hop_size > frame_size || window.len() != frame_size {
+            return None;
+        }
+        let mut planner = FftPlanner::new();
+        let normalization = Self::normalization(&window, hop_size);
+        Some(StftEngine {
+            frame_size,
+            hop_size,
+            synthesis_hop: hop_size,
+            window,
+            forward: planner.plan_fft_forward(frame_size),
+            inverse: planner.plan_fft_inverse(frame_size),
+            pending: Vec::new(),
+            overlap: vec![0.0; frame_size],
+            normalization,
+        })
+    }
+    // window applied twice (analysis and synthesis), averaged over the frames overlapping a sample
+    fn normalization(window: &[f64], hop: usize) -> f64 {
+        window.iter().map(|w| w * w).sum::<f64>() / hop as f64
+    }
+    pub fn with_synthesis_hop(mut self, synthesis_hop: usize) -> Option<Self> {
+        if synthesis_hop == 0 || synthesis_hop > self.frame_size {
+            return None;
+        }
+        self.synthesis_hop = synthesis_hop;
+        self.normalization = Self::normalization(&self.window, synthesis_hop);
+        Some(self)
+    }
+    pub fn analyze(&mut self, samples: &[f64]) -> Vec<Vec<Complex<f64>>> {
+        self.pending.extend_from_slice(samples);
+        let mut spectra = Vec::new();
+        while self.pending.len() >= self.frame_size {
+            let mut frame: Vec<Complex<f64>> = self.pending[..self.frame_size].iter().zip(self.window.iter())
+                .map(|(x, w)| Complex::new(x * w, 0.0))
+                .collect();
+            self.forward.process(&mut frame);
+            spectra.push(frame);
+            self.pending.drain(..self.hop_size);
+        }
+        spectra
+    }
+    // Returns the `synthesis_hop` samples completed by this frame.
+    pub fn synthesize(&mut self, mut spectrum: Vec<Complex<f64>>) -> Vec<f64> {
+        self.inverse.process(&mut spectrum);
+        let scale = 1.0 / (self.frame_size as f64 * self.normalization);
+        for (k, value) in spectrum.iter().enumerate() {
+            self.overlap[k] += value.re * self.window[k] * scale;
+        }
+        let ready: Vec<f64> = self.overlap.drain(..self.synthesis_hop).collect();
+        self.overlap.extend(std::iter::repeat_n(0.0, self.synthesis_hop));
+        ready
+    }
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.overlap = vec![0.0; self.frame_size];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_functions::window;
+    #[test]
+    fn test_identity_reconstruction() {
+        let mut engine = StftEngine::new(64, 16, window("hann", 64).unwrap()).unwrap();
+        let signal: Vec<f64> = (0..1024).map(|n| (0.05 * n as f64).sin() + 0.3 * (0.31 * n as f64).cos()).collect();
+        let mut output = Vec::new();
+        for chunk in signal.chunks(100) {
+            for spectrum in engine.analyze(chunk) {
+                output.extend(engine.synthesize(spectrum));
+            }
+        }
+        // the first frame_size - hop_size samples are missing the contribution of earlier frames
+        for n in 48..output.len() {
+            assert!((output[n] - signal[n]).abs() < 1.0e-9);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use rustfft::num_complex::Complex;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::spectral::StftEngine;
+use crate::window_functions::window;
+
+stream_block! {
+    pub struct SpectralSubtraction {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "frame_size": usize = 512,
+            "hop_size": usize = 128,
+            "window": String = "hann".to_string(),
+            "noise_estimation": String = "initial".to_string(),
+            "noise_frames": usize = 10,
+            "minimum_window": usize = 64,
+            "smoothing": f64 = 0.9,
+            "over_subtraction": f64 = 2.0,
+            "floor": f64 = 0.01,
+        },
+        state: {
+            "noise_spectrum": Vec<f64> = Vec::<f64>::new(),
+            "frame_count": usize = 0,
+            "smoothed_power": Vec<f64> = Vec::<f64>::new(),
+            "power_history": Vec<Vec<f64>> = Vec::<Vec<f64>>::new(),
+        },
+        fields: { stft: Option<StftEngine> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let hop_size = self.get_statics::<usize>("hop_size")?.get_value();
+            let window_name = self.get_statics::<String>("window")?.get_value();
+            let noise_estimation = self.get_statics::<String>("noise_estimation")?.get_value();
+            let minimum_window = self.get_statics::<usize>("minimum_window")?.get_value();
+            if !["initial", "minimum_statistics"].contains(&noise_estimation.as_str()) || minimum_window == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let Some(window) = window(&window_name, frame_size) else {
+                return Err(StreamingError::InvalidStatics)
+            };
+            self.stft = StftEngine::new(frame_size, hop_size, window);
+            if self.stft.is_none() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("noise_spectrum", vec![0.0; frame_size])?;
+            self.set_state_value("frame_count", 0usize)?;
+            self.set_state_value("smoothed_power", Vec::<f64>::new())?;
+            self.set_state_value("power_history", Vec::<Vec<f64>>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let noise_estimation = self.get_statics::<String>("noise_estimation")?.get_value();
+            let noise_frames = self.get_statics::<usize>("noise_frames")?.get_value();
+            let minimum_window = self.get_statics::<usize>("minimum_window")?.get_value();
+            let smoothing = self.get_statics::<f64>("smoothing")?.get_value();
+            let over_subtraction = self.get_statics::<f64>("over_subtraction")?.get_value();
+            let floor = self.get_statics::<f64>("floor")?.get_value();
+            let mut noise = self.get_state_value::<Vec<f64>>("noise_spectrum")?;
+            let mut frame_count = self.get_state_value::<usize>("frame_count")?;
+            let mut smoothed_power = self.get_state_value::<Vec<f64>>("smoothed_power")?;
+            let mut power_history = self.get_state_value::<Vec<Vec<f64>>>("power_history")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let mut output_signal = Vec::<f64>::new();
+            {
+                let _lock = self.lock.lock().unwrap();
+                let Some(stft) = self.stft.as_mut() else {
+                    return Err(StreamingError::InvalidStatics)
+                };
+                for mut spectrum in stft.analyze(&input_signal) {
+                    let power: Vec<f64> = spectrum.iter().map(|c| c.norm_sqr()).collect();
+                    if noise_estimation == "initial" {
+                        // the first noise_frames frames are noise only, their mean power is the estimate
+                        if frame_count < noise_frames {
+                            for (estimate, p) in noise.iter_mut().zip(power.iter()) {
+                                *estimate += (p - *estimate) / (frame_count + 1) as f64;
+                            }
+                        }
+                    } else {
+                        if smoothed_power.is_empty() {
+                            smoothed_power = power.clone();
+                        }
+                        for (smoothed, p) in smoothed_power.iter_mut().zip(power.iter()) {
+                            *smoothed = smoothing * *smoothed + (1.0 - smoothing) * p;
+                        }
+                        power_history.push(smoothed_power.clone());
+                        if power_history.len() > minimum_window {
+                            power_history.remove(0);
+                        }
+                        for (bin, estimate) in noise.iter_mut().enumerate() {
+                            *estimate = MINIMUM_BIAS * power_history.iter().map(|frame| frame[bin]).fold(f64::INFINITY, f64::min);
+                        }
+                    }
+                    frame_count += 1;
+                    subtract_noise(&mut spectrum, &noise, over_subtraction, floor);
+                    output_signal.extend(stft.synthesize(spectrum));
+                }
+            }
+            self.set_state_value("noise_spectrum", noise)?;
+            self.set_state_value("frame_count", frame_count)?;
+            self.set_state_value("smoothed_power", smoothed_power)?;
+            self.set_state_value("power_history", power_history)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// Minimum statistics underestimate the mean noise power, this compensates the bias.
+const MINIMUM_BIAS: f64 = 1.5;
+
+// Power spectral subtraction with over-subtraction factor and spectral floor (Berouti et al.),
+// the phase of the noisy spectrum is kept.
+pub fn subtract_noise(spectrum: &mut [Complex<f64>], noise: &[f64], over_subtraction: f64, floor: f64) {
+    for (value, noise_power) in spectrum.iter_mut().zip(noise.iter()) {
+        let power = value.norm_sqr();
+        if power <= 0.0 {
+            continue;
+        }
+        let cleaned = (power - over_subtraction * noise_power).max(floor * noise_power);
+        *value *= (cleaned / power).sqrt();
+    }
+}
+
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use rustfft::num_complex::Complex;
+use crate::spectral::StftEngine;
+use crate::window_functions::window;
+
+stream_block! {
+    pub struct Stft {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<Complex<f64>> },
+        statics: {
+            "frame_size": usize = 512,
+            "hop_size": usize = 128,
+            "window": String = "hann".to_string(),
+            "onesided": bool = false,
+        },
+        fields: { stft: Option<StftEngine> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let hop_size = self.get_statics::<usize>("hop_size")?.get_value();
+            let window_name = self.get_statics::<String>("window")?.get_value();
+            let Some(window) = window(&window_name, frame_size) else {
+                return Err(StreamingError::InvalidStatics)
+            };
+            self.stft = StftEngine::new(frame_size, hop_size, window);
+            if self.stft.is_none() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let onesided = self.get_statics::<bool>("onesided")?.get_value();
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let spectra;
+            {
+                let _lock = self.lock.lock().unwrap();
+                let Some(stft) = self.stft.as_mut() else {
+                    return Err(StreamingError::InvalidStatics)
+                };
+                spectra = stft.analyze(&input_signal);
+            }
+            for mut spectrum in spectra {
+                if onesided {
+                    spectrum.truncate(frame_size / 2 + 1);
+                }
+                self.send_output::<Vec<Complex<f64>>>("output", spectrum)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+stream_block! {
+    pub struct Istft {
+        inputs: { "input": Vec<Complex<f64>> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "frame_size": usize = 512,
+            "hop_size": usize = 128,
+            "window": String = "hann".to_string(),
+            "onesided": bool = false,
+        },
+        fields: { stft: Option<StftEngine> = None },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let hop_size = self.get_statics::<usize>("hop_size")?.get_value();
+            let window_name = self.get_statics::<String>("window")?.get_value();
+            let Some(window) = window(&window_name, frame_size) else {
+                return Err(StreamingError::InvalidStatics)
+            };
+            self.stft = StftEngine::new(frame_size, hop_size, window);
+            if self.stft.is_none() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let onesided = self.get_statics::<bool>("onesided")?.get_value();
+            let spectrum = self.recv_input::<Vec<Complex<f64>>>("input")?;
+            let expected = if onesided { frame_size / 2 + 1 } else { frame_size };
+            if spectrum.len() != expected {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let output_signal;
+            {
+                let _lock = self.lock.lock().unwrap();
+                let Some(stft) = self.stft.as_mut() else {
+                    return Err(StreamingError::InvalidStatics)
+                };
+                output_signal = stft.synthesize(full_spectrum(spectrum, frame_size));
+            }
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// Onesided spectra keep bins 0..=frame_size/2, the rest follows from conjugate symmetry.
+fn full_spectrum(mut spectrum: Vec<Complex<f64>>, frame_size: usize) -> Vec<Complex<f64>> {
+    for k in spectrum.len()..frame_size {
+        let mirrored = spectrum[frame_size - k].conj();
+        spectrum.push(mirrored);
+    }
+    spectrum
+}
+
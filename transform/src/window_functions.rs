@@ -0,0 +1,61 @@
+use std::f64::consts::PI;
+
+// Periodic windows, as used for spectral analysis with overlapping frames.
+pub fn window(name: &str, size: usize) -> Option<Vec<f64>> {
+    let n = size as f64;
+    let cosine_sum = |coefficients: &[f64]| -> Vec<f64> {
+        (0..size).map(|k| {
+            coefficients.iter().enumerate()
+                .map(|(m, c)| if m % 2 == 0 { 1.0 } else { -1.0 } * c * (2.0 * PI * m as f64 * k as f64 / n).cos())
+                .sum()
+        }).collect()
+    };
+    match name {
+        "rectangular" => Some(vec![1.0; size]),
+        "hann" => Some(cosine_sum(&[0.5, 0.5])),
+        "hamming" => Some(cosine_sum(&[0.54, 0.46])),
+        "blackman" => Some(cosine_sum(&[0.42, 0.5, 0.08])),
+        "sqrt_hann" => Some(cosine_sum(&[0.5, 0.5]).iter().map(|w| w.sqrt()).collect()),
+        "blackman_harris" => Some(cosine_sum(&[0.35875, 0.48829, 0.14128, 0.01168])),
+        "flat_top" => Some(cosine_sum(&[0.21557895, 0.41663158, 0.277263158, 0.083578947, 0.006947368])),
+        _ => None,
+    }
+}
+
+// Zeroth order modified Bessel function of the first kind, power series.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    for k in 1..64 {
+        term *= (x / (2.0 * k as f64)).powi(2);
+        sum += term;
+        if term < 1.0e-16 * sum {
+            break;
+        }
+    }
+    sum
+}
+
+// Windows with a shape parameter: Kaiser (beta) and Tukey (taper fraction alpha, 0 is
+// rectangular and 1 is Hann). Other names are forwarded to `window`.
+pub fn parametric_window(name: &str, size: usize, parameter: f64) -> Option<Vec<f64>> {
+    let n = size as f64;
+    match name {
+        "kaiser" if parameter >= 0.0 => Some((0..size).map(|k| {
+            let r = 2.0 * k as f64 / n - 1.0;
+            bessel_i0(parameter * (1.0 - r * r).sqrt()) / bessel_i0(parameter)
+        }).collect()),
+        "tukey" if (0.0..=1.0).contains(&parameter) => Some((0..size).map(|k| {
+            let x = k as f64 / n;
+            if x < parameter / 2.0 {
+                0.5 * (1.0 - (2.0 * PI * x / parameter).cos())
+            } else if x <= 1.0 - parameter / 2.0 {
+                1.0
+            } else {
+                0.5 * (1.0 - (2.0 * PI * (1.0 - x) / parameter).cos())
+            }
+        }).collect()),
+        "kaiser" | "tukey" => None,
+        _ => window(name, size),
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::window_functions::parametric_window;
+
+stream_block! {
+    pub struct Window {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "window": String = "hann".to_string(),
+            // beta for kaiser, taper fraction for tukey, ignored by the other windows
+            "parameter": f64 = 0.5,
+            "normalization": String = "none".to_string(),
+        },
+        state: { "coefficients": Vec<f64> = Vec::<f64>::new() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let window_name = self.get_statics::<String>("window")?.get_value();
+            let parameter = self.get_statics::<f64>("parameter")?.get_value();
+            let normalization = self.get_statics::<String>("normalization")?.get_value();
+            if normalized_window(&window_name, 8, parameter, &normalization).is_none() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state_value("coefficients", Vec::<f64>::new())?;
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let window_name = self.get_statics::<String>("window")?.get_value();
+            let parameter = self.get_statics::<f64>("parameter")?.get_value();
+            let normalization = self.get_statics::<String>("normalization")?.get_value();
+            let mut coefficients = self.get_state_value::<Vec<f64>>("coefficients")?;
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let output_signal: Vec<f64>;
+            {
+                let _lock = self.lock.lock().unwrap();
+                // recomputed only when the frame length changes
+                if coefficients.len() != input_signal.len() {
+                    coefficients = normalized_window(&window_name, input_signal.len(), parameter, &normalization).unwrap_or_default();
+                }
+                output_signal = input_signal.iter().zip(coefficients.iter()).map(|(x, w)| x * w).collect();
+            }
+            self.set_state_value("coefficients", coefficients)?;
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
+// "coherent_gain" keeps the amplitude of a bin-centered tone, "energy" keeps the power of
+// broadband noise.
+fn normalized_window(name: &str, size: usize, parameter: f64, normalization: &str) -> Option<Vec<f64>> {
+    let weights = parametric_window(name, size, parameter)?;
+    let scale = match normalization {
+        "none" => 1.0,
+        "coherent_gain" => weights.iter().sum::<f64>() / size as f64,
+        "energy" => (weights.iter().map(|w| w * w).sum::<f64>() / size as f64).sqrt(),
+        _ => return None,
+    };
+    Some(weights.iter().map(|w| w / scale).collect())
+}
+
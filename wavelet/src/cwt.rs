@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use rustfft::{Fft, FftPlanner};
+use crate::morlet::morlet_scalogram;
+
+stream_block! {
+    pub struct Cwt {
+        inputs: { "input": Vec<f64> },
+        outputs: { "scalogram": Vec<Vec<f64>> },
+        statics: {
+            "frame_size": usize = 1024,
+            "frequencies": Vec<f64> = Vec::<f64>::new(),
+            "sample_rate": f64 = 1.0,
+            "omega0": f64 = 6.0,
+        },
+        fields: {
+            forward: Option<Arc<dyn Fft<f64>>> = None,
+            inverse: Option<Arc<dyn Fft<f64>>> = None,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let frequencies = self.get_statics::<Vec<f64>>("frequencies")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let omega0 = self.get_statics::<f64>("omega0")?.get_value();
+            if frame_size < 2 || frequencies.is_empty() || sample_rate.is_nan() || sample_rate <= 0.0 || omega0.is_nan() || omega0 <= 0.0
+                || frequencies.iter().any(|f| f.is_nan() || *f <= 0.0 || *f > sample_rate / 2.0) {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let mut planner = FftPlanner::new();
+            self.forward = Some(planner.plan_fft_forward(frame_size));
+            self.inverse = Some(planner.plan_fft_inverse(frame_size));
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let frame_size = self.get_statics::<usize>("frame_size")?.get_value();
+            let frequencies = self.get_statics::<Vec<f64>>("frequencies")?.get_value();
+            let sample_rate = self.get_statics::<f64>("sample_rate")?.get_value();
+            let omega0 = self.get_statics::<f64>("omega0")?.get_value();
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            if input_signal.len() != frame_size {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            }
+            let scalogram = {
+                let _lock = self.lock.lock().unwrap();
+                morlet_scalogram(&input_signal, &frequencies, sample_rate, omega0,
+                    self.forward.as_ref().unwrap(), self.inverse.as_ref().unwrap())
+            };
+            self.send_output::<Vec<Vec<f64>>>("scalogram", scalogram)?;
+            Ok(())
+        }
+    }
+}
+
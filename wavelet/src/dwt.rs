@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use block_template::stream_block;
+use data_model::streaming_data::{StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use processor_engine::connectors::{ConnectorTrait, Input, Output};
+use crate::wavelets::{scaling_filter, shrink, universal_threshold, wavedec, waverec};
+
+stream_block! {
+    pub struct Dwt {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<Vec<f64>> },
+        statics: {
+            "wavelet": String = "db4".to_string(),
+            "levels": usize = 3,
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let wavelet = self.get_statics::<String>("wavelet")?.get_value();
+            let levels = self.get_statics::<usize>("levels")?.get_value();
+            if scaling_filter(&wavelet).is_none() || levels == 0 {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let wavelet = self.get_statics::<String>("wavelet")?.get_value();
+            let levels = self.get_statics::<usize>("levels")?.get_value();
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let coefficients = {
+                let _lock = self.lock.lock().unwrap();
+                wavedec(&input_signal, &wavelet, levels)
+            };
+            // the frame length has to be a multiple of 2^levels
+            let Some(coefficients) = coefficients else {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            };
+            self.send_output::<Vec<Vec<f64>>>("output", coefficients)?;
+            Ok(())
+        }
+    }
+}
+stream_block! {
+    pub struct Idwt {
+        inputs: { "input": Vec<Vec<f64>> },
+        outputs: { "output": Vec<f64> },
+        statics: { "wavelet": String = "db4".to_string() },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let wavelet = self.get_statics::<String>("wavelet")?.get_value();
+            if scaling_filter(&wavelet).is_none() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let wavelet = self.get_statics::<String>("wavelet")?.get_value();
+            let coefficients = self.recv_input::<Vec<Vec<f64>>>("input")?;
+            let output_signal = {
+                let _lock = self.lock.lock().unwrap();
+                waverec(&coefficients, &wavelet)
+            };
+            let Some(output_signal) = output_signal else {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            };
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+stream_block! {
+    pub struct WaveletDenoise {
+        inputs: { "input": Vec<f64> },
+        outputs: { "output": Vec<f64> },
+        statics: {
+            "wavelet": String = "db4".to_string(),
+            "levels": usize = 3,
+            "threshold": f64 = 0.0,
+            "mode": String = "soft".to_string(),
+        },
+    }
+    impl StreamProcessor {
+        fn init(&mut self) -> Result<(), StreamingError> {
+            if self.check_state(StreamingState::Running) {
+                return Err(StreamingError::InvalidStateTransition)
+            }
+            if !self.is_initialized() {
+                return Err(StreamingError::InvalidStatics)
+            }
+            let wavelet = self.get_statics::<String>("wavelet")?.get_value();
+            let levels = self.get_statics::<usize>("levels")?.get_value();
+            let threshold = self.get_statics::<f64>("threshold")?.get_value();
+            let mode = self.get_statics::<String>("mode")?.get_value();
+            if scaling_filter(&wavelet).is_none() || levels == 0 || threshold.is_nan() || threshold < 0.0
+                || (mode != "soft" && mode != "hard") {
+                return Err(StreamingError::InvalidStatics)
+            }
+            self.set_state(StreamingState::Initial);
+            Ok(())
+        }
+        fn process(&mut self) -> Result<(), StreamingError> {
+            let wavelet = self.get_statics::<String>("wavelet")?.get_value();
+            let levels = self.get_statics::<usize>("levels")?.get_value();
+            let threshold = self.get_statics::<f64>("threshold")?.get_value();
+            let soft = self.get_statics::<String>("mode")?.get_value() == "soft";
+            let input_signal = self.recv_input::<Vec<f64>>("input")?;
+            let output_signal = {
+                let _lock = self.lock.lock().unwrap();
+                wavedec(&input_signal, &wavelet, levels).and_then(|mut coefficients| {
+                    // a zero threshold selects the universal threshold estimated on every frame
+                    let threshold = if threshold > 0.0 {
+                        threshold
+                    } else {
+                        universal_threshold(coefficients.last().unwrap(), input_signal.len())
+                    };
+                    coefficients.iter_mut().skip(1).flatten().for_each(|d| *d = shrink(*d, threshold, soft));
+                    waverec(&coefficients, &wavelet)
+                })
+            };
+            let Some(output_signal) = output_signal else {
+                self.stop()?;
+                return Err(StreamingError::InvalidInput);
+            };
+            self.send_output::<Vec<f64>>("output", output_signal)?;
+            Ok(())
+        }
+    }
+}
+
@@ -0,0 +1,44 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+use rustfft::{Fft, num_complex::Complex};
+
+// Continuous transform of one frame with analytic Morlet wavelets, computed by FFT so the frame
+// is treated as periodic. Each row is |W| for one analysis frequency, normalized so that a unit
+// amplitude sinusoid at that frequency has magnitude one.
+pub fn morlet_scalogram(signal: &[f64], frequencies: &[f64], sample_rate: f64, omega0: f64,
+    forward: &Arc<dyn Fft<f64>>, inverse: &Arc<dyn Fft<f64>>) -> Vec<Vec<f64>> {
+    let n = signal.len();
+    let mut spectrum: Vec<Complex<f64>> = signal.iter().map(|x| Complex::new(*x, 0.0)).collect();
+    forward.process(&mut spectrum);
+    frequencies.iter().map(|frequency| {
+        let scale = omega0 * sample_rate / (2.0 * PI * frequency);
+        let mut buffer: Vec<Complex<f64>> = spectrum.iter().enumerate().map(|(k, x)| {
+            // only positive frequencies, the Nyquist bin is split between both halves
+            if k == 0 || 2 * k > n {
+                return Complex::new(0.0, 0.0);
+            }
+            let omega = 2.0 * PI * k as f64 / n as f64;
+            let weight = if 2 * k == n { 1.0 } else { 2.0 };
+            x * weight * (-0.5 * (scale * omega - omega0).powi(2)).exp()
+        }).collect();
+        inverse.process(&mut buffer);
+        buffer.iter().map(|w| w.norm() / n as f64).collect()
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustfft::FftPlanner;
+    #[test]
+    fn test_morlet_tone() {
+        let n = 512;
+        let sample_rate = 1000.0;
+        let mut planner = FftPlanner::new();
+        let (forward, inverse) = (planner.plan_fft_forward(n), planner.plan_fft_inverse(n));
+        let signal: Vec<f64> = (0..n).map(|k| 0.5 * (2.0 * PI * 125.0 * k as f64 / sample_rate).cos()).collect();
+        let scalogram = morlet_scalogram(&signal, &[125.0, 250.0], sample_rate, 6.0, &forward, &inverse);
+        assert!(scalogram[0].iter().all(|w| (w - 0.5).abs() < 1.0e-6));
+        assert!(scalogram[1].iter().all(|w| *w < 0.01));
+    }
+}
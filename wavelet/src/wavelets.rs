@@ -0,0 +1,121 @@
+use std::f64::consts::SQRT_2;
+
+// Orthonormal scaling (low-pass) filters, "haar" is the same as "db1".
+pub fn scaling_filter(name: &str) -> Option<Vec<f64>> {
+    let sqrt_3 = 3.0f64.sqrt();
+    match name {
+        "haar" | "db1" => Some(vec![1.0 / SQRT_2, 1.0 / SQRT_2]),
+        "db2" => Some([1.0 + sqrt_3, 3.0 + sqrt_3, 3.0 - sqrt_3, 1.0 - sqrt_3].iter().map(|c| c / (4.0 * SQRT_2)).collect()),
+        "db3" => Some(vec![0.33267055295008263, 0.8068915093110925, 0.45987750211849154,
+            -0.13501102001025458, -0.08544127388202666, 0.03522629188570954]),
+        "db4" => Some(vec![0.23037781330889653, 0.7148465705529157, 0.6308807679298589, -0.027983769416859854,
+            -0.18703481171909309, 0.030841381835560764, 0.0328830116668852, -0.010597401785069032]),
+        _ => None,
+    }
+}
+
+// Quadrature mirror of the scaling filter, g(n) = (-1)^n h(L-1-n).
+pub fn wavelet_filter(scaling: &[f64]) -> Vec<f64> {
+    scaling.iter().rev().enumerate().map(|(n, h)| if n.is_multiple_of(2) { *h } else { -h }).collect()
+}
+
+// One periodized analysis step, the signal length must be even.
+pub fn analysis_step(signal: &[f64], scaling: &[f64], wavelet: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let n = signal.len();
+    (0..n / 2).map(|k| {
+        scaling.iter().zip(wavelet.iter()).enumerate().fold((0.0, 0.0), |(a, d), (m, (h, g))| {
+            let x = signal[(2 * k + m) % n];
+            (a + h * x, d + g * x)
+        })
+    }).unzip()
+}
+
+pub fn synthesis_step(approximation: &[f64], detail: &[f64], scaling: &[f64], wavelet: &[f64]) -> Vec<f64> {
+    let n = 2 * approximation.len();
+    let mut signal = vec![0.0; n];
+    for (k, (a, d)) in approximation.iter().zip(detail.iter()).enumerate() {
+        for (m, (h, g)) in scaling.iter().zip(wavelet.iter()).enumerate() {
+            signal[(2 * k + m) % n] += h * a + g * d;
+        }
+    }
+    signal
+}
+
+// Multilevel decomposition ordered as [a_L, d_L, ..., d_1], the frame length must be a multiple
+// of 2^levels. None for an unknown wavelet or an incompatible length.
+pub fn wavedec(signal: &[f64], name: &str, levels: usize) -> Option<Vec<Vec<f64>>> {
+    let scaling = scaling_filter(name)?;
+    let wavelet = wavelet_filter(&scaling);
+    if levels == 0 || signal.is_empty() || !signal.len().is_multiple_of(1 << levels) {
+        return None;
+    }
+    let mut details = Vec::new();
+    let mut approximation = signal.to_vec();
+    for _ in 0..levels {
+        let (a, d) = analysis_step(&approximation, &scaling, &wavelet);
+        details.push(d);
+        approximation = a;
+    }
+    details.push(approximation);
+    details.reverse();
+    Some(details)
+}
+
+pub fn waverec(coefficients: &[Vec<f64>], name: &str) -> Option<Vec<f64>> {
+    let scaling = scaling_filter(name)?;
+    let wavelet = wavelet_filter(&scaling);
+    let (first, details) = coefficients.split_first()?;
+    let mut approximation = first.clone();
+    for detail in details {
+        if detail.len() != approximation.len() {
+            return None;
+        }
+        approximation = synthesis_step(&approximation, detail, &scaling, &wavelet);
+    }
+    Some(approximation)
+}
+
+// Universal threshold sigma * sqrt(2 ln N), the noise level is estimated from the median absolute
+// deviation of the finest detail level.
+pub fn universal_threshold(finest_detail: &[f64], length: usize) -> f64 {
+    let mut magnitudes: Vec<f64> = finest_detail.iter().map(|d| d.abs()).collect();
+    if magnitudes.is_empty() {
+        return 0.0;
+    }
+    magnitudes.sort_by(|a, b| a.total_cmp(b));
+    let median = magnitudes[magnitudes.len() / 2];
+    median / 0.6745 * (2.0 * (length as f64).ln()).sqrt()
+}
+
+pub fn shrink(value: f64, threshold: f64, soft: bool) -> f64 {
+    if value.abs() <= threshold {
+        0.0
+    } else if soft {
+        value.signum() * (value.abs() - threshold)
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_perfect_reconstruction() {
+        let signal: Vec<f64> = (0..64).map(|k| (k as f64 * 0.3).sin() + if k > 30 { 1.0 } else { 0.0 }).collect();
+        for name in ["haar", "db2", "db3", "db4"] {
+            let scaling = scaling_filter(name).unwrap();
+            assert!((scaling.iter().sum::<f64>() - SQRT_2).abs() < 1.0e-9);
+            let coefficients = wavedec(&signal, name, 3).unwrap();
+            assert_eq!(coefficients.iter().map(|c| c.len()).collect::<Vec<_>>(), vec![8, 8, 16, 32]);
+            let energy: f64 = coefficients.iter().flatten().map(|c| c * c).sum();
+            assert!((energy - signal.iter().map(|x| x * x).sum::<f64>()).abs() < 1.0e-8);
+            let rebuilt = waverec(&coefficients, name).unwrap();
+            assert!(rebuilt.iter().zip(signal.iter()).all(|(a, b)| (a - b).abs() < 1.0e-9));
+        }
+        let haar = wavedec(&[1.0, 3.0, 2.0, 2.0], "haar", 1).unwrap();
+        assert!((haar[0][0] - 4.0 / SQRT_2).abs() < 1.0e-12 && (haar[1][0] + 2.0 / SQRT_2).abs() < 1.0e-12);
+        assert!(wavedec(&signal[..60], "db2", 3).is_none());
+        assert_eq!(shrink(-3.0, 1.0, true), -2.0);
+    }
+}